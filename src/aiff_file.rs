@@ -0,0 +1,319 @@
+use std::fs;
+
+mod error;
+mod extended;
+mod wave;
+
+use crate::wave_file::chunk::{self, Chunks};
+pub use error::AiffError;
+
+/// The compression type of uncompressed, big-endian AIFF-C files
+const COMPRESSION_NONE: [u8; 4] = *b"NONE";
+/// The compression type of uncompressed, little-endian AIFF-C files
+const COMPRESSION_SOWT: [u8; 4] = *b"sowt";
+
+/// Stores data resembling an AIFF file: uncompressed PCM audio with big-endian samples.
+///
+/// # Parameters
+/// - `num_channels`: the number of channels the file will have
+/// - `sample_rate`: The sample rate of the file in Hz (e.g. 22050, 44100, ...)
+/// - `bits_per_sample`: The amount of bits per sample: 8, 16, 24 or 32. Samples are signed, also
+///   8-bit samples.
+///
+/// # Examples
+/// ```rust,no_run
+/// # use jaudio::aiff_file::*;
+/// # use jaudio::wave_file::WaveFile;
+/// # use std::fs;
+/// #
+/// # fn main() {
+///  let aiff = AiffFile::from_file("audio.aiff").unwrap();
+///
+///  // Converted to a wave file, with little-endian samples
+///  let wave = WaveFile::try_from(aiff).unwrap();
+///  fs::write("file.wav", wave.to_bytes()).unwrap();
+/// # }
+/// ```
+pub struct AiffFile {
+    num_channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u32,
+    block_align: u32,
+    audio_byte_data: Vec<u8>, // Big-endian samples
+}
+
+// New
+impl AiffFile {
+    /// ## Parameters
+    /// - `num_channels`: the number of channels the file will have
+    /// - `sample_rate`: The sample rate of the file in Hz (e.g. 22050, 44100, ...)
+    /// - `bits_per_sample`: The amount of bits per sample: 8, 16, 24 or 32.
+    ///
+    /// ## Panics
+    /// If `bits_per_sample` is not 8, 16, 24 or 32:
+    /// ```rust,should_panic
+    /// # use jaudio::aiff_file::*;
+    /// AiffFile::new(2, 44100, 12);
+    /// ```
+    pub fn new(num_channels: u16, sample_rate: u32, bits_per_sample: u32) -> AiffFile {
+        if !matches!(bits_per_sample, 8 | 16 | 24 | 32) {
+            panic!("AIFF files need 8, 16, 24 or 32 bits per sample, got {}", bits_per_sample);
+        }
+        let block_align = num_channels as u32 * (bits_per_sample / 8);
+        AiffFile { num_channels, sample_rate, bits_per_sample, block_align, audio_byte_data: Vec::new() }
+    }
+}
+
+// Functions on instance
+impl AiffFile {
+    /// Adds big-endian audio data to `AiffFile` from bytes.
+    ///
+    /// # Panics
+    /// If the amount of bytes is not divisible by `block_align`. Use
+    /// [try_add_bytes](AiffFile::try_add_bytes) for a non-panicking version.
+    ///
+    /// # Parameters
+    /// - bytes: will be moved to `audio_byte_data` of `AiffFile`, leaving `bytes` empty.
+    pub fn add_bytes(&mut self, bytes: &mut Vec<u8>) {
+        if self.try_add_bytes(bytes).is_err() {
+            panic!("Trying to add a chunck that does not fit evenly; this would cause un-aligned blocks.");
+        }
+    }
+
+    /// Adds big-endian audio data to `AiffFile` from bytes, like [add_bytes](AiffFile::add_bytes),
+    /// but returns an error instead of panicking.
+    ///
+    /// # Errors
+    /// [AiffError::MisalignedData] if the amount of bytes is not divisible by `block_align`.
+    /// `bytes` is left untouched in that case.
+    pub fn try_add_bytes(&mut self, bytes: &mut Vec<u8>) -> Result<(), AiffError> {
+        if !(bytes.len() as u32).is_multiple_of(self.block_align) {
+            return Err(AiffError::MisalignedData { len: bytes.len(), block_align: self.block_align });
+        }
+        self.audio_byte_data.append(bytes);
+        Ok(())
+    }
+
+    /// Returns the audio data, with big-endian samples
+    pub fn bytes(&mut self) -> &mut Vec<u8> {
+        &mut self.audio_byte_data
+    }
+
+    /// Returns the number of channels
+    pub fn num_channels(&self) -> u16 {
+        self.num_channels
+    }
+
+    /// Returns the sample rate in Hz
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the amount of bits per sample
+    pub fn bits_per_sample(&self) -> u32 {
+        self.bits_per_sample
+    }
+
+    /// Returns the block align: the amount of bytes of a frame (one sample for every channel)
+    pub fn block_align(&self) -> u32 {
+        self.block_align
+    }
+
+    /// Returns the amount of bytes of audio data
+    pub fn data_len(&self) -> usize {
+        self.audio_byte_data.len()
+    }
+
+    /// Returns the number of frames (one sample for every channel) in the audio data
+    pub fn num_frames(&self) -> u64 {
+        if self.block_align == 0 {
+            return 0;
+        }
+        self.audio_byte_data.len() as u64 / self.block_align as u64
+    }
+
+    /// A byte representation of the `AiffFile`, with a `FORM`/`AIFF` header, a COMM chunk and an
+    /// SSND chunk.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::aiff_file::*;
+    /// let mut aiff = AiffFile::new(2, 44100, 16);
+    /// aiff.add_bytes(&mut vec![0, 1, 0, 2, 0, 3, 0, 4]);
+    ///
+    /// let bytes = aiff.to_bytes();
+    /// assert_eq!(&bytes[0..4], b"FORM");
+    /// assert_eq!(&bytes[8..12], b"AIFF");
+    /// assert_eq!(bytes.len(), 12 + 8 + 18 + 8 + 8 + 8);
+    /// // 44100 as an 80-bit extended precision number
+    /// assert_eq!(&bytes[28..38], [0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]);
+    ///
+    /// let mut parsed = AiffFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!((parsed.num_channels(), parsed.sample_rate(), parsed.bits_per_sample()), (2, 44100, 16));
+    /// assert_eq!(parsed.bytes(), &[0, 1, 0, 2, 0, 3, 0, 4]);
+    /// assert_eq!(parsed.to_bytes(), bytes);
+    /// ```
+    ///
+    /// ## Panics
+    /// If the audio doesn't fit in the 32-bit sizes of an AIFF file, see
+    /// [try_to_bytes](AiffFile::try_to_bytes).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.try_to_bytes().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// A byte representation of the `AiffFile`, like [to_bytes](AiffFile::to_bytes).
+    ///
+    /// # Errors
+    /// [AiffError::TooLarge] if the audio doesn't fit in the 32-bit sizes of an AIFF file. The
+    /// frame count is then too large as well.
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, AiffError> {
+        let data_len = self.audio_byte_data.len();
+        let pad_size = data_len % 2;
+        // COMM chunk, and the SSND chunk with its offset and block size
+        let form_size = 4 + (8 + 18) + (8 + 8 + data_len as u64 + pad_size as u64);
+        // Every frame holds at least a byte, so the frame count fits when the FORM size does
+        if form_size > u32::MAX as u64 {
+            return Err(AiffError::TooLarge { size: form_size });
+        }
+
+        let mut comm = Vec::with_capacity(18);
+        comm.extend(self.num_channels.to_be_bytes());
+        comm.extend((self.num_frames() as u32).to_be_bytes());
+        comm.extend((self.bits_per_sample as u16).to_be_bytes());
+        comm.extend(extended::encode(self.sample_rate));
+
+        let mut data = Vec::with_capacity(8 + form_size as usize);
+        data.extend(b"FORM");
+        data.extend((form_size as u32).to_be_bytes());
+        data.extend(b"AIFF");
+        chunk::write_chunk_big_endian(&mut data, b"COMM", &comm);
+        data.extend(b"SSND");
+        data.extend(((8 + data_len) as u32).to_be_bytes());
+        data.extend(0u32.to_be_bytes()); // offset
+        data.extend(0u32.to_be_bytes()); // block size
+        data.extend(&self.audio_byte_data);
+        // Chunks with an odd size are padded with a byte that is not counted in their size
+        if pad_size != 0 {
+            data.push(0);
+        }
+        Ok(data)
+    }
+}
+
+// Static functions
+impl AiffFile {
+    /// Reads an AIFF or AIFF-C file and returns an `AiffFile` populated with the spec from its
+    /// COMM chunk and the audio data from its SSND chunk.
+    ///
+    /// # Errors
+    /// Returns the same errors as [fs::read()](std::fs::read) and
+    /// [from_bytes](AiffFile::from_bytes).
+    pub fn from_file(file_path: &str) -> Result<AiffFile, AiffError> {
+        let file_content = fs::read(file_path)?;
+        AiffFile::from_bytes(&file_content)
+    }
+
+    /// Parses an in-memory AIFF or AIFF-C file and returns an `AiffFile` populated with the spec
+    /// from its COMM chunk and the audio data from its SSND chunk.
+    ///
+    /// AIFF-C files are read when they are uncompressed: the `NONE` compression type, or `sowt`,
+    /// of which the little-endian samples are converted to big-endian.
+    ///
+    /// # Errors
+    /// - [AiffError::NotAiff] if `bytes` does not start with the `FORM`/`AIFF` or `FORM`/`AIFC`
+    ///   magic
+    /// - [AiffError::Truncated] if the FORM chunk is too short to hold its form type, a chunk
+    ///   header is truncated, a chunk declares more bytes than `bytes` contains, or the COMM chunk
+    ///   is too short or declares more frames than the SSND chunk contains
+    /// - [AiffError::MissingChunk] if the COMM chunk is missing, or the SSND chunk of a file
+    ///   with audio
+    /// - [AiffError::UnsupportedCompression] for compressed AIFF-C files
+    /// - [AiffError::UnsupportedBitsPerSample] if the samples are not 8, 16, 24 or 32 bits
+    ///
+    /// # Examples
+    /// An AIFF-C file with little-endian samples:
+    /// ```rust
+    /// # use jaudio::aiff_file::*;
+    /// let comm: Vec<u8> = [
+    ///     &1u16.to_be_bytes()[..], &2u32.to_be_bytes(), &16u16.to_be_bytes(),
+    ///     &[0x40, 0x0B, 0xFA, 0, 0, 0, 0, 0, 0, 0], b"sowt", &[0, 0],
+    /// ].concat();
+    /// let ssnd = [&[0; 8][..], &1000i16.to_le_bytes(), &(-2i16).to_le_bytes()].concat();
+    ///
+    /// let mut bytes = b"FORM".to_vec();
+    /// bytes.extend(((4 + 8 + comm.len() + 8 + ssnd.len()) as u32).to_be_bytes());
+    /// bytes.extend(b"AIFCCOMM");
+    /// bytes.extend((comm.len() as u32).to_be_bytes());
+    /// bytes.extend(&comm);
+    /// bytes.extend(b"SSND");
+    /// bytes.extend((ssnd.len() as u32).to_be_bytes());
+    /// bytes.extend(&ssnd);
+    ///
+    /// let mut aiff = AiffFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!(aiff.sample_rate(), 8000);
+    /// assert_eq!(aiff.bytes(), &[&1000i16.to_be_bytes()[..], &(-2i16).to_be_bytes()].concat());
+    ///
+    /// assert!(matches!(AiffFile::from_bytes(b"RIFF\0\0\0\0WAVE"), Err(AiffError::NotAiff)));
+    /// assert!(matches!(AiffFile::from_bytes(b"FORM\0\0\0\0AIFF"), Err(AiffError::Truncated)));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<AiffFile, AiffError> {
+        if bytes.len() < 12 || &bytes[0..4] != b"FORM" {
+            return Err(AiffError::NotAiff);
+        }
+        let aifc = match &bytes[8..12] {
+            b"AIFF" => false,
+            b"AIFC" => true,
+            _ => return Err(AiffError::NotAiff),
+        };
+        // The FORM chunk size counts everything after the size field itself
+        let form_size = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        if form_size < 4 {
+            return Err(AiffError::Truncated);
+        }
+        let end = bytes.len().min(form_size.saturating_add(8));
+
+        let mut comm = None;
+        let mut ssnd = None;
+        for chunk in Chunks::within_big_endian(&bytes[12..end]) {
+            let chunk = chunk.map_err(|_| AiffError::Truncated)?;
+            match &chunk.id {
+                b"COMM" if comm.is_none() => comm = Some(chunk.data),
+                b"SSND" if ssnd.is_none() => ssnd = Some(chunk.data),
+                _ => {}
+            }
+        }
+        let comm = comm.ok_or(AiffError::MissingChunk("COMM"))?;
+        if comm.len() < 18 || (aifc && comm.len() < 22) {
+            return Err(AiffError::Truncated);
+        }
+        let num_channels = u16::from_be_bytes([comm[0], comm[1]]);
+        let num_frames = u32::from_be_bytes(comm[2..6].try_into().unwrap()) as usize;
+        let bits_per_sample = u16::from_be_bytes([comm[6], comm[7]]) as u32;
+        let sample_rate = extended::decode(comm[8..18].try_into().unwrap());
+        let compression: [u8; 4] = if aifc { comm[18..22].try_into().unwrap() } else { COMPRESSION_NONE };
+        if compression != COMPRESSION_NONE && compression != COMPRESSION_SOWT {
+            return Err(AiffError::UnsupportedCompression(compression));
+        }
+        if !matches!(bits_per_sample, 8 | 16 | 24 | 32) {
+            return Err(AiffError::UnsupportedBitsPerSample(bits_per_sample));
+        }
+
+        let mut aiff = AiffFile::new(num_channels, sample_rate, bits_per_sample);
+        let data_len = num_frames.checked_mul(aiff.block_align as usize).ok_or(AiffError::Truncated)?;
+        if data_len == 0 {
+            return Ok(aiff);
+        }
+        // The SSND chunk starts with the offset of the audio and the block size
+        let ssnd = ssnd.ok_or(AiffError::MissingChunk("SSND"))?;
+        let offset = ssnd.get(0..4).map(|b| u32::from_be_bytes(b.try_into().unwrap()) as usize);
+        let data = offset
+            .and_then(|offset| ssnd.get(8usize.checked_add(offset)?..)?.get(..data_len))
+            .ok_or(AiffError::Truncated)?;
+        aiff.audio_byte_data = data.to_vec();
+        if compression == COMPRESSION_SOWT {
+            let sample_size = (bits_per_sample / 8) as usize;
+            aiff.audio_byte_data.chunks_exact_mut(sample_size).for_each(<[u8]>::reverse);
+        }
+        Ok(aiff)
+    }
+}
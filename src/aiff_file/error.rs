@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors that can occur while working with an [AiffFile](super::AiffFile).
+#[derive(Debug)]
+pub enum AiffError {
+    /// The bytes do not start with the `FORM`/`AIFF` or `FORM`/`AIFC` magic, so this is not an
+    /// AIFF file at all.
+    NotAiff,
+    /// A chunk that is required is not present in the file. Contains the id of the chunk, e.g.
+    /// `"COMM"` or `"SSND"`.
+    MissingChunk(&'static str),
+    /// The compression type of an AIFF-C file is not supported. Only uncompressed audio (`NONE`
+    /// and `sowt`) can be read.
+    UnsupportedCompression([u8; 4]),
+    /// The wave format can't be stored in an AIFF file, which only holds PCM. Contains the format
+    /// code.
+    UnsupportedFormat(u16),
+    /// The amount of bits per sample is not supported.
+    UnsupportedBitsPerSample(u32),
+    /// The amount of bytes given is not divisible by the block align of the file, adding them
+    /// would cause un-aligned blocks.
+    MisalignedData {
+        /// The amount of bytes that was given
+        len: usize,
+        /// The block align the amount of bytes should be divisible by
+        block_align: u32,
+    },
+    /// The file ends in the middle of a header, or a chunk declares more bytes than the file
+    /// contains.
+    Truncated,
+    /// The audio doesn't fit in the 32-bit sizes of an AIFF file.
+    TooLarge {
+        /// The size the FORM chunk would have, in bytes
+        size: u64,
+    },
+    /// An I/O error occurred while reading or writing a file.
+    Io(io::Error),
+}
+
+impl fmt::Display for AiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AiffError::NotAiff => write!(f, "not a FORM/AIFF file"),
+            AiffError::MissingChunk(id) => write!(f, "missing `{}` chunk", id),
+            AiffError::UnsupportedCompression(id) => {
+                write!(f, "unsupported compression type `{}`", String::from_utf8_lossy(id))
+            }
+            AiffError::UnsupportedFormat(format) => write!(f, "unsupported audio format {:#06x}", format),
+            AiffError::UnsupportedBitsPerSample(bits) => write!(f, "unsupported bits per sample: {}", bits),
+            AiffError::MisalignedData { len, block_align } => write!(
+                f,
+                "{} bytes do not fit evenly in blocks of {} bytes",
+                len, block_align
+            ),
+            AiffError::Truncated => write!(f, "the file is truncated or a chunk size is corrupt"),
+            AiffError::TooLarge { size } => write!(
+                f,
+                "a FORM chunk of {} bytes doesn't fit in a 32-bit size",
+                size
+            ),
+            AiffError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl Error for AiffError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AiffError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AiffError {
+    fn from(err: io::Error) -> Self {
+        AiffError::Io(err)
+    }
+}
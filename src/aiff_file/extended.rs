@@ -0,0 +1,33 @@
+//! The 80-bit IEEE 754 extended precision numbers AIFF stores its sample rate in.
+
+/// The exponent bias of an 80-bit extended precision number
+const BIAS: i32 = 16383;
+
+/// Encodes `value` as an 80-bit extended precision number: a sign bit and 15-bit exponent,
+/// followed by a 64-bit mantissa with an explicit integer bit.
+pub(crate) fn encode(value: u32) -> [u8; 10] {
+    let mut out = [0; 10];
+    if value == 0 {
+        return out;
+    }
+    // Normalize, so the integer bit is the highest bit of the mantissa
+    let shift = value.leading_zeros() + 32;
+    let exponent = (BIAS + 63 - shift as i32) as u16;
+    out[0..2].copy_from_slice(&exponent.to_be_bytes());
+    out[2..10].copy_from_slice(&((value as u64) << shift).to_be_bytes());
+    out
+}
+
+/// Decodes an 80-bit extended precision number, rounded to the nearest integer. Negative numbers
+/// and numbers that don't fit in a `u32` saturate.
+pub(crate) fn decode(bytes: &[u8; 10]) -> u32 {
+    let sign_exponent = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+    if sign_exponent & 0x8000 != 0 || mantissa == 0 {
+        return 0;
+    }
+    let exponent = (sign_exponent & 0x7FFF) as i32 - BIAS - 63;
+    let value = mantissa as f64 * 2f64.powi(exponent);
+    // Casting a float saturates
+    value.round() as u32
+}
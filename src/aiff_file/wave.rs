@@ -0,0 +1,91 @@
+//! Conversion between AIFF files and wave files.
+
+use std::mem;
+
+use super::{AiffError, AiffFile};
+use crate::wave_file::{AudioFormat, WaveError, WaveFile};
+
+/// Converts the samples in `bytes` between big-endian AIFF samples and little-endian wave
+/// samples. 8-bit samples are signed in AIFF files and unsigned in wave files, so their sign bit
+/// is flipped instead.
+fn swap_samples(bytes: &mut [u8], bits_per_sample: u32) {
+    match bits_per_sample {
+        8 => bytes.iter_mut().for_each(|sample| *sample ^= 0x80),
+        bits => bytes.chunks_exact_mut(bits as usize / 8).for_each(<[u8]>::reverse),
+    }
+}
+
+impl TryFrom<AiffFile> for WaveFile {
+    type Error = WaveError;
+
+    /// Converts an `AiffFile` to a PCM `WaveFile` with the same spec, swapping the byte order of
+    /// the samples.
+    ///
+    /// # Errors
+    /// The same errors as [WaveFile::try_new], e.g. [WaveError::InvalidSpec] if the file has no
+    /// channels, a sample rate of 0 or a byte rate that doesn't fit in 32 bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::aiff_file::*;
+    /// # use jaudio::wave_file::*;
+    /// let mut aiff = AiffFile::new(1, 22050, 16);
+    /// aiff.add_bytes(&mut [1000i16, -2].iter().flat_map(|s| s.to_be_bytes()).collect());
+    ///
+    /// let wave = WaveFile::try_from(aiff).unwrap();
+    /// assert_eq!((wave.audio_format(), wave.sample_rate()), (AudioFormat::PCM, 22050));
+    /// assert_eq!(wave.samples_i16().unwrap(), [1000, -2]);
+    ///
+    /// let mut aiff = AiffFile::try_from(wave).unwrap();
+    /// assert_eq!(aiff.bytes(), &[0x03, 0xE8, 0xFF, 0xFE]);
+    ///
+    /// let aiff = AiffFile::new(u16::MAX, u32::MAX, 32);
+    /// assert!(matches!(WaveFile::try_from(aiff), Err(WaveError::InvalidSpec(_))));
+    /// ```
+    fn try_from(mut aiff: AiffFile) -> Result<Self, Self::Error> {
+        let mut wave = WaveFile::try_new(AudioFormat::PCM, aiff.num_channels, aiff.sample_rate, aiff.bits_per_sample as u16)?;
+        swap_samples(&mut aiff.audio_byte_data, aiff.bits_per_sample);
+        wave.add_bytes(&mut aiff.audio_byte_data);
+        Ok(wave)
+    }
+}
+
+impl TryFrom<WaveFile> for AiffFile {
+    type Error = AiffError;
+
+    /// Converts a PCM `WaveFile` to an `AiffFile` with the same spec, swapping the byte order of
+    /// the samples. Metadata of the wave file is not kept.
+    ///
+    /// # Errors
+    /// [AiffError::UnsupportedFormat] if the wave file is not PCM (also when it is stored with
+    /// `WAVE_FORMAT_EXTENSIBLE`) and [AiffError::UnsupportedBitsPerSample] if its samples are not
+    /// 8, 16, 24 or 32 bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::aiff_file::*;
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 8000, 8);
+    /// wave.add_bytes(&mut vec![0x80, 0xFF]);
+    ///
+    /// let mut aiff = AiffFile::try_from(wave).unwrap();
+    /// // 8-bit AIFF samples are signed
+    /// assert_eq!(aiff.bytes(), &[0x00, 0x7F]);
+    ///
+    /// let float = WaveFile::new(AudioFormat::IeeeFloat, 2, 8000, 32);
+    /// assert!(matches!(AiffFile::try_from(float), Err(AiffError::UnsupportedFormat(3))));
+    /// ```
+    fn try_from(mut wave: WaveFile) -> Result<Self, Self::Error> {
+        if wave.audio_format() != AudioFormat::PCM {
+            return Err(AiffError::UnsupportedFormat(wave.audio_format().get_val()));
+        }
+        let bits_per_sample = wave.bits_per_sample() as u32;
+        if !matches!(bits_per_sample, 8 | 16 | 24 | 32) {
+            return Err(AiffError::UnsupportedBitsPerSample(bits_per_sample));
+        }
+        let mut aiff = AiffFile::new(wave.num_channels(), wave.sample_rate(), bits_per_sample);
+        aiff.audio_byte_data = mem::take(wave.bytes());
+        swap_samples(&mut aiff.audio_byte_data, bits_per_sample);
+        Ok(aiff)
+    }
+}
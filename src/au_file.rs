@@ -0,0 +1,326 @@
+use std::fs;
+
+mod error;
+mod wave;
+
+pub use error::AuError;
+
+/// The magic the header of an au file starts with
+const MAGIC: [u8; 4] = *b".snd";
+/// The size of the header without an annotation
+const HEADER_SIZE: u32 = 24;
+/// The data size that signals the size is unknown, and the audio data runs to the end of the file
+const UNKNOWN_DATA_SIZE: u32 = u32::MAX;
+
+/// The encodings of the samples of an [AuFile].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuEncoding {
+    /// ITU G.711 µ-law companded 8-bit samples
+    MuLaw,
+    /// 8-bit signed linear PCM
+    Linear8,
+    /// 16-bit signed linear PCM
+    Linear16,
+    /// 24-bit signed linear PCM
+    Linear24,
+    /// 32-bit signed linear PCM
+    Linear32,
+    /// 32-bit IEEE 754 floating point
+    Float,
+    /// 64-bit IEEE 754 floating point
+    Double,
+    /// ITU G.711 A-law companded 8-bit samples
+    ALaw,
+}
+
+impl AuEncoding {
+    /// The encoding code as stored in the header
+    fn get_val(&self) -> u32 {
+        match self {
+            AuEncoding::MuLaw => 1,
+            AuEncoding::Linear8 => 2,
+            AuEncoding::Linear16 => 3,
+            AuEncoding::Linear24 => 4,
+            AuEncoding::Linear32 => 5,
+            AuEncoding::Float => 6,
+            AuEncoding::Double => 7,
+            AuEncoding::ALaw => 27,
+        }
+    }
+
+    /// The amount of bits of a sample
+    pub fn bits_per_sample(&self) -> u32 {
+        match self {
+            AuEncoding::MuLaw | AuEncoding::ALaw | AuEncoding::Linear8 => 8,
+            AuEncoding::Linear16 => 16,
+            AuEncoding::Linear24 => 24,
+            AuEncoding::Linear32 | AuEncoding::Float => 32,
+            AuEncoding::Double => 64,
+        }
+    }
+}
+
+impl TryFrom<u32> for AuEncoding {
+    type Error = AuError;
+
+    /// Maps a raw encoding code from an au header back to an `AuEncoding`.
+    ///
+    /// # Errors
+    /// [AuError::UnsupportedEncoding] if the code does not correspond to any `AuEncoding`.
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        match code {
+            1 => Ok(AuEncoding::MuLaw),
+            2 => Ok(AuEncoding::Linear8),
+            3 => Ok(AuEncoding::Linear16),
+            4 => Ok(AuEncoding::Linear24),
+            5 => Ok(AuEncoding::Linear32),
+            6 => Ok(AuEncoding::Float),
+            7 => Ok(AuEncoding::Double),
+            27 => Ok(AuEncoding::ALaw),
+            _ => Err(AuError::UnsupportedEncoding(code)),
+        }
+    }
+}
+
+/// Stores data resembling a Sun/NeXT au file: a short header followed by big-endian samples.
+///
+/// # Parameters
+/// - `encoding`: the encoding of the samples
+/// - `num_channels`: the number of channels the file will have
+/// - `sample_rate`: The sample rate of the file in Hz (e.g. 8000, 44100, ...)
+///
+/// # Examples
+/// ```rust,no_run
+/// # use jaudio::au_file::*;
+/// # use jaudio::wave_file::WaveFile;
+/// # use std::fs;
+/// #
+/// # fn main() {
+///  let au = AuFile::from_file("audio.au").unwrap();
+///
+///  // Converted to a wave file, with little-endian samples
+///  let wave = WaveFile::try_from(au).unwrap();
+///  fs::write("file.wav", wave.to_bytes()).unwrap();
+/// # }
+/// ```
+pub struct AuFile {
+    encoding: AuEncoding,
+    num_channels: u16,
+    sample_rate: u32,
+    block_align: u32,
+    annotation: Vec<u8>,
+    audio_byte_data: Vec<u8>, // Big-endian samples
+}
+
+// New
+impl AuFile {
+    /// ## Parameters
+    /// - `encoding`: the encoding of the samples
+    /// - `num_channels`: the number of channels the file will have
+    /// - `sample_rate`: The sample rate of the file in Hz (e.g. 8000, 44100, ...)
+    pub fn new(encoding: AuEncoding, num_channels: u16, sample_rate: u32) -> AuFile {
+        let block_align = num_channels as u32 * (encoding.bits_per_sample() / 8);
+        AuFile { encoding, num_channels, sample_rate, block_align, annotation: Vec::new(), audio_byte_data: Vec::new() }
+    }
+}
+
+// Functions on instance
+impl AuFile {
+    /// Adds big-endian audio data to `AuFile` from bytes.
+    ///
+    /// # Panics
+    /// If the amount of bytes is not divisible by `block_align`. Use
+    /// [try_add_bytes](AuFile::try_add_bytes) for a non-panicking version.
+    ///
+    /// # Parameters
+    /// - bytes: will be moved to `audio_byte_data` of `AuFile`, leaving `bytes` empty.
+    pub fn add_bytes(&mut self, bytes: &mut Vec<u8>) {
+        if self.try_add_bytes(bytes).is_err() {
+            panic!("Trying to add a chunck that does not fit evenly; this would cause un-aligned blocks.");
+        }
+    }
+
+    /// Adds big-endian audio data to `AuFile` from bytes, like [add_bytes](AuFile::add_bytes),
+    /// but returns an error instead of panicking.
+    ///
+    /// # Errors
+    /// [AuError::MisalignedData] if the amount of bytes is not divisible by `block_align`.
+    /// `bytes` is left untouched in that case.
+    pub fn try_add_bytes(&mut self, bytes: &mut Vec<u8>) -> Result<(), AuError> {
+        if !(bytes.len() as u32).is_multiple_of(self.block_align) {
+            return Err(AuError::MisalignedData { len: bytes.len(), block_align: self.block_align });
+        }
+        self.audio_byte_data.append(bytes);
+        Ok(())
+    }
+
+    /// Returns the audio data, with big-endian samples
+    pub fn bytes(&mut self) -> &mut Vec<u8> {
+        &mut self.audio_byte_data
+    }
+
+    /// Returns the encoding of the samples
+    pub fn encoding(&self) -> AuEncoding {
+        self.encoding
+    }
+
+    /// Returns the number of channels
+    pub fn num_channels(&self) -> u16 {
+        self.num_channels
+    }
+
+    /// Returns the sample rate in Hz
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the amount of bits per sample
+    pub fn bits_per_sample(&self) -> u32 {
+        self.encoding.bits_per_sample()
+    }
+
+    /// Returns the block align: the amount of bytes of a frame (one sample for every channel)
+    pub fn block_align(&self) -> u32 {
+        self.block_align
+    }
+
+    /// Returns the amount of bytes of audio data
+    pub fn data_len(&self) -> usize {
+        self.audio_byte_data.len()
+    }
+
+    /// Returns the number of frames (one sample for every channel) in the audio data
+    pub fn num_frames(&self) -> u64 {
+        if self.block_align == 0 {
+            return 0;
+        }
+        self.audio_byte_data.len() as u64 / self.block_align as u64
+    }
+
+    /// Returns the annotation: the free-form bytes between the header and the audio data, often
+    /// a nul-terminated description of the file.
+    pub fn annotation(&self) -> &[u8] {
+        &self.annotation
+    }
+
+    /// Sets the annotation, see [annotation](AuFile::annotation).
+    pub fn set_annotation(&mut self, annotation: Vec<u8>) {
+        self.annotation = annotation;
+    }
+
+    /// A byte representation of the `AuFile`.
+    ///
+    /// Audio data that doesn't fit in the 32-bit data size is written with the "size unknown"
+    /// data size.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::au_file::*;
+    /// let mut au = AuFile::new(AuEncoding::Linear16, 1, 8000);
+    /// au.add_bytes(&mut vec![0x03, 0xE8, 0xFF, 0xFE]);
+    ///
+    /// let bytes = au.to_bytes();
+    /// assert_eq!(&bytes[0..4], b".snd");
+    /// assert_eq!(&bytes[4..8], 24u32.to_be_bytes());
+    /// assert_eq!(&bytes[8..12], 4u32.to_be_bytes());
+    /// assert_eq!(&bytes[12..16], 3u32.to_be_bytes());
+    /// assert_eq!(bytes.len(), 24 + 4);
+    ///
+    /// let mut parsed = AuFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!(parsed.encoding(), AuEncoding::Linear16);
+    /// assert_eq!(parsed.bytes(), &[0x03, 0xE8, 0xFF, 0xFE]);
+    /// assert_eq!(parsed.to_bytes(), bytes);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let data_offset = HEADER_SIZE as usize + self.annotation.len();
+        let data_size = u32::try_from(self.audio_byte_data.len()).unwrap_or(UNKNOWN_DATA_SIZE);
+
+        let mut data = Vec::with_capacity(data_offset + self.audio_byte_data.len());
+        data.extend(MAGIC);
+        data.extend((data_offset as u32).to_be_bytes());
+        data.extend(data_size.to_be_bytes());
+        data.extend(self.encoding.get_val().to_be_bytes());
+        data.extend(self.sample_rate.to_be_bytes());
+        data.extend((self.num_channels as u32).to_be_bytes());
+        data.extend(&self.annotation);
+        data.extend(&self.audio_byte_data);
+        data
+    }
+}
+
+// Static functions
+impl AuFile {
+    /// Reads an au file and returns an `AuFile` populated with the spec from its header and its
+    /// audio data.
+    ///
+    /// # Errors
+    /// Returns the same errors as [fs::read()](std::fs::read) and
+    /// [from_bytes](AuFile::from_bytes).
+    pub fn from_file(file_path: &str) -> Result<AuFile, AuError> {
+        let file_content = fs::read(file_path)?;
+        AuFile::from_bytes(&file_content)
+    }
+
+    /// Parses an in-memory au file and returns an `AuFile` populated with the spec from its
+    /// header and its audio data.
+    ///
+    /// When the data size is unknown (`0xFFFFFFFF`), as written by programs that stream to a pipe,
+    /// the audio data runs to the end of `bytes`. A partial frame at the end of the audio data is
+    /// dropped.
+    ///
+    /// # Errors
+    /// - [AuError::NotAu] if `bytes` does not start with the `.snd` magic
+    /// - [AuError::Truncated] if the header is truncated, or declares more audio data than
+    ///   `bytes` contains
+    /// - [AuError::UnsupportedEncoding] if the encoding is not one of [AuEncoding]
+    /// - [AuError::TooManyChannels] if the header declares more than 65535 channels
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::au_file::*;
+    /// let mut bytes = b".snd".to_vec();
+    /// for field in [28, u32::MAX, 1, 8000, 2] {
+    ///     bytes.extend(field.to_be_bytes());
+    /// }
+    /// bytes.extend(b"ann\0");
+    /// bytes.extend([0xFF, 0x7F, 0x00]);
+    ///
+    /// let mut au = AuFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!((au.encoding(), au.num_channels()), (AuEncoding::MuLaw, 2));
+    /// assert_eq!(au.annotation(), b"ann\0");
+    /// assert_eq!(au.bytes(), &[0xFF, 0x7F]);
+    ///
+    /// assert!(matches!(AuFile::from_bytes(b"RIFF\0\0\0\0WAVE"), Err(AuError::NotAu)));
+    ///
+    /// bytes[20..24].copy_from_slice(&u32::MAX.to_be_bytes());
+    /// assert!(matches!(AuFile::from_bytes(&bytes), Err(AuError::TooManyChannels(u32::MAX))));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<AuFile, AuError> {
+        if !bytes.starts_with(&MAGIC) {
+            return Err(AuError::NotAu);
+        }
+        if bytes.len() < HEADER_SIZE as usize {
+            return Err(AuError::Truncated);
+        }
+        let u32_at = |i: usize| u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap());
+        let data_offset = u32_at(4) as usize;
+        let data_size = u32_at(8);
+        let encoding = AuEncoding::try_from(u32_at(12))?;
+        let num_channels = u32_at(20);
+        let num_channels = u16::try_from(num_channels).map_err(|_| AuError::TooManyChannels(num_channels))?;
+        let mut au = AuFile::new(encoding, num_channels, u32_at(16));
+
+        let annotation = bytes.get(HEADER_SIZE as usize..data_offset).ok_or(AuError::Truncated)?;
+        au.annotation = annotation.to_vec();
+        let data = &bytes[data_offset..];
+        let data = if data_size == UNKNOWN_DATA_SIZE {
+            data
+        } else {
+            data.get(..data_size as usize).ok_or(AuError::Truncated)?
+        };
+        // Only whole frames are kept
+        let block_align = (au.block_align as usize).max(1);
+        au.audio_byte_data = data[..data.len() - data.len() % block_align].to_vec();
+        Ok(au)
+    }
+}
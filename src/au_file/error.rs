@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors that can occur while working with an [AuFile](super::AuFile).
+#[derive(Debug)]
+pub enum AuError {
+    /// The bytes do not start with the `.snd` magic, so this is not an au file at all.
+    NotAu,
+    /// The encoding code in the header is not supported.
+    UnsupportedEncoding(u32),
+    /// The amount of bits per sample is not supported for the format.
+    UnsupportedBitsPerSample(u32),
+    /// The header declares more channels than a frame can hold, at most 65535 are supported.
+    /// Contains the number of channels.
+    TooManyChannels(u32),
+    /// The amount of bytes given is not divisible by the block align of the file, adding them
+    /// would cause un-aligned blocks.
+    MisalignedData {
+        /// The amount of bytes that was given
+        len: usize,
+        /// The block align the amount of bytes should be divisible by
+        block_align: u32,
+    },
+    /// The file ends in the middle of the header, or the header declares more audio data than the
+    /// file contains.
+    Truncated,
+    /// An I/O error occurred while reading or writing a file.
+    Io(io::Error),
+}
+
+impl fmt::Display for AuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuError::NotAu => write!(f, "not a Sun/NeXT au file"),
+            AuError::UnsupportedEncoding(encoding) => write!(f, "unsupported encoding {}", encoding),
+            AuError::UnsupportedBitsPerSample(bits) => write!(f, "unsupported bits per sample: {}", bits),
+            AuError::TooManyChannels(channels) => write!(f, "too many channels: {}", channels),
+            AuError::MisalignedData { len, block_align } => write!(
+                f,
+                "{} bytes do not fit evenly in blocks of {} bytes",
+                len, block_align
+            ),
+            AuError::Truncated => write!(f, "the file is truncated or the data size is corrupt"),
+            AuError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl Error for AuError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AuError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AuError {
+    fn from(err: io::Error) -> Self {
+        AuError::Io(err)
+    }
+}
@@ -0,0 +1,101 @@
+//! Conversion between au files and wave files.
+
+use std::mem;
+
+use super::{AuEncoding, AuError, AuFile};
+use crate::wave_file::{AudioFormat, WaveError, WaveFile};
+
+/// Converts the samples in `bytes` between big-endian au samples and little-endian wave samples.
+/// 8-bit linear samples are signed in au files and unsigned in wave files, so their sign bit is
+/// flipped instead. Companded samples are a single byte and are kept as they are.
+fn swap_samples(bytes: &mut [u8], encoding: AuEncoding) {
+    match encoding {
+        AuEncoding::MuLaw | AuEncoding::ALaw => {}
+        AuEncoding::Linear8 => bytes.iter_mut().for_each(|sample| *sample ^= 0x80),
+        encoding => bytes.chunks_exact_mut(encoding.bits_per_sample() as usize / 8).for_each(<[u8]>::reverse),
+    }
+}
+
+impl TryFrom<AuFile> for WaveFile {
+    type Error = WaveError;
+
+    /// Converts an `AuFile` to a `WaveFile` with the same spec, swapping the byte order of the
+    /// samples. Linear encodings become PCM, floating point encodings IEEE float and companded
+    /// encodings A-law or µ-law.
+    ///
+    /// # Errors
+    /// The same errors as [WaveFile::try_new], e.g. [WaveError::InvalidSpec] if the file has no
+    /// channels, a sample rate of 0 or a byte rate that doesn't fit in 32 bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::au_file::*;
+    /// # use jaudio::wave_file::*;
+    /// let mut au = AuFile::new(AuEncoding::Linear16, 1, 8000);
+    /// au.add_bytes(&mut [1000i16, -2].iter().flat_map(|s| s.to_be_bytes()).collect());
+    ///
+    /// let wave = WaveFile::try_from(au).unwrap();
+    /// assert_eq!((wave.audio_format(), wave.sample_rate()), (AudioFormat::PCM, 8000));
+    /// assert_eq!(wave.samples_i16().unwrap(), [1000, -2]);
+    ///
+    /// let mut au = AuFile::try_from(wave).unwrap();
+    /// assert_eq!(au.bytes(), &[0x03, 0xE8, 0xFF, 0xFE]);
+    ///
+    /// let au = AuFile::new(AuEncoding::Double, u16::MAX, u32::MAX);
+    /// assert!(matches!(WaveFile::try_from(au), Err(WaveError::InvalidSpec(_))));
+    /// ```
+    fn try_from(mut au: AuFile) -> Result<Self, Self::Error> {
+        let audio_format = match au.encoding {
+            AuEncoding::MuLaw => AudioFormat::MuLaw,
+            AuEncoding::ALaw => AudioFormat::ALaw,
+            AuEncoding::Float | AuEncoding::Double => AudioFormat::IeeeFloat,
+            _ => AudioFormat::PCM,
+        };
+        let mut wave = WaveFile::try_new(audio_format, au.num_channels, au.sample_rate, au.bits_per_sample() as u16)?;
+        swap_samples(&mut au.audio_byte_data, au.encoding);
+        wave.add_bytes(&mut au.audio_byte_data);
+        Ok(wave)
+    }
+}
+
+impl TryFrom<WaveFile> for AuFile {
+    type Error = AuError;
+
+    /// Converts a `WaveFile` to an `AuFile` with the same spec, swapping the byte order of the
+    /// samples. Metadata of the wave file is not kept.
+    ///
+    /// # Errors
+    /// [AuError::UnsupportedBitsPerSample] if the samples of a PCM file are not 8, 16, 24 or 32
+    /// bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::au_file::*;
+    /// # use jaudio::wave_file::*;
+    /// let mut pcm = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// pcm.add_samples_i16(&[0, 1000, -1000]).unwrap();
+    /// let mulaw = WaveFile::from_pcm16_as_mulaw(&pcm).unwrap();
+    ///
+    /// let au = AuFile::try_from(mulaw).unwrap();
+    /// assert_eq!(au.encoding(), AuEncoding::MuLaw);
+    /// assert_eq!(au.to_bytes().len(), 24 + 3);
+    /// ```
+    fn try_from(mut wave: WaveFile) -> Result<Self, Self::Error> {
+        let bits_per_sample = wave.bits_per_sample() as u32;
+        let encoding = match (wave.audio_format(), bits_per_sample) {
+            (AudioFormat::MuLaw, _) => AuEncoding::MuLaw,
+            (AudioFormat::ALaw, _) => AuEncoding::ALaw,
+            (AudioFormat::IeeeFloat, 32) => AuEncoding::Float,
+            (AudioFormat::IeeeFloat, _) => AuEncoding::Double,
+            (_, 8) => AuEncoding::Linear8,
+            (_, 16) => AuEncoding::Linear16,
+            (_, 24) => AuEncoding::Linear24,
+            (_, 32) => AuEncoding::Linear32,
+            (_, bits) => return Err(AuError::UnsupportedBitsPerSample(bits)),
+        };
+        let mut au = AuFile::new(encoding, wave.num_channels(), wave.sample_rate());
+        au.audio_byte_data = mem::take(wave.bytes());
+        swap_samples(&mut au.audio_byte_data, encoding);
+        Ok(au)
+    }
+}
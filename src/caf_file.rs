@@ -0,0 +1,336 @@
+use std::fs;
+
+mod error;
+mod wave;
+
+use crate::wave_file::AudioFormat;
+pub use error::CafError;
+
+/// The size of the file header: the `caff` magic, the file version and the file flags
+const HEADER_SIZE: usize = 8;
+/// The size of the payload of the Audio Description (`desc`) chunk
+const DESC_SIZE: usize = 32;
+/// The format id of linear PCM
+const FORMAT_LPCM: [u8; 4] = *b"lpcm";
+/// The format flag of floating point samples
+const FLAG_IS_FLOAT: u32 = 1 << 0;
+/// The format flag of little-endian samples
+const FLAG_IS_LITTLE_ENDIAN: u32 = 1 << 1;
+
+/// Stores data resembling a Core Audio Format (CAF) file with linear PCM audio.
+///
+/// The samples are stored little-endian, like in a wave file, whatever the byte order of the file
+/// they were read from.
+///
+/// # Parameters
+/// - `audio_format`: [PCM](AudioFormat::PCM) or [IeeeFloat](AudioFormat::IeeeFloat)
+/// - `num_channels`: the number of channels the file will have
+/// - `sample_rate`: The sample rate of the file in Hz (e.g. 44100.0, 48000.0, ...)
+/// - `bits_per_sample`: The amount of bits per sample: 8, 16, 24 or 32 for PCM and 32 or 64 for
+///   float. 8-bit samples are signed.
+///
+/// # Examples
+/// ```rust,no_run
+/// # use jaudio::caf_file::*;
+/// # use jaudio::wave_file::WaveFile;
+/// # use std::fs;
+/// #
+/// # fn main() {
+///  let caf = CafFile::from_file("audio.caf").unwrap();
+///
+///  let wave = WaveFile::try_from(caf).unwrap();
+///  fs::write("file.wav", wave.to_bytes()).unwrap();
+/// # }
+/// ```
+pub struct CafFile {
+    audio_format: AudioFormat,
+    num_channels: u16,
+    sample_rate: f64,
+    bits_per_sample: u32,
+    block_align: u32,
+    audio_byte_data: Vec<u8>, // Little-endian samples
+}
+
+// New
+impl CafFile {
+    /// ## Parameters
+    /// - `audio_format`: [PCM](AudioFormat::PCM) or [IeeeFloat](AudioFormat::IeeeFloat)
+    /// - `num_channels`: the number of channels the file will have
+    /// - `sample_rate`: The sample rate of the file in Hz (e.g. 44100.0, 48000.0, ...)
+    /// - `bits_per_sample`: The amount of bits per sample: 8, 16, 24 or 32 for PCM and 32 or 64
+    ///   for float.
+    ///
+    /// ## Panics
+    /// If `audio_format` is not PCM or IEEE float, or `bits_per_sample` is not supported for it:
+    /// ```rust,should_panic
+    /// # use jaudio::caf_file::*;
+    /// # use jaudio::wave_file::AudioFormat;
+    /// CafFile::new(AudioFormat::IeeeFloat, 2, 44100.0, 16);
+    /// ```
+    pub fn new(audio_format: AudioFormat, num_channels: u16, sample_rate: f64, bits_per_sample: u32) -> CafFile {
+        if !is_supported(audio_format, bits_per_sample) {
+            panic!("CAF files can't store {}-bit {:?} samples", bits_per_sample, audio_format);
+        }
+        let block_align = num_channels as u32 * (bits_per_sample / 8);
+        CafFile { audio_format, num_channels, sample_rate, bits_per_sample, block_align, audio_byte_data: Vec::new() }
+    }
+}
+
+/// Whether the samples of `audio_format` with `bits_per_sample` bits can be stored in a CAF file
+fn is_supported(audio_format: AudioFormat, bits_per_sample: u32) -> bool {
+    match audio_format {
+        AudioFormat::PCM => matches!(bits_per_sample, 8 | 16 | 24 | 32),
+        AudioFormat::IeeeFloat => matches!(bits_per_sample, 32 | 64),
+        _ => false,
+    }
+}
+
+// Functions on instance
+impl CafFile {
+    /// Adds little-endian audio data to `CafFile` from bytes.
+    ///
+    /// # Panics
+    /// If the amount of bytes is not divisible by `block_align`. Use
+    /// [try_add_bytes](CafFile::try_add_bytes) for a non-panicking version.
+    ///
+    /// # Parameters
+    /// - bytes: will be moved to `audio_byte_data` of `CafFile`, leaving `bytes` empty.
+    pub fn add_bytes(&mut self, bytes: &mut Vec<u8>) {
+        if self.try_add_bytes(bytes).is_err() {
+            panic!("Trying to add a chunck that does not fit evenly; this would cause un-aligned blocks.");
+        }
+    }
+
+    /// Adds little-endian audio data to `CafFile` from bytes, like
+    /// [add_bytes](CafFile::add_bytes), but returns an error instead of panicking.
+    ///
+    /// # Errors
+    /// [CafError::MisalignedData] if the amount of bytes is not divisible by `block_align`.
+    /// `bytes` is left untouched in that case.
+    pub fn try_add_bytes(&mut self, bytes: &mut Vec<u8>) -> Result<(), CafError> {
+        if !(bytes.len() as u32).is_multiple_of(self.block_align) {
+            return Err(CafError::MisalignedData { len: bytes.len(), block_align: self.block_align });
+        }
+        self.audio_byte_data.append(bytes);
+        Ok(())
+    }
+
+    /// Returns the audio data, with little-endian samples
+    pub fn bytes(&mut self) -> &mut Vec<u8> {
+        &mut self.audio_byte_data
+    }
+
+    /// Returns the format of the samples: PCM or IEEE float
+    pub fn audio_format(&self) -> AudioFormat {
+        self.audio_format
+    }
+
+    /// Returns the number of channels
+    pub fn num_channels(&self) -> u16 {
+        self.num_channels
+    }
+
+    /// Returns the sample rate in Hz
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Returns the amount of bits per sample
+    pub fn bits_per_sample(&self) -> u32 {
+        self.bits_per_sample
+    }
+
+    /// Returns the block align: the amount of bytes of a frame (one sample for every channel)
+    pub fn block_align(&self) -> u32 {
+        self.block_align
+    }
+
+    /// Returns the amount of bytes of audio data
+    pub fn data_len(&self) -> usize {
+        self.audio_byte_data.len()
+    }
+
+    /// Returns the number of frames (one sample for every channel) in the audio data
+    pub fn num_frames(&self) -> u64 {
+        if self.block_align == 0 {
+            return 0;
+        }
+        self.audio_byte_data.len() as u64 / self.block_align as u64
+    }
+
+    /// A byte representation of the `CafFile`, with a `desc` chunk and a `data` chunk with
+    /// little-endian samples.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::caf_file::*;
+    /// # use jaudio::wave_file::AudioFormat;
+    /// let mut caf = CafFile::new(AudioFormat::IeeeFloat, 1, 48000.0, 32);
+    /// caf.add_bytes(&mut 0.5f32.to_le_bytes().to_vec());
+    ///
+    /// let bytes = caf.to_bytes();
+    /// assert_eq!(&bytes[0..8], b"caff\0\x01\0\0");
+    /// assert_eq!(&bytes[8..12], b"desc");
+    /// assert_eq!(&bytes[20..28], 48000f64.to_be_bytes());
+    /// assert_eq!(&bytes[28..32], b"lpcm");
+    /// // Float and little-endian
+    /// assert_eq!(&bytes[32..36], 3u32.to_be_bytes());
+    /// assert_eq!(bytes.len(), 8 + 12 + 32 + 12 + 4 + 4);
+    ///
+    /// let mut parsed = CafFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!(parsed.audio_format(), AudioFormat::IeeeFloat);
+    /// assert_eq!(parsed.bytes(), &0.5f32.to_le_bytes());
+    /// assert_eq!(parsed.to_bytes(), bytes);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let data_len = self.audio_byte_data.len();
+        let mut flags = FLAG_IS_LITTLE_ENDIAN;
+        if self.audio_format == AudioFormat::IeeeFloat {
+            flags |= FLAG_IS_FLOAT;
+        }
+
+        let mut data = Vec::with_capacity(HEADER_SIZE + 12 + DESC_SIZE + 12 + 4 + data_len);
+        data.extend(b"caff");
+        data.extend(1u16.to_be_bytes()); // file version
+        data.extend(0u16.to_be_bytes()); // file flags
+        // Audio Description chunk
+        data.extend(b"desc");
+        data.extend((DESC_SIZE as i64).to_be_bytes());
+        data.extend(self.sample_rate.to_be_bytes());
+        data.extend(FORMAT_LPCM);
+        data.extend(flags.to_be_bytes());
+        data.extend(self.block_align.to_be_bytes()); // bytes per packet
+        data.extend(1u32.to_be_bytes()); // frames per packet
+        data.extend((self.num_channels as u32).to_be_bytes());
+        data.extend(self.bits_per_sample.to_be_bytes());
+        // Audio Data chunk, starting with the edit count
+        data.extend(b"data");
+        data.extend((4 + data_len as i64).to_be_bytes());
+        data.extend(0u32.to_be_bytes());
+        data.extend(&self.audio_byte_data);
+        data
+    }
+}
+
+// Static functions
+impl CafFile {
+    /// Reads a CAF file and returns a `CafFile` populated with the spec from its `desc` chunk and
+    /// the audio data from its `data` chunk.
+    ///
+    /// # Errors
+    /// Returns the same errors as [fs::read()](std::fs::read) and
+    /// [from_bytes](CafFile::from_bytes).
+    pub fn from_file(file_path: &str) -> Result<CafFile, CafError> {
+        let file_content = fs::read(file_path)?;
+        CafFile::from_bytes(&file_content)
+    }
+
+    /// Parses an in-memory CAF file and returns a `CafFile` populated with the spec from its
+    /// `desc` chunk and the audio data from its `data` chunk.
+    ///
+    /// Big-endian samples are converted to little-endian. A `data` chunk with a size of -1, as
+    /// written by programs that don't know the size up front, runs to the end of `bytes`. A
+    /// partial frame at the end of the audio data is dropped.
+    ///
+    /// # Errors
+    /// - [CafError::NotCaf] if `bytes` does not start with the `caff` magic
+    /// - [CafError::Truncated] if a chunk header is truncated, a chunk declares more bytes than
+    ///   `bytes` contains, or the `desc` chunk is too short
+    /// - [CafError::MissingChunk] if the `desc` or `data` chunk is missing
+    /// - [CafError::UnsupportedFormat] if the audio is not linear PCM
+    /// - [CafError::TooManyChannels] if the `desc` chunk declares more than 65535 channels
+    /// - [CafError::UnsupportedBitsPerSample] if the bits per sample are not supported, or the
+    ///   samples are not packed into whole bytes
+    ///
+    /// # Examples
+    /// A file with big-endian samples and a data chunk of unknown size:
+    /// ```rust
+    /// # use jaudio::caf_file::*;
+    /// # use jaudio::wave_file::AudioFormat;
+    /// let mut bytes = b"caff\0\x01\0\0desc".to_vec();
+    /// bytes.extend(32i64.to_be_bytes());
+    /// bytes.extend(44100f64.to_be_bytes());
+    /// bytes.extend(b"lpcm");
+    /// for field in [0u32, 4, 1, 2, 16] {
+    ///     bytes.extend(field.to_be_bytes());
+    /// }
+    /// bytes.extend(b"data");
+    /// bytes.extend((-1i64).to_be_bytes());
+    /// bytes.extend(0u32.to_be_bytes());
+    /// bytes.extend([0x03, 0xE8, 0xFF, 0xFE]);
+    ///
+    /// let mut caf = CafFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!((caf.audio_format(), caf.num_channels(), caf.sample_rate()), (AudioFormat::PCM, 2, 44100.0));
+    /// assert_eq!(caf.bytes(), &[0xE8, 0x03, 0xFE, 0xFF]);
+    ///
+    /// assert!(matches!(CafFile::from_bytes(b"RIFF\0\0\0\0WAVE"), Err(CafError::NotCaf)));
+    ///
+    /// bytes[44..48].copy_from_slice(&u32::MAX.to_be_bytes());
+    /// assert!(matches!(CafFile::from_bytes(&bytes), Err(CafError::TooManyChannels(u32::MAX))));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<CafFile, CafError> {
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != b"caff" {
+            return Err(CafError::NotCaf);
+        }
+        let mut desc = None;
+        let mut data = None;
+        let mut pos = HEADER_SIZE;
+        while pos < bytes.len() {
+            let header = bytes.get(pos..pos + 12).ok_or(CafError::Truncated)?;
+            let id: [u8; 4] = header[0..4].try_into().unwrap();
+            let size = i64::from_be_bytes(header[4..12].try_into().unwrap());
+            let start = pos + 12;
+            let chunk = match size {
+                // Only the data chunk can have an unknown size, it is the last chunk
+                -1 if &id == b"data" => &bytes[start..],
+                size => usize::try_from(size)
+                    .ok()
+                    .and_then(|size| bytes.get(start..start.checked_add(size)?))
+                    .ok_or(CafError::Truncated)?,
+            };
+            match &id {
+                b"desc" if desc.is_none() => desc = Some(chunk),
+                b"data" if data.is_none() => data = Some(chunk),
+                _ => {}
+            }
+            pos = start + chunk.len();
+        }
+        let desc = desc.ok_or(CafError::MissingChunk("desc"))?;
+        let data = data.ok_or(CafError::MissingChunk("data"))?;
+        if desc.len() < DESC_SIZE {
+            return Err(CafError::Truncated);
+        }
+
+        let u32_at = |i: usize| u32::from_be_bytes(desc[i..i + 4].try_into().unwrap());
+        let sample_rate = f64::from_be_bytes(desc[0..8].try_into().unwrap());
+        let format_id: [u8; 4] = desc[8..12].try_into().unwrap();
+        let flags = u32_at(12);
+        let bytes_per_packet = u32_at(16);
+        let frames_per_packet = u32_at(20);
+        let num_channels = u32_at(24);
+        let num_channels = u16::try_from(num_channels).map_err(|_| CafError::TooManyChannels(num_channels))?;
+        let bits_per_sample = u32_at(28);
+        if format_id != FORMAT_LPCM {
+            return Err(CafError::UnsupportedFormat(format_id));
+        }
+        let audio_format = if flags & FLAG_IS_FLOAT != 0 { AudioFormat::IeeeFloat } else { AudioFormat::PCM };
+        if !is_supported(audio_format, bits_per_sample) {
+            return Err(CafError::UnsupportedBitsPerSample(bits_per_sample));
+        }
+        let mut caf = CafFile::new(audio_format, num_channels, sample_rate, bits_per_sample);
+        // Samples that are not packed, like 24-bit samples in 4 bytes, are not supported
+        if frames_per_packet != 1 || bytes_per_packet != caf.block_align {
+            return Err(CafError::UnsupportedBitsPerSample(bits_per_sample));
+        }
+
+        // The audio data is preceded by the edit count
+        let audio = data.get(4..).ok_or(CafError::Truncated)?;
+        let block_align = (caf.block_align as usize).max(1);
+        caf.audio_byte_data = audio[..audio.len() - audio.len() % block_align].to_vec();
+        if flags & FLAG_IS_LITTLE_ENDIAN == 0 {
+            let sample_size = (bits_per_sample / 8) as usize;
+            caf.audio_byte_data.chunks_exact_mut(sample_size).for_each(<[u8]>::reverse);
+        }
+        Ok(caf)
+    }
+}
@@ -0,0 +1,70 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors that can occur while working with a [CafFile](super::CafFile).
+#[derive(Debug)]
+pub enum CafError {
+    /// The bytes do not start with the `caff` magic, so this is not a CAF file at all.
+    NotCaf,
+    /// A chunk that is required is not present in the file. Contains the type of the chunk, e.g.
+    /// `"desc"` or `"data"`.
+    MissingChunk(&'static str),
+    /// The format of the audio is not supported. Only linear PCM (`lpcm`) can be stored. Contains
+    /// the format id, e.g. `*b"aac "`.
+    UnsupportedFormat([u8; 4]),
+    /// The amount of bits per sample, or the way the samples are packed, is not supported.
+    UnsupportedBitsPerSample(u32),
+    /// The `desc` chunk declares more channels than a frame can hold, at most 65535 are
+    /// supported. Contains the number of channels.
+    TooManyChannels(u32),
+    /// The amount of bytes given is not divisible by the block align of the file, adding them
+    /// would cause un-aligned blocks.
+    MisalignedData {
+        /// The amount of bytes that was given
+        len: usize,
+        /// The block align the amount of bytes should be divisible by
+        block_align: u32,
+    },
+    /// The file ends in the middle of a header, or a chunk declares more bytes than the file
+    /// contains.
+    Truncated,
+    /// An I/O error occurred while reading or writing a file.
+    Io(io::Error),
+}
+
+impl fmt::Display for CafError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CafError::NotCaf => write!(f, "not a CAF file"),
+            CafError::MissingChunk(id) => write!(f, "missing `{}` chunk", id),
+            CafError::UnsupportedFormat(id) => {
+                write!(f, "unsupported audio format `{}`", String::from_utf8_lossy(id))
+            }
+            CafError::UnsupportedBitsPerSample(bits) => write!(f, "unsupported bits per sample: {}", bits),
+            CafError::TooManyChannels(channels) => write!(f, "too many channels: {}", channels),
+            CafError::MisalignedData { len, block_align } => write!(
+                f,
+                "{} bytes do not fit evenly in blocks of {} bytes",
+                len, block_align
+            ),
+            CafError::Truncated => write!(f, "the file is truncated or a chunk size is corrupt"),
+            CafError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl Error for CafError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CafError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CafError {
+    fn from(err: io::Error) -> Self {
+        CafError::Io(err)
+    }
+}
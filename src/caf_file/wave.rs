@@ -0,0 +1,89 @@
+//! Conversion between CAF files and wave files.
+
+use std::mem;
+
+use super::{CafError, CafFile};
+use crate::wave_file::{AudioFormat, WaveError, WaveFile};
+
+/// Flips the sign bit of 8-bit samples, which are signed in CAF files and unsigned in wave files.
+/// Samples of other sizes are little-endian in both.
+fn convert_samples(bytes: &mut [u8], audio_format: AudioFormat, bits_per_sample: u32) {
+    if audio_format == AudioFormat::PCM && bits_per_sample == 8 {
+        bytes.iter_mut().for_each(|sample| *sample ^= 0x80);
+    }
+}
+
+impl TryFrom<CafFile> for WaveFile {
+    type Error = WaveError;
+
+    /// Converts a `CafFile` to a `WaveFile` with the same spec. The sample rate is rounded to the
+    /// nearest Hz.
+    ///
+    /// # Errors
+    /// The same errors as [WaveFile::try_new], e.g. [WaveError::InvalidSpec] if the file has no
+    /// channels, a sample rate that rounds to 0 or a byte rate that doesn't fit in 32 bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::caf_file::*;
+    /// # use jaudio::wave_file::*;
+    /// let mut caf = CafFile::new(AudioFormat::PCM, 1, 22050.0, 16);
+    /// caf.add_bytes(&mut [1000i16, -2].iter().flat_map(|s| s.to_le_bytes()).collect());
+    ///
+    /// let wave = WaveFile::try_from(caf).unwrap();
+    /// assert_eq!((wave.audio_format(), wave.sample_rate()), (AudioFormat::PCM, 22050));
+    /// assert_eq!(wave.samples_i16().unwrap(), [1000, -2]);
+    ///
+    /// let caf = CafFile::try_from(wave).unwrap();
+    /// assert_eq!(caf.sample_rate(), 22050.0);
+    ///
+    /// let caf = CafFile::new(AudioFormat::PCM, 2, 0.2, 16);
+    /// assert!(matches!(WaveFile::try_from(caf), Err(WaveError::InvalidSpec(_))));
+    /// ```
+    fn try_from(mut caf: CafFile) -> Result<Self, Self::Error> {
+        let sample_rate = caf.sample_rate.round() as u32;
+        let mut wave = WaveFile::try_new(caf.audio_format, caf.num_channels, sample_rate, caf.bits_per_sample as u16)?;
+        convert_samples(&mut caf.audio_byte_data, caf.audio_format, caf.bits_per_sample);
+        wave.add_bytes(&mut caf.audio_byte_data);
+        Ok(wave)
+    }
+}
+
+impl TryFrom<WaveFile> for CafFile {
+    type Error = CafError;
+
+    /// Converts a PCM or IEEE float `WaveFile` to a `CafFile` with the same spec. Metadata of the
+    /// wave file is not kept.
+    ///
+    /// # Errors
+    /// [CafError::UnsupportedFormat] for A-law and µ-law files (with the `alaw` or `ulaw` format
+    /// id) and [CafError::UnsupportedBitsPerSample] if the bits per sample are not supported for
+    /// the format.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::caf_file::*;
+    /// # use jaudio::wave_file::*;
+    /// let mut pcm = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// pcm.add_samples_i16(&[0, 1000]).unwrap();
+    /// let mulaw = WaveFile::from_pcm16_as_mulaw(&pcm).unwrap();
+    ///
+    /// assert!(matches!(CafFile::try_from(mulaw), Err(CafError::UnsupportedFormat(id)) if &id == b"ulaw"));
+    /// ```
+    fn try_from(mut wave: WaveFile) -> Result<Self, Self::Error> {
+        let audio_format = wave.audio_format();
+        let bits_per_sample = wave.bits_per_sample() as u32;
+        match audio_format {
+            AudioFormat::ALaw => return Err(CafError::UnsupportedFormat(*b"alaw")),
+            AudioFormat::MuLaw => return Err(CafError::UnsupportedFormat(*b"ulaw")),
+            _ if !super::is_supported(audio_format, bits_per_sample) => {
+                return Err(CafError::UnsupportedBitsPerSample(bits_per_sample));
+            }
+            _ => {}
+        }
+        let mut caf = CafFile::new(audio_format, wave.num_channels(), wave.sample_rate() as f64, bits_per_sample);
+        caf.audio_byte_data = mem::take(wave.bytes());
+        convert_samples(&mut caf.audio_byte_data, audio_format, bits_per_sample);
+        Ok(caf)
+    }
+}
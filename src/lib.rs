@@ -5,9 +5,31 @@
 //!
 //! Currently implemented:
 //! - [Wave Files](wave_file/WaveFile)
+//! - [AIFF Files](aiff_file/AiffFile)
+//! - [Sun/NeXT au Files](au_file/AuFile)
+//! - [Core Audio Format Files](caf_file/CafFile)
 
 /// For building and reading wave audio files.
 ///
 /// ## Valuable resources
 /// [*WAVE PCM soundfile format*. Stanford.edu (Dec 10, 2008). (Wayback machine link)](https://web.archive.org/web/20081210162727/https://ccrma.stanford.edu/CCRMA/Courses/422/projects/WaveFormat/")
-pub mod wave_file;
\ No newline at end of file
+pub mod wave_file;
+
+/// For building and reading AIFF audio files, and converting them to and from wave files.
+///
+/// ## Valuable resources
+/// [*Audio Interchange File Format: "AIFF"*. Apple Computer, Inc. (Jan 4, 1989).](https://www.mmsp.ece.mcgill.ca/Documents/AudioFormats/AIFF/Docs/AIFF-1.3.pdf)
+pub mod aiff_file;
+
+/// For building and reading Sun/NeXT au audio files, and converting them to and from wave files.
+///
+/// ## Valuable resources
+/// [*Audio File Formats: AU*. McGill University.](https://www.mmsp.ece.mcgill.ca/Documents/AudioFormats/AU/AU.html)
+pub mod au_file;
+
+/// For building and reading Core Audio Format (CAF) files with linear PCM audio, and converting
+/// them to and from wave files.
+///
+/// ## Valuable resources
+/// [*Core Audio Format Specification*. Apple Inc. (Mar 8, 2006).](https://developer.apple.com/library/archive/documentation/MusicAudio/Reference/CAFSpec/CAF_intro/CAF_intro.html)
+pub mod caf_file;
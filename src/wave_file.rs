@@ -1,16 +1,163 @@
-use std::error::Error;
 use std::fs;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+mod acid;
+mod adpcm;
+mod append;
+#[cfg(feature = "tokio")]
+mod async_io;
+mod bext;
+mod builder;
+mod channels;
+mod checksum;
+mod compare;
+pub(crate) mod chunk;
+mod convert;
+mod csv;
+mod cue;
+mod data;
+mod edit;
+mod error;
+mod fade;
+#[cfg(feature = "flac")]
+mod flac;
+mod fmt;
+mod frames;
+mod g711;
+pub mod generate;
+#[cfg(any(feature = "flac", feature = "mp3"))]
+mod id3;
+mod info;
+mod inspect;
+mod json;
+mod metadata;
+mod level;
+mod mix;
+#[cfg(feature = "mp3")]
+mod mp3;
+mod parallel;
+mod peaks;
+mod process;
+mod progress;
+mod raw;
+mod recover;
+mod reader;
+mod repeat;
+mod resample;
+mod rf64;
+mod rifx;
+mod sample;
+mod sampler;
+mod silence;
+mod simd;
+mod spec;
+mod split;
+mod stream;
+mod validate;
+mod view;
+mod w64;
+mod writer;
+
+use chunk::{Chunks, HeaderWriter};
+use data::AudioData;
+use fmt::{FmtChunk, SUBFORMAT_GUID_TAIL};
+use rf64::Container;
+pub use acid::AcidInfo;
+#[cfg(feature = "tokio")]
+pub use async_io::AsyncWaveWriter;
+pub use bext::BroadcastExt;
+pub use builder::WaveBuilder;
+pub use channels::DownmixMode;
+pub use compare::CompareReport;
+pub use convert::DitherMode;
+pub use csv::SampleTextFormat;
+pub use cue::CuePoint;
+pub use edit::TrimEnd;
+pub use error::WaveError;
+pub use fade::FadeCurve;
+pub use frames::{ChannelSamples, FrameSlices, FrameWindows, Samples};
+pub use generate::SweepKind;
+pub use info::Info;
+pub use inspect::{ChunkInfo, WaveInfo};
+pub use level::ClipRegion;
+pub use mix::ClipPolicy;
+pub use peaks::peaks_from_file;
+pub use process::{process_file, process_file_with_progress, WaveOp};
+pub use progress::ProgressUpdate;
+pub use raw::{ByteOrder, Signedness};
+pub use reader::{read_range, Frames, WaveReader};
+pub use recover::{repair_header, ParseOptions, RepairReport};
+pub use resample::ResampleQuality;
+pub use rf64::Rf64Mode;
+pub use sample::{Sample, I24};
+pub use sampler::{LoopRegion, LoopType, SamplerInfo};
+pub use spec::WaveSpec;
+pub use split::split_file_every;
+pub use stream::WaveFileReader;
+pub use validate::ValidationIssue;
+pub use view::WaveView;
+pub use writer::WaveWriter;
+
+/// A four character code identifying a RIFF chunk, like `*b"data"`.
+pub type FourCC = [u8; 4];
 
 /// The possible audio formats for [WaveFile](wave_file/WaveFile)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AudioFormat {
     /// PCM format
-    PCM
+    PCM,
+    /// IEEE 754 floating point format, with 32 or 64 bits per sample
+    IeeeFloat,
+    /// `WAVE_FORMAT_EXTENSIBLE`: PCM data with an extended fmt chunk that contains the valid bits
+    /// per sample, a channel mask and a sub format GUID.
+    ///
+    /// Files with more than 2 channels are always written this way. Use
+    /// [set_extensible](WaveFile::set_extensible) to write float data with this format.
+    Extensible,
+    /// ITU G.711 A-law companded 8-bit samples
+    ALaw,
+    /// ITU G.711 µ-law companded 8-bit samples
+    MuLaw,
 }
 
 impl AudioFormat {
-    fn get_val(&self) -> u32 {
+    /// The format code as stored in the 2 byte audio format field of the fmt chunk
+    pub(crate) fn get_val(&self) -> u16 {
         match self {
-            AudioFormat::PCM => 1
+            AudioFormat::PCM => 1,
+            AudioFormat::IeeeFloat => 3,
+            AudioFormat::Extensible => 0xFFFE,
+            AudioFormat::ALaw => 6,
+            AudioFormat::MuLaw => 7,
+        }
+    }
+}
+
+impl TryFrom<u16> for AudioFormat {
+    type Error = WaveError;
+    
+    /// Maps a raw format code from a fmt chunk back to an `AudioFormat`.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] if the code does not correspond to any `AudioFormat`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// assert_eq!(AudioFormat::try_from(1).unwrap(), AudioFormat::PCM);
+    /// assert!(matches!(AudioFormat::try_from(0x55), Err(WaveError::UnsupportedFormat(0x55))));
+    /// ```
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        match code {
+            1 => Ok(AudioFormat::PCM),
+            3 => Ok(AudioFormat::IeeeFloat),
+            0xFFFE => Ok(AudioFormat::Extensible),
+            6 => Ok(AudioFormat::ALaw),
+            7 => Ok(AudioFormat::MuLaw),
+            _ => Err(WaveError::UnsupportedFormat(code)),
         }
     }
 }
@@ -22,13 +169,13 @@ impl AudioFormat {
 /// - `num_channels`: the number of channels the file will have (mono or stereo)
 /// - `sample_rate`: The sample rate of tha wave file in Hz (e.g. 22050, 44100, ...)
 /// - `bits_per_sample`: The amount of bits per sample. If 16 bits, the audio sample will contain 2
-/// bytes per channel. (e.g. 8, 16, ...). Important to take into account when adding bytes to the WaveFile!
+///   bytes per channel. (e.g. 8, 16, ...). Important to take into account when adding bytes to the WaveFile!
 /// - `byte_rate`
 /// - `block_align`
 /// - `chunks`: contains all audio data
 ///
 /// # Examples
-/// ```rust
+/// ```rust,no_run
 /// # use jaudio::wave_file::*;
 /// # use std::fs;
 /// #
@@ -45,15 +192,27 @@ impl AudioFormat {
 ///  fs::write(path, wave.to_bytes()).unwrap();
 /// # }
 /// ```
+#[derive(Clone)]
 pub struct WaveFile {
-    audio_format: u32,
-    num_channels: u32,
+    audio_format: u16,
+    num_channels: u16,
     sample_rate: u32,
-    bits_per_sample: u32,
+    bits_per_sample: u16,
+    valid_bits_per_sample: u16,
     // Non-public
     byte_rate: u32,
-    block_align: u32,
-    audio_byte_data: Vec<u8>, // Vector of bytes
+    block_align: u16,
+    extensible: bool,
+    channel_mask: u32,
+    fact_frames: Option<u32>,
+    info: Info,
+    cue_points: Vec<CuePoint>,
+    sampler_info: Option<SamplerInfo>,
+    broadcast_ext: Option<BroadcastExt>,
+    acid_info: Option<AcidInfo>,
+    extra_chunks: Vec<(FourCC, Vec<u8>)>,
+    rf64_mode: Rf64Mode,
+    audio_byte_data: AudioData,
 }
 
 // New
@@ -63,22 +222,187 @@ impl WaveFile {
     /// - `num_channels`: the number of channels the file will have (mono or stereo)
     /// - `sample_rate`: The sample rate of tha wave file in Hz (e.g. 22050, 44100, ...)
     /// - `bits_per_sample`: The amount of bits per sample. If 16 bits, the audio sample will contain 2
-    /// bytes per channel. (e.g. 8, 16, ...). Important to take into account when adding bytes to the WaveFile!
+    ///   bytes per channel. (e.g. 8, 16, ...). Important to take into account when adding bytes to the WaveFile!
+    ///   PCM samples of a size that isn't a multiple of 8 (e.g. 12 bits) are stored in the next
+    ///   whole amount of bytes, see [valid_bits_per_sample](WaveFile::valid_bits_per_sample).
+    ///
+    /// ## Panics
+    /// If `audio_format` is [AudioFormat::IeeeFloat] and `bits_per_sample` is not 32 or 64, or if
+    /// it is [AudioFormat::ALaw] or [AudioFormat::MuLaw] and `bits_per_sample` is not 8. Also if
+    /// the block align doesn't fit in 16 bits or the byte rate doesn't fit in 32 bits:
+    /// ```rust,should_panic
+    /// # use jaudio::wave_file::*;
+    /// WaveFile::new(AudioFormat::PCM, 65535, 192000, 32);
+    /// ```
+    ///
+    /// Other invalid arguments, like 0 channels, aren't checked. Use [try_new](WaveFile::try_new)
+    /// for a constructor that checks all of them.
     pub fn new(
         audio_format: AudioFormat,
-        num_channels: u32,
+        num_channels: u16,
         sample_rate: u32,
-        bits_per_sample: u32
+        bits_per_sample: u16
     ) -> WaveFile
     {
+        if audio_format == AudioFormat::IeeeFloat && bits_per_sample != 32 && bits_per_sample != 64 {
+            panic!("IEEE float wave files need 32 or 64 bits per sample, got {}", bits_per_sample);
+        }
+        if matches!(audio_format, AudioFormat::ALaw | AudioFormat::MuLaw) && bits_per_sample != 8 {
+            panic!("A-law and µ-law wave files need 8 bits per sample, got {}", bits_per_sample);
+        }
+        
+        // Samples of e.g. 12 bits are stored in 16-bit containers
+        let valid_bits_per_sample = bits_per_sample;
+        let bits_per_sample = bits_per_sample.next_multiple_of(8);
+        
         // Subchunck 1 calculations
-        let byte_rate = sample_rate * num_channels * (bits_per_sample / 8);
-        let block_align = num_channels * (bits_per_sample / 8);
+        let block_align = num_channels.checked_mul(bits_per_sample / 8);
+        let byte_rate = block_align.and_then(|block_align| (block_align as u32).checked_mul(sample_rate));
+        let (Some(byte_rate), Some(block_align)) = (byte_rate, block_align) else {
+            panic!("the block align or byte rate of {} channels of {} bits at {} Hz doesn't fit", num_channels, bits_per_sample, sample_rate);
+        };
         
-        let audio_format: u32 = audio_format.get_val();
+        // Extensible is written with a PCM sub format
+        let extensible = audio_format == AudioFormat::Extensible;
+        let audio_format = if extensible { AudioFormat::PCM.get_val() } else { audio_format.get_val() };
+        let channel_mask = fmt::default_channel_mask(num_channels);
         
         // Return new WaveFile
-        WaveFile{ audio_format, num_channels, sample_rate, bits_per_sample, byte_rate, block_align, audio_byte_data: Vec::new() }
+        WaveFile{ audio_format, num_channels, sample_rate, bits_per_sample, valid_bits_per_sample, byte_rate, block_align, extensible, channel_mask, fact_frames: None, info: Info::default(), cue_points: Vec::new(), sampler_info: None, broadcast_ext: None, acid_info: None, extra_chunks: Vec::new(), rf64_mode: Rf64Mode::Auto, audio_byte_data: AudioData::new(block_align) }
+    }
+    
+    /// Creates a new `WaveFile` like [new](WaveFile::new), but returns an error instead of
+    /// creating a file that can't be written or read back.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedBitsPerSample] if `bits_per_sample` is not supported for
+    ///   `audio_format`: 1 to 32 for PCM and extensible PCM, 32 or 64 for IEEE float and 8 for
+    ///   A-law and µ-law
+    /// - [WaveError::InvalidSpec] if `num_channels` or `sample_rate` is 0, the block align
+    ///   doesn't fit in 16 bits or the byte rate doesn't fit in 32 bits
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let wave = WaveFile::try_new(AudioFormat::PCM, 2, 44100, 16).unwrap();
+    /// assert_eq!(wave.block_align(), 4);
+    ///
+    /// assert!(matches!(WaveFile::try_new(AudioFormat::PCM, 0, 44100, 0), Err(WaveError::UnsupportedBitsPerSample(0))));
+    /// assert!(matches!(WaveFile::try_new(AudioFormat::PCM, 0, 44100, 16), Err(WaveError::InvalidSpec(_))));
+    /// assert!(matches!(WaveFile::try_new(AudioFormat::PCM, 40000, 44100, 16), Err(WaveError::InvalidSpec(_))));
+    /// assert!(matches!(WaveFile::try_new(AudioFormat::IeeeFloat, 1, 44100, 16), Err(WaveError::UnsupportedBitsPerSample(16))));
+    /// assert!(matches!(WaveFile::try_new(AudioFormat::PCM, 1, 44100, u16::MAX), Err(WaveError::UnsupportedBitsPerSample(u16::MAX))));
+    /// ```
+    pub fn try_new(
+        audio_format: AudioFormat,
+        num_channels: u16,
+        sample_rate: u32,
+        bits_per_sample: u16
+    ) -> Result<WaveFile, WaveError>
+    {
+        // Extensible files are written with a PCM sub format
+        let format = if audio_format == AudioFormat::Extensible { AudioFormat::PCM } else { audio_format };
+        // PCM samples are stored in whole bytes
+        let container_bits = if format == AudioFormat::PCM { bits_per_sample.checked_next_multiple_of(8) } else { Some(bits_per_sample) };
+        let Some(container_bits) = container_bits.filter(|&bits| bits_per_sample > 0 && sample::check_supported(format, bits).is_ok()) else {
+            return Err(WaveError::UnsupportedBitsPerSample(bits_per_sample));
+        };
+        WaveSpec { format, channels: num_channels, sample_rate, bits_per_sample: container_bits }.validate()?;
+        Ok(WaveFile::new(audio_format, num_channels, sample_rate, bits_per_sample))
+    }
+}
+
+impl Default for WaveFile {
+    /// An empty file of [WaveSpec::cd_quality]: stereo 16-bit PCM at 44.1 kHz.
+    fn default() -> Self {
+        WaveFile::new_cd_quality()
+    }
+}
+
+impl PartialEq for WaveFile {
+    /// Two files are equal if they have the same [spec](WaveFile::spec) and audio data. Metadata
+    /// like the [info](WaveFile::info) or cue points isn't compared.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_samples_i16(&[1, 2, 3]).unwrap();
+    ///
+    /// let mut other = wave.clone();
+    /// other.info_mut().title = Some("Other".to_string());
+    /// assert_eq!(wave, other);
+    ///
+    /// other.add_samples_i16(&[4]).unwrap();
+    /// assert_ne!(wave, other);
+    /// ```
+    fn eq(&self, other: &Self) -> bool {
+        self.spec() == other.spec() && self.audio_byte_data == other.audio_byte_data
+    }
+}
+
+impl Eq for WaveFile {}
+
+impl std::fmt::Debug for WaveFile {
+    /// Shows the spec and the size of the audio data, but not the audio data itself, so the
+    /// output stays short for long files.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_bytes(&mut vec![0; 4 * 44100 * 60]);
+    /// assert_eq!(
+    ///     format!("{:?}", wave),
+    ///     "WaveFile { format: PCM, channels: 2, sample_rate: 44100, bits_per_sample: 16, num_frames: 2646000, data_len: 10584000, .. }"
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WaveFile")
+            .field("format", &self.audio_format())
+            .field("channels", &self.num_channels)
+            .field("sample_rate", &self.sample_rate)
+            .field("bits_per_sample", &self.bits_per_sample)
+            .field("num_frames", &self.num_frames())
+            .field("data_len", &self.audio_byte_data.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for WaveFile {
+    /// Shows a one line summary of the spec, the duration as `m:ss.mmm` and the size of the audio
+    /// data with a binary prefix.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_bytes(&mut vec![0; 4 * (44100 * 222 + 6615)]);
+    /// assert_eq!(wave.to_string(), "PCM 44100 Hz, 2 ch, 16-bit, 3:42.150 (37.4 MiB)");
+    ///
+    /// let wave = WaveFile::new(AudioFormat::MuLaw, 1, 8000, 8);
+    /// assert_eq!(wave.to_string(), "µ-law 8000 Hz, 1 ch, 8-bit, 0:00.000 (0 B)");
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = match self.audio_format() {
+            AudioFormat::PCM | AudioFormat::Extensible => "PCM",
+            AudioFormat::IeeeFloat => "IEEE float",
+            AudioFormat::ALaw => "A-law",
+            AudioFormat::MuLaw => "µ-law",
+        };
+        let millis = self.duration().as_millis();
+        write!(
+            f,
+            "{} {} Hz, {} ch, {}-bit, {}:{:02}.{:03} (",
+            format, self.sample_rate, self.num_channels, self.valid_bits_per_sample,
+            millis / 60_000, millis / 1000 % 60, millis % 1000
+        )?;
+        let len = self.audio_byte_data.len() as f64;
+        let prefixes = ["KiB", "MiB", "GiB", "TiB"];
+        match (1..=prefixes.len() as i32).rev().find(|&power| len >= 1024f64.powi(power)) {
+            Some(power) => write!(f, "{:.1} {})", len / 1024f64.powi(power), prefixes[power as usize - 1]),
+            None => write!(f, "{} B)", len),
+        }
     }
 }
 
@@ -91,125 +415,735 @@ impl WaveFile {
     ///
     /// # Panics
     /// If the given `bytes` do not conform to the sample size in bytes. So, if it is not
-    /// divisible by `block_align`.
+    /// divisible by `block_align`. Use [try_add_bytes](WaveFile::try_add_bytes) for a
+    /// non-panicking version.
     ///
     /// # Parameters
     /// - bytes: will be moved to `audio_byte_data` of `WaveFile`, leaving `bytes` empty.
     pub fn add_bytes(&mut self, bytes: &mut Vec<u8>) {
-        // Ex. if each sample is 2 bytes long -> don't allow add_bytes methodif the amount of bytes is not % by 2
-        if bytes.len() as u32 % self.block_align != 0 {
+        if self.try_add_bytes(bytes).is_err() {
             panic!("Trying to add a chunck that does not fit evenly; this would cause un-aligned blocks.");
         }
+    }
+    
+    /// Adds audio data to `WaveFile` from bytes, like [add_bytes](WaveFile::add_bytes), but returns
+    /// an error instead of panicking.
+    ///
+    /// # Errors
+    /// [WaveError::MisalignedData] if the amount of bytes is not divisible by `block_align`.
+    /// `bytes` is left untouched in that case, so the caller can pad, truncate or reject it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    ///
+    /// let mut bytes = vec![0; 6];
+    /// match wave.try_add_bytes(&mut bytes) {
+    ///     Err(WaveError::MisalignedData { len, block_align }) => {
+    ///         assert_eq!((len, block_align), (6, 4));
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// assert_eq!(bytes.len(), 6);
+    /// ```
+    pub fn try_add_bytes(&mut self, bytes: &mut Vec<u8>) -> Result<(), WaveError> {
+        // Ex. if each sample is 2 bytes long -> don't allow add_bytes methodif the amount of bytes is not % by 2
+        if !bytes.len().is_multiple_of(self.block_align as usize) {
+            return Err(WaveError::MisalignedData { len: bytes.len(), block_align: self.block_align });
+        }
         
         self.audio_byte_data.append(bytes);
+        Ok(())
+    }
+    
+    /// Adds a copy of `bytes` to the audio data, like [try_add_bytes](WaveFile::try_add_bytes),
+    /// but leaves the caller's buffer as it is.
+    ///
+    /// # Errors
+    /// [WaveError::MisalignedData] if the amount of bytes is not divisible by `block_align`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// let bytes = [1, 0, 2, 0];
+    /// wave.add_bytes_from_slice(&bytes).unwrap();
+    /// wave.add_bytes_from_slice(&bytes).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [1, 2, 1, 2]);
+    ///
+    /// assert!(wave.add_bytes_from_slice(&bytes[..3]).is_err());
+    /// ```
+    pub fn add_bytes_from_slice(&mut self, bytes: &[u8]) -> Result<(), WaveError> {
+        if !(bytes.len() as u64).is_multiple_of(self.block_align as u64) {
+            return Err(WaveError::MisalignedData { len: bytes.len(), block_align: self.block_align });
+        }
+        self.audio_byte_data.extend_from_slice(bytes);
+        Ok(())
     }
     
-    /// Returns the audio data
+    /// Replaces the audio data with a shared buffer, without copying it.
+    ///
+    /// The buffer is only read while the data isn't changed, so a large capture buffer can be
+    /// written with [write_to](WaveFile::write_to) or read as samples without a copy. Changing
+    /// the data in place, e.g. with [bytes](WaveFile::bytes), copies the buffer first; samples
+    /// added later are stored next to it.
+    ///
+    /// # Errors
+    /// [WaveError::MisalignedData] if the amount of bytes is not divisible by `block_align`. The
+    /// audio data is left as it is then.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::sync::Arc;
+    /// let capture: Arc<[u8]> = Arc::from(vec![1, 0, 2, 0, 3, 0, 4, 0]);
+    ///
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 8000, 16);
+    /// wave.set_data_shared(Arc::clone(&capture)).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [1, 2, 3, 4]);
+    ///
+    /// // The capture buffer isn't changed by changes to the file
+    /// wave.add_samples_i16(&[5, 6]).unwrap();
+    /// assert_eq!(wave.num_frames(), 3);
+    /// assert_eq!(capture.len(), 8);
+    /// ```
+    pub fn set_data_shared(&mut self, bytes: Arc<[u8]>) -> Result<(), WaveError> {
+        if !(bytes.len() as u64).is_multiple_of(self.block_align as u64) {
+            return Err(WaveError::MisalignedData { len: bytes.len(), block_align: self.block_align });
+        }
+        self.audio_byte_data.set_shared(bytes);
+        Ok(())
+    }
+    
+    /// Returns the audio data, see [make_contiguous](WaveFile::make_contiguous).
     pub fn bytes(&mut self) -> &mut Vec<u8> {
-        &mut self.audio_byte_data
+        self.make_contiguous()
+    }
+    
+    /// Moves the audio data into a single buffer and returns it.
+    ///
+    /// Audio data is stored in segments, so that adding data to a large file never moves the data
+    /// that is already there. Merging them copies all the data once; it stays in one buffer until
+    /// more data is added. Audio data set with [set_data_shared](WaveFile::set_data_shared) is
+    /// copied as well.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// wave.add_bytes(&mut vec![128; 2 << 20]);
+    /// wave.add_bytes(&mut vec![255; 2 << 20]);
+    ///
+    /// let data = wave.make_contiguous();
+    /// assert_eq!(data.len(), 4 << 20);
+    /// assert_eq!((data[0], data[(4 << 20) - 1]), (128, 255));
+    /// ```
+    pub fn make_contiguous(&mut self) -> &mut Vec<u8> {
+        self.audio_byte_data.make_contiguous()
+    }
+    
+    /// Returns the number of channels
+    pub fn num_channels(&self) -> u16 {
+        self.num_channels
+    }
+    
+    /// Returns the sample rate in Hz
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    
+    /// Returns the amount of bits per sample
+    pub fn bits_per_sample(&self) -> u16 {
+        self.bits_per_sample
+    }
+    
+    /// Returns the amount of bits of every sample that carry audio, for files of which the samples
+    /// don't fill their container: e.g. 12 for 12-bit samples stored in 16 bits. Otherwise this is
+    /// the same as [bits_per_sample](WaveFile::bits_per_sample), which is the size of the
+    /// container.
+    ///
+    /// The valid bits are the most significant bits of the container, so the samples can be read
+    /// as samples of the container size. They are stored in the `WAVE_FORMAT_EXTENSIBLE` fmt
+    /// chunk.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 12);
+    /// assert_eq!((wave.bits_per_sample(), wave.valid_bits_per_sample()), (16, 12));
+    /// assert_eq!(wave.block_align(), 2);
+    /// wave.add_samples_i16(&[0x7FF0, -0x8000]).unwrap();
+    ///
+    /// let parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+    /// assert!(parsed.is_extensible());
+    /// assert_eq!((parsed.bits_per_sample(), parsed.valid_bits_per_sample()), (16, 12));
+    /// assert_eq!(parsed.samples_i16().unwrap(), [0x7FF0, -0x8000]);
+    /// ```
+    pub fn valid_bits_per_sample(&self) -> u16 {
+        self.valid_bits_per_sample
+    }
+    
+    /// Sets the amount of bits of every sample that carry audio, see
+    /// [valid_bits_per_sample](WaveFile::valid_bits_per_sample). The audio data isn't changed.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedBitsPerSample] if `valid_bits_per_sample` is 0 or more than the
+    /// bits per sample, or if the file isn't PCM.
+    pub fn set_valid_bits_per_sample(&mut self, valid_bits_per_sample: u16) -> Result<(), WaveError> {
+        let is_pcm = self.audio_format() == AudioFormat::PCM;
+        if valid_bits_per_sample == 0 || valid_bits_per_sample > self.bits_per_sample || (!is_pcm && valid_bits_per_sample != self.bits_per_sample) {
+            return Err(WaveError::UnsupportedBitsPerSample(valid_bits_per_sample));
+        }
+        self.valid_bits_per_sample = valid_bits_per_sample;
+        Ok(())
+    }
+    
+    /// Returns the byte rate: the amount of bytes of audio data per second
+    pub fn byte_rate(&self) -> u32 {
+        self.byte_rate
+    }
+    
+    /// Returns the amount of bytes of audio data
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_bytes(&mut vec![0; 400]);
+    ///
+    /// let parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+    /// assert_eq!(parsed.num_channels(), 2);
+    /// assert_eq!(parsed.sample_rate(), 44100);
+    /// assert_eq!(parsed.bits_per_sample(), 16);
+    /// assert_eq!(parsed.byte_rate(), 44100 * 4);
+    /// assert_eq!(parsed.audio_format(), AudioFormat::PCM);
+    /// assert_eq!(parsed.data_len(), 400);
+    /// ```
+    pub fn data_len(&self) -> usize {
+        self.audio_byte_data.len()
+    }
+    
+    /// Returns the number of frames (one sample for every channel) in the audio data
+    pub fn num_frames(&self) -> u64 {
+        if self.block_align == 0 {
+            return 0;
+        }
+        self.audio_byte_data.len() as u64 / self.block_align as u64
+    }
+    
+    /// Reserves room for at least `additional` more frames, so adding them doesn't allocate.
+    ///
+    /// The audio data that is already there is not moved; the room is reserved in a new segment
+    /// if needed (see [make_contiguous](WaveFile::make_contiguous)).
+    ///
+    /// # Errors
+    /// [WaveError::CapacityOverflow] if the bytes of the frames don't fit in memory. Nothing is
+    /// reserved in that case.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, 2]).unwrap();
+    /// wave.reserve_frames(44100).unwrap();
+    /// assert!(wave.capacity_frames() >= 44101);
+    ///
+    /// assert!(wave.reserve_frames(u64::MAX / 2).is_err());
+    /// ```
+    pub fn reserve_frames(&mut self, additional: u64) -> Result<(), WaveError> {
+        // Allocations are limited to isize::MAX bytes
+        let bytes = additional
+            .checked_mul(self.block_align as u64)
+            .and_then(|bytes| usize::try_from(bytes).ok())
+            .filter(|&bytes| self.audio_byte_data.len().checked_add(bytes).is_some_and(|total| total <= isize::MAX as usize))
+            .ok_or(WaveError::CapacityOverflow { frames: additional, block_align: self.block_align })?;
+        self.audio_byte_data.reserve(bytes);
+        Ok(())
+    }
+    
+    /// Returns the amount of frames the audio data can hold without allocating, including the
+    /// frames already in it
+    pub fn capacity_frames(&self) -> u64 {
+        if self.block_align == 0 {
+            return 0;
+        }
+        self.audio_byte_data.capacity() as u64 / self.block_align as u64
+    }
+    
+    /// Returns the playback duration of the audio data
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 8000, 16);
+    /// assert_eq!(wave.duration(), Duration::ZERO);
+    ///
+    /// wave.add_bytes(&mut vec![0; 4 * 12000]);
+    /// assert_eq!(wave.num_frames(), 12000);
+    /// assert_eq!(wave.duration(), Duration::from_millis(1500));
+    /// assert_eq!(wave.duration_secs_f64(), 1.5);
+    /// ```
+    pub fn duration(&self) -> Duration {
+        self.duration_of(self.num_frames())
+    }
+
+    /// Returns the playback duration of `frames` frames at the sample rate of the file
+    fn duration_of(&self, frames: u64) -> Duration {
+        if self.sample_rate == 0 {
+            return Duration::ZERO;
+        }
+        let frames = frames as u128;
+        let rate = self.sample_rate as u128;
+        let secs = frames / rate;
+        let nanos = (frames % rate) * 1_000_000_000 / rate;
+        Duration::new(secs as u64, nanos as u32)
+    }
+    
+    /// Returns the amount of frames that play for `duration` at the sample rate of the file,
+    /// rounded to the nearest frame
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// assert_eq!(wave.frames_for_duration(Duration::from_secs(2)), 88200);
+    /// assert_eq!(wave.frames_for_duration(Duration::from_micros(10)), 0);
+    /// assert_eq!(wave.frames_for_duration(Duration::from_micros(12)), 1);
+    /// ```
+    pub fn frames_for_duration(&self, duration: Duration) -> u64 {
+        let rate = self.sample_rate as u128;
+        ((duration.as_nanos() * rate + 500_000_000) / 1_000_000_000) as u64
+    }
+    
+    /// Returns the playback duration of the audio data in seconds
+    pub fn duration_secs_f64(&self) -> f64 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+        self.num_frames() as f64 / self.sample_rate as f64
+    }
+    
+    /// Returns the audio format of the samples in the file.
+    ///
+    /// For files written with `WAVE_FORMAT_EXTENSIBLE` this is the sub format (PCM or IEEE float),
+    /// see [is_extensible](WaveFile::is_extensible).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 1, 48000, 32);
+    /// wave.add_bytes(&mut 0.5f32.to_le_bytes().to_vec());
+    ///
+    /// let mut parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+    /// assert_eq!(parsed.audio_format(), AudioFormat::IeeeFloat);
+    /// assert_eq!(f32::from_le_bytes(parsed.bytes()[..4].try_into().unwrap()), 0.5);
+    /// ```
+    pub fn audio_format(&self) -> AudioFormat {
+        // Only valid format codes are ever stored
+        AudioFormat::try_from(self.audio_format).unwrap()
+    }
+    
+    /// Whether the file is written with the `WAVE_FORMAT_EXTENSIBLE` fmt chunk. This is always the
+    /// case for files with more than 2 channels, and for files of which not all bits of the
+    /// samples are [valid](WaveFile::valid_bits_per_sample).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// // 5.1
+    /// let wave = WaveFile::new(AudioFormat::PCM, 6, 48000, 24);
+    /// assert!(wave.is_extensible());
+    /// assert_eq!(wave.channel_mask(), 0x3F);
+    /// // RIFF header, 40 byte fmt chunk, fact chunk and empty data chunk
+    /// assert_eq!(wave.to_bytes().len(), 12 + 48 + 12 + 8);
+    /// ```
+    pub fn is_extensible(&self) -> bool {
+        self.extensible || self.num_channels > 2 || self.valid_bits_per_sample != self.bits_per_sample
+    }
+    
+    /// Opts in to (or out of) writing the file with the `WAVE_FORMAT_EXTENSIBLE` fmt chunk. Files
+    /// with more than 2 channels are written as extensible regardless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 48000, 32);
+    /// wave.set_extensible(true);
+    ///
+    /// let parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+    /// assert!(parsed.is_extensible());
+    /// assert_eq!(parsed.audio_format(), AudioFormat::IeeeFloat);
+    /// assert_eq!(parsed.channel_mask(), 0x3);
+    /// ```
+    pub fn set_extensible(&mut self, extensible: bool) {
+        self.extensible = extensible;
+    }
+    
+    /// Returns the channel mask: a bit for every speaker position the channels are mapped to (e.g.
+    /// `0x3F` for 5.1). Only written to the file when it [is extensible](WaveFile::is_extensible).
+    ///
+    /// Defaults to the conventional speaker positions for the amount of channels.
+    pub fn channel_mask(&self) -> u32 {
+        self.channel_mask
+    }
+    
+    /// Sets the channel mask, see [channel_mask](WaveFile::channel_mask).
+    pub fn set_channel_mask(&mut self, channel_mask: u32) {
+        self.channel_mask = channel_mask;
+    }
+    
+    /// Returns the number of sample frames stored in the fact chunk of the file this `WaveFile`
+    /// was parsed from, or `None` if there was no fact chunk (or the `WaveFile` was not parsed).
+    ///
+    /// When writing, the fact chunk is always computed from the audio data. A value that is
+    /// larger than the frames in the data chunk indicates the file was truncated.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 48000, 32);
+    /// wave.add_bytes(&mut vec![0; 8 * 10]);
+    ///
+    /// let bytes = wave.to_bytes();
+    /// assert_eq!(&bytes[38..50], [b"fact", &4u32.to_le_bytes()[..], &10u32.to_le_bytes()].concat());
+    ///
+    /// let parsed = WaveFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!(parsed.fact_frames(), Some(10));
+    /// ```
+    pub fn fact_frames(&self) -> Option<u32> {
+        self.fact_frames
     }
     
     /// Returns the block align, can be used to check if the bytes passed to `to_bytes` is divisible
     /// by `block_align`.
-    pub fn block_align(&self) -> u32 {
+    pub fn block_align(&self) -> u16 {
         self.block_align
     }
     
     /// A byte representation of the `WaveFile`.
     ///
-    /// Can be used to write to a file.
+    /// Can be used to write to a file. Audio that doesn't fit in the 32-bit sizes of a RIFF file
+    /// (about 4 GB) is written as RF64, see [Rf64Mode].
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// for bits in [8, 16, 24, 32] {
+    ///     let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, bits);
+    ///     let audio: Vec<u8> = (0..wave.block_align() as u8 * 3).collect();
+    ///     wave.add_bytes(&mut audio.clone());
+    ///
+    ///     let bytes = wave.to_bytes();
+    ///     assert_eq!(bytes.len(), 44 + audio.len());
+    ///
+    ///     let mut parsed = WaveFile::from_bytes(&bytes).unwrap();
+    ///     assert_eq!(parsed.bytes(), &audio);
+    ///     assert_eq!(parsed.to_bytes(), bytes);
+    /// }
+    /// ```
+    ///
+    /// A data chunk with an odd amount of bytes is followed by a pad byte:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// wave.add_bytes(&mut vec![1, 2, 3]);
+    ///
+    /// let bytes = wave.to_bytes();
+    /// assert_eq!(bytes.len(), 44 + 3 + 1);
+    /// assert_eq!(&bytes[40..44], 3u32.to_le_bytes());
+    /// assert_eq!(WaveFile::from_bytes(&bytes).unwrap().bytes(), &[1, 2, 3]);
+    /// ```
+    ///
+    /// ## Panics
+    /// If the file doesn't fit in RIFF and the [Rf64Mode] is [Never](Rf64Mode::Never), see
+    /// [try_to_bytes](WaveFile::try_to_bytes).
     pub fn to_bytes(&self) -> Vec<u8> {
-        let subchunk1_size: u32 = 16; // If longer than 16 -> support ExtraPrams field (but necessary?)
-        let chunk_id: String = String::from("RIFF");
-        let format: String = String::from("WAVE");
-        // Sub chunk 1 (fmt)
-        let subchunk1_id: String = String::from("fmt ");
-        // Stores: suchunk1_size; audio_format, num_channels, sample_rate, byte_rate, block_align, bits_per_sample
-    
-        // Sub Chunk 2 (data)
-        let subchunk2_id: String = String::from("data");
-        // stores: subchunk2_size
-        
-        // Subchunk 2 calculations
-        let num_bytes_in_data: u32 = self.audio_byte_data.len() as u32;
-        
-        let num_samples = num_bytes_in_data / (2 * self.num_channels);
+        self.try_to_bytes().unwrap_or_else(|err| panic!("{}", err))
+    }
     
-        let subchunk2_size = num_samples * self.num_channels * (self.bits_per_sample / 8);
-        
-        // chunk calculation
-        let chunk_size = 4 + (8 + subchunk1_size) + (8 + subchunk2_size);
+    /// A byte representation of the `WaveFile`, like [to_bytes](WaveFile::to_bytes).
+    ///
+    /// # Errors
+    /// [WaveError::TooLarge] if the file doesn't fit in the 32-bit sizes of a RIFF file and the
+    /// [Rf64Mode] is [Never](Rf64Mode::Never).
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, WaveError> {
+        let mut data = Vec::new();
+        self.to_bytes_into(&mut data)?;
+        Ok(data)
+    }
+    
+    /// Appends the byte representation of the `WaveFile` (see [to_bytes](WaveFile::to_bytes)) to
+    /// `out`, reserving exactly the [encoded_size](WaveFile::encoded_size) first. Clearing and
+    /// reusing `out` avoids allocating a new buffer for every file.
+    ///
+    /// # Errors
+    /// [WaveError::TooLarge] if the file doesn't fit in the 32-bit sizes of a RIFF file and the
+    /// [Rf64Mode] is [Never](Rf64Mode::Never). Nothing is appended then.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// let mut buffer = Vec::new();
+    /// for i in 0..3 {
+    ///     wave.add_samples_i16(&[i; 100]).unwrap();
+    ///     buffer.clear();
+    ///     wave.to_bytes_into(&mut buffer).unwrap();
+    ///     assert_eq!(buffer, wave.to_bytes());
+    /// }
+    /// ```
+    pub fn to_bytes_into(&self, out: &mut Vec<u8>) -> Result<(), WaveError> {
+        out.reserve_exact(self.encoded_size() as usize);
+        self.write_wave(out)
+    }
+    
+    /// Returns the exact amount of bytes [write_to](WaveFile::write_to) writes: the header, the
+    /// audio data with its pad byte and the metadata chunks after it.
+    ///
+    /// For a file that is too large to write as RIFF while RF64 is not allowed (see [Rf64Mode]),
+    /// this is the size it would have as RIFF.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::MuLaw, 1, 8000, 8);
+    /// wave.add_bytes(&mut vec![0xFF; 101]);
+    /// wave.info_mut().title = Some("Odd".to_string());
+    /// wave.add_chunk(*b"iXML", b"<BWFXML/>".to_vec());
+    ///
+    /// assert_eq!(wave.encoded_size(), wave.to_bytes().len() as u64);
+    /// ```
+    pub fn encoded_size(&self) -> u64 {
+        let data_size = self.audio_byte_data.len() as u64;
+        let trailing_size = self.trailing_chunks().len() as u64;
+        let container = self.container(data_size, trailing_size, false).unwrap_or(Container::Riff);
+        8 + self.riff_size(data_size, trailing_size, container)
+    }
+    
+    /// Writes the byte representation of the `WaveFile` (see [to_bytes](WaveFile::to_bytes)) to
+    /// `w`, without building the whole file in memory first.
+    ///
+    /// Writes are issued for the header and the audio data separately, so wrapping unbuffered
+    /// writers like [fs::File] in a [BufWriter](std::io::BufWriter) is not needed.
+    ///
+    /// # Errors
+    /// Any error returned by `w`. If the file doesn't fit in RIFF and the [Rf64Mode] is
+    /// [Never](Rf64Mode::Never), an error of kind [InvalidInput](io::ErrorKind::InvalidInput)
+    /// wrapping [WaveError::TooLarge] is returned before anything is written.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use jaudio::wave_file::*;
+    /// # use std::fs::File;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_bytes(&mut vec![0; 4 * 44100]);
+    ///
+    /// let mut file = File::create("file.wav").unwrap();
+    /// wave.write_to(&mut file).unwrap();
+    /// ```
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_wave(w).map_err(|err| match err {
+            WaveError::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::InvalidInput, err),
+        })
+    }
+    
+    fn write_wave<W: Write>(&self, w: &mut W) -> Result<(), WaveError> {
+        let (header, tail) = self.header_and_tail(self.audio_byte_data.len() as u64, false)?;
+        w.write_all(&header)?;
+        for segment in self.audio_byte_data.segments() {
+            w.write_all(segment)?;
+        }
+        w.write_all(&tail)?;
+        Ok(())
+    }
+    
+    /// The bytes of a file with a data chunk of `subchunk2_size` bytes around the audio data: the
+    /// header, and the pad byte of the data chunk followed by the trailing chunks. With `reserve`,
+    /// a RIFF header has room for a `ds64` chunk, like the header of a [WaveWriter].
+    pub(crate) fn header_and_tail(&self, subchunk2_size: u64, reserve: bool) -> Result<(Vec<u8>, Vec<u8>), WaveError> {
+        let trailing = self.trailing_chunks();
+        let container = self.container(subchunk2_size, trailing.len() as u64, reserve)?;
+        let header = self.header(subchunk2_size, trailing.len() as u64, container);
+        // Chunks with an odd size are padded with a byte that is not counted in their size
+        let pad: &[u8] = if subchunk2_size.is_multiple_of(2) { &[] } else { &[0] };
+        Ok((header, [pad, &trailing].concat()))
+    }
+    
+    /// The size of the fmt chunk payload and of the fact chunk (0 if there is none)
+    fn fmt_and_fact_size(&self) -> (u32, u32) {
+        let extensible = self.is_extensible();
+        let is_pcm = self.audio_format() == AudioFormat::PCM;
+        // Non-PCM formats have an extension, preceded by its size (cbSize). For extensible files
+        // the extension is 22 bytes long, for other formats it is empty.
+        let subchunk1_size: u32 = if extensible { 40 } else if is_pcm { 16 } else { 18 };
+        // The fact chunk (required for every format other than WAVE_FORMAT_PCM, which includes
+        // extensible PCM) stores the number of sample frames
+        let fact_size: u32 = if is_pcm && !extensible { 0 } else { 8 + 4 };
+        (subchunk1_size, fact_size)
+    }
+    
+    /// The size of the RIFF chunk (the file without its first 8 bytes) for a data chunk of
+    /// `subchunk2_size` bytes followed by `trailing_size` bytes of other chunks.
+    pub(crate) fn riff_size(&self, subchunk2_size: u64, trailing_size: u64, container: Container) -> u64 {
+        let (subchunk1_size, fact_size) = self.fmt_and_fact_size();
+        let ds64_size = if container == Container::Riff { 0 } else { 8 + rf64::DS64_SIZE as u64 };
+        // Chunks with an odd size are padded with a byte that is not counted in their size
+        let pad_size = subchunk2_size % 2;
+        4 + ds64_size + (8 + subchunk1_size as u64) + fact_size as u64 + (8 + subchunk2_size + pad_size) + trailing_size
+    }
+    
+    /// The bytes of the file up to the start of the audio data, for a data chunk of
+    /// `subchunk2_size` bytes followed by `trailing_size` bytes of other chunks. The audio data
+    /// itself, the pad byte that follows it and the trailing chunks are not included.
+    pub(crate) fn header(&self, subchunk2_size: u64, trailing_size: u64, container: Container) -> Vec<u8> {
+        let extensible = self.is_extensible();
+        let is_pcm = self.audio_format() == AudioFormat::PCM;
+        let (subchunk1_size, fact_size) = self.fmt_and_fact_size();
+        let num_frames = subchunk2_size / self.block_align as u64;
+        let riff_size = self.riff_size(subchunk2_size, trailing_size, container);
+        // RF64 stores the sizes in the ds64 chunk, and sets the 32-bit sizes to their maximum
+        let (riff_size_32, subchunk2_size_32) = if container == Container::Rf64 {
+            (u32::MAX, u32::MAX)
+        } else {
+            (riff_size as u32, subchunk2_size as u32)
+        };
         
-        // Convert to bytes //
+        let mut header = HeaderWriter::with_capacity(128);
         // Chunk descriptor
-        let chunk_id: &[u8] = chunk_id.as_bytes();
-        let chunk_size = chunk_size.to_le_bytes(); // in little endian (le)
-        let format = format.as_bytes();
-        
+        let magic = if container == Container::Rf64 { b"RF64" } else { b"RIFF" };
+        header.chunk_header(magic, riff_size_32).bytes(b"WAVE");
+        // ds64 subchunk, or room for it
+        match container {
+            Container::Riff => {}
+            Container::RiffWithJunk => {
+                header.chunk_header(b"JUNK", rf64::DS64_SIZE).bytes(&[0; rf64::DS64_SIZE as usize]);
+            }
+            Container::Rf64 => {
+                header
+                    .chunk_header(b"ds64", rf64::DS64_SIZE)
+                    .u64(riff_size)
+                    .u64(subchunk2_size)
+                    .u64(num_frames) // sample count
+                    .u32(0); // table length
+            }
+        }
         // fmt subchunk
-        // TODO: something more efficient for 2 byte long arrays
-        let subchunk1_id = subchunk1_id.as_bytes();
-        let subchunk1_size = subchunk1_size.to_le_bytes(); // this has 4 bytes
-        let mut i = 0;
-        let audio_format: [u8; 2] = self.audio_format.to_le_bytes().into_iter().filter(|_| {
-            if i < 2 { i += 1; true } else { false }
-        }).collect::<Vec<u8>>()
-            .as_slice()
-            .try_into()
-            .unwrap(); // this has 2
-        let mut i = 0;
-        let num_channels: [u8; 2] = self.num_channels.to_le_bytes().into_iter().filter(|_| {
-            if i < 2 { i += 1; true } else { false }
-        })
-            .collect::<Vec<u8>>()
-            .as_slice()
-            .try_into()
-            .unwrap();
-        let sample_rate = self.sample_rate.to_le_bytes();
-        let byte_rate = self.byte_rate.to_le_bytes();
-        let mut i = 0;
-        let block_align: [u8; 2] = self.block_align.to_le_bytes().into_iter().filter(|_| {
-            if i < 2 { i += 1; true } else { false }
-        })
-            .collect::<Vec<u8>>()
-            .as_slice()
-            .try_into()
-            .unwrap();
-        let mut i = 0;
-        let bits_per_sample: [u8; 2] = self.bits_per_sample.to_le_bytes().into_iter().filter(|_| {
-            if i < 2 { i += 1; true } else { false }
-        })
-            .collect::<Vec<u8>>()
-            .as_slice()
-            .try_into()
-            .unwrap();
-        
-        // data subchunk
-        let subchunk2_id = subchunk2_id.as_bytes();
-        let subchunk2_size = subchunk2_size.to_le_bytes();
-        // data = self.audio_byte_data
-        
-        let mut data = Vec::new();
-        // head
-        data.extend_from_slice(chunk_id);
-        data.extend(chunk_size);
-        data.extend(format);
-        // subchunk 1
-        data.extend(subchunk1_id);
-        data.extend(subchunk1_size);
-        data.extend(audio_format);
-        data.extend(num_channels);
-        data.extend(sample_rate);
-        data.extend(byte_rate);
-        data.extend(block_align);
-        data.extend(bits_per_sample);
-        
-        // subchunk 2
-        data.extend(subchunk2_id);
-        data.extend(subchunk2_size);
-        data.extend(&self.audio_byte_data);
-        
-        data
+        let format_tag = if extensible { AudioFormat::Extensible.get_val() } else { self.audio_format };
+        header
+            .chunk_header(b"fmt ", subchunk1_size)
+            .u16(format_tag)
+            .u16(self.num_channels)
+            .u32(self.sample_rate)
+            .u32(self.byte_rate)
+            .u16(self.block_align)
+            .u16(self.bits_per_sample);
+        if extensible {
+            header
+                .u16(22) // cbSize
+                .u16(self.valid_bits_per_sample)
+                .u32(self.channel_mask)
+                .u16(self.audio_format) // sub format
+                .bytes(&SUBFORMAT_GUID_TAIL);
+        } else if !is_pcm {
+            header.u16(0); // cbSize
+        }
+        // fact subchunk
+        if fact_size != 0 {
+            header.chunk_header(b"fact", 4).u32(num_frames.min(u32::MAX as u64) as u32);
+        }
+        // data subchunk, followed by the audio data
+        header.chunk_header(b"data", subchunk2_size_32);
+        header.into_bytes()
+    }
+}
+
+// Companding
+impl WaveFile {
+    /// Decodes an A-law or µ-law file into a 16-bit PCM file with the same channels and sample
+    /// rate. A 16-bit PCM file is returned as a copy.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] for any other format and
+    /// [WaveError::UnsupportedBitsPerSample] for PCM files that are not 16-bit.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut pcm = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// pcm.add_bytes(&mut [0i16, 1000, -1000, 32000].iter().flat_map(|s| s.to_le_bytes()).collect());
+    ///
+    /// let mulaw = WaveFile::from_pcm16_as_mulaw(&pcm).unwrap();
+    /// assert_eq!(mulaw.audio_format(), AudioFormat::MuLaw);
+    /// assert_eq!(mulaw.block_align(), 1);
+    ///
+    /// let mut decoded = WaveFile::from_bytes(&mulaw.to_bytes()).unwrap().to_pcm16().unwrap();
+    /// let samples: Vec<i16> = decoded.bytes()
+    ///     .chunks(2)
+    ///     .map(|b| i16::from_le_bytes([b[0], b[1]]))
+    ///     .collect();
+    /// assert_eq!(samples, [0, 988, -988, 32124]);
+    /// ```
+    pub fn to_pcm16(&self) -> Result<WaveFile, WaveError> {
+        let decode = match self.audio_format() {
+            AudioFormat::ALaw => g711::alaw_to_pcm16,
+            AudioFormat::MuLaw => g711::mulaw_to_pcm16,
+            AudioFormat::PCM if self.bits_per_sample == 16 => {
+                let mut pcm = self.empty_like(AudioFormat::PCM, 16);
+                pcm.audio_byte_data = self.audio_byte_data.clone();
+                return Ok(pcm);
+            }
+            AudioFormat::PCM => return Err(WaveError::UnsupportedBitsPerSample(self.bits_per_sample)),
+            format => return Err(WaveError::UnsupportedFormat(format.get_val())),
+        };
+        let mut pcm = self.empty_like(AudioFormat::PCM, 16);
+        pcm.audio_byte_data.set(self.audio_byte_data.iter().flat_map(|&s| decode(s).to_le_bytes()).collect());
+        Ok(pcm)
+    }
+    
+    /// Encodes a 16-bit PCM file as µ-law, keeping the channels and sample rate.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] if `pcm16` is not a PCM file and
+    /// [WaveError::UnsupportedBitsPerSample] if it is not 16-bit.
+    pub fn from_pcm16_as_mulaw(pcm16: &WaveFile) -> Result<WaveFile, WaveError> {
+        pcm16.compand(AudioFormat::MuLaw, g711::linear_to_mulaw)
+    }
+    
+    /// Encodes a 16-bit PCM file as A-law, keeping the channels and sample rate.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] if `pcm16` is not a PCM file and
+    /// [WaveError::UnsupportedBitsPerSample] if it is not 16-bit.
+    pub fn from_pcm16_as_alaw(pcm16: &WaveFile) -> Result<WaveFile, WaveError> {
+        pcm16.compand(AudioFormat::ALaw, g711::linear_to_alaw)
+    }
+    
+    fn compand(&self, audio_format: AudioFormat, encode: fn(i16) -> u8) -> Result<WaveFile, WaveError> {
+        if self.audio_format() != AudioFormat::PCM {
+            return Err(WaveError::UnsupportedFormat(self.audio_format().get_val()));
+        }
+        if self.bits_per_sample != 16 {
+            return Err(WaveError::UnsupportedBitsPerSample(self.bits_per_sample));
+        }
+        let mut companded = self.empty_like(audio_format, 8);
+        companded.audio_byte_data.set(self.audio_byte_data
+            .chunks_exact(2)
+            .map(|s| encode(i16::from_le_bytes([s[0], s[1]])))
+            .collect());
+        Ok(companded)
+    }
+    
+    /// A `WaveFile` without audio data, with the same channels, sample rate and channel layout as
+    /// this one but a different sample format.
+    fn empty_like(&self, audio_format: AudioFormat, bits_per_sample: u16) -> WaveFile {
+        let mut wave = WaveFile::new(audio_format, self.num_channels, self.sample_rate, bits_per_sample);
+        wave.extensible = self.extensible;
+        wave.channel_mask = self.channel_mask;
+        wave
     }
 }
 
@@ -217,16 +1151,292 @@ impl WaveFile {
 impl WaveFile {
     /// Returns only the data part of a wave file.
     ///
-    /// This method can only read PCM format (for the moment)
+    /// The RIFF chunks of the file are walked until the `data` chunk is found, so files with
+    /// extra chunks (LIST, JUNK, fact, ...) or an extended fmt chunk are read correctly.
     ///
-    /// # Errors (from [fs::read()](std::fs::read))
+    /// The data is returned as stored, so the format of the file has to be known to interpret it.
+    /// The exception is IMA ADPCM, which is decoded to 16-bit PCM.
+    ///
+    /// # Errors
+    /// [WaveError::Io] with the errors from [fs::read()](std::fs::read):
     /// This function will return an error if path does not already exist.
     /// Other errors may also be returned according to [OpenOptions::open](std::fs::OpenOptions::open).
     ///
     /// It will also return an error if it encounters while reading an error of a kind other than
-    /// [io::ErrorKind::Interrupted](std::io::error::ErrorKind::Interrupted).
-    pub fn file_to_data(file_path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    /// [io::ErrorKind::Interrupted].
+    ///
+    /// [WaveError::NotRiff] if the file does not start with the `RIFF`/`WAVE` magic,
+    /// [WaveError::MissingChunk] if it has no `data` chunk and [WaveError::Truncated] if a chunk
+    /// declares more bytes than the file contains.
+    pub fn file_to_data(file_path: &str) -> Result<Vec<u8>, WaveError> {
+        let file_content = fs::read(file_path)?;
+        // RIFX data is converted to little-endian, Wave64 chunks have no FourCC
+        if file_content.starts_with(b"RIFX") || w64::is_w64_magic(&file_content) {
+            return Ok(WaveFile::from_bytes(&file_content)?.audio_byte_data.into_vec());
+        }
+        let (mut fmt, mut data) = (None, None);
+        for chunk in Chunks::new(&file_content)? {
+            let chunk = chunk?;
+            match &chunk.id {
+                b"fmt " if fmt.is_none() => fmt = Some(chunk.data),
+                // The fmt chunk comes before the data chunk
+                b"data" => {
+                    data = Some(chunk.data);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let data = data.ok_or(WaveError::MissingChunk("data"))?;
+        if fmt.is_some_and(|fmt| fmt.len() >= 2 && u16::from_le_bytes([fmt[0], fmt[1]]) == adpcm::IMA_ADPCM) {
+            return Ok(WaveFile::from_bytes(&file_content)?.audio_byte_data.into_vec());
+        }
+        Ok(data.to_vec())
+    }
+    
+    /// Reads a wave file and returns a `WaveFile` populated with the spec from its fmt chunk and
+    /// the audio data from its data chunk.
+    ///
+    /// This method can read PCM, IEEE float, A-law, µ-law and IMA ADPCM files (for the moment)
+    ///
+    /// # Errors
+    /// Returns the same errors as [fs::read()](std::fs::read) and
+    /// [from_bytes](WaveFile::from_bytes).
+    pub fn from_file(file_path: &str) -> Result<WaveFile, WaveError> {
         let file_content = fs::read(file_path)?;
-        Ok(file_content[44..].to_vec())
+        WaveFile::from_bytes(&file_content)
+    }
+    
+    /// Parses an in-memory wave file and returns a `WaveFile` populated with the spec from its fmt
+    /// chunk and the audio data from its data chunk.
+    ///
+    /// This method can read PCM, IEEE float, A-law and µ-law files (for the moment), also when they
+    /// are stored with `WAVE_FORMAT_EXTENSIBLE`. The audio data is not converted, so the bytes of a
+    /// float file can be reinterpreted as `f32` or `f64` samples. Companded files can be decoded
+    /// with [to_pcm16](WaveFile::to_pcm16).
+    ///
+    /// IMA ADPCM files are decoded, and returned as a 16-bit PCM `WaveFile`.
+    ///
+    /// Big-endian RIFX files are read as well. Their audio data is converted to little-endian, so
+    /// the `WaveFile` is the same as for a RIFF file. The metadata chunks this crate reads, like
+    /// `cue ` and `smpl`, are converted too. Other chunks are kept with their payload as stored.
+    ///
+    /// Sony Wave64 files are read as well. Their chunks with a RIFF counterpart are read as
+    /// those, chunks with other GUIDs are skipped.
+    ///
+    /// # Errors
+    /// - [WaveError::NotRiff] if `bytes` does not start with the `RIFF`/`WAVE`, `RF64`/`WAVE` or
+    ///   `RIFX`/`WAVE` magic, or the `riff`/`wave` GUIDs of a Wave64 file
+    /// - [WaveError::Truncated] if a chunk header is truncated, a chunk declares more bytes than
+    ///   `bytes` contains or the fmt or ds64 chunk is too short
+    /// - [WaveError::MissingChunk] if the fmt or data chunk is missing, or the ds64 chunk of an
+    ///   RF64 file
+    /// - [WaveError::UnsupportedFormat] if the (sub) format is not PCM, IEEE float, A-law, µ-law or
+    ///   IMA ADPCM, or for IMA ADPCM in a RIFX file
+    /// - [WaveError::InvalidSpec] if the fmt chunk has no channels, or if IMA ADPCM would be
+    ///   decoded to PCM with a sample rate of 0 or a byte rate that doesn't fit in 32 bits
+    /// - [WaveError::UnsupportedBitsPerSample] if the fmt chunk has 0 or more than 64 bits per
+    ///   sample
+    /// - [WaveError::BlockAlignMismatch] if the block align in the fmt chunk is not the amount of
+    ///   channels times the bytes per sample
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_bytes(&mut vec![0, 0, 255, 127]);
+    ///
+    /// let mut bytes = wave.to_bytes();
+    /// let parsed = WaveFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!(parsed.block_align(), 2);
+    ///
+    /// // A header with the block align zeroed
+    /// bytes[32..34].copy_from_slice(&0u16.to_le_bytes());
+    /// assert!(matches!(
+    ///     WaveFile::from_bytes(&bytes),
+    ///     Err(WaveError::BlockAlignMismatch { declared: 0, computed: 2 })
+    /// ));
+    /// bytes[34..36].copy_from_slice(&u16::MAX.to_le_bytes());
+    /// assert!(matches!(WaveFile::from_bytes(&bytes), Err(WaveError::UnsupportedBitsPerSample(u16::MAX))));
+    ///
+    /// assert!(matches!(
+    ///     WaveFile::from_bytes(b"definitely not a wave file"),
+    ///     Err(WaveError::NotRiff)
+    /// ));
+    /// ```
+    ///
+    /// IMA ADPCM, including a partial final block:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// // Mono, 8000 Hz, blocks of 36 bytes containing 65 samples
+    /// let fmt: Vec<u8> = [
+    ///     &0x11u16.to_le_bytes()[..], &1u16.to_le_bytes(), &8000u32.to_le_bytes(),
+    ///     &4055u32.to_le_bytes(), &36u16.to_le_bytes(), &4u16.to_le_bytes(),
+    ///     &2u16.to_le_bytes(), &65u16.to_le_bytes(),
+    /// ].concat();
+    /// // A full block and a partial block of 5 samples, both with a predictor of 100 that no
+    /// // nibble changes
+    /// let mut data = vec![0; 36 + 6];
+    /// data[0] = 100;
+    /// data[36] = 100;
+    ///
+    /// let mut bytes = b"RIFF".to_vec();
+    /// bytes.extend(((4 + 8 + fmt.len() + 8 + data.len()) as u32).to_le_bytes());
+    /// bytes.extend(b"WAVEfmt ");
+    /// bytes.extend((fmt.len() as u32).to_le_bytes());
+    /// bytes.extend(&fmt);
+    /// bytes.extend(b"data");
+    /// bytes.extend((data.len() as u32).to_le_bytes());
+    /// bytes.extend(&data);
+    ///
+    /// let mut wave = WaveFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!(wave.audio_format(), AudioFormat::PCM);
+    /// assert_eq!(wave.block_align(), 2);
+    /// assert_eq!(wave.bytes().len(), (65 + 5) * 2);
+    /// assert!(wave.bytes().chunks(2).all(|s| s == 100i16.to_le_bytes()));
+    /// ```
+    ///
+    /// RIFX, with big-endian sizes and samples:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// // Stereo, 22050 Hz, 16 bits
+    /// let fmt: Vec<u8> = [
+    ///     &1u16.to_be_bytes()[..], &2u16.to_be_bytes(), &22050u32.to_be_bytes(),
+    ///     &88200u32.to_be_bytes(), &4u16.to_be_bytes(), &16u16.to_be_bytes(),
+    /// ].concat();
+    /// let data: Vec<u8> = [1i16, -2, 300, -400].iter().flat_map(|s| s.to_be_bytes()).collect();
+    /// let info = [&b"INFOINAM"[..], &6u32.to_be_bytes(), b"Tape\0\0"].concat();
+    /// // A cue point with id 7 at frame 1, and its label
+    /// let cue: Vec<u8> = [1u32, 7, 1].iter().flat_map(|n| n.to_be_bytes())
+    ///     .chain(*b"data").chain([0u32, 0, 1].iter().flat_map(|n| n.to_be_bytes())).collect();
+    /// let adtl = [&b"adtllabl"[..], &6u32.to_be_bytes(), &7u32.to_be_bytes(), b"A\0"].concat();
+    ///
+    /// let mut bytes = b"RIFX".to_vec();
+    /// let sizes = [fmt.len(), data.len(), info.len(), cue.len(), adtl.len()];
+    /// bytes.extend((4 + sizes.iter().map(|size| 8 + size).sum::<usize>() as u32).to_be_bytes());
+    /// bytes.extend(b"WAVEfmt ");
+    /// bytes.extend((fmt.len() as u32).to_be_bytes());
+    /// bytes.extend(&fmt);
+    /// bytes.extend(b"data");
+    /// bytes.extend((data.len() as u32).to_be_bytes());
+    /// bytes.extend(&data);
+    /// for (id, payload) in [(b"LIST", &info), (b"cue ", &cue), (b"LIST", &adtl)] {
+    ///     bytes.extend(id);
+    ///     bytes.extend((payload.len() as u32).to_be_bytes());
+    ///     bytes.extend(payload);
+    /// }
+    ///
+    /// let wave = WaveFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!((wave.num_channels(), wave.sample_rate(), wave.bits_per_sample()), (2, 22050, 16));
+    /// assert_eq!(wave.samples_i16().unwrap(), [1, -2, 300, -400]);
+    /// assert_eq!(wave.info().title.as_deref(), Some("Tape"));
+    /// assert_eq!(wave.cue_points(), [CuePoint { id: 7, position_frames: 1, label: Some(String::from("A")) }]);
+    /// // Written as RIFF
+    /// assert_eq!(&wave.to_bytes()[0..4], b"RIFF");
+    /// ```
+    ///
+    /// Wave64, with GUID chunk ids and 64-bit sizes that count the chunk header:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let guid_tail = [0xF3, 0xAC, 0xD3, 0x11, 0x8C, 0xD1, 0x00, 0xC0, 0x4F, 0x8E, 0xDB, 0x8A];
+    /// let chunk = |id: &[u8], payload: &[u8]| {
+    ///     let mut chunk = [id, &guid_tail[..], &(24 + payload.len() as u64).to_le_bytes(), payload].concat();
+    ///     // Chunks are aligned to 8 bytes
+    ///     chunk.resize(chunk.len().next_multiple_of(8), 0);
+    ///     chunk
+    /// };
+    /// let fmt: Vec<u8> = [
+    ///     &1u16.to_le_bytes()[..], &1u16.to_le_bytes(), &8000u32.to_le_bytes(),
+    ///     &16000u32.to_le_bytes(), &2u16.to_le_bytes(), &16u16.to_le_bytes(),
+    /// ].concat();
+    /// let body = [
+    ///     &b"wave"[..], &guid_tail, &chunk(b"fmt ", &fmt), &chunk(b"data", &[1, 0, 2, 0, 3, 0]),
+    /// ].concat();
+    ///
+    /// let mut bytes = b"riff".to_vec();
+    /// bytes.extend([0x2E, 0x91, 0xCF, 0x11, 0xA5, 0xD6, 0x28, 0xDB, 0x04, 0xC1, 0x00, 0x00]);
+    /// bytes.extend((24 + body.len() as u64).to_le_bytes());
+    /// bytes.extend(&body);
+    ///
+    /// let wave = WaveFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!((wave.num_channels(), wave.sample_rate()), (1, 8000));
+    /// assert_eq!(wave.samples_i16().unwrap(), [1, 2, 3]);
+    /// assert_eq!(WaveReader::new(&bytes[..]).unwrap().data_len(), 6);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<WaveFile, WaveError> {
+        if bytes.starts_with(b"RIFX") {
+            return WaveFile::from_bytes(&rifx::to_riff(bytes)?);
+        }
+        if w64::is_w64_magic(bytes) {
+            return WaveFile::from_chunks(w64::W64Chunks::new(bytes)?);
+        }
+        let chunks = Chunks::new(bytes)?;
+        let rf64 = chunks.is_rf64();
+        let mut wave = WaveFile::from_chunks(chunks)?;
+        if rf64 {
+            wave.rf64_mode = Rf64Mode::Always;
+        }
+        Ok(wave)
+    }
+    
+    /// Builds a `WaveFile` from the fmt, fact and data chunks and the metadata chunks of a file
+    fn from_chunks<'a>(chunks: impl Iterator<Item = Result<chunk::Chunk<'a>, WaveError>>) -> Result<WaveFile, WaveError> {
+        let mut fmt = None;
+        let mut fact = None;
+        let mut data = None;
+        let mut metadata = Vec::new();
+        for chunk in chunks {
+            let chunk = chunk?;
+            match &chunk.id {
+                b"fmt " if fmt.is_none() => fmt = Some(chunk.data),
+                b"fact" if fact.is_none() => fact = Some(chunk.data),
+                b"data" if data.is_none() => data = Some(chunk.data),
+                // The ds64 chunk is handled by Chunks, JUNK chunks only reserve space
+                b"fmt " | b"fact" | b"data" | b"ds64" | b"JUNK" => {}
+                _ => metadata.push(chunk),
+            }
+        }
+        let fmt = fmt.ok_or(WaveError::MissingChunk("fmt "))?;
+        let data = data.ok_or(WaveError::MissingChunk("data"))?;
+        
+        let fact_frames = fact
+            .filter(|fact| fact.len() >= 4)
+            .map(|fact| u32::from_le_bytes(fact[0..4].try_into().unwrap()));
+        
+        // IMA ADPCM is decoded to PCM
+        let mut wave = if fmt.len() >= 2 && u16::from_le_bytes([fmt[0], fmt[1]]) == adpcm::IMA_ADPCM {
+            adpcm::decode_ima(fmt, data, fact_frames)?
+        } else {
+            let mut wave = WaveFile::from_fmt(&FmtChunk::parse(fmt)?);
+            wave.audio_byte_data.set(data.to_vec());
+            wave
+        };
+        wave.fact_frames = fact_frames;
+        wave.read_metadata(&metadata);
+        Ok(wave)
+    }
+    
+    /// An empty `WaveFile` with the spec from a parsed fmt chunk
+    fn from_fmt(fmt: &FmtChunk) -> WaveFile {
+        WaveFile {
+            audio_format: fmt.audio_format.get_val(),
+            num_channels: fmt.num_channels,
+            sample_rate: fmt.sample_rate,
+            bits_per_sample: fmt.bits_per_sample,
+            valid_bits_per_sample: fmt.valid_bits_per_sample,
+            byte_rate: fmt.byte_rate,
+            block_align: fmt.block_align,
+            extensible: fmt.extensible,
+            channel_mask: fmt.channel_mask,
+            fact_frames: None,
+            info: Info::default(),
+            cue_points: Vec::new(),
+            sampler_info: None,
+            broadcast_ext: None,
+            acid_info: None,
+            extra_chunks: Vec::new(),
+            rf64_mode: Rf64Mode::Auto,
+            audio_byte_data: AudioData::new(fmt.block_align),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,125 @@
+use super::chunk;
+use super::WaveFile;
+
+const ONE_SHOT: u32 = 0x01;
+const ROOT_NOTE_SET: u32 = 0x02;
+const STRETCH: u32 = 0x04;
+const DISK_BASED: u32 = 0x08;
+
+/// The tempo and beat information of the `acid` chunk of a wave file, used by loop libraries.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+/// assert_eq!(wave.tempo_bpm(), None);
+/// wave.set_acid_info(Some(AcidInfo { num_beats: 8, tempo: 128.0, root_note: Some(57), ..AcidInfo::default() }));
+///
+/// let bytes = wave.to_bytes();
+/// assert_eq!(&bytes[44..52], [&b"acid"[..], &24u32.to_le_bytes()].concat());
+///
+/// let parsed = WaveFile::from_bytes(&bytes).unwrap();
+/// assert_eq!(parsed.tempo_bpm(), Some(128.0));
+/// assert_eq!(parsed.acid_info(), wave.acid_info());
+/// assert!(parsed.acid_info().unwrap().is_loop);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AcidInfo {
+    /// Whether the audio is a loop, rather than a one-shot
+    pub is_loop: bool,
+    /// The MIDI note of the key of the audio, 60 is middle C
+    pub root_note: Option<u16>,
+    /// Whether the audio should be stretched to match the tempo of the project
+    pub stretch: bool,
+    /// Whether the audio should be played from disk, rather than memory
+    pub disk_based: bool,
+    /// The amount of beats in the audio
+    pub num_beats: u32,
+    /// The lower number of the time signature
+    pub meter_denominator: u16,
+    /// The upper number of the time signature
+    pub meter_numerator: u16,
+    /// The tempo in beats per minute
+    pub tempo: f32,
+}
+
+impl Default for AcidInfo {
+    fn default() -> Self {
+        AcidInfo {
+            is_loop: true,
+            root_note: None,
+            stretch: true,
+            disk_based: false,
+            num_beats: 0,
+            meter_denominator: 4,
+            meter_numerator: 4,
+            tempo: 120.0,
+        }
+    }
+}
+
+impl AcidInfo {
+    /// Parses an `acid` chunk, `None` if it is too short
+    pub(crate) fn parse(acid: &[u8]) -> Option<AcidInfo> {
+        if acid.len() < 24 {
+            return None;
+        }
+        let u16_at = |i: usize| u16::from_le_bytes([acid[i], acid[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(acid[i..i + 4].try_into().unwrap());
+        let flags = u32_at(0);
+        Some(AcidInfo {
+            is_loop: flags & ONE_SHOT == 0,
+            root_note: (flags & ROOT_NOTE_SET != 0).then(|| u16_at(4)),
+            stretch: flags & STRETCH != 0,
+            disk_based: flags & DISK_BASED != 0,
+            num_beats: u32_at(12),
+            meter_denominator: u16_at(16),
+            meter_numerator: u16_at(18),
+            tempo: f32::from_bits(u32_at(20)),
+        })
+    }
+
+    /// Appends the `acid` chunk to `out`
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        let mut flags = 0;
+        for (set, flag) in [
+            (!self.is_loop, ONE_SHOT),
+            (self.root_note.is_some(), ROOT_NOTE_SET),
+            (self.stretch, STRETCH),
+            (self.disk_based, DISK_BASED),
+        ] {
+            if set {
+                flags |= flag;
+            }
+        }
+        let mut acid = Vec::with_capacity(24);
+        acid.extend(flags.to_le_bytes());
+        acid.extend(self.root_note.unwrap_or(60).to_le_bytes());
+        // Unknown fields, with the values ACID writes
+        acid.extend(0x8000u16.to_le_bytes());
+        acid.extend(0f32.to_le_bytes());
+        acid.extend(self.num_beats.to_le_bytes());
+        acid.extend(self.meter_denominator.to_le_bytes());
+        acid.extend(self.meter_numerator.to_le_bytes());
+        acid.extend(self.tempo.to_le_bytes());
+        chunk::write_chunk(out, b"acid", &acid);
+    }
+}
+
+// Acid
+impl WaveFile {
+    /// Returns the tempo and beat information of the `acid` chunk, if the file has one
+    pub fn acid_info(&self) -> Option<&AcidInfo> {
+        self.acid_info.as_ref()
+    }
+
+    /// Sets or removes (with `None`) the tempo and beat information written as the `acid` chunk
+    pub fn set_acid_info(&mut self, acid_info: Option<AcidInfo>) {
+        self.acid_info = acid_info;
+    }
+
+    /// Returns the tempo in beats per minute from the `acid` chunk, if the file has one
+    pub fn tempo_bpm(&self) -> Option<f32> {
+        self.acid_info.map(|acid| acid.tempo)
+    }
+}
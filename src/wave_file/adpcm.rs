@@ -0,0 +1,115 @@
+//! Decoding of IMA ADPCM (format code 0x11) into 16-bit PCM.
+
+use super::{AudioFormat, WaveError, WaveFile};
+
+/// The format code of IMA (DVI) ADPCM
+pub(crate) const IMA_ADPCM: u16 = 0x11;
+
+const INDEX_TABLE: [i32; 16] = [-1, -1, -1, -1, 2, 4, 6, 8, -1, -1, -1, -1, 2, 4, 6, 8];
+
+const STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408,
+    449, 494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066,
+    2272, 2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630,
+    9493, 10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794,
+    32767,
+];
+
+/// The decoder state of a single channel
+struct Channel {
+    predictor: i32,
+    step_index: i32,
+}
+
+impl Channel {
+    fn decode(&mut self, nibble: u8) -> i16 {
+        let step = STEP_TABLE[self.step_index as usize];
+        let mut diff = step >> 3;
+        if nibble & 1 != 0 { diff += step >> 2; }
+        if nibble & 2 != 0 { diff += step >> 1; }
+        if nibble & 4 != 0 { diff += step; }
+        if nibble & 8 != 0 { diff = -diff; }
+
+        self.predictor = (self.predictor + diff).clamp(i16::MIN as i32, i16::MAX as i32);
+        self.step_index = (self.step_index + INDEX_TABLE[nibble as usize]).clamp(0, 88);
+        self.predictor as i16
+    }
+}
+
+/// Decodes the data chunk of an IMA ADPCM file into a 16-bit PCM `WaveFile`.
+///
+/// - `fmt`: the payload of the fmt chunk
+/// - `data`: the payload of the data chunk
+/// - `num_frames`: the sample frame count from the fact chunk, if present
+pub(crate) fn decode_ima(fmt: &[u8], data: &[u8], num_frames: Option<u32>) -> Result<WaveFile, WaveError> {
+    if fmt.len() < 16 {
+        return Err(WaveError::Truncated);
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([fmt[i], fmt[i + 1]]) as usize;
+    let num_channels = u16_at(2);
+    let sample_rate = u32::from_le_bytes(fmt[4..8].try_into().unwrap());
+    let block_align = u16_at(12);
+    let bits_per_sample = u16_at(14);
+
+    if bits_per_sample != 4 {
+        return Err(WaveError::UnsupportedBitsPerSample(bits_per_sample as u16));
+    }
+    // Every block starts with a 4 byte header per channel, followed by groups of 4 bytes
+    // (8 samples) per channel
+    if num_channels == 0 || block_align < 4 * num_channels || (block_align - 4 * num_channels) % (4 * num_channels) != 0 {
+        return Err(WaveError::UnsupportedFormat(IMA_ADPCM));
+    }
+    let mut samples_per_block = (block_align - 4 * num_channels) * 2 / num_channels + 1;
+    // The extension (cbSize = 2) contains the samples per block
+    if fmt.len() >= 20 && u16_at(16) >= 2 && u16_at(18) != 0 {
+        samples_per_block = samples_per_block.min(u16_at(18));
+    }
+
+    // The header is untrusted, so the byte rate of the PCM file may not fit
+    let mut wave = WaveFile::try_new(AudioFormat::PCM, num_channels as u16, sample_rate, 16)?;
+    let mut pcm: Vec<i16> = Vec::with_capacity(data.len() / block_align * samples_per_block * num_channels + samples_per_block * num_channels);
+    // The final block may be partial
+    for block in data.chunks(block_align) {
+        if block.len() < 4 * num_channels {
+            break;
+        }
+        let (header, body) = block.split_at(4 * num_channels);
+
+        let mut channels: Vec<Channel> = header.chunks_exact(4).map(|h| Channel {
+            predictor: i16::from_le_bytes([h[0], h[1]]) as i32,
+            step_index: (h[2] as i32).clamp(0, 88),
+        }).collect();
+
+        // The first sample of every block is the predictor in the header
+        let mut block_samples: Vec<i16> = channels.iter().map(|c| c.predictor as i16).collect();
+
+        // Per channel 8 samples are stored in 4 bytes, the low nibble first. Channels are
+        // interleaved per 4 bytes.
+        let mut decoded = vec![Vec::with_capacity(samples_per_block); num_channels];
+        for group in body.chunks(4 * num_channels) {
+            for (ch, bytes) in group.chunks(4).enumerate() {
+                if num_channels > 1 && bytes.len() < 4 {
+                    break;
+                }
+                for &byte in bytes {
+                    decoded[ch].push(channels[ch].decode(byte & 0x0F));
+                    decoded[ch].push(channels[ch].decode(byte >> 4));
+                }
+            }
+        }
+
+        let block_frames = decoded.iter().map(|c| c.len()).min().unwrap_or(0).min(samples_per_block - 1);
+        for i in 0..block_frames {
+            block_samples.extend(decoded.iter().map(|c| c[i]));
+        }
+        pcm.extend(block_samples);
+    }
+
+    if let Some(num_frames) = num_frames {
+        pcm.truncate(num_frames as usize * num_channels);
+    }
+
+    wave.audio_byte_data.set(pcm.iter().flat_map(|s| s.to_le_bytes()).collect());
+    Ok(wave)
+}
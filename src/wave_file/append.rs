@@ -0,0 +1,121 @@
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+
+use super::recover::Layout;
+use super::{FmtChunk, WaveError, WaveFile};
+
+// Appending
+impl WaveFile {
+    /// Appends audio data to the end of the data chunk of the RIFF or RF64 file at `file_path`,
+    /// without reading or rewriting the audio that is already there. `bytes` should already be in
+    /// the format of the file, like for [add_bytes](WaveFile::add_bytes).
+    ///
+    /// The data chunk has to be the last chunk of the file: chunks after it are not moved, the
+    /// file is rejected instead. Files written by this crate put their metadata after the data
+    /// chunk, so only files written without metadata, or by a [WaveWriter](super::WaveWriter),
+    /// can be appended to.
+    ///
+    /// The audio data is written first, after which the RIFF size, the data size and the frame
+    /// count of the `fact` chunk are updated; for RF64 files these are the sizes in the `ds64`
+    /// chunk. Sizes that were already wrong are corrected as well. If writing is interrupted
+    /// between the two, [repair_header](super::repair_header) fixes the sizes.
+    ///
+    /// # Errors
+    /// - [WaveError::NotRiff] if the file doesn't start with the `RIFF`/`WAVE` or `RF64`/`WAVE`
+    ///   magic
+    /// - [WaveError::MissingChunk] if the file has no fmt chunk or no data chunk
+    /// - [WaveError::ChunksAfterData] if the data chunk isn't the last chunk of the file
+    /// - [WaveError::MisalignedData] if `bytes` isn't a whole amount of frames
+    /// - [WaveError::TooLarge] if the audio data of a RIFF file no longer fits in its 32-bit sizes
+    /// - the errors of [WaveSpec::new](super::WaveSpec::new) if the fmt chunk describes a format
+    ///   that isn't supported
+    /// - [WaveError::Io] if the file can't be read or written
+    ///
+    /// Nothing is written when an error other than [WaveError::Io] is returned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::fs;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_samples_i16(&[1, 2]).unwrap();
+    /// let path = std::env::temp_dir().join("jaudio-append-to-file.wav");
+    /// let path = path.to_str().unwrap();
+    /// fs::write(path, wave.to_bytes()).unwrap();
+    ///
+    /// WaveFile::append_to_file(path, &[3, 0, 4, 0]).unwrap();
+    /// WaveFile::append_to_file(path, &[5, 0]).unwrap();
+    /// assert_eq!(WaveFile::from_file(path).unwrap().samples_i16().unwrap(), [1, 2, 3, 4, 5]);
+    ///
+    /// // Half a frame
+    /// assert!(matches!(
+    ///     WaveFile::append_to_file(path, &[6]),
+    ///     Err(WaveError::MisalignedData { len: 1, block_align: 2 })
+    /// ));
+    ///
+    /// // Metadata is written after the data chunk
+    /// wave.info_mut().title = Some("Log".to_string());
+    /// fs::write(path, wave.to_bytes()).unwrap();
+    /// assert!(matches!(WaveFile::append_to_file(path, &[6, 0]), Err(WaveError::ChunksAfterData)));
+    /// # fs::remove_file(path).unwrap();
+    /// ```
+    pub fn append_to_file(file_path: &str, bytes: &[u8]) -> Result<(), WaveError> {
+        let mut file = OpenOptions::new().read(true).write(true).open(file_path)?;
+        let len = file.metadata()?.len();
+        let layout = Layout::scan(&mut file, len)?;
+        let fmt = FmtChunk::parse(layout.fmt.as_deref().ok_or(WaveError::MissingChunk("fmt "))?)?;
+        let data = layout.data.as_ref().ok_or(WaveError::MissingChunk("data"))?;
+        let spec = WaveFile::from_fmt(&fmt).spec();
+        spec.validate()?;
+
+        // The pad byte of the data chunk, or a missing one, may follow the audio data
+        let data_end = data.offset + 8 + data.actual;
+        if len - data_end > 1 {
+            return Err(WaveError::ChunksAfterData);
+        }
+        let block_align = fmt.block_align;
+        if block_align > 0 && !bytes.len().is_multiple_of(block_align as usize) {
+            return Err(WaveError::MisalignedData { len: bytes.len(), block_align });
+        }
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        let data_size = data.actual + bytes.len() as u64;
+        let pad = data_size % 2;
+        let riff_size = data_end + bytes.len() as u64 + pad - 8;
+        let frames = data_size / block_align.max(1) as u64;
+        let mut patches = Vec::new();
+        match layout.ds64 {
+            Some(ds64) => {
+                patches.push((ds64, riff_size.to_le_bytes().to_vec()));
+                patches.push((ds64 + 8, data_size.to_le_bytes().to_vec()));
+                patches.push((ds64 + 16, frames.to_le_bytes().to_vec()));
+            }
+            None => {
+                let size = |size: u64| u32::try_from(size).map_err(|_| WaveError::TooLarge { size: riff_size });
+                patches.push((4, size(riff_size)?.to_le_bytes().to_vec()));
+                patches.push((data.offset + 4, size(data_size)?.to_le_bytes().to_vec()));
+            }
+        }
+        if let Some(fact) = layout.fact {
+            // Files with more frames than fit in the fact chunk keep the count in the ds64 chunk
+            let frames = u32::try_from(frames).unwrap_or(u32::MAX);
+            patches.push((fact, frames.to_le_bytes().to_vec()));
+        }
+
+        // The new audio data overwrites the old pad byte
+        file.seek(SeekFrom::Start(data_end))?;
+        file.write_all(bytes)?;
+        if pad == 1 {
+            file.write_all(&[0])?;
+        }
+        file.set_len(data_end + bytes.len() as u64 + pad)?;
+        for (offset, patch) in patches {
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&patch)?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+}
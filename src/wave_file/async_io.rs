@@ -0,0 +1,249 @@
+//! Reading and writing files from tokio code, without blocking the runtime.
+
+use std::io::SeekFrom;
+
+use tokio::fs::{self, File};
+use tokio::io::{AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use super::rf64::Container;
+use super::{AudioFormat, Info, Rf64Mode, Sample, WaveError, WaveFile, WaveSpec};
+
+/// Writes a wave file block by block to an async writer, like [WaveWriter](super::WaveWriter)
+/// does to a blocking one.
+///
+/// The header is written with placeholder sizes when the writer is created, and patched with the
+/// real sizes in [finalize](AsyncWaveWriter::finalize). Unlike a `WaveWriter`, dropping the
+/// writer can't finalize it, because that needs to await the writer. A file that isn't finalized
+/// has a data chunk size of 0, which [ParseOptions::lenient](super::ParseOptions::lenient)
+/// recovers from.
+///
+/// The header leaves room for a `ds64` chunk in a `JUNK` chunk, so files that grow beyond 4 GB
+/// are turned into RF64 files when finalized (see [Rf64Mode]).
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::io::Cursor;
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut writer = AsyncWaveWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM, 2, 44100, 16).await.unwrap();
+/// for _ in 0..10 {
+///     writer.write_samples_i16(&[0, 0, 1000, -1000]).await.unwrap();
+/// }
+/// writer.info_mut().title = Some("Streamed".to_string());
+/// let bytes = writer.finalize().await.unwrap().into_inner();
+/// assert_eq!(&bytes[12..16], b"JUNK");
+///
+/// let wave = WaveFile::from_bytes(&bytes).unwrap();
+/// assert_eq!(wave.samples_i16().unwrap().len(), 40);
+/// assert_eq!(wave.info().title.as_deref(), Some("Streamed"));
+/// # });
+/// ```
+pub struct AsyncWaveWriter<W: AsyncWrite + AsyncSeek + Unpin> {
+    writer: W,
+    /// Holds the spec, its data is only used for samples that are being written
+    spec: WaveFile,
+    /// The position of the start of the header in `writer`
+    start: u64,
+    data_len: u64,
+}
+
+impl<W: AsyncWrite + AsyncSeek + Unpin> AsyncWaveWriter<W> {
+    /// Creates a new `AsyncWaveWriter` and writes the header to `writer`, at its current
+    /// position.
+    ///
+    /// See [WaveFile::new] for the parameters.
+    ///
+    /// # Errors
+    /// The errors of [WaveFile::try_new] if the parameters don't describe a wave file, nothing is
+    /// written then. Otherwise any error returned by `writer`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::io::Cursor;
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let writer = AsyncWaveWriter::new(Cursor::new(Vec::new()), AudioFormat::ALaw, 1, 8000, 16).await;
+    /// assert!(matches!(writer, Err(WaveError::UnsupportedBitsPerSample(16))));
+    /// # });
+    /// ```
+    pub async fn new(
+        mut writer: W,
+        audio_format: AudioFormat,
+        num_channels: u16,
+        sample_rate: u32,
+        bits_per_sample: u16
+    ) -> Result<AsyncWaveWriter<W>, WaveError>
+    {
+        let spec = WaveFile::try_new(audio_format, num_channels, sample_rate, bits_per_sample)?;
+        let start = writer.stream_position().await?;
+        // Room is left for a ds64 chunk, in case the file grows too large for RIFF
+        writer.write_all(&spec.header(0, 0, Container::RiffWithJunk)).await?;
+        Ok(AsyncWaveWriter { writer, spec, start, data_len: 0 })
+    }
+
+    /// Creates a new `AsyncWaveWriter` for audio of `spec` and writes the header to `writer`, at
+    /// its current position.
+    ///
+    /// # Errors
+    /// The errors of [WaveSpec::new] if the spec is invalid, nothing is written then. Otherwise
+    /// any error returned by `writer`.
+    pub async fn with_spec(writer: W, spec: WaveSpec) -> Result<AsyncWaveWriter<W>, WaveError> {
+        spec.validate()?;
+        AsyncWaveWriter::new(writer, spec.format, spec.channels, spec.sample_rate, spec.bits_per_sample).await
+    }
+
+    /// Returns the spec of the audio data written by this writer.
+    pub fn spec(&self) -> WaveSpec {
+        self.spec.spec()
+    }
+
+    /// Writes audio data to the data chunk.
+    ///
+    /// # Errors
+    /// [WaveError::MisalignedData] if the amount of bytes isn't divisible by the block align and
+    /// [WaveError::TooLarge] if the file would no longer fit in RIFF while RF64 is not allowed,
+    /// nothing is written in those cases. Otherwise any error returned by the underlying writer.
+    pub async fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), WaveError> {
+        self.spec.check_streamed(self.data_len, bytes.len())?;
+        self.writer.write_all(bytes).await?;
+        self.data_len += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Writes interleaved 16-bit samples to the data chunk.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the file isn't
+    /// 16-bit PCM and [WaveError::MisalignedData] if the amount of samples isn't divisible by the
+    /// amount of channels. Otherwise any error returned by the underlying writer.
+    pub async fn write_samples_i16(&mut self, samples: &[i16]) -> Result<(), WaveError> {
+        self.spec.add_samples_i16(samples)?;
+        self.write_spec_data().await
+    }
+
+    /// Writes interleaved samples of any [Sample] type to the data chunk, converting them to the
+    /// format and bit depth of the file like [WaveFile::add_samples].
+    ///
+    /// # Errors
+    /// The errors of [WaveFile::add_samples], or any error returned by the underlying writer.
+    pub async fn write_samples<S: Sample>(&mut self, samples: &[S]) -> Result<(), WaveError> {
+        self.spec.add_samples(samples)?;
+        self.write_spec_data().await
+    }
+
+    /// Writes the samples that were encoded into the data of `spec`
+    async fn write_spec_data(&mut self) -> Result<(), WaveError> {
+        let bytes = std::mem::take(self.spec.audio_byte_data.make_contiguous());
+        self.write_bytes(&bytes).await
+    }
+
+    /// Returns the metadata of the `LIST`/`INFO` chunk for editing. It is written after the audio
+    /// data when the writer is finalized.
+    pub fn info_mut(&mut self) -> &mut Info {
+        self.spec.info_mut()
+    }
+
+    /// Sets when the file is written as RF64, see [Rf64Mode]. By default a file is turned into
+    /// RF64 when finalized if it grew too large for RIFF.
+    pub fn set_rf64_mode(&mut self, rf64_mode: Rf64Mode) {
+        self.spec.set_rf64_mode(rf64_mode);
+    }
+
+    /// The amount of bytes of audio data written so far.
+    pub fn data_len(&self) -> u64 {
+        self.data_len
+    }
+
+    /// Pads the data chunk and patches the sizes in the header, then returns the underlying
+    /// writer, positioned at the end of the file.
+    ///
+    /// # Errors
+    /// [WaveError::TooLarge] if the trailing chunks make the file too large for RIFF while RF64
+    /// is not allowed, the header is not patched then. Otherwise any error returned by the
+    /// underlying writer.
+    pub async fn finalize(mut self) -> Result<W, WaveError> {
+        let (header, tail) = self.spec.header_and_tail(self.data_len, true)?;
+        let writer = &mut self.writer;
+        writer.write_all(&tail).await?;
+        let end = writer.stream_position().await?;
+        writer.seek(SeekFrom::Start(self.start)).await?;
+        writer.write_all(&header).await?;
+        writer.seek(SeekFrom::Start(end)).await?;
+        writer.flush().await?;
+        Ok(self.writer)
+    }
+}
+
+// Async
+impl WaveFile {
+    /// Reads a wave file like [from_file](WaveFile::from_file), reading the file with tokio so
+    /// the runtime isn't blocked.
+    ///
+    /// # Errors
+    /// The errors of [from_file](WaveFile::from_file).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_samples_i16(&[1, 2, 3]).unwrap();
+    /// let path = std::env::temp_dir().join("jaudio-from-file-async.wav");
+    /// let path = path.to_str().unwrap();
+    ///
+    /// wave.write_to_file_async(path).await.unwrap();
+    /// let read = WaveFile::from_file_async(path).await.unwrap();
+    /// assert_eq!(read.samples_i16().unwrap(), [1, 2, 3]);
+    /// # tokio::fs::remove_file(path).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn from_file_async(file_path: &str) -> Result<WaveFile, WaveError> {
+        let file_content = fs::read(file_path).await?;
+        WaveFile::from_bytes(&file_content)
+    }
+
+    /// Writes the byte representation of the `WaveFile` (see [to_bytes](WaveFile::to_bytes)) to
+    /// the file at `file_path` with tokio, so the runtime isn't blocked. An existing file is
+    /// replaced.
+    ///
+    /// # Errors
+    /// - [WaveError::TooLarge] if the file doesn't fit in RIFF and the [Rf64Mode] is
+    ///   [Never](Rf64Mode::Never)
+    /// - [WaveError::Io] if the file can't be written
+    pub async fn write_to_file_async(&self, file_path: &str) -> Result<(), WaveError> {
+        let mut file = File::create(file_path).await?;
+        self.write_to_async(&mut file).await
+    }
+
+    /// Writes the byte representation of the `WaveFile` (see [to_bytes](WaveFile::to_bytes)) to
+    /// the async writer `w`, like [write_to](WaveFile::write_to). The audio data is written
+    /// from where it is stored, without copying it first.
+    ///
+    /// # Errors
+    /// - [WaveError::TooLarge] if the file doesn't fit in RIFF and the [Rf64Mode] is
+    ///   [Never](Rf64Mode::Never), nothing is written then
+    /// - [WaveError::Io] for any error returned by `w`
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let mut wave = WaveFile::new(AudioFormat::MuLaw, 1, 8000, 8);
+    /// wave.add_bytes(&mut vec![0xFF; 101]);
+    ///
+    /// let mut bytes = Vec::new();
+    /// wave.write_to_async(&mut bytes).await.unwrap();
+    /// assert_eq!(bytes, wave.to_bytes());
+    /// # });
+    /// ```
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, w: &mut W) -> Result<(), WaveError> {
+        let (header, tail) = self.header_and_tail(self.audio_byte_data.len() as u64, false)?;
+        w.write_all(&header).await?;
+        for segment in self.audio_byte_data.segments() {
+            w.write_all(segment).await?;
+        }
+        w.write_all(&tail).await?;
+        w.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use super::chunk;
+use super::WaveFile;
+
+/// The size of the fixed fields of a `bext` chunk, up to the coding history
+const FIXED_SIZE: usize = 602;
+
+/// The Broadcast Wave Format metadata of the `bext` chunk of a wave file (EBU Tech 3285).
+///
+/// Text fields are ASCII, stored in fields of a fixed size. Longer text is cut off when
+/// written.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 48000, 24);
+/// wave.set_broadcast_ext(Some(BroadcastExt {
+///     description: String::from("Interview, take 3"),
+///     originator: String::from("Field recorder"),
+///     origination_date: String::from("2008-12-10"),
+///     origination_time: String::from("16:27:27"),
+///     // More than 24 hours at 48 kHz, which doesn't fit in 32 bits
+///     time_reference: 0x0000_0001_2345_6789,
+///     coding_history: String::from("A=PCM,F=48000,W=24,M=stereo,T=original\r\n"),
+///     ..BroadcastExt::default()
+/// }));
+///
+/// let bytes = wave.to_bytes();
+/// let parsed = WaveFile::from_bytes(&bytes).unwrap();
+/// assert_eq!(parsed.broadcast_ext(), wave.broadcast_ext());
+/// assert_eq!(parsed.broadcast_ext().unwrap().time_reference, 4886718345);
+///
+/// // TimeReferenceLow and TimeReferenceHigh
+/// let bext = bytes.windows(4).position(|w| w == b"bext").unwrap() + 8;
+/// assert_eq!(bytes[bext + 338..bext + 346], [0x89, 0x67, 0x45, 0x23, 0x01, 0, 0, 0]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BroadcastExt {
+    /// A description of the sound, at most 256 characters
+    pub description: String,
+    /// The name of the originator, at most 32 characters
+    pub originator: String,
+    /// A unique reference given by the originator, at most 32 characters
+    pub originator_reference: String,
+    /// The date of creation, formatted like `yyyy-mm-dd`
+    pub origination_date: String,
+    /// The time of creation, formatted like `hh:mm:ss`
+    pub origination_time: String,
+    /// The time of the first frame since midnight, in frames
+    pub time_reference: u64,
+    /// The version of the chunk, 2 includes the loudness fields
+    pub version: u16,
+    /// The SMPTE UMID (ST 330) of the audio, all zero if not used
+    #[cfg_attr(feature = "serde", serde(with = "umid"))]
+    pub umid: [u8; 64],
+    /// The integrated loudness in LUFS times 100 (version 2)
+    pub loudness_value: i16,
+    /// The loudness range in LU times 100 (version 2)
+    pub loudness_range: i16,
+    /// The maximum true peak level in dBTP times 100 (version 2)
+    pub max_true_peak_level: i16,
+    /// The highest momentary loudness in LUFS times 100 (version 2)
+    pub max_momentary_loudness: i16,
+    /// The highest short term loudness in LUFS times 100 (version 2)
+    pub max_short_term_loudness: i16,
+    /// The coding history, lines of text ending in a carriage return and line feed
+    pub coding_history: String,
+}
+
+/// Serializes the UMID as a sequence of bytes, serde only implements arrays of up to 32 elements
+#[cfg(feature = "serde")]
+mod umid {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(umid: &[u8; 64], serializer: S) -> Result<S::Ok, S::Error> {
+        umid.as_slice().serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 64], D::Error> {
+        let umid = Vec::<u8>::deserialize(deserializer)?;
+        umid.as_slice().try_into().map_err(|_| D::Error::invalid_length(umid.len(), &"64 bytes"))
+    }
+}
+
+impl Default for BroadcastExt {
+    fn default() -> Self {
+        BroadcastExt {
+            description: String::new(),
+            originator: String::new(),
+            originator_reference: String::new(),
+            origination_date: String::new(),
+            origination_time: String::new(),
+            time_reference: 0,
+            version: 2,
+            umid: [0; 64],
+            loudness_value: 0,
+            loudness_range: 0,
+            max_true_peak_level: 0,
+            max_momentary_loudness: 0,
+            max_short_term_loudness: 0,
+            coding_history: String::new(),
+        }
+    }
+}
+
+/// The text of a fixed size field, up to the first null byte
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Appends `text` as a field of `size` bytes, cut off or padded with null bytes
+fn write_text(out: &mut Vec<u8>, text: &str, size: usize) {
+    let bytes = &text.as_bytes()[..text.len().min(size)];
+    out.extend(bytes);
+    out.resize(out.len() + size - bytes.len(), 0);
+}
+
+impl BroadcastExt {
+    /// Parses a `bext` chunk, `None` if it is too short
+    pub(crate) fn parse(bext: &[u8]) -> Option<BroadcastExt> {
+        if bext.len() < FIXED_SIZE {
+            return None;
+        }
+        let u32_at = |i: usize| u32::from_le_bytes(bext[i..i + 4].try_into().unwrap());
+        let i16_at = |i: usize| i16::from_le_bytes([bext[i], bext[i + 1]]);
+        Some(BroadcastExt {
+            description: text(&bext[0..256]),
+            originator: text(&bext[256..288]),
+            originator_reference: text(&bext[288..320]),
+            origination_date: text(&bext[320..330]),
+            origination_time: text(&bext[330..338]),
+            // TimeReferenceLow and TimeReferenceHigh
+            time_reference: u32_at(338) as u64 | (u32_at(342) as u64) << 32,
+            version: u16::from_le_bytes([bext[346], bext[347]]),
+            umid: bext[348..412].try_into().unwrap(),
+            loudness_value: i16_at(412),
+            loudness_range: i16_at(414),
+            max_true_peak_level: i16_at(416),
+            max_momentary_loudness: i16_at(418),
+            max_short_term_loudness: i16_at(420),
+            coding_history: text(&bext[FIXED_SIZE..]),
+        })
+    }
+
+    /// Appends the `bext` chunk to `out`
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        let mut bext = Vec::with_capacity(FIXED_SIZE + self.coding_history.len());
+        write_text(&mut bext, &self.description, 256);
+        write_text(&mut bext, &self.originator, 32);
+        write_text(&mut bext, &self.originator_reference, 32);
+        write_text(&mut bext, &self.origination_date, 10);
+        write_text(&mut bext, &self.origination_time, 8);
+        bext.extend((self.time_reference as u32).to_le_bytes());
+        bext.extend(((self.time_reference >> 32) as u32).to_le_bytes());
+        bext.extend(self.version.to_le_bytes());
+        bext.extend(self.umid);
+        for value in [
+            self.loudness_value,
+            self.loudness_range,
+            self.max_true_peak_level,
+            self.max_momentary_loudness,
+            self.max_short_term_loudness,
+        ] {
+            bext.extend(value.to_le_bytes());
+        }
+        // Reserved
+        bext.resize(FIXED_SIZE, 0);
+        bext.extend(self.coding_history.as_bytes());
+        chunk::write_chunk(out, b"bext", &bext);
+    }
+}
+
+// Broadcast Wave
+impl WaveFile {
+    /// Returns the Broadcast Wave metadata of the `bext` chunk, if the file has one
+    pub fn broadcast_ext(&self) -> Option<&BroadcastExt> {
+        self.broadcast_ext.as_ref()
+    }
+
+    /// Sets or removes (with `None`) the Broadcast Wave metadata written as the `bext` chunk
+    pub fn set_broadcast_ext(&mut self, broadcast_ext: Option<BroadcastExt>) {
+        self.broadcast_ext = broadcast_ext;
+    }
+}
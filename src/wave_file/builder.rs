@@ -0,0 +1,146 @@
+use super::{AudioFormat, WaveError, WaveFile, WaveSpec};
+
+/// Audio data added to a [WaveBuilder], added to the file when it is built
+#[derive(Debug, Clone)]
+enum Data {
+    Bytes(Vec<u8>),
+    I16(Vec<i16>),
+    F32(Vec<f32>),
+}
+
+/// Builds a [WaveFile] step by step, see [WaveFile::builder].
+///
+/// Unset properties default to stereo 16-bit PCM at 44.1 kHz. Nothing is checked until
+/// [build](WaveBuilder::build) is called, so the properties can be set in any order.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let wave = WaveFile::builder()
+///     .channels(2)
+///     .sample_rate(44_100)
+///     .bits(16)
+///     .format(AudioFormat::PCM)
+///     .samples_i16(&[0, 0, 1000, -1000])
+///     .build()
+///     .unwrap();
+/// assert_eq!(wave.num_frames(), 2);
+///
+/// // µ-law needs 8 bits per sample
+/// let result = WaveFile::builder().format(AudioFormat::MuLaw).build();
+/// assert!(matches!(result, Err(WaveError::UnsupportedBitsPerSample(16))));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WaveBuilder {
+    spec: WaveSpec,
+    data: Vec<Data>,
+}
+
+impl Default for WaveBuilder {
+    fn default() -> Self {
+        WaveBuilder { spec: WaveSpec::cd_quality(), data: Vec::new() }
+    }
+}
+
+impl WaveBuilder {
+    /// Sets the audio format, [AudioFormat::PCM] by default.
+    pub fn format(mut self, format: AudioFormat) -> WaveBuilder {
+        self.spec.format = format;
+        self
+    }
+
+    /// Sets the amount of channels, 2 by default.
+    pub fn channels(mut self, channels: u16) -> WaveBuilder {
+        self.spec.channels = channels;
+        self
+    }
+
+    /// Sets the sample rate in Hz, 44100 by default.
+    pub fn sample_rate(mut self, sample_rate: u32) -> WaveBuilder {
+        self.spec.sample_rate = sample_rate;
+        self
+    }
+
+    /// Sets the amount of bits per sample, 16 by default.
+    pub fn bits(mut self, bits_per_sample: u16) -> WaveBuilder {
+        self.spec.bits_per_sample = bits_per_sample;
+        self
+    }
+
+    /// Sets the format, channels, sample rate and bits per sample at once.
+    pub fn spec(mut self, spec: WaveSpec) -> WaveBuilder {
+        self.spec = spec;
+        self
+    }
+
+    /// Adds audio data that is already in the format of the file, see [WaveFile::try_add_bytes].
+    pub fn bytes(mut self, bytes: Vec<u8>) -> WaveBuilder {
+        self.data.push(Data::Bytes(bytes));
+        self
+    }
+
+    /// Adds interleaved 16-bit samples, which are converted to the format of the file when it is
+    /// built (see [WaveFile::add_samples_i16_converted]).
+    pub fn samples_i16(mut self, samples: &[i16]) -> WaveBuilder {
+        self.data.push(Data::I16(samples.to_vec()));
+        self
+    }
+
+    /// Adds interleaved 32-bit float samples, which are converted to the format of the file when
+    /// it is built (see [WaveFile::add_samples_f32_converted]).
+    pub fn samples_f32(mut self, samples: &[f32]) -> WaveBuilder {
+        self.data.push(Data::F32(samples.to_vec()));
+        self
+    }
+
+    /// Builds the `WaveFile`, adding the audio data in the order it was given.
+    ///
+    /// # Errors
+    /// - The errors of [WaveSpec::new] if the properties don't describe a valid wave file
+    /// - [WaveError::MisalignedData] if audio data doesn't consist of whole frames
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let wave = WaveFile::builder()
+    ///     .channels(1)
+    ///     .format(AudioFormat::IeeeFloat)
+    ///     .bits(32)
+    ///     .samples_i16(&[16384])
+    ///     .samples_f32(&[-0.25])
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(wave.samples_f32().unwrap(), [0.5, -0.25]);
+    ///
+    /// let result = WaveFile::builder().channels(2).samples_i16(&[1, 2, 3]).build();
+    /// assert!(matches!(result, Err(WaveError::MisalignedData { .. })));
+    /// ```
+    pub fn build(self) -> Result<WaveFile, WaveError> {
+        self.spec.validate()?;
+        let mut wave = WaveFile::with_spec(self.spec);
+        for data in self.data {
+            match data {
+                Data::Bytes(mut bytes) => wave.try_add_bytes(&mut bytes)?,
+                Data::I16(samples) => wave.add_samples(&samples)?,
+                Data::F32(samples) => wave.add_samples(&samples)?,
+            }
+        }
+        Ok(wave)
+    }
+}
+
+// Builder
+impl WaveFile {
+    /// Returns a [WaveBuilder] to build a `WaveFile` with, which checks the spec instead of
+    /// panicking like [WaveFile::new].
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let wave = WaveFile::builder().build().unwrap();
+    /// assert_eq!(wave.spec(), WaveSpec::cd_quality());
+    /// ```
+    pub fn builder() -> WaveBuilder {
+        WaveBuilder::default()
+    }
+}
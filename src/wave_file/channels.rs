@@ -0,0 +1,336 @@
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use super::sample;
+use super::{WaveError, WaveFile};
+
+/// How the channels are combined by [WaveFile::to_mono].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownmixMode {
+    /// The mean of the channels. Never clips.
+    Average,
+    /// The sum of the channels, attenuated by 3 dB. Keeps the loudness of uncorrelated channels,
+    /// but may clip.
+    SumMinus3Db,
+    /// The sum of the channels, attenuated by 6 dB (halved). Keeps the loudness of identical
+    /// stereo channels, but may clip with more channels.
+    SumMinus6Db,
+}
+
+// Channels
+impl WaveFile {
+    /// Splits the audio into one mono file for every channel, with the same format as this file.
+    ///
+    /// # Errors
+    /// The same errors as [WaveFile::try_new] for a mono file with the format of this file, e.g.
+    /// [WaveError::InvalidSpec] if the sample rate is 0.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, -1, 2, -2, 3, -3]).unwrap();
+    ///
+    /// let channels = wave.split_channels().unwrap();
+    /// assert_eq!(channels[0].num_channels(), 1);
+    /// assert_eq!(channels[0].samples_i16().unwrap(), [1, 2, 3]);
+    /// assert_eq!(channels[1].samples_i16().unwrap(), [-1, -2, -3]);
+    ///
+    /// let merged = WaveFile::from_channels(&channels, false).unwrap();
+    /// assert_eq!(merged.samples_i16().unwrap(), wave.samples_i16().unwrap());
+    /// ```
+    pub fn split_channels(&self) -> Result<Vec<WaveFile>, WaveError> {
+        (0..self.num_channels as usize).map(|channel| self.select_channels(&[channel])).collect()
+    }
+
+    /// Interleaves mono files into one file with a channel for every file, in order.
+    ///
+    /// The files need the same format. If `pad` is true, files that are shorter than the longest
+    /// file are padded with silence, otherwise they all need the same length.
+    ///
+    /// # Errors
+    /// - [WaveError::FormatMismatch] if a file isn't mono or the formats of the files differ
+    /// - [WaveError::LengthMismatch] if the files don't have the same amount of frames and `pad`
+    ///   is false
+    /// - [WaveError::InvalidSpec] if `channels` is empty or has more than 65535 files, or the
+    ///   block align or byte rate of the result doesn't fit
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut left = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// left.add_samples(&[1u8, 2, 3]).unwrap();
+    /// let mut right = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// right.add_samples(&[4u8]).unwrap();
+    ///
+    /// let channels = [left, right];
+    ///
+    /// assert!(matches!(
+    ///     WaveFile::from_channels(&channels, false),
+    ///     Err(WaveError::LengthMismatch { expected: 3, found: 1 })
+    /// ));
+    ///
+    /// let stereo = WaveFile::from_channels(&channels, true).unwrap();
+    /// assert_eq!(stereo.num_channels(), 2);
+    /// assert_eq!(stereo.samples_as::<u8>().unwrap(), [1, 4, 2, 128, 3, 128]);
+    ///
+    /// assert!(matches!(WaveFile::from_channels(&[], false), Err(WaveError::InvalidSpec(_))));
+    /// ```
+    pub fn from_channels(channels: &[WaveFile], pad: bool) -> Result<WaveFile, WaveError> {
+        let Some(first) = channels.first() else {
+            return Err(WaveError::InvalidSpec("a wave file needs at least one channel"));
+        };
+        for channel in channels {
+            if channel.num_channels != 1 {
+                return Err(WaveError::FormatMismatch {
+                    property: "amount of channels",
+                    expected: 1,
+                    found: channel.num_channels.into(),
+                });
+            }
+            first.check_same_format(channel)?;
+        }
+        let frames = channels.iter().map(WaveFile::num_frames).max().unwrap_or(0);
+        if !pad {
+            if let Some(channel) = channels.iter().find(|c| c.num_frames() != first.num_frames()) {
+                return Err(WaveError::LengthMismatch { expected: first.num_frames(), found: channel.num_frames() });
+            }
+        }
+
+        let mut wave = first.empty_with_channels(channels.len())?;
+        let mut silence = first.empty_with_channels(1)?;
+        silence.append_silent_frames(1);
+        let data = wave.audio_byte_data.make_contiguous();
+        data.reserve(frames as usize * wave.block_align as usize);
+        for frame in 0..frames {
+            for channel in channels {
+                let sample = channel.frame(frame).unwrap_or(silence.frame(0).unwrap());
+                data.extend_from_slice(sample);
+            }
+        }
+        Ok(wave)
+    }
+
+    /// Returns a mono copy of the file, combining the channels of every frame according to
+    /// `mode`. Samples are combined as normalized values, integer samples saturate at the limits
+    /// of their bit depth.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[i16::MAX, i16::MAX, 1000, 3000, -20000, 20000]).unwrap();
+    ///
+    /// let mono = wave.to_mono(DownmixMode::Average).unwrap();
+    /// assert_eq!(mono.num_channels(), 1);
+    /// assert_eq!(mono.block_align(), 2);
+    /// assert_eq!(mono.byte_rate(), 88200);
+    /// assert_eq!(mono.samples_i16().unwrap(), [i16::MAX, 2000, 0]);
+    ///
+    /// let mono = wave.to_mono(DownmixMode::SumMinus3Db).unwrap();
+    /// assert_eq!(mono.samples_i16().unwrap(), [i16::MAX, 2828, 0]);
+    /// ```
+    pub fn to_mono(&self, mode: DownmixMode) -> Result<WaveFile, WaveError> {
+        let channels = self.num_channels.max(1) as f64;
+        let scale = match mode {
+            DownmixMode::Average => 1.0 / channels,
+            DownmixMode::SumMinus3Db => FRAC_1_SQRT_2,
+            DownmixMode::SumMinus6Db => 0.5,
+        };
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        let mut sums = vec![0.0; self.num_frames() as usize];
+        self.for_each_sample(|frame, _, sample| sums[frame as usize] += sample)?;
+
+        let mut wave = self.empty_with_channels(1)?;
+        let data = wave.audio_byte_data.make_contiguous();
+        data.reserve(sums.len() * (bits / 8) as usize);
+        for sum in sums {
+            sample::encode(sum * scale, format, bits, data);
+        }
+        Ok(wave)
+    }
+
+    /// Returns a copy of a mono file with its channel duplicated to `num_channels` channels.
+    ///
+    /// # Errors
+    /// - [WaveError::FormatMismatch] if the file isn't mono
+    /// - [WaveError::InvalidSpec] if `num_channels` is 0, or the block align or byte rate of the
+    ///   result doesn't fit
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 48000, 24);
+    /// wave.add_samples(&[I24::MIN, I24::MAX]).unwrap();
+    ///
+    /// let surround = wave.to_channels(6).unwrap();
+    /// assert_eq!(surround.num_channels(), 6);
+    /// assert_eq!(surround.block_align(), 18);
+    /// assert_eq!(surround.byte_rate(), 48000 * 18);
+    /// assert_eq!(surround.samples_as::<I24>().unwrap(), [[I24::MIN; 6], [I24::MAX; 6]].concat());
+    ///
+    /// assert!(matches!(wave.to_channels(40000), Err(WaveError::InvalidSpec(_))));
+    /// assert!(matches!(wave.to_channels(0), Err(WaveError::InvalidSpec(_))));
+    /// ```
+    pub fn to_channels(&self, num_channels: u16) -> Result<WaveFile, WaveError> {
+        if self.num_channels != 1 {
+            return Err(WaveError::FormatMismatch { property: "amount of channels", expected: 1, found: self.num_channels.into() });
+        }
+        self.select_channels(&vec![0; num_channels as usize])
+    }
+
+    /// Swaps the samples of channels `a` and `b` in every frame.
+    ///
+    /// # Errors
+    /// [WaveError::ChannelOutOfRange] if either channel doesn't exist.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 3, 44100, 16);
+    /// wave.add_samples_i16(&[1, 2, 3, 4, 5, 6]).unwrap();
+    ///
+    /// wave.swap_channels(0, 2).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [3, 2, 1, 6, 5, 4]);
+    /// assert!(matches!(wave.swap_channels(1, 3), Err(WaveError::ChannelOutOfRange { channel: 3, num_channels: 3 })));
+    /// ```
+    pub fn swap_channels(&mut self, a: usize, b: usize) -> Result<(), WaveError> {
+        let mut order: Vec<usize> = (0..self.num_channels as usize).collect();
+        self.check_channel(a)?;
+        self.check_channel(b)?;
+        order.swap(a, b);
+        self.reorder_channels(&order)
+    }
+
+    /// Reorders the channels of every frame: channel `i` of the result is channel `order[i]` of
+    /// the file. To duplicate or drop channels, use [WaveFile::map_channels].
+    ///
+    /// # Errors
+    /// [WaveError::ChannelOutOfRange] if a channel in `order` doesn't exist, or
+    /// [WaveError::InvalidChannelOrder] if `order` doesn't contain every channel exactly once.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 3, 44100, 24);
+    /// let samples: Vec<I24> = (1..=6).map(|s| I24::new(s).unwrap()).collect();
+    /// wave.add_samples(&samples).unwrap();
+    ///
+    /// wave.reorder_channels(&[2, 0, 1]).unwrap();
+    /// let reordered: Vec<i32> = wave.samples_as::<I24>().unwrap().into_iter().map(I24::value).collect();
+    /// assert_eq!(reordered, [3, 1, 2, 6, 4, 5]);
+    ///
+    /// assert!(matches!(wave.reorder_channels(&[0, 0, 1]), Err(WaveError::InvalidChannelOrder)));
+    /// assert!(matches!(wave.reorder_channels(&[0, 1]), Err(WaveError::InvalidChannelOrder)));
+    /// ```
+    pub fn reorder_channels(&mut self, order: &[usize]) -> Result<(), WaveError> {
+        let mut used = vec![false; self.num_channels as usize];
+        for &channel in order {
+            self.check_channel(channel)?;
+            if std::mem::replace(&mut used[channel], true) {
+                return Err(WaveError::InvalidChannelOrder);
+            }
+        }
+        if order.len() != used.len() {
+            return Err(WaveError::InvalidChannelOrder);
+        }
+        let data = self.channel_bytes(order);
+        self.audio_byte_data.set(data);
+        Ok(())
+    }
+
+    /// Returns a copy of the file with only channel `channel`, as a mono file.
+    ///
+    /// # Errors
+    /// [WaveError::ChannelOutOfRange] if the channel doesn't exist.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 44100, 64);
+    /// wave.add_samples(&[0.5f64, -0.5, 0.25, -0.25]).unwrap();
+    ///
+    /// assert_eq!(wave.extract_channel(1).unwrap().samples_as::<f64>().unwrap(), [-0.5, -0.25]);
+    /// assert!(wave.extract_channel(2).is_err());
+    /// ```
+    pub fn extract_channel(&self, channel: usize) -> Result<WaveFile, WaveError> {
+        self.map_channels(&[channel])
+    }
+
+    /// Returns a copy of the file with the channels in `channels`: channel `i` of the result is
+    /// channel `channels[i]` of the file. Unlike [WaveFile::reorder_channels], channels may be
+    /// duplicated or left out, which changes the amount of channels.
+    ///
+    /// # Errors
+    /// - [WaveError::ChannelOutOfRange] if a channel in `channels` doesn't exist
+    /// - [WaveError::InvalidSpec] if `channels` is empty or has more than 65535 channels, or the
+    ///   block align or byte rate of the result doesn't fit
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 3, 44100, 8);
+    /// wave.add_samples(&[1u8, 2, 3]).unwrap();
+    ///
+    /// let mapped = wave.map_channels(&[2, 2, 0, 2]).unwrap();
+    /// assert_eq!(mapped.num_channels(), 4);
+    /// assert_eq!(mapped.samples_as::<u8>().unwrap(), [3, 3, 1, 3]);
+    ///
+    /// assert!(matches!(wave.map_channels(&[0; 70000]), Err(WaveError::InvalidSpec(_))));
+    /// ```
+    pub fn map_channels(&self, channels: &[usize]) -> Result<WaveFile, WaveError> {
+        for &channel in channels {
+            self.check_channel(channel)?;
+        }
+        self.select_channels(channels)
+    }
+
+    /// Errors if the file doesn't have a channel `channel`
+    fn check_channel(&self, channel: usize) -> Result<(), WaveError> {
+        if channel >= self.num_channels as usize {
+            return Err(WaveError::ChannelOutOfRange { channel, num_channels: self.num_channels });
+        }
+        Ok(())
+    }
+
+    /// Returns a copy of the file with the given channels, in order. Indices must be in range.
+    pub(crate) fn select_channels(&self, channels: &[usize]) -> Result<WaveFile, WaveError> {
+        let mut wave = self.empty_with_channels(channels.len())?;
+        wave.audio_byte_data.set(self.channel_bytes(channels));
+        Ok(wave)
+    }
+
+    /// Returns the samples of the given channels of every frame, in order. Indices must be in
+    /// range.
+    fn channel_bytes(&self, channels: &[usize]) -> Vec<u8> {
+        let sample_len = (self.bits_per_sample / 8) as usize;
+        let mut data = Vec::with_capacity(self.num_frames() as usize * channels.len() * sample_len);
+        for frame in self.frames() {
+            for &channel in channels {
+                data.extend_from_slice(&frame[channel * sample_len..(channel + 1) * sample_len]);
+            }
+        }
+        data
+    }
+
+    /// Returns a file without audio data with the format of this file, but `num_channels`
+    /// channels.
+    ///
+    /// # Errors
+    /// [WaveError::InvalidSpec] if `num_channels` doesn't fit in 16 bits, and the same errors as
+    /// [WaveFile::try_new].
+    pub(crate) fn empty_with_channels(&self, num_channels: usize) -> Result<WaveFile, WaveError> {
+        let num_channels = u16::try_from(num_channels)
+            .map_err(|_| WaveError::InvalidSpec("a wave file can't have more than 65535 channels"))?;
+        let mut wave = WaveFile::try_new(self.audio_format(), num_channels, self.sample_rate, self.bits_per_sample)?;
+        wave.valid_bits_per_sample = self.valid_bits_per_sample;
+        wave.extensible = self.extensible;
+        if num_channels == self.num_channels {
+            wave.channel_mask = self.channel_mask;
+        }
+        Ok(wave)
+    }
+}
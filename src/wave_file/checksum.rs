@@ -0,0 +1,252 @@
+use super::sample::{self, Sample};
+use super::{g711, AudioFormat, WaveError, WaveFile, I24};
+
+/// The amount of bytes converted at once for the checksums
+const BLOCK_LEN: usize = 1 << 16;
+
+// Checksums
+impl WaveFile {
+    /// Returns the MD5 digest of the samples, which only depends on the audio: files with the
+    /// same samples have the same digest, whatever their header, container or metadata.
+    ///
+    /// The digest is taken over the samples in the order they are stored, the channels of a
+    /// frame interleaved, each sample as a little-endian two's complement integer:
+    /// - integer PCM samples in `valid_bits_per_sample` bits, shifted right by the unused bits,
+    ///   in as many whole bytes as those bits need; 8-bit samples become signed by subtracting 128
+    /// - A-law and µ-law samples expanded to 16-bit PCM, in 2 bytes
+    /// - IEEE float samples as stored, in 4 or 8 bytes
+    ///
+    /// For integer PCM this is the layout FLAC takes the MD5 of its STREAMINFO block over, so
+    /// the digest matches that of the same audio encoded as FLAC.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let hex = |digest: [u8; 16]| digest.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    ///
+    /// let wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// assert_eq!(hex(wave.data_md5().unwrap()), "d41d8cd98f00b204e9800998ecf8427e");
+    ///
+    /// // Signed 8-bit samples "abc"
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// wave.add_samples(&[b'a' + 128, b'b' + 128, b'c' + 128]).unwrap();
+    /// assert_eq!(hex(wave.data_md5().unwrap()), "900150983cd24fb0d6963f7d28e17f72");
+    ///
+    /// // Metadata doesn't change the digest
+    /// let digest = wave.data_md5().unwrap();
+    /// wave.info_mut().title = Some("abc".to_string());
+    /// assert_eq!(WaveFile::from_bytes(&wave.to_bytes()).unwrap().data_md5().unwrap(), digest);
+    /// ```
+    pub fn data_md5(&self) -> Result<[u8; 16], WaveError> {
+        let mut md5 = Md5::new();
+        self.for_each_canonical_block(|bytes| md5.update(bytes))?;
+        Ok(md5.finish())
+    }
+
+    /// Returns the CRC-32 checksum of the samples, in the layout of
+    /// [data_md5](WaveFile::data_md5). The checksum is the one of zlib, PNG and gzip: the
+    /// reflected polynomial `0xEDB88320`, starting from and finished with all bits inverted.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// wave.add_samples(&b"123456789".map(|b| b + 128)).unwrap();
+    /// assert_eq!(wave.data_crc32().unwrap(), 0xCBF43926);
+    ///
+    /// // A µ-law file hashes like the 16-bit PCM it expands to
+    /// let mut mulaw = WaveFile::new(AudioFormat::MuLaw, 1, 8000, 8);
+    /// mulaw.add_samples_i16_converted(&[1000, -2000, 30000]).unwrap();
+    /// let pcm = mulaw.convert_to(AudioFormat::PCM, 16, DitherMode::None).unwrap();
+    /// assert_eq!(mulaw.data_crc32().unwrap(), pcm.data_crc32().unwrap());
+    /// ```
+    pub fn data_crc32(&self) -> Result<u32, WaveError> {
+        let table = crc32_table();
+        let mut crc = !0u32;
+        self.for_each_canonical_block(|bytes| {
+            for &byte in bytes {
+                crc = table[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+            }
+        })?;
+        Ok(!crc)
+    }
+
+    /// Returns whether the two files hold the same audio: the same amount of channels, sample
+    /// rate and frames, and samples that decode to the same values. The way the samples are
+    /// stored doesn't matter, so a PCM file and the same samples in a `WAVE_FORMAT_EXTENSIBLE`
+    /// file are equal, as are 16-bit samples and the same samples widened to 24 bits. Metadata is
+    /// ignored.
+    ///
+    /// Files whose samples can't be decoded are never equal, and neither are NaN float samples.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, -2, 3, -4]).unwrap();
+    ///
+    /// let mut extensible = wave.clone();
+    /// extensible.set_extensible(true);
+    /// extensible.info_mut().title = Some("Copy".to_string());
+    /// assert!(wave.content_eq(&extensible));
+    /// assert!(wave.content_eq(&wave.convert_bit_depth(24, DitherMode::None)));
+    ///
+    /// let mut louder = wave.clone();
+    /// louder.apply_gain_db(6.0, ClipPolicy::Clip).unwrap();
+    /// assert!(!wave.content_eq(&louder));
+    /// ```
+    pub fn content_eq(&self, other: &WaveFile) -> bool {
+        if self.check_same_timing(other).is_err() || self.num_frames() != other.num_frames() {
+            return false;
+        }
+        match (self.iter_samples::<f64>(), other.iter_samples::<f64>()) {
+            (Ok(samples), Ok(other_samples)) => samples.eq(other_samples),
+            _ => false,
+        }
+    }
+
+    /// Calls `f` with the samples in the layout of [data_md5](WaveFile::data_md5), a block of
+    /// bytes at a time
+    fn for_each_canonical_block(&self, mut f: impl FnMut(&[u8])) -> Result<(), WaveError> {
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        sample::check_supported(format, bits)?;
+        let valid_bits = self.valid_bits_per_sample.clamp(1, bits);
+        if matches!(format, AudioFormat::IeeeFloat) || (bits > 8 && format == AudioFormat::PCM && valid_bits == bits) {
+            // Stored in the layout already
+            self.audio_byte_data.segments().for_each(f);
+            return Ok(());
+        }
+
+        let sample_len = (bits / 8) as usize;
+        let shift = bits - valid_bits;
+        let canonical_len = valid_bits.div_ceil(8) as usize;
+        let mut block = Vec::with_capacity(BLOCK_LEN + 8);
+        for bytes in self.audio_byte_data.chunks_exact(sample_len) {
+            match format {
+                AudioFormat::ALaw => block.extend(g711::alaw_to_pcm16(bytes[0]).to_le_bytes()),
+                AudioFormat::MuLaw => block.extend(g711::mulaw_to_pcm16(bytes[0]).to_le_bytes()),
+                _ => {
+                    let value = match bits {
+                        8 => bytes[0] as i32 - 128,
+                        16 => i16::read_le(bytes) as i32,
+                        24 => I24::read_le(bytes).value(),
+                        _ => i32::read_le(bytes),
+                    };
+                    block.extend_from_slice(&(value >> shift).to_le_bytes()[..canonical_len]);
+                }
+            }
+            if block.len() >= BLOCK_LEN {
+                f(&block);
+                block.clear();
+            }
+        }
+        f(&block);
+        Ok(())
+    }
+}
+
+/// Returns the table of the CRC-32 of every byte value
+fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        *entry = (0..8).fold(i as u32, |crc, _| if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 });
+    }
+    table
+}
+
+/// The MD5 message digest (RFC 1321)
+struct Md5 {
+    state: [u32; 4],
+    /// The bytes of the current block that haven't been processed yet
+    block: [u8; 64],
+    block_len: usize,
+    /// The total amount of bytes
+    len: u64,
+}
+
+/// The amounts of bits the words are rotated by in every round
+const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+/// The constants added in every step: the integer parts of `abs(sin(i + 1)) * 2^32`
+const K: [u32; 64] = [
+    0xD76A_A478, 0xE8C7_B756, 0x2420_70DB, 0xC1BD_CEEE,
+    0xF57C_0FAF, 0x4787_C62A, 0xA830_4613, 0xFD46_9501,
+    0x6980_98D8, 0x8B44_F7AF, 0xFFFF_5BB1, 0x895C_D7BE,
+    0x6B90_1122, 0xFD98_7193, 0xA679_438E, 0x49B4_0821,
+    0xF61E_2562, 0xC040_B340, 0x265E_5A51, 0xE9B6_C7AA,
+    0xD62F_105D, 0x0244_1453, 0xD8A1_E681, 0xE7D3_FBC8,
+    0x21E1_CDE6, 0xC337_07D6, 0xF4D5_0D87, 0x455A_14ED,
+    0xA9E3_E905, 0xFCEF_A3F8, 0x676F_02D9, 0x8D2A_4C8A,
+    0xFFFA_3942, 0x8771_F681, 0x6D9D_6122, 0xFDE5_380C,
+    0xA4BE_EA44, 0x4BDE_CFA9, 0xF6BB_4B60, 0xBEBF_BC70,
+    0x289B_7EC6, 0xEAA1_27FA, 0xD4EF_3085, 0x0488_1D05,
+    0xD9D4_D039, 0xE6DB_99E5, 0x1FA2_7CF8, 0xC4AC_5665,
+    0xF429_2244, 0x432A_FF97, 0xAB94_23A7, 0xFC93_A039,
+    0x655B_59C3, 0x8F0C_CC92, 0xFFEF_F47D, 0x8584_5DD1,
+    0x6FA8_7E4F, 0xFE2C_E6E0, 0xA301_4314, 0x4E08_11A1,
+    0xF753_7E82, 0xBD3A_F235, 0x2AD7_D2BB, 0xEB86_D391,
+];
+
+impl Md5 {
+    fn new() -> Md5 {
+        Md5 { state: [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476], block: [0; 64], block_len: 0, len: 0 }
+    }
+
+    fn update(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+        while !bytes.is_empty() {
+            let len = (64 - self.block_len).min(bytes.len());
+            self.block[self.block_len..self.block_len + len].copy_from_slice(&bytes[..len]);
+            self.block_len += len;
+            bytes = &bytes[len..];
+            if self.block_len == 64 {
+                self.process();
+                self.block_len = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 16] {
+        let bits = self.len.wrapping_mul(8);
+        // A one bit, zeros up to 8 bytes before the end of a block, then the length in bits
+        self.update(&[0x80]);
+        while self.block_len != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_le_bytes());
+        let mut digest = [0; 16];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+
+    fn process(&mut self) {
+        let mut words = [0u32; 16];
+        for (word, bytes) in words.iter_mut().zip(self.block.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        let [mut a, mut b, mut c, mut d] = self.state;
+        for i in 0..64 {
+            let (f, word) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a.wrapping_add(f).wrapping_add(K[i]).wrapping_add(words[word]).rotate_left(SHIFTS[i / 16 * 4 + i % 4]);
+            (a, b, c, d) = (d, b.wrapping_add(rotated), b, c);
+        }
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
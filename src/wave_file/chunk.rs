@@ -0,0 +1,171 @@
+//! Walking the chunks of a RIFF file.
+
+use super::{rf64, WaveError};
+
+/// A single chunk of a RIFF file: its FourCC identifier and its payload.
+pub(crate) struct Chunk<'a> {
+    pub(crate) id: [u8; 4],
+    pub(crate) data: &'a [u8],
+}
+
+/// Iterates over the sub-chunks of the `WAVE` form of a RIFF file.
+pub(crate) struct Chunks<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// The size of the data chunk from the ds64 chunk of an RF64 file
+    rf64_data_size: Option<u64>,
+    /// Whether the chunk sizes are big-endian, as in RIFX files
+    big_endian: bool,
+}
+
+impl<'a> Chunks<'a> {
+    /// Verifies the `RIFF`/`WAVE` magic and returns an iterator over the chunks following it.
+    ///
+    /// RF64 files are read as well, with the sizes from their `ds64` chunk.
+    pub(crate) fn new(bytes: &'a [u8]) -> Result<Chunks<'a>, WaveError> {
+        if bytes.len() < 12 || &bytes[8..12] != b"WAVE" {
+            return Err(WaveError::NotRiff);
+        }
+        // The RIFF chunk size counts everything after the size field itself
+        let (riff_size, rf64_data_size) = if rf64::is_rf64_magic(&bytes[0..4]) {
+            // The ds64 chunk comes first
+            let ds64 = match Chunks::within(&bytes[12..]).next() {
+                Some(Ok(chunk)) if &chunk.id == b"ds64" => chunk.data,
+                Some(Ok(_)) | None => return Err(WaveError::MissingChunk("ds64")),
+                Some(Err(err)) => return Err(err),
+            };
+            let ds64 = rf64::Ds64::parse(ds64).ok_or(WaveError::Truncated)?;
+            (ds64.riff_size, Some(ds64.data_size))
+        } else if &bytes[0..4] == b"RIFF" {
+            (u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as u64, None)
+        } else {
+            return Err(WaveError::NotRiff);
+        };
+        let end = bytes.len().min(usize::try_from(riff_size).unwrap_or(usize::MAX).saturating_add(8));
+        Ok(Chunks { bytes: &bytes[..end], pos: 12, rf64_data_size, big_endian: false })
+    }
+
+    /// Returns an iterator over chunks that directly follow each other in `bytes`, like the
+    /// sub-chunks of a `LIST` chunk.
+    pub(crate) fn within(bytes: &'a [u8]) -> Chunks<'a> {
+        Chunks { bytes, pos: 0, rf64_data_size: None, big_endian: false }
+    }
+
+    /// Verifies the `RIFX`/`WAVE` magic of a big-endian RIFF file and returns an iterator over
+    /// the chunks following it.
+    pub(crate) fn rifx(bytes: &'a [u8]) -> Result<Chunks<'a>, WaveError> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFX" || &bytes[8..12] != b"WAVE" {
+            return Err(WaveError::NotRiff);
+        }
+        let riff_size = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        let end = bytes.len().min(riff_size.saturating_add(8));
+        Ok(Chunks { bytes: &bytes[..end], pos: 12, rf64_data_size: None, big_endian: true })
+    }
+
+    /// Like [within](Chunks::within), for chunks with big-endian sizes.
+    pub(crate) fn within_big_endian(bytes: &'a [u8]) -> Chunks<'a> {
+        Chunks { bytes, pos: 0, rf64_data_size: None, big_endian: true }
+    }
+
+    /// Returns whether the chunks are those of an RF64 file.
+    pub(crate) fn is_rf64(&self) -> bool {
+        self.rf64_data_size.is_some()
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = Result<Chunk<'a>, WaveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.bytes.len() {
+            return None;
+        }
+        let chunk = self.bytes.get(self.pos..self.pos + 8).and_then(|header| {
+            let id: [u8; 4] = header[0..4].try_into().unwrap();
+            let size = header[4..8].try_into().unwrap();
+            let size = if self.big_endian { u32::from_be_bytes(size) } else { u32::from_le_bytes(size) };
+            let size = match size {
+                // The size of the data chunk of an RF64 file is stored in the ds64 chunk
+                u32::MAX if &id == b"data" => match self.rf64_data_size {
+                    Some(size) => usize::try_from(size).ok()?,
+                    None => u32::MAX as usize,
+                },
+                size => size as usize,
+            };
+            let start = self.pos + 8;
+            let data = self.bytes.get(start..start.checked_add(size)?)?;
+            Some(Chunk { id, data })
+        });
+        match chunk {
+            Some(chunk) => {
+                // Chunks with an odd size are followed by a pad byte, which may be missing at
+                // the end of the file
+                self.pos += 8 + chunk.data.len() + chunk.data.len() % 2;
+                Some(Ok(chunk))
+            }
+            None => {
+                self.pos = self.bytes.len();
+                Some(Err(WaveError::Truncated))
+            }
+        }
+    }
+}
+
+/// Serializes the little-endian fields of the header of a wave file.
+pub(crate) struct HeaderWriter {
+    bytes: Vec<u8>,
+}
+
+impl HeaderWriter {
+    pub(crate) fn with_capacity(capacity: usize) -> HeaderWriter {
+        HeaderWriter { bytes: Vec::with_capacity(capacity) }
+    }
+
+    /// Writes a FourCC, or any other bytes as they are
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) -> &mut HeaderWriter {
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) -> &mut HeaderWriter {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub(crate) fn u32(&mut self, value: u32) -> &mut HeaderWriter {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub(crate) fn u64(&mut self, value: u64) -> &mut HeaderWriter {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    /// Writes the header of a chunk: its FourCC and the size of its payload
+    pub(crate) fn chunk_header(&mut self, id: &[u8; 4], size: u32) -> &mut HeaderWriter {
+        self.bytes(id).u32(size)
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Appends a chunk with the given `id` and payload to `out`, followed by a pad byte if the
+/// payload has an odd size.
+pub(crate) fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    out.extend(id);
+    out.extend((data.len() as u32).to_le_bytes());
+    out.extend(data);
+    if !data.len().is_multiple_of(2) {
+        out.push(0);
+    }
+}
+
+/// Appends a chunk like [write_chunk], with a big-endian size as in AIFF and RIFX files.
+pub(crate) fn write_chunk_big_endian(out: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    out.extend(id);
+    out.extend((data.len() as u32).to_be_bytes());
+    out.extend(data);
+    if !data.len().is_multiple_of(2) {
+        out.push(0);
+    }
+}
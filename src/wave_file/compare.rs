@@ -0,0 +1,129 @@
+use super::{WaveError, WaveFile};
+
+/// How much the samples of two files differ, see [WaveFile::compare].
+///
+/// The differences are between normalized samples, where 1.0 is full scale, and there is a value
+/// for every channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareReport {
+    /// The largest absolute difference between two samples
+    pub max_diff: Vec<f64>,
+    /// The root mean square of the differences
+    pub rms_diff: Vec<f64>,
+    /// The signal to noise ratio in dB: the power of the samples of the first file over the
+    /// power of the differences. Infinite when the samples are the same.
+    pub snr_db: Vec<f64>,
+    /// The amount of frames that were compared, those of the shorter file
+    pub frames: u64,
+    /// The amounts of frames of the first and the other file, if they differ. The frames past
+    /// the end of the shorter file aren't compared.
+    pub length_mismatch: Option<(u64, u64)>,
+}
+
+// Comparing
+impl WaveFile {
+    /// Compares the samples of this file with those of `other`, which can have a different
+    /// format or bit depth, for checking processing that isn't lossless, like resampling or
+    /// dither. This file is the reference the signal to noise ratio is measured against.
+    ///
+    /// Files of different lengths are compared up to the end of the shorter one, and the
+    /// mismatch is reported.
+    ///
+    /// # Errors
+    /// - [WaveError::FormatMismatch] if the files don't have the same amount of channels and
+    ///   sample rate
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    ///   one of the files can't be decoded
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[16384, 0, -16384, 0, 16384, 0]).unwrap();
+    /// let mut other = WaveFile::new(AudioFormat::IeeeFloat, 2, 44100, 32);
+    /// other.add_samples_f32(&[0.5, 0.0, -0.5, 0.25, 0.25, 0.0, 1.0, 1.0]).unwrap();
+    ///
+    /// let report = wave.compare(&other).unwrap();
+    /// assert_eq!(report.max_diff, [0.25, 0.25]);
+    /// assert_eq!(report.rms_diff[0], (0.25f64 * 0.25 / 3.0).sqrt());
+    /// assert!((report.snr_db[0] - 10.0 * 12.0f64.log10()).abs() < 1e-9);
+    /// assert_eq!(report.snr_db[1], f64::NEG_INFINITY);
+    /// assert_eq!((report.frames, report.length_mismatch), (3, Some((3, 4))));
+    ///
+    /// assert_eq!(wave.compare(&wave).unwrap().snr_db, [f64::INFINITY, f64::INFINITY]);
+    /// ```
+    pub fn compare(&self, other: &WaveFile) -> Result<CompareReport, WaveError> {
+        self.check_same_timing(other)?;
+        let channels = self.num_channels as usize;
+        let mut signal = vec![0.0; channels];
+        let mut noise = vec![0.0; channels];
+        let mut max_diff = vec![0.0f64; channels];
+        let samples = self.iter_samples::<f64>()?.zip(other.iter_samples::<f64>()?);
+        for (i, (sample, other_sample)) in samples.enumerate() {
+            let channel = i % channels;
+            let diff = sample - other_sample;
+            signal[channel] += sample * sample;
+            noise[channel] += diff * diff;
+            max_diff[channel] = max_diff[channel].max(diff.abs());
+        }
+
+        let frames = self.num_frames().min(other.num_frames());
+        let rms_diff = noise.iter().map(|noise| (noise / frames.max(1) as f64).sqrt()).collect();
+        let snr_db = signal
+            .iter()
+            .zip(&noise)
+            .map(|(signal, noise)| if *noise == 0.0 { f64::INFINITY } else { 10.0 * (signal / noise).log10() })
+            .collect();
+        let length_mismatch = Some((self.num_frames(), other.num_frames())).filter(|(frames, other)| frames != other);
+        Ok(CompareReport { max_diff, rms_diff, snr_db, frames, length_mismatch })
+    }
+
+    /// Asserts that `other` holds the same audio as this file, give or take `max_diff` for every
+    /// normalized sample, for tests of processing that isn't lossless.
+    ///
+    /// ## Panics
+    /// With a message that says what differs if the files don't have the same amount of channels,
+    /// sample rate and frames, if their samples can't be decoded, or at the first sample that
+    /// differs by more than `max_diff`, giving its frame, channel and both values.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 24);
+    /// wave.add_samples(&[I24::new(1000).unwrap(), I24::new(-300000).unwrap()]).unwrap();
+    ///
+    /// let converted = wave.convert_bit_depth(16, DitherMode::None);
+    /// // Rounded to the nearest 16-bit sample
+    /// wave.assert_close(&converted, 0.5 / 32768.0);
+    ///
+    /// let result = std::panic::catch_unwind(|| wave.assert_close(&converted, 1e-6));
+    /// assert!(result.is_err());
+    /// ```
+    pub fn assert_close(&self, other: &WaveFile, max_diff: f64) {
+        if let Err(err) = self.check_same_timing(other) {
+            panic!("the files can't be compared: {}", err);
+        }
+        let (frames, other_frames) = (self.num_frames(), other.num_frames());
+        assert_eq!(frames, other_frames, "the files don't have the same amount of frames");
+        let samples = match (self.iter_samples::<f64>(), other.iter_samples::<f64>()) {
+            (Ok(samples), Ok(other_samples)) => samples.zip(other_samples),
+            (Err(err), _) | (_, Err(err)) => panic!("the samples can't be decoded: {}", err),
+        };
+        let channels = self.num_channels as usize;
+        for (i, (sample, other_sample)) in samples.enumerate() {
+            let diff = (sample - other_sample).abs();
+            // NaN never counts as close
+            if diff > max_diff || diff.is_nan() {
+                panic!(
+                    "frame {}, channel {}: {} and {} differ by {}, more than {}",
+                    i / channels,
+                    i % channels,
+                    sample,
+                    other_sample,
+                    diff,
+                    max_diff
+                );
+            }
+        }
+    }
+}
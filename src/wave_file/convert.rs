@@ -0,0 +1,213 @@
+use std::ops::ControlFlow;
+
+use super::progress::{self, Progress};
+use super::{parallel, sample};
+use super::{AudioFormat, ProgressUpdate, WaveError, WaveFile};
+
+/// The dither applied when samples are converted to a lower bit depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DitherMode {
+    /// Samples are only rounded to the nearest value. The conversion is deterministic.
+    None,
+    /// Triangular probability density function dither of ±1 LSB of the target bit depth is added
+    /// before rounding, which trades the quantization distortion of quiet material for a low noise
+    /// floor. The noise is generated from a fixed seed, so converting the same file twice gives
+    /// the same result.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let quiet: Vec<I24> = (0..1000).map(|i| I24::new((i % 7 - 3) * 100).unwrap()).collect();
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 48000, 24);
+    /// wave.add_samples(&quiet).unwrap();
+    ///
+    /// let plain = wave.convert_bit_depth(16, DitherMode::None).samples_i16().unwrap();
+    /// let dithered = wave.convert_bit_depth(16, DitherMode::Triangular).samples_i16().unwrap();
+    /// assert_ne!(plain, dithered);
+    /// assert!(plain.iter().zip(&dithered).all(|(a, b)| (a - b).abs() <= 1));
+    /// assert_eq!(dithered, wave.convert_bit_depth(16, DitherMode::Triangular).samples_i16().unwrap());
+    /// ```
+    Triangular,
+}
+
+// Converting
+impl WaveFile {
+    /// Returns a copy of the file with its samples rescaled to `target_bits` bits per sample.
+    ///
+    /// Integer files stay integer PCM. IEEE float files stay float when `target_bits` is 32 or
+    /// 64, and become integer PCM otherwise. A-law and µ-law files are expanded to integer PCM.
+    /// Use [WaveFile::convert_to] to pick the target format explicitly.
+    ///
+    /// `dither` is only applied when the depth is reduced to integer PCM.
+    ///
+    /// ## Panics
+    /// If the samples of the file can't be decoded, or the resulting format isn't supported (see
+    /// [WaveFile::convert_to]).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 48000, 24);
+    /// wave.add_samples(&[I24::MIN, I24::new(-256).unwrap(), I24::new(384).unwrap(), I24::MAX]).unwrap();
+    ///
+    /// let converted = wave.convert_bit_depth(16, DitherMode::None);
+    /// assert_eq!(converted.bits_per_sample(), 16);
+    /// assert_eq!(converted.block_align(), 2);
+    /// assert_eq!(converted.byte_rate(), 96000);
+    /// assert_eq!(converted.samples_i16().unwrap(), [i16::MIN, -1, 2, i16::MAX]);
+    ///
+    /// // Increasing the depth is lossless
+    /// let wide = converted.convert_bit_depth(32, DitherMode::Triangular);
+    /// assert_eq!(wide.samples_as::<i32>().unwrap(), [i32::MIN, -1 << 16, 2 << 16, i32::MAX - 0xFFFF]);
+    /// assert_eq!(wide.convert_bit_depth(16, DitherMode::None).samples_i16().unwrap(), [i16::MIN, -1, 2, i16::MAX]);
+    /// ```
+    pub fn convert_bit_depth(&self, target_bits: u16, dither: DitherMode) -> WaveFile {
+        let format = match self.audio_format() {
+            AudioFormat::IeeeFloat if target_bits == 32 || target_bits == 64 => AudioFormat::IeeeFloat,
+            _ => AudioFormat::PCM,
+        };
+        self.convert_to(format, target_bits, dither).unwrap()
+    }
+
+    /// Converts the samples like [convert_bit_depth](WaveFile::convert_bit_depth), calling
+    /// `progress` as the frames are converted (see [ProgressUpdate]).
+    ///
+    /// # Errors
+    /// - [WaveError::Cancelled] if `progress` cancels the conversion
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    ///   the file can't be decoded or the resulting format isn't supported
+    pub fn convert_bit_depth_with_progress(
+        &self,
+        target_bits: u16,
+        dither: DitherMode,
+        progress: impl FnMut(ProgressUpdate) -> ControlFlow<()>,
+    ) -> Result<WaveFile, WaveError> {
+        let format = match self.audio_format() {
+            AudioFormat::IeeeFloat if target_bits == 32 || target_bits == 64 => AudioFormat::IeeeFloat,
+            _ => AudioFormat::PCM,
+        };
+        let mut noise = self.dither_noise(format, target_bits, dither);
+        let mut progress = Progress::new(self.num_frames(), progress);
+        self.convert_with_noise(format, target_bits, noise.as_mut(), &mut progress)
+    }
+
+    /// Returns a copy of the file with its samples converted to the given format and bit depth.
+    ///
+    /// Integer samples are rounded and clamped to their range. `dither` is only applied when the
+    /// target is integer PCM with fewer bits than the source. Float sources always count as having
+    /// more bits, A-law and µ-law as 16-bit.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded or `format` and `bits_per_sample` can't be encoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 8);
+    /// wave.add_samples(&[0u8, 64, 128, 255]).unwrap();
+    ///
+    /// let float = wave.convert_to(AudioFormat::IeeeFloat, 32, DitherMode::None).unwrap();
+    /// assert_eq!(float.samples_f32().unwrap(), [-1.0, -0.5, 0.0, 127.0 / 128.0]);
+    ///
+    /// let back = float.convert_to(AudioFormat::PCM, 8, DitherMode::None).unwrap();
+    /// assert_eq!(back.samples_as::<u8>().unwrap(), [0, 64, 128, 255]);
+    /// ```
+    pub fn convert_to(&self, format: AudioFormat, bits_per_sample: u16, dither: DitherMode) -> Result<WaveFile, WaveError> {
+        let mut noise = self.dither_noise(format, bits_per_sample, dither);
+        let mut progress = Progress::new(self.num_frames(), progress::ignore);
+        self.convert_with_noise(format, bits_per_sample, noise.as_mut(), &mut progress)
+    }
+
+    /// Returns the noise generator for converting the samples of the file to `format` and
+    /// `bits_per_sample` with `dither`, if dither is needed
+    pub(crate) fn dither_noise(&self, format: AudioFormat, bits_per_sample: u16, dither: DitherMode) -> Option<Tpdf> {
+        let source_bits = match self.audio_format() {
+            AudioFormat::IeeeFloat => u16::MAX,
+            AudioFormat::ALaw | AudioFormat::MuLaw => 16,
+            _ => self.bits_per_sample,
+        };
+        match dither {
+            DitherMode::Triangular if format == AudioFormat::PCM && bits_per_sample < source_bits => {
+                Some(Tpdf::new(bits_per_sample))
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts the samples like [convert_to](WaveFile::convert_to), adding the dither of `noise`
+    /// and reporting the frames converted to `progress`
+    pub(crate) fn convert_with_noise(
+        &self,
+        format: AudioFormat,
+        bits_per_sample: u16,
+        mut noise: Option<&mut Tpdf>,
+        progress: &mut Progress<impl FnMut(ProgressUpdate) -> ControlFlow<()>>,
+    ) -> Result<WaveFile, WaveError> {
+        let source_format = self.audio_format();
+        sample::check_supported(source_format, self.bits_per_sample)?;
+        sample::check_supported(format, bits_per_sample)?;
+
+        let mut wave = self.empty_like(format, bits_per_sample);
+        let bytes_per_sample = (self.bits_per_sample / 8) as usize;
+        let samples = (self.audio_byte_data.len() / bytes_per_sample) as u64;
+        let mut data = Vec::with_capacity(samples as usize * (bits_per_sample / 8) as usize);
+        let channels = self.num_channels.max(1) as u64;
+        // The samples are converted a round of frames at a time, split over threads with the
+        // `parallel` feature. The dither of a round is generated before it is split, so it is
+        // added in the same order.
+        parallel::map_rounds(
+            0..samples,
+            progress::INTERVAL * channels,
+            |round| {
+                let dither: Vec<f64> = noise.as_mut().map_or_else(Vec::new, |noise| round.clone().map(|_| noise.next()).collect());
+                (round.start, dither)
+            },
+            |(start, dither), part| {
+                let mut out = Vec::with_capacity((part.end - part.start) as usize * (bits_per_sample / 8) as usize);
+                let bytes = part.start as usize * bytes_per_sample..part.end as usize * bytes_per_sample;
+                let values = self.audio_byte_data.slices(bytes).flat_map(|slice| slice.chunks_exact(bytes_per_sample));
+                for (i, bytes) in (part.start..).zip(values) {
+                    let value = sample::decode(bytes, source_format, self.bits_per_sample);
+                    let dither = dither.get((i - start) as usize).copied().unwrap_or(0.0);
+                    sample::encode(value + dither, format, bits_per_sample, &mut out);
+                }
+                out
+            },
+            |end, parts| {
+                for part in parts {
+                    data.extend_from_slice(&part);
+                }
+                progress.update(end / channels)
+            },
+        )?;
+        progress.finish()?;
+        wave.audio_byte_data.set(data);
+        Ok(wave)
+    }
+}
+
+/// Generates triangular dither noise of ±1 LSB from two uniform values of a xorshift generator.
+pub(crate) struct Tpdf {
+    state: u64,
+    lsb: f64,
+}
+
+impl Tpdf {
+    fn new(bits: u16) -> Tpdf {
+        Tpdf { state: 0x9E37_79B9_7F4A_7C15, lsb: 1.0 / (1u64 << (bits - 1)) as f64 }
+    }
+
+    /// Returns the next uniform value in `[0, 1)`
+    fn uniform(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn next(&mut self) -> f64 {
+        (self.uniform() - self.uniform()) * self.lsb
+    }
+}
+
@@ -0,0 +1,189 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::ops::RangeInclusive;
+
+use super::sample::{self, Sample};
+use super::{AudioFormat, WaveError, WaveFile, WaveSpec, I24};
+
+/// The amount of text collected before it is written by [WaveFile::export_csv]
+const WRITE_LEN: usize = 1 << 16;
+
+/// How samples are written as text by [WaveFile::export_csv] and read by
+/// [WaveFile::import_csv].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleTextFormat {
+    /// The integer values of the samples as they are stored: unsigned from 0 to 255 for 8-bit
+    /// PCM, signed for wider PCM, and the code bytes for A-law and µ-law. Writing and reading
+    /// them back is lossless. IEEE float samples can't be written as integers.
+    Integer,
+    /// Normalized values from -1.0 to 1.0, like [Sample::to_f64], with `precision` digits after
+    /// the decimal point. Integer samples read back are rounded and clamped.
+    Float {
+        /// The amount of digits after the decimal point
+        precision: usize,
+    },
+}
+
+// CSV
+impl WaveFile {
+    /// Writes the samples as comma separated text to `writer`, one frame per line with a column
+    /// for every channel, for comparing them with other tools like MATLAB. Every line ends with
+    /// a newline. There is no header line.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] if the samples are IEEE float and `format` is
+    ///   [SampleTextFormat::Integer]
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    ///   the file can't be decoded
+    /// - [WaveError::Io] if `writer` fails
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[0, -1, 16384, i16::MIN]).unwrap();
+    ///
+    /// let mut text = Vec::new();
+    /// wave.export_csv(&mut text, SampleTextFormat::Integer).unwrap();
+    /// assert_eq!(String::from_utf8(text).unwrap(), "0,-1\n16384,-32768\n");
+    ///
+    /// let mut text = Vec::new();
+    /// wave.export_csv(&mut text, SampleTextFormat::Float { precision: 3 }).unwrap();
+    /// assert_eq!(String::from_utf8(text).unwrap(), "0.000,-0.000\n0.500,-1.000\n");
+    /// ```
+    pub fn export_csv<W: Write>(&self, mut writer: W, format: SampleTextFormat) -> Result<(), WaveError> {
+        let (audio_format, bits) = (self.audio_format(), self.bits_per_sample);
+        sample::check_supported(audio_format, bits)?;
+        if format == SampleTextFormat::Integer && audio_format == AudioFormat::IeeeFloat {
+            return Err(WaveError::UnsupportedFormat(audio_format.get_val()));
+        }
+        let sample_len = (bits / 8) as usize;
+        let mut text = String::with_capacity(WRITE_LEN);
+        for frame in self.frames() {
+            for (channel, bytes) in frame.chunks_exact(sample_len).enumerate() {
+                if channel > 0 {
+                    text.push(',');
+                }
+                match format {
+                    SampleTextFormat::Integer => text.push_str(&integer(bytes, audio_format, bits).to_string()),
+                    SampleTextFormat::Float { precision } => {
+                        text.push_str(&format!("{:.*}", precision, sample::decode(bytes, audio_format, bits)))
+                    }
+                }
+            }
+            text.push('\n');
+            if text.len() >= WRITE_LEN {
+                writer.write_all(text.as_bytes())?;
+                text.clear();
+            }
+        }
+        writer.write_all(text.as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads samples written as comma separated text, like by
+    /// [export_csv](WaveFile::export_csv), into a new file of `spec`: one frame per line with a
+    /// column for every channel.
+    ///
+    /// Spaces around values and empty lines are ignored, and lines may end with `\r\n`. With
+    /// [SampleTextFormat::Float], integer samples are rounded and clamped to their range, and its
+    /// precision is ignored.
+    ///
+    /// # Errors
+    /// - the errors of [WaveSpec::new] if `spec` is invalid
+    /// - [WaveError::UnsupportedFormat] if `spec` is IEEE float and `format` is
+    ///   [SampleTextFormat::Integer]
+    /// - [WaveError::InvalidCsv] with the line and column of the first value that isn't a
+    ///   number, is out of the range of the samples of `spec`, or is missing or extra
+    /// - [WaveError::Io] if `reader` fails or the text isn't UTF-8
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::new(AudioFormat::PCM, 2, 44100, 24).unwrap();
+    /// let text = "0, -8388608\r\n\n8388607,42\n";
+    ///
+    /// let wave = WaveFile::import_csv(text.as_bytes(), spec, SampleTextFormat::Integer).unwrap();
+    /// assert_eq!(wave.samples_as::<i32>().unwrap(), [0, i32::MIN, i32::MAX - 0xFF, 42 << 8]);
+    ///
+    /// // Integer data survives the round trip
+    /// let mut exported = Vec::new();
+    /// wave.export_csv(&mut exported, SampleTextFormat::Integer).unwrap();
+    /// let parsed = WaveFile::import_csv(&exported[..], spec, SampleTextFormat::Integer).unwrap();
+    /// assert_eq!(parsed.samples_as::<I24>().unwrap(), wave.samples_as::<I24>().unwrap());
+    ///
+    /// assert!(matches!(
+    ///     WaveFile::import_csv("1,2\n3,8388608\n".as_bytes(), spec, SampleTextFormat::Integer),
+    ///     Err(WaveError::InvalidCsv { line: 2, column: 2, .. })
+    /// ));
+    /// assert!(matches!(
+    ///     WaveFile::import_csv("0.5\n".as_bytes(), spec, SampleTextFormat::Float { precision: 0 }),
+    ///     Err(WaveError::InvalidCsv { line: 1, column: 2, problem: "missing sample" })
+    /// ));
+    /// ```
+    pub fn import_csv<R: Read>(reader: R, spec: WaveSpec, format: SampleTextFormat) -> Result<WaveFile, WaveError> {
+        spec.validate()?;
+        let (audio_format, bits) = (spec.format, spec.bits_per_sample);
+        let range = integer_range(audio_format, bits);
+        if format == SampleTextFormat::Integer && range.is_none() {
+            return Err(WaveError::UnsupportedFormat(audio_format.get_val()));
+        }
+        let channels = spec.channels as usize;
+        let mut wave = WaveFile::with_spec(spec);
+        let mut data = Vec::new();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let invalid = |column: usize, problem| WaveError::InvalidCsv { line: i + 1, column: column + 1, problem };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut columns = 0;
+            for (column, value) in line.split(',').map(str::trim).enumerate() {
+                if column == channels {
+                    return Err(invalid(column, "more samples than channels"));
+                }
+                match (format, &range) {
+                    (SampleTextFormat::Integer, Some(range)) => {
+                        let value: i64 = value.parse().map_err(|_| invalid(column, "not an integer"))?;
+                        if !range.contains(&value) {
+                            return Err(invalid(column, "out of range for the sample format"));
+                        }
+                        data.extend_from_slice(&value.to_le_bytes()[..(bits / 8) as usize]);
+                    }
+                    _ => {
+                        let value: f64 = value.parse().map_err(|_| invalid(column, "not a number"))?;
+                        sample::encode(value, audio_format, bits, &mut data);
+                    }
+                }
+                columns += 1;
+            }
+            if columns < channels {
+                return Err(invalid(columns, "missing sample"));
+            }
+        }
+        wave.audio_byte_data.set(data);
+        Ok(wave)
+    }
+}
+
+/// Returns the integer value of a sample as it is stored, see [SampleTextFormat::Integer]. The
+/// format must be supported and not IEEE float.
+fn integer(bytes: &[u8], format: AudioFormat, bits_per_sample: u16) -> i64 {
+    match (format, bits_per_sample) {
+        (AudioFormat::PCM, 16) => i16::read_le(bytes).into(),
+        (AudioFormat::PCM, 24) => I24::read_le(bytes).value().into(),
+        (AudioFormat::PCM, 32) => i32::read_le(bytes).into(),
+        _ => bytes[0].into(),
+    }
+}
+
+/// Returns the range of the integer values of samples as they are stored, `None` for IEEE float
+fn integer_range(format: AudioFormat, bits_per_sample: u16) -> Option<RangeInclusive<i64>> {
+    match (format, bits_per_sample) {
+        (AudioFormat::IeeeFloat, _) => None,
+        (AudioFormat::PCM, 16) => Some(i16::MIN.into()..=i16::MAX.into()),
+        (AudioFormat::PCM, 24) => Some(I24::MIN.value().into()..=I24::MAX.value().into()),
+        (AudioFormat::PCM, 32) => Some(i32::MIN.into()..=i32::MAX.into()),
+        _ => Some(0..=u8::MAX.into()),
+    }
+}
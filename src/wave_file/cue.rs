@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use super::chunk::{self, Chunks};
+use super::WaveFile;
+
+/// A marker at a position in the audio, stored in the `cue ` chunk of a wave file. Its label is
+/// stored in a `labl` entry of the `LIST`/`adtl` chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CuePoint {
+    /// The identifier of the cue point, unique within the file
+    pub id: u32,
+    /// The position of the cue point in sample frames from the start of the audio
+    pub position_frames: u32,
+    /// The name of the cue point
+    pub label: Option<String>,
+}
+
+/// Parses the cue points of a `cue ` chunk, and the labels of the sub-chunks of `LIST` chunks of
+/// type `adtl` (without the type).
+///
+/// Also returns the sub-chunks that didn't become a label, like `note` and `ltxt` entries or
+/// labels of cue points that don't exist, so they can be kept as they are.
+pub(crate) fn parse(cue: &[u8], adtl: &[u8]) -> (Vec<CuePoint>, Vec<u8>) {
+    let count = cue.get(0..4).map_or(0, |count| u32::from_le_bytes(count.try_into().unwrap()) as usize);
+    let mut cue_points: Vec<CuePoint> = cue
+        .get(4..)
+        .unwrap_or(&[])
+        .chunks_exact(24)
+        .take(count)
+        .map(|point| {
+            let u32_at = |i: usize| u32::from_le_bytes(point[i..i + 4].try_into().unwrap());
+            // The offset of the cue point in the data chunk (dwSampleOffset)
+            CuePoint { id: u32_at(0), position_frames: u32_at(20), label: None }
+        })
+        .collect();
+    let mut indices = HashMap::new();
+    for (i, point) in cue_points.iter().enumerate().rev() {
+        indices.insert(point.id, i);
+    }
+
+    let mut unparsed = Vec::new();
+    for entry in Chunks::within(adtl).map_while(Result::ok) {
+        let point = match entry.data.get(0..4) {
+            Some(id) if &entry.id == b"labl" => indices.remove(&u32::from_le_bytes(id.try_into().unwrap())),
+            _ => None,
+        };
+        match point {
+            Some(i) => {
+                let text = &entry.data[4..];
+                let end = text.iter().position(|&b| b == 0).unwrap_or(text.len());
+                cue_points[i].label = Some(String::from_utf8_lossy(&text[..end]).into_owned());
+            }
+            None => chunk::write_chunk(&mut unparsed, &entry.id, entry.data),
+        }
+    }
+    (cue_points, unparsed)
+}
+
+/// Appends the `cue ` chunk and, if any cue point has a label, the `LIST`/`adtl` chunk to `out`
+pub(crate) fn write(cue_points: &[CuePoint], out: &mut Vec<u8>) {
+    if cue_points.is_empty() {
+        return;
+    }
+    let mut cue = (cue_points.len() as u32).to_le_bytes().to_vec();
+    for point in cue_points {
+        cue.extend(point.id.to_le_bytes());
+        cue.extend(point.position_frames.to_le_bytes()); // dwPosition
+        cue.extend(b"data"); // fccChunk
+        cue.extend(0u32.to_le_bytes()); // dwChunkStart
+        cue.extend(0u32.to_le_bytes()); // dwBlockStart
+        cue.extend(point.position_frames.to_le_bytes()); // dwSampleOffset
+    }
+    chunk::write_chunk(out, b"cue ", &cue);
+
+    let mut adtl = b"adtl".to_vec();
+    for point in cue_points {
+        if let Some(label) = &point.label {
+            let mut labl = point.id.to_le_bytes().to_vec();
+            labl.extend(label.as_bytes());
+            labl.push(0);
+            chunk::write_chunk(&mut adtl, b"labl", &labl);
+        }
+    }
+    if adtl.len() > 4 {
+        chunk::write_chunk(out, b"LIST", &adtl);
+    }
+}
+
+// Cue points
+impl WaveFile {
+    /// Returns the cue points (markers) of the file
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[0; 2 * 44100]).unwrap();
+    /// let verse = wave.add_cue_point(11025, Some("Verse"));
+    /// let end = wave.add_cue_point(44099, None);
+    /// assert_eq!((verse, end), (1, 2));
+    ///
+    /// let parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+    /// assert_eq!(parsed.cue_points(), [
+    ///     CuePoint { id: 1, position_frames: 11025, label: Some(String::from("Verse")) },
+    ///     CuePoint { id: 2, position_frames: 44099, label: None },
+    /// ]);
+    /// ```
+    pub fn cue_points(&self) -> &[CuePoint] {
+        &self.cue_points
+    }
+
+    /// Returns the cue points of the file for editing. Their ids should stay unique.
+    pub fn cue_points_mut(&mut self) -> &mut Vec<CuePoint> {
+        &mut self.cue_points
+    }
+
+    /// Adds a cue point at `position_frames` with an optional label, and returns its id: one more
+    /// than the largest id of the cue points so far.
+    pub fn add_cue_point(&mut self, position_frames: u32, label: Option<&str>) -> u32 {
+        let id = self.cue_points.iter().map(|point| point.id).max().map_or(1, |id| id.wrapping_add(1));
+        self.cue_points.push(CuePoint { id, position_frames, label: label.map(String::from) });
+        id
+    }
+}
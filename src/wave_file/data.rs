@@ -0,0 +1,340 @@
+//! Storage of the audio data of a wave file.
+
+use std::iter::FusedIterator;
+use std::mem;
+use std::ops::{Deref, Range};
+use std::slice::{self, ChunksExact};
+use std::sync::Arc;
+
+/// The size from which a buffer that is appended becomes a segment of its own, and the most a
+/// segment grows by at once when little data is appended at a time
+const SEGMENT_SIZE: usize = 1 << 20;
+
+/// A part of the audio data: either owned, or a buffer shared with the caller that is only copied
+/// once it is changed.
+#[derive(Debug, Clone)]
+enum Segment {
+    Owned(Vec<u8>),
+    Shared(Arc<[u8]>),
+}
+
+impl Segment {
+    /// Returns the segment as a `Vec` that can be changed, copying a shared buffer first
+    fn to_mut(&mut self) -> &mut Vec<u8> {
+        if let Segment::Shared(shared) = self {
+            *self = Segment::Owned(shared.to_vec());
+        }
+        match self {
+            Segment::Owned(owned) => owned,
+            Segment::Shared(_) => unreachable!(),
+        }
+    }
+}
+
+impl Deref for Segment {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Segment::Owned(owned) => owned,
+            Segment::Shared(shared) => shared,
+        }
+    }
+}
+
+/// The audio data of a [WaveFile](super::WaveFile), stored as a list of segments so appending
+/// never moves the data that is already there.
+///
+/// New segments only start at a multiple of the block align, so frames never span two segments.
+/// Only the last segment can be empty.
+#[derive(Debug, Clone)]
+pub(crate) struct AudioData {
+    segments: Vec<Segment>,
+    /// The offset of the first byte of every segment
+    starts: Vec<usize>,
+    block_align: usize,
+}
+
+impl AudioData {
+    /// Creates empty audio data for a file with the given block align
+    pub(crate) fn new(block_align: u16) -> AudioData {
+        AudioData { segments: Vec::new(), starts: Vec::new(), block_align: block_align.max(1) as usize }
+    }
+
+    /// Replaces the data with `bytes`
+    pub(crate) fn set(&mut self, bytes: Vec<u8>) {
+        self.segments = vec![Segment::Owned(bytes)];
+        self.starts = vec![0];
+    }
+
+    /// Replaces the data with a shared buffer, without copying it
+    pub(crate) fn set_shared(&mut self, bytes: Arc<[u8]>) {
+        self.segments = vec![Segment::Shared(bytes)];
+        self.starts = vec![0];
+    }
+
+    /// The amount of bytes
+    pub(crate) fn len(&self) -> usize {
+        match (self.starts.last(), self.segments.last()) {
+            (Some(start), Some(last)) => start + last.len(),
+            _ => 0,
+        }
+    }
+
+    /// The amount of bytes the data can grow to without allocating
+    pub(crate) fn capacity(&self) -> usize {
+        self.len() + self.spare_capacity()
+    }
+
+    /// The amount of bytes that can be appended without allocating
+    pub(crate) fn spare_capacity(&self) -> usize {
+        match self.segments.last() {
+            Some(Segment::Owned(last)) => last.capacity() - last.len(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the segments in order
+    pub(crate) fn segments(&self) -> impl DoubleEndedIterator<Item = &[u8]> + '_ {
+        self.segments.iter().map(|segment| &segment[..])
+    }
+
+    /// Makes sure `additional` bytes can be appended without allocating, by starting a new
+    /// segment if the last one has too little room. The data that is already there is not moved,
+    /// unless the last segment doesn't end at a whole frame.
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        if self.spare_capacity() >= additional {
+            return;
+        }
+        let block_align = self.block_align;
+        match self.segments.last_mut() {
+            Some(last) if last.is_empty() || !last.len().is_multiple_of(block_align) => last.to_mut().reserve(additional),
+            _ => {
+                self.starts.push(self.len());
+                self.segments.push(Segment::Owned(Vec::with_capacity(additional)));
+            }
+        }
+    }
+
+    /// Appends bytes by letting `f` push them onto the last segment, which has room for at least
+    /// `additional` bytes
+    pub(crate) fn extend_with(&mut self, additional: usize, f: impl FnOnce(&mut Vec<u8>)) {
+        if self.spare_capacity() < additional {
+            // Grow like a `Vec` while the data is small
+            self.reserve(additional.max(self.len().min(SEGMENT_SIZE)));
+        }
+        match self.segments.last_mut() {
+            Some(last) => f(last.to_mut()),
+            // Nothing was reserved for empty data
+            None => {
+                let mut bytes = Vec::new();
+                f(&mut bytes);
+                self.set(bytes);
+            }
+        }
+    }
+
+    /// Appends a copy of `bytes`
+    pub(crate) fn extend_from_slice(&mut self, bytes: &[u8]) {
+        if !bytes.is_empty() {
+            self.extend_with(bytes.len(), |last| last.extend_from_slice(bytes));
+        }
+    }
+
+    /// Appends `bytes`, leaving it empty. A large buffer becomes a segment of its own without
+    /// being copied.
+    pub(crate) fn append(&mut self, bytes: &mut Vec<u8>) {
+        if bytes.len() < SEGMENT_SIZE || self.spare_capacity() >= bytes.len() || !self.len().is_multiple_of(self.block_align) {
+            self.extend_from_slice(bytes);
+            bytes.clear();
+            return;
+        }
+        if self.segments.last().is_some_and(|last| last.is_empty()) {
+            self.segments.pop();
+            self.starts.pop();
+        }
+        self.starts.push(self.len());
+        self.segments.push(Segment::Owned(mem::take(bytes)));
+    }
+
+    /// Merges the segments into one, and returns it as a `Vec` that can be changed
+    pub(crate) fn make_contiguous(&mut self) -> &mut Vec<u8> {
+        if self.segments.len() != 1 {
+            let bytes = self.to_vec();
+            self.set(bytes);
+        }
+        self.segments[0].to_mut()
+    }
+
+    /// Returns the bytes in `range` if they are stored in a single segment, which is always the
+    /// case for a range within a frame
+    pub(crate) fn get(&self, range: Range<usize>) -> Option<&[u8]> {
+        let index = self.starts.partition_point(|&start| start <= range.start).checked_sub(1)?;
+        let start = self.starts[index];
+        self.segments[index].get(range.start - start..range.end.checked_sub(start)?)
+    }
+
+    /// Returns the bytes in `range` like [get](AudioData::get), copying a shared segment first
+    pub(crate) fn get_mut(&mut self, range: Range<usize>) -> Option<&mut [u8]> {
+        let index = self.starts.partition_point(|&start| start <= range.start).checked_sub(1)?;
+        let start = self.starts[index];
+        self.segments[index].to_mut().get_mut(range.start - start..range.end.checked_sub(start)?)
+    }
+
+    /// Returns the parts of the segments that make up the bytes in `range`
+    pub(crate) fn slices(&self, range: Range<usize>) -> impl Iterator<Item = &[u8]> + '_ {
+        self.segments.iter().zip(&self.starts).filter_map(move |(segment, &start)| {
+            let from = range.start.max(start) - start;
+            let to = range.end.min(start + segment.len()).checked_sub(start)?;
+            (from < to).then(|| &segment[from..to])
+        })
+    }
+
+    /// Returns the segments as `Vec`s that can be changed, copying shared segments first
+    pub(crate) fn segments_mut(&mut self) -> impl Iterator<Item = &mut Vec<u8>> + '_ {
+        self.segments.iter_mut().map(Segment::to_mut)
+    }
+
+    /// Returns an iterator over chunks of `size` bytes, which should divide the block align
+    pub(crate) fn chunks_exact(&self, size: usize) -> DataChunks<'_> {
+        DataChunks {
+            segments: self.segments.iter(),
+            front: [].chunks_exact(size),
+            back: [].chunks_exact(size),
+            size,
+            remaining: self.segments.iter().map(|segment| segment.len() / size).sum(),
+        }
+    }
+
+    /// Returns an iterator over the bytes
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &u8> + '_ {
+        self.segments.iter().flat_map(|segment| segment.iter())
+    }
+
+    /// Returns a copy of the data in a single `Vec`
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+        for segment in &self.segments {
+            bytes.extend_from_slice(segment);
+        }
+        bytes
+    }
+
+    /// Returns the data in a single `Vec`, which is only copied if it isn't a single owned segment
+    pub(crate) fn into_vec(mut self) -> Vec<u8> {
+        mem::take(self.make_contiguous())
+    }
+}
+
+impl PartialEq for AudioData {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        // Compare the overlapping parts of the segments of both
+        let (mut segments, mut other_segments) = (self.segments(), other.segments());
+        let (mut bytes, mut other_bytes): (&[u8], &[u8]) = (&[], &[]);
+        loop {
+            if bytes.is_empty() {
+                match segments.next() {
+                    Some(segment) => bytes = segment,
+                    None => return true,
+                }
+            } else if other_bytes.is_empty() {
+                match other_segments.next() {
+                    Some(segment) => other_bytes = segment,
+                    None => return true,
+                }
+            } else {
+                let n = bytes.len().min(other_bytes.len());
+                if bytes[..n] != other_bytes[..n] {
+                    return false;
+                }
+                bytes = &bytes[n..];
+                other_bytes = &other_bytes[n..];
+            }
+        }
+    }
+}
+
+/// An iterator over chunks of the same size of [AudioData], see [AudioData::chunks_exact]
+#[derive(Debug, Clone)]
+pub(crate) struct DataChunks<'a> {
+    segments: slice::Iter<'a, Segment>,
+    front: ChunksExact<'a, u8>,
+    back: ChunksExact<'a, u8>,
+    size: usize,
+    remaining: usize,
+}
+
+impl<'a> Iterator for DataChunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(chunk) = self.front.next() {
+                self.remaining -= 1;
+                return Some(chunk);
+            }
+            match self.segments.next() {
+                Some(segment) => self.front = segment.chunks_exact(self.size),
+                None => break,
+            }
+        }
+        let chunk = self.back.next()?;
+        self.remaining -= 1;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        // Skip whole segments instead of every chunk
+        loop {
+            let len = self.front.len();
+            if n < len {
+                self.remaining -= n + 1;
+                return self.front.nth(n);
+            }
+            n -= len;
+            self.remaining -= len;
+            self.front = [].chunks_exact(self.size);
+            match self.segments.next() {
+                Some(segment) => self.front = segment.chunks_exact(self.size),
+                None => break,
+            }
+        }
+        let len = self.back.len();
+        if n < len {
+            self.remaining -= n + 1;
+            return self.back.nth(n);
+        }
+        self.remaining -= len;
+        self.back = [].chunks_exact(self.size);
+        None
+    }
+}
+
+impl DoubleEndedIterator for DataChunks<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(chunk) = self.back.next_back() {
+                self.remaining -= 1;
+                return Some(chunk);
+            }
+            match self.segments.next_back() {
+                Some(segment) => self.back = segment.chunks_exact(self.size),
+                None => break,
+            }
+        }
+        let chunk = self.front.next_back()?;
+        self.remaining -= 1;
+        Some(chunk)
+    }
+}
+
+impl ExactSizeIterator for DataChunks<'_> {}
+
+impl FusedIterator for DataChunks<'_> {}
@@ -0,0 +1,417 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use super::sample;
+use super::{DitherMode, ResampleQuality, WaveError, WaveFile, WaveSpec};
+
+/// The normalized magnitude under which a sample counts as zero for
+/// [WaveFile::trim_to_zero_crossing], about -60 dBFS.
+const ZERO_THRESHOLD: f64 = 1.0 / 1024.0;
+
+/// The ends of the audio data to trim, see [WaveFile::trim_to_zero_crossing].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimEnd {
+    /// Only trim the start.
+    Start,
+    /// Only trim the end.
+    End,
+    /// Trim both the start and the end.
+    Both,
+}
+
+// Editing
+impl WaveFile {
+    /// Appends the audio data of `other` to the end of this file.
+    ///
+    /// # Errors
+    /// [WaveError::FormatMismatch] if the two files don't have the same audio format, amount of
+    /// channels, sample rate and bits per sample. Nothing is appended in that case.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// wave.add_samples_i16(&[1, 2]).unwrap();
+    /// let mut other = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// other.add_samples_i16(&[3]).unwrap();
+    ///
+    /// wave.append(&other).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [1, 2, 3]);
+    ///
+    /// let other = WaveFile::new(AudioFormat::PCM, 1, 48000, 16);
+    /// assert!(matches!(
+    ///     wave.append(&other),
+    ///     Err(WaveError::FormatMismatch { property: "sample rate", expected: 44100, found: 48000 })
+    /// ));
+    /// ```
+    pub fn append(&mut self, other: &WaveFile) -> Result<(), WaveError> {
+        self.check_same_format(other)?;
+        for segment in other.audio_byte_data.segments() {
+            self.audio_byte_data.extend_from_slice(segment);
+        }
+        Ok(())
+    }
+
+    /// Appends the audio data of `other` to the end of this file, first converting it to the
+    /// format, bit depth and sample rate of this file when they differ (see
+    /// [WaveFile::convert_to] and [WaveFile::resample]).
+    ///
+    /// # Errors
+    /// [WaveError::FormatMismatch] if the amount of channels differs, or the errors of
+    /// [WaveFile::convert_to] when the samples can't be converted.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 22050, 16);
+    /// let mut other = WaveFile::new(AudioFormat::IeeeFloat, 1, 44100, 32);
+    /// other.add_samples_f32(&[0.5; 100]).unwrap();
+    ///
+    /// wave.append_converting(&other).unwrap();
+    /// assert_eq!(wave.num_frames(), 50);
+    /// assert_eq!(wave.duration(), other.duration());
+    /// ```
+    pub fn append_converting(&mut self, other: &WaveFile) -> Result<(), WaveError> {
+        if self.num_channels != other.num_channels {
+            return Err(WaveError::FormatMismatch {
+                property: "amount of channels",
+                expected: self.num_channels.into(),
+                found: other.num_channels.into(),
+            });
+        }
+        let converted = other.convert_to(self.audio_format(), self.bits_per_sample, DitherMode::None)?;
+        if converted.sample_rate == self.sample_rate {
+            self.append(&converted)
+        } else {
+            self.append(&converted.resample(self.sample_rate, ResampleQuality::Sinc))
+        }
+    }
+
+    /// Copies the audio between `start` and `end` into a new file with the same format. Both
+    /// times are rounded to the nearest frame, see [WaveFile::slice_frames].
+    ///
+    /// # Errors
+    /// [WaveError::InvalidRange] if `start` is after `end`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 10, 16);
+    /// wave.add_samples_i16(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+    ///
+    /// let preview = wave.slice(Duration::from_millis(200), Duration::from_millis(500)).unwrap();
+    /// assert_eq!(preview.samples_i16().unwrap(), [2, 3, 4]);
+    /// assert_eq!(preview.sample_rate(), 10);
+    ///
+    /// // The end is clamped to the end of the file
+    /// let tail = wave.slice(Duration::from_millis(800), Duration::from_secs(60)).unwrap();
+    /// assert_eq!(tail.samples_i16().unwrap(), [8, 9]);
+    /// ```
+    pub fn slice(&self, start: Duration, end: Duration) -> Result<WaveFile, WaveError> {
+        self.slice_frames(self.frames_for_duration(start)..self.frames_for_duration(end))
+    }
+
+    /// Copies the frames in `range` into a new file with the same format.
+    ///
+    /// The range is clamped to the audio data: frames past the end of the file are left out, so a
+    /// range that is (partly) out of bounds gives a shorter or empty file. An empty range gives a
+    /// file without audio data.
+    ///
+    /// # Errors
+    /// [WaveError::InvalidRange] if the range starts after it ends.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, 2, 3, 4, 5, 6]).unwrap();
+    ///
+    /// assert_eq!(wave.slice_frames(1..2).unwrap().samples_i16().unwrap(), [3, 4]);
+    /// assert_eq!(wave.slice_frames(2..2).unwrap().num_frames(), 0);
+    /// assert_eq!(wave.slice_frames(5..9).unwrap().num_frames(), 0);
+    /// assert!(matches!(wave.slice_frames(2..1), Err(WaveError::InvalidRange { start: 2, end: 1 })));
+    /// ```
+    pub fn slice_frames(&self, range: Range<u64>) -> Result<WaveFile, WaveError> {
+        let bytes = self.frame_byte_range(range)?;
+        let mut wave = self.empty_like(self.audio_format(), self.bits_per_sample);
+        wave.audio_byte_data.reserve(bytes.len());
+        for slice in self.audio_byte_data.slices(bytes) {
+            wave.audio_byte_data.extend_from_slice(slice);
+        }
+        Ok(wave)
+    }
+
+    /// Inserts the audio data of `other` before the frame `at_frame`, moving the audio after it
+    /// back. Inserting at [WaveFile::num_frames] appends the data.
+    ///
+    /// # Errors
+    /// [WaveError::FormatMismatch] if the files don't have the same format (see
+    /// [WaveFile::append]), or [WaveError::InvalidRange] if `at_frame` is past the end of the
+    /// file. Nothing is inserted in that case.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 24);
+    /// wave.add_samples(&[I24::new(1).unwrap(), I24::new(2).unwrap(), I24::new(5).unwrap(), I24::new(6).unwrap()]).unwrap();
+    /// let mut other = WaveFile::new(AudioFormat::PCM, 2, 44100, 24);
+    /// other.add_samples(&[I24::new(3).unwrap(), I24::new(4).unwrap()]).unwrap();
+    ///
+    /// wave.insert_frames(1, &other).unwrap();
+    /// assert_eq!(wave.samples_as::<i32>().unwrap(), [1 << 8, 2 << 8, 3 << 8, 4 << 8, 5 << 8, 6 << 8]);
+    /// assert!(wave.insert_frames(4, &other).is_err());
+    /// ```
+    pub fn insert_frames(&mut self, at_frame: u64, other: &WaveFile) -> Result<(), WaveError> {
+        self.check_same_format(other)?;
+        let frames = self.num_frames();
+        if at_frame > frames {
+            return Err(WaveError::InvalidRange { start: at_frame, end: frames });
+        }
+        let at = at_frame as usize * self.block_align as usize;
+        self.audio_byte_data.make_contiguous().splice(at..at, other.audio_byte_data.iter().copied());
+        Ok(())
+    }
+
+    /// Removes the frames in `range`, moving the audio after it forward. The range is clamped to
+    /// the audio data like in [WaveFile::slice_frames].
+    ///
+    /// # Errors
+    /// [WaveError::InvalidRange] if the range starts after it ends.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 8);
+    /// wave.add_samples(&[1u8, 2, 3, 4, 5]).unwrap();
+    ///
+    /// wave.remove_frames(1..3).unwrap();
+    /// assert_eq!(wave.samples_as::<u8>().unwrap(), [1, 4, 5]);
+    /// wave.remove_frames(2..100).unwrap();
+    /// assert_eq!(wave.samples_as::<u8>().unwrap(), [1, 4]);
+    /// ```
+    pub fn remove_frames(&mut self, range: Range<u64>) -> Result<(), WaveError> {
+        let bytes = self.frame_byte_range(range)?;
+        self.audio_byte_data.make_contiguous().drain(bytes);
+        Ok(())
+    }
+
+    /// Reverses the order of the frames in the audio data. The samples within every frame, and
+    /// their bytes, keep their order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let samples: Vec<I24> = [1, -2, 3, -4, 5, -6].into_iter().map(|s| I24::new(s << 12).unwrap()).collect();
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 48000, 24);
+    /// wave.add_samples(&samples).unwrap();
+    ///
+    /// wave.reverse();
+    /// let reversed: Vec<i32> = wave.samples_as::<I24>().unwrap().into_iter().map(|s| s.value() >> 12).collect();
+    /// assert_eq!(reversed, [5, -6, 3, -4, 1, -2]);
+    /// ```
+    pub fn reverse(&mut self) {
+        let block_align = self.block_align as usize;
+        let frames = self.num_frames() as usize;
+        let data = self.audio_byte_data.make_contiguous();
+        for i in 0..frames / 2 {
+            let (front, back) = data.split_at_mut((frames - 1 - i) * block_align);
+            front[i * block_align..(i + 1) * block_align].swap_with_slice(&mut back[..block_align]);
+        }
+    }
+
+    /// Returns the byte range of the frames in `range`, clamped to the audio data
+    fn frame_byte_range(&self, range: Range<u64>) -> Result<Range<usize>, WaveError> {
+        if range.start > range.end {
+            return Err(WaveError::InvalidRange { start: range.start, end: range.end });
+        }
+        let frames = self.num_frames();
+        let block_align = self.block_align as usize;
+        let start = range.start.min(frames) as usize * block_align;
+        let end = range.end.min(frames) as usize * block_align;
+        Ok(start..end)
+    }
+
+    /// Creates a file of the given spec containing `duration` of silence, rounded to whole frames.
+    ///
+    /// ## Panics
+    /// Like [WaveFile::new], if the format and bits per sample of `spec` don't go together.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let spec = WaveSpec { format: AudioFormat::PCM, channels: 2, sample_rate: 8000, bits_per_sample: 8 };
+    /// let wave = WaveFile::silence(spec, Duration::from_millis(10));
+    /// assert_eq!(wave.num_frames(), 80);
+    /// assert!(wave.frames().all(|frame| frame == [0x80, 0x80]));
+    /// ```
+    pub fn silence(spec: WaveSpec, duration: Duration) -> WaveFile {
+        let mut wave = WaveFile::new(spec.format, spec.channels, spec.sample_rate, spec.bits_per_sample);
+        wave.append_silence(duration);
+        wave
+    }
+
+    /// Appends `duration` of silence to the audio data, rounded to whole frames (see
+    /// [WaveFile::frames_for_duration]).
+    ///
+    /// Silence is stored as the zero value of the format: `0x80` for 8-bit PCM, which is unsigned,
+    /// the encoded zero for A-law and µ-law and all zero bytes otherwise.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 1, 1000, 32);
+    /// wave.add_samples_f32(&[0.5]).unwrap();
+    /// wave.append_silence(Duration::from_micros(2600));
+    /// assert_eq!(wave.samples_f32().unwrap(), [0.5, 0.0, 0.0, 0.0]);
+    ///
+    /// let mut wave = WaveFile::new(AudioFormat::MuLaw, 1, 8000, 8);
+    /// wave.append_silence(Duration::from_millis(1));
+    /// assert_eq!(wave.samples_i16().unwrap(), [0; 8]);
+    /// ```
+    pub fn append_silence(&mut self, duration: Duration) {
+        self.append_silent_frames(self.frames_for_duration(duration));
+    }
+
+    /// Appends `frames` frames of silence to the audio data
+    pub(crate) fn append_silent_frames(&mut self, frames: u64) {
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        let mut zero = Vec::new();
+        if sample::check_supported(format, bits).is_ok() {
+            sample::encode(0.0, format, bits, &mut zero);
+        } else {
+            zero.resize((bits / 8) as usize, 0);
+        }
+        let frame = zero.repeat(self.num_channels as usize);
+        self.audio_byte_data.extend_with(frames as usize * frame.len(), |data| {
+            for _ in 0..frames {
+                data.extend_from_slice(&frame);
+            }
+        });
+    }
+
+    /// Concatenates the audio data of `files` into a new file with the format of the first file.
+    ///
+    /// # Errors
+    /// - [WaveError::FormatMismatch] if the files don't all share the same format (see
+    ///   [WaveFile::append])
+    /// - [WaveError::InvalidSpec] if `files` is empty, as there is no format to give the result
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let clips: Vec<WaveFile> = (0..3)
+    ///     .map(|i| {
+    ///         let mut clip = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    ///         clip.add_samples_i16(&[i, -i]).unwrap();
+    ///         clip
+    ///     })
+    ///     .collect();
+    ///
+    /// let wave = WaveFile::concat(&clips).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [0, 0, 1, -1, 2, -2]);
+    ///
+    /// assert!(matches!(WaveFile::concat(&[]), Err(WaveError::InvalidSpec(_))));
+    /// ```
+    pub fn concat(files: &[WaveFile]) -> Result<WaveFile, WaveError> {
+        let Some(first) = files.first() else {
+            return Err(WaveError::InvalidSpec("can't concatenate an empty list of files"));
+        };
+        let mut wave = first.empty_like(first.audio_format(), first.bits_per_sample);
+        for file in files {
+            wave.check_same_format(file)?;
+        }
+        wave.audio_byte_data.reserve(files.iter().map(|f| f.audio_byte_data.len()).sum());
+        for segment in files.iter().flat_map(|file| file.audio_byte_data.segments()) {
+            wave.audio_byte_data.extend_from_slice(segment);
+        }
+        Ok(wave)
+    }
+
+    /// Removes whole frames from the start and/or the end of the audio data, up to the first
+    /// (or last) frame in which every channel is silent (under about -60 dBFS). That frame is
+    /// kept, so the audio starts and ends near zero and doesn't click when it is looped or
+    /// concatenated.
+    ///
+    /// Returns the amount of frames that were removed. If no frame is silent in every channel,
+    /// nothing is removed.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[
+    ///     9000, 5000,
+    ///     10, 4000, // only the left channel is near zero
+    ///     -5, 20,
+    ///     8000, -8000,
+    ///     0, 12,
+    ///     3000, 3000,
+    /// ]).unwrap();
+    ///
+    /// assert_eq!(wave.trim_to_zero_crossing(TrimEnd::Both).unwrap(), 3);
+    /// assert_eq!(wave.samples_i16().unwrap(), [-5, 20, 8000, -8000, 0, 12]);
+    /// ```
+    pub fn trim_to_zero_crossing(&mut self, end: TrimEnd) -> Result<u64, WaveError> {
+        let samples = self.samples_as::<f64>()?;
+        let channels = self.num_channels.max(1) as usize;
+        let silent: Vec<bool> = samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().all(|s| s.abs() < ZERO_THRESHOLD))
+            .collect();
+        let (Some(first), Some(last)) = (silent.iter().position(|&s| s), silent.iter().rposition(|&s| s)) else {
+            return Ok(0);
+        };
+
+        let block_align = self.block_align as usize;
+        let mut removed = 0;
+        if matches!(end, TrimEnd::End | TrimEnd::Both) {
+            self.audio_byte_data.make_contiguous().truncate((last + 1) * block_align);
+            removed += silent.len() - last - 1;
+        }
+        if matches!(end, TrimEnd::Start | TrimEnd::Both) {
+            self.audio_byte_data.make_contiguous().drain(..first * block_align);
+            removed += first;
+        }
+        Ok(removed as u64)
+    }
+
+    /// Checks that the data of `other` can be combined with the data of this file as is
+    pub(crate) fn check_same_format(&self, other: &WaveFile) -> Result<(), WaveError> {
+        ensure_same([("audio format", self.audio_format.into(), other.audio_format.into())])?;
+        self.check_same_layout(other)
+    }
+
+    /// Checks that the frames of `other` have the same channels and duration as the frames of this
+    /// file, so their decoded samples can be compared
+    pub(crate) fn check_same_timing(&self, other: &WaveFile) -> Result<(), WaveError> {
+        ensure_same([
+            ("amount of channels", self.num_channels.into(), other.num_channels.into()),
+            ("sample rate", self.sample_rate, other.sample_rate),
+        ])
+    }
+
+    /// Checks that the samples of `other` line up with the samples of this file, so the files can
+    /// be combined sample by sample
+    pub(crate) fn check_same_layout(&self, other: &WaveFile) -> Result<(), WaveError> {
+        ensure_same([
+            ("amount of channels", self.num_channels.into(), other.num_channels.into()),
+            ("sample rate", self.sample_rate, other.sample_rate),
+            ("bits per sample", self.bits_per_sample.into(), other.bits_per_sample.into()),
+        ])
+    }
+}
+
+/// Returns [WaveError::FormatMismatch] for the first property of which the values differ
+fn ensure_same<const N: usize>(properties: [(&'static str, u32, u32); N]) -> Result<(), WaveError> {
+    match properties.into_iter().find(|(_, expected, found)| expected != found) {
+        Some((property, expected, found)) => Err(WaveError::FormatMismatch { property, expected, found }),
+        None => Ok(()),
+    }
+}
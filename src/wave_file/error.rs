@@ -0,0 +1,263 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use super::AudioFormat;
+
+/// Errors that can occur while working with a [WaveFile](super::WaveFile).
+#[derive(Debug)]
+pub enum WaveError {
+    /// The bytes do not start with the `RIFF`/`WAVE` magic, so this is not a wave file at all.
+    NotRiff,
+    /// A chunk that is required is not present in the file. Contains the FourCC of the chunk,
+    /// e.g. `"fmt "` or `"data"`.
+    MissingChunk(&'static str),
+    /// The audio format code in the fmt chunk is not supported.
+    UnsupportedFormat(u16),
+    /// The amount of bits per sample is not supported for the audio format or operation.
+    UnsupportedBitsPerSample(u16),
+    /// The amount of bytes given is not divisible by the block align of the file, adding them
+    /// would cause un-aligned blocks.
+    MisalignedData {
+        /// The amount of bytes that was given
+        len: usize,
+        /// The block align the amount of bytes should be divisible by
+        block_align: u16,
+    },
+    /// The samples in the file can't be converted to the requested type without losing
+    /// precision.
+    LossyConversion {
+        /// The format of the samples in the file
+        format: AudioFormat,
+        /// The bits per sample of the file
+        bits_per_sample: u16,
+    },
+    /// Two files can't be combined because a property of their formats differs.
+    FormatMismatch {
+        /// The property that differs, e.g. `"sample rate"`
+        property: &'static str,
+        /// The value of the file that is combined into
+        expected: u32,
+        /// The value of the other file
+        found: u32,
+    },
+    /// Two files can't be combined because they don't have the same amount of frames.
+    LengthMismatch {
+        /// The amount of frames of the first file
+        expected: u64,
+        /// The amount of frames of the other file
+        found: u64,
+    },
+    /// A channel index is not smaller than the amount of channels of the file.
+    ChannelOutOfRange {
+        /// The index of the channel
+        channel: usize,
+        /// The amount of channels of the file
+        num_channels: u16,
+    },
+    /// A channel order doesn't contain every channel of the file exactly once.
+    InvalidChannelOrder,
+    /// A loop of the sampler information starts after it ends, or ends after the audio.
+    LoopOutOfRange {
+        /// The first frame of the loop
+        start_frame: u32,
+        /// The last frame of the loop
+        end_frame: u32,
+        /// The amount of frames of the audio
+        num_frames: u64,
+    },
+    /// A range of frames starts after it ends.
+    InvalidRange {
+        /// The first frame of the range
+        start: u64,
+        /// The frame after the last frame of the range
+        end: u64,
+    },
+    /// A sample would go outside of the range of the format, and clipping was not allowed.
+    Clipping {
+        /// The index of the frame containing the sample
+        frame: u64,
+    },
+    /// The file doesn't fit in the 32-bit sizes of a RIFF file, and writing it as RF64 was not
+    /// allowed (see [Rf64Mode](super::Rf64Mode)).
+    TooLarge {
+        /// The size the RIFF chunk would have, in bytes
+        size: u64,
+    },
+    /// A spec can't describe a wave file: it has no channels, a sample rate of 0, or a byte rate
+    /// that doesn't fit in 32 bits. Contains a description of the problem.
+    InvalidSpec(&'static str),
+    /// A FLAC stream is not valid: it doesn't start with the `fLaC` marker or is corrupt.
+    /// Contains a description of the problem. Only returned with the `flac` feature.
+    Flac(&'static str),
+    /// An MP3 stream is not valid: it contains no MPEG audio frames, or uses a layer other than
+    /// Layer III. Contains a description of the problem. Only returned with the `mp3` feature.
+    Mp3(&'static str),
+    /// The file ends in the middle of a header, or a chunk declares more bytes than the file
+    /// contains.
+    Truncated,
+    /// The size of the data chunk doesn't match the audio data that follows it: it is 0 or
+    /// `0xFFFFFFFF` while audio follows, or more than the file contains. Only returned when
+    /// parsing strictly (see [ParseOptions](super::ParseOptions)).
+    InvalidDataSize {
+        /// The size declared in the data chunk header
+        declared: u64,
+        /// The amount of bytes of audio data actually in the file
+        actual: u64,
+    },
+    /// The file ends with a chunk of an odd size without the pad byte that should follow it. Only
+    /// returned when parsing strictly (see [ParseOptions](super::ParseOptions)).
+    MissingPadByte,
+    /// The byte rate in the fmt chunk is not the sample rate times the block align. Only returned
+    /// when parsing strictly (see [ParseOptions](super::ParseOptions)).
+    ByteRateMismatch {
+        /// The byte rate in the fmt chunk
+        declared: u32,
+        /// The byte rate computed from the sample rate and block align
+        computed: u64,
+    },
+    /// The block align in the fmt chunk is not the amount of channels times the bytes per
+    /// sample, so the frames of the audio data can't be found.
+    BlockAlignMismatch {
+        /// The block align in the fmt chunk
+        declared: u16,
+        /// The block align computed from the amount of channels and bits per sample
+        computed: u32,
+    },
+    /// Audio can't be appended to a file in place, because its data chunk is followed by other
+    /// chunks.
+    ChunksAfterData,
+    /// The bytes needed for an amount of frames don't fit in memory.
+    CapacityOverflow {
+        /// The amount of frames
+        frames: u64,
+        /// The size of a frame in bytes
+        block_align: u16,
+    },
+    /// A long-running operation was cancelled by its progress callback (see
+    /// [ProgressUpdate](super::ProgressUpdate)).
+    Cancelled,
+    /// A character isn't a DTMF digit: `0` to `9`, `*`, `#` or `A` to `D`. Contains the character.
+    InvalidDtmfDigit(char),
+    /// The overlap of a crossfade is longer than one of the clips.
+    OverlapTooLong {
+        /// The amount of frames of the overlap
+        overlap: u64,
+        /// The amount of frames of the shortest clip
+        frames: u64,
+    },
+    /// A value in sample text can't be read, see [WaveFile::import_csv](super::WaveFile::import_csv).
+    InvalidCsv {
+        /// The line of the value, counting from 1
+        line: usize,
+        /// The column of the value, counting from 1
+        column: usize,
+        /// What is wrong with the value
+        problem: &'static str,
+    },
+    /// An I/O error occurred while reading or writing a file.
+    Io(io::Error),
+}
+
+impl fmt::Display for WaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaveError::NotRiff => write!(f, "not a RIFF/WAVE file"),
+            WaveError::MissingChunk(id) => write!(f, "missing `{}` chunk", id),
+            WaveError::UnsupportedFormat(format) => write!(f, "unsupported audio format {:#06x}", format),
+            WaveError::UnsupportedBitsPerSample(bits) => write!(f, "unsupported bits per sample: {}", bits),
+            WaveError::MisalignedData { len, block_align } => write!(
+                f,
+                "{} bytes do not fit evenly in blocks of {} bytes",
+                len, block_align
+            ),
+            WaveError::LossyConversion { format, bits_per_sample } => write!(
+                f,
+                "converting {}-bit {:?} samples would lose precision",
+                bits_per_sample, format
+            ),
+            WaveError::FormatMismatch { property, expected, found } => write!(
+                f,
+                "the {} of the files doesn't match: expected {}, found {}",
+                property, expected, found
+            ),
+            WaveError::LengthMismatch { expected, found } => write!(
+                f,
+                "the files don't have the same length: expected {} frames, found {}",
+                expected, found
+            ),
+            WaveError::ChannelOutOfRange { channel, num_channels } => write!(
+                f,
+                "channel {} is out of range for a file with {} channels",
+                channel, num_channels
+            ),
+            WaveError::InvalidChannelOrder => write!(f, "the channel order is not a permutation of the channels"),
+            WaveError::LoopOutOfRange { start_frame, end_frame, num_frames } => write!(
+                f,
+                "the loop from frame {} to {} doesn't fit in audio of {} frames",
+                start_frame, end_frame, num_frames
+            ),
+            WaveError::InvalidRange { start, end } => write!(f, "the range {}..{} starts after it ends", start, end),
+            WaveError::Clipping { frame } => write!(f, "a sample in frame {} would clip", frame),
+            WaveError::TooLarge { size } => write!(
+                f,
+                "a RIFF chunk of {} bytes doesn't fit in a 32-bit size, RF64 is needed",
+                size
+            ),
+            WaveError::InvalidSpec(problem) => write!(f, "invalid spec: {}", problem),
+            WaveError::Flac(problem) => write!(f, "invalid FLAC stream: {}", problem),
+            WaveError::Mp3(problem) => write!(f, "invalid MP3 stream: {}", problem),
+            WaveError::Truncated => write!(f, "the file is truncated or a chunk size is corrupt"),
+            WaveError::InvalidDataSize { declared, actual } => write!(
+                f,
+                "the data chunk declares {} bytes, but contains {}",
+                declared, actual
+            ),
+            WaveError::MissingPadByte => write!(f, "the pad byte after the last chunk is missing"),
+            WaveError::ByteRateMismatch { declared, computed } => write!(
+                f,
+                "the byte rate {} doesn't match the sample rate times the block align, {}",
+                declared, computed
+            ),
+            WaveError::BlockAlignMismatch { declared, computed } => write!(
+                f,
+                "the block align {} doesn't match the channels times the bytes per sample, {}",
+                declared, computed
+            ),
+            WaveError::Cancelled => write!(f, "the operation was cancelled"),
+            WaveError::ChunksAfterData => write!(f, "the data chunk is not the last chunk of the file"),
+            WaveError::InvalidDtmfDigit(digit) => write!(f, "{:?} is not a DTMF digit", digit),
+            WaveError::OverlapTooLong { overlap, frames } => write!(
+                f,
+                "an overlap of {} frames is longer than a clip of {} frames",
+                overlap, frames
+            ),
+            WaveError::InvalidCsv { line, column, problem } => write!(
+                f,
+                "invalid sample text at line {}, column {}: {}",
+                line, column, problem
+            ),
+            WaveError::CapacityOverflow { frames, block_align } => write!(
+                f,
+                "{} frames of {} bytes don't fit in memory",
+                frames, block_align
+            ),
+            WaveError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl Error for WaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WaveError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WaveError {
+    fn from(err: io::Error) -> Self {
+        WaveError::Io(err)
+    }
+}
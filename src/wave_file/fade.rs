@@ -0,0 +1,163 @@
+use std::f64::consts::FRAC_PI_2;
+use std::time::Duration;
+
+use super::sample;
+use super::{WaveError, WaveFile};
+
+/// The shape of the gain ramp of [WaveFile::fade_in] and [WaveFile::fade_out].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeCurve {
+    /// The gain changes linearly.
+    Linear,
+    /// The gain changes exponentially over a range of 60 dB, which sounds like an even change in
+    /// loudness. The gain starts at silence.
+    Exponential,
+    /// The gain follows a quarter sine, so a fade in and a fade out overlapping each other keep
+    /// the same power.
+    EqualPower,
+}
+
+impl FadeCurve {
+    /// Returns the gain at `t` in `[0, 1]`, going from 0 to 1
+    fn gain(self, t: f64) -> f64 {
+        match self {
+            FadeCurve::Linear => t,
+            FadeCurve::Exponential => (1000f64.powf(t) - 1.0) / 999.0,
+            FadeCurve::EqualPower => (t * FRAC_PI_2).sin(),
+        }
+    }
+}
+
+// Fading
+impl WaveFile {
+    /// Fades in the start of the audio data over `duration`, rounded to whole frames. The first
+    /// frame becomes silent. If `duration` is longer than the file, the whole file is faded.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 1000, 16);
+    /// wave.add_samples_i16(&[1000; 20]).unwrap();
+    ///
+    /// wave.fade_in(Duration::from_millis(4), FadeCurve::Linear).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap()[..10], [0, 0, 250, 250, 500, 500, 750, 750, 1000, 1000]);
+    /// ```
+    pub fn fade_in(&mut self, duration: Duration, curve: FadeCurve) -> Result<(), WaveError> {
+        let len = self.frames_for_duration(duration).min(self.num_frames());
+        self.map_samples(0..len, |frame, _, sample| {
+            sample * curve.gain(frame as f64 / len as f64)
+        })
+    }
+
+    /// Fades out the end of the audio data over `duration`, rounded to whole frames. The last
+    /// frame becomes silent, which removes the click of audio that stops abruptly. If `duration`
+    /// is longer than the file, the whole file is faded.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// wave.add_samples(&[255u8; 100]).unwrap();
+    ///
+    /// // 5 ms is 40 frames at 8 kHz
+    /// wave.fade_out(Duration::from_millis(5), FadeCurve::EqualPower).unwrap();
+    /// let samples = wave.samples_as::<u8>().unwrap();
+    /// assert!(samples[..60].iter().all(|&s| s == 255));
+    /// assert!(samples[70] < 255 && samples[98] > 128);
+    /// assert_eq!(samples[99], 128);
+    ///
+    /// // Longer than the file
+    /// wave.fade_out(Duration::from_secs(1), FadeCurve::Exponential).unwrap();
+    /// assert!(wave.samples_as::<u8>().unwrap()[0] < 255);
+    /// ```
+    pub fn fade_out(&mut self, duration: Duration, curve: FadeCurve) -> Result<(), WaveError> {
+        let frames = self.num_frames();
+        let len = self.frames_for_duration(duration).min(frames);
+        let start = frames - len;
+        self.map_samples(start..frames, |frame, _, sample| {
+            sample * curve.gain((frames - 1 - frame) as f64 / len as f64)
+        })
+    }
+
+    /// Appends the audio data of `other`, overlapping its start with the end of this file for
+    /// `overlap`, rounded to whole frames. Over the overlap this file fades out while `other` fades
+    /// in with the opposite gain of `curve`, so the join doesn't click. The file grows by the
+    /// length of `other` minus the overlap.
+    ///
+    /// With [FadeCurve::Linear] the gains add up to 1, which suits clips of the same sound. With
+    /// [FadeCurve::EqualPower] the power adds up to 1, which keeps the loudness of unrelated
+    /// clips even. The overlapping samples are summed as normalized `f64` values and clipped to
+    /// full scale.
+    ///
+    /// # Errors
+    /// - [WaveError::FormatMismatch] if the two files don't have the same audio format, amount of
+    ///   channels, sample rate and bits per sample
+    /// - [WaveError::OverlapTooLong] if the overlap is longer than this file or `other`
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples
+    ///   can't be decoded
+    ///
+    /// The file is unchanged when an error is returned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 1, 1000, 32);
+    /// wave.add_samples_f32(&[0.5; 10]).unwrap();
+    /// let mut other = WaveFile::new(AudioFormat::IeeeFloat, 1, 1000, 32);
+    /// other.add_samples_f32(&[-0.5; 10]).unwrap();
+    ///
+    /// let mut joined = wave.clone();
+    /// joined.append_crossfaded(&other, Duration::from_millis(4), FadeCurve::Linear).unwrap();
+    /// assert_eq!(joined.num_frames(), 10 + 10 - 4);
+    /// assert_eq!(joined.samples_f32().unwrap()[5..11], [0.5, 0.375, 0.125, -0.125, -0.375, -0.5]);
+    ///
+    /// // Equal power keeps the level of unrelated audio
+    /// let mut joined = wave.clone();
+    /// joined.append_crossfaded(&wave, Duration::from_millis(4), FadeCurve::EqualPower).unwrap();
+    /// assert!(joined.samples_f32().unwrap()[6..10].iter().all(|&s| s > 0.5 && s < 0.71));
+    ///
+    /// let result = wave.append_crossfaded(&other, Duration::from_millis(11), FadeCurve::Linear);
+    /// assert!(matches!(result, Err(WaveError::OverlapTooLong { overlap: 11, frames: 10 })));
+    /// ```
+    pub fn append_crossfaded(&mut self, other: &WaveFile, overlap: Duration, curve: FadeCurve) -> Result<(), WaveError> {
+        self.check_same_format(other)?;
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        sample::check_supported(format, bits)?;
+        let len = self.frames_for_duration(overlap);
+        let frames = self.num_frames();
+        let shortest = frames.min(other.num_frames());
+        if len > shortest {
+            return Err(WaveError::OverlapTooLong { overlap: len, frames: shortest });
+        }
+
+        let overlap_bytes = len as usize * self.block_align as usize;
+        let sample_len = (bits / 8) as usize;
+        let mut head = other
+            .audio_byte_data
+            .slices(0..overlap_bytes)
+            .flat_map(|slice| slice.chunks_exact(sample_len))
+            .map(|bytes| sample::decode(bytes, format, bits));
+        let start = frames - len;
+        self.map_samples(start..frames, |frame, _, sample| {
+            // Halfway into the frame, so the gains are symmetric over the overlap
+            let t = ((frame - start) as f64 + 0.5) / len as f64;
+            let faded = sample * curve.gain(1.0 - t) + head.next().unwrap_or(0.0) * curve.gain(t);
+            faded.clamp(-1.0, 1.0)
+        })?;
+        for slice in other.audio_byte_data.slices(overlap_bytes..other.audio_byte_data.len()) {
+            self.audio_byte_data.extend_from_slice(slice);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,438 @@
+//! Decoding of FLAC streams into PCM, following RFC 9639.
+
+use std::fs;
+
+use super::id3::skip_id3v2;
+use super::{AudioFormat, WaveError, WaveFile};
+
+/// The size of the payload of the STREAMINFO metadata block
+const STREAMINFO_SIZE: usize = 34;
+/// The 14 bits every frame header starts with
+const FRAME_SYNC: u64 = 0x3FFE;
+
+/// Reads a stream of bits, most significant bit first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// The position in bits
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Reads `n` bits, at most 64, as an unsigned number
+    fn read(&mut self, mut n: u32) -> Result<u64, WaveError> {
+        let mut value = 0u64;
+        while n > 0 {
+            let byte = *self.bytes.get(self.pos / 8).ok_or(WaveError::Truncated)?;
+            let available = 8 - (self.pos % 8) as u32;
+            let take = available.min(n);
+            let bits = (byte as u64 >> (available - take)) & ((1 << take) - 1);
+            value = (value << take) | bits;
+            self.pos += take as usize;
+            n -= take;
+        }
+        Ok(value)
+    }
+
+    /// Reads `n` bits, at most 64, as a two's complement number
+    fn read_signed(&mut self, n: u32) -> Result<i64, WaveError> {
+        if n == 0 {
+            return Ok(0);
+        }
+        let value = self.read(n)?;
+        Ok(((value << (64 - n)) as i64) >> (64 - n))
+    }
+
+    /// Reads a unary coded number: the amount of zero bits before the next one bit
+    fn read_unary(&mut self) -> Result<u32, WaveError> {
+        let mut count = 0;
+        loop {
+            let byte = *self.bytes.get(self.pos / 8).ok_or(WaveError::Truncated)?;
+            let offset = self.pos % 8;
+            // The bits that haven't been read yet, at the top of the byte
+            let bits = byte << offset;
+            if bits != 0 {
+                let zeros = bits.leading_zeros();
+                self.pos += zeros as usize + 1;
+                return Ok(count + zeros);
+            }
+            count += 8 - offset as u32;
+            self.pos += 8 - offset;
+        }
+    }
+
+    /// Skips to the start of the next byte, unless already there
+    fn align(&mut self) {
+        self.pos = self.pos.next_multiple_of(8);
+    }
+
+    /// The position in bytes, rounded down
+    fn byte_pos(&self) -> usize {
+        self.pos / 8
+    }
+}
+
+/// CRC-8 with polynomial `x^8 + x^2 + x + 1`, as used for frame headers
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// CRC-16 with polynomial `x^16 + x^15 + x^2 + 1`, as used for whole frames
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// The fields of the STREAMINFO metadata block that are needed for decoding
+struct StreamInfo {
+    sample_rate: u32,
+    num_channels: u32,
+    bits_per_sample: u32,
+    /// The amount of frames (inter-channel samples) in the stream, 0 if unknown
+    total_frames: u64,
+}
+
+/// Parses the `fLaC` marker and the metadata blocks, returns the STREAMINFO and the position
+/// of the first frame.
+fn parse_metadata(bytes: &[u8]) -> Result<(StreamInfo, usize), WaveError> {
+    if !bytes.starts_with(b"fLaC") {
+        return Err(WaveError::Flac("missing `fLaC` marker"));
+    }
+    let mut pos = 4;
+    let mut stream_info = None;
+    loop {
+        let header = bytes.get(pos..pos + 4).ok_or(WaveError::Truncated)?;
+        let is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7F;
+        let size = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        let block = bytes.get(pos + 4..pos + 4 + size).ok_or(WaveError::Truncated)?;
+        if block_type == 0 && stream_info.is_none() {
+            if size < STREAMINFO_SIZE {
+                return Err(WaveError::Truncated);
+            }
+            // Minimum and maximum block and frame size, followed by the fields below
+            let mut reader = BitReader { bytes: &block[10..18], pos: 0 };
+            stream_info = Some(StreamInfo {
+                sample_rate: reader.read(20)? as u32,
+                num_channels: reader.read(3)? as u32 + 1,
+                bits_per_sample: reader.read(5)? as u32 + 1,
+                total_frames: reader.read(36)?,
+            });
+        } else if stream_info.is_none() {
+            return Err(WaveError::Flac("the first metadata block is not STREAMINFO"));
+        }
+        pos += 4 + size;
+        if is_last {
+            break;
+        }
+    }
+    Ok((stream_info.unwrap(), pos))
+}
+
+/// Decodes a partitioned Rice coded residual of `block_size - order` samples into `out`.
+fn read_residual(reader: &mut BitReader, block_size: usize, order: usize, out: &mut Vec<i64>) -> Result<(), WaveError> {
+    let (param_bits, escape) = match reader.read(2)? {
+        0 => (4, 0b1111),
+        1 => (5, 0b11111),
+        _ => return Err(WaveError::Flac("reserved residual coding method")),
+    };
+    let partition_order = reader.read(4)? as u32;
+    let partition_size = block_size >> partition_order;
+    if partition_size << partition_order != block_size || partition_size < order {
+        return Err(WaveError::Flac("invalid residual partition order"));
+    }
+    for partition in 0..1usize << partition_order {
+        let count = if partition == 0 { partition_size - order } else { partition_size };
+        let param = reader.read(param_bits)? as u32;
+        if param == escape {
+            // Unencoded samples of a fixed amount of bits
+            let bits = reader.read(5)? as u32;
+            for _ in 0..count {
+                out.push(reader.read_signed(bits)?);
+            }
+        } else {
+            for _ in 0..count {
+                let quotient = reader.read_unary()? as u64;
+                let value = (quotient << param) | reader.read(param)?;
+                // Zigzag encoded
+                out.push((value >> 1) as i64 ^ -((value & 1) as i64));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a subframe of `block_size` samples of `bits_per_sample` bits.
+fn read_subframe(reader: &mut BitReader, bits_per_sample: u32, block_size: usize) -> Result<Vec<i64>, WaveError> {
+    if reader.read(1)? != 0 {
+        return Err(WaveError::Flac("invalid subframe header"));
+    }
+    let subframe_type = reader.read(6)?;
+    let wasted_bits = if reader.read(1)? == 1 { reader.read_unary()? + 1 } else { 0 };
+    let bits = bits_per_sample.checked_sub(wasted_bits).ok_or(WaveError::Flac("too many wasted bits"))?;
+
+    let mut samples = Vec::with_capacity(block_size);
+    match subframe_type {
+        // Constant
+        0 => samples.resize(block_size, reader.read_signed(bits)?),
+        // Verbatim
+        1 => {
+            for _ in 0..block_size {
+                samples.push(reader.read_signed(bits)?);
+            }
+        }
+        // Fixed prediction of order 0 to 4
+        8..=12 => {
+            let order = (subframe_type - 8) as usize;
+            if order > block_size {
+                return Err(WaveError::Flac("predictor order is larger than the block size"));
+            }
+            for _ in 0..order {
+                samples.push(reader.read_signed(bits)?);
+            }
+            read_residual(reader, block_size, order, &mut samples)?;
+            for i in order..block_size {
+                let s = &samples;
+                let prediction = match order {
+                    0 => 0,
+                    1 => s[i - 1],
+                    2 => 2 * s[i - 1] - s[i - 2],
+                    3 => 3 * s[i - 1] - 3 * s[i - 2] + s[i - 3],
+                    _ => 4 * s[i - 1] - 6 * s[i - 2] + 4 * s[i - 3] - s[i - 4],
+                };
+                samples[i] += prediction;
+            }
+        }
+        // Linear prediction of order 1 to 32
+        32..=63 => {
+            let order = (subframe_type - 31) as usize;
+            if order > block_size {
+                return Err(WaveError::Flac("predictor order is larger than the block size"));
+            }
+            for _ in 0..order {
+                samples.push(reader.read_signed(bits)?);
+            }
+            let precision = match reader.read(4)? {
+                0b1111 => return Err(WaveError::Flac("invalid predictor precision")),
+                precision => precision as u32 + 1,
+            };
+            let shift = reader.read_signed(5)?;
+            if shift < 0 {
+                return Err(WaveError::Flac("negative predictor shift"));
+            }
+            let mut coefficients = Vec::with_capacity(order);
+            for _ in 0..order {
+                coefficients.push(reader.read_signed(precision)?);
+            }
+            read_residual(reader, block_size, order, &mut samples)?;
+            for i in order..block_size {
+                // Wrapping, so corrupt coefficients don't panic
+                let prediction = coefficients.iter().enumerate().fold(0i64, |sum, (j, coefficient)| {
+                    sum.wrapping_add(coefficient.wrapping_mul(samples[i - 1 - j]))
+                });
+                samples[i] = samples[i].wrapping_add(prediction >> shift);
+            }
+        }
+        _ => return Err(WaveError::Flac("reserved subframe type")),
+    }
+    if wasted_bits > 0 {
+        samples.iter_mut().for_each(|sample| *sample <<= wasted_bits);
+    }
+    Ok(samples)
+}
+
+/// Decodes the frame starting at the position of `reader`, which must be at the start of a
+/// byte. Returns the samples of every channel.
+fn read_frame(reader: &mut BitReader, info: &StreamInfo) -> Result<Vec<Vec<i64>>, WaveError> {
+    let start = reader.byte_pos();
+    if reader.read(14)? != FRAME_SYNC {
+        return Err(WaveError::Flac("lost frame sync"));
+    }
+    // Reserved bit and blocking strategy
+    reader.read(2)?;
+    let block_size_code = reader.read(4)?;
+    let sample_rate_code = reader.read(4)?;
+    let channel_assignment = reader.read(4)?;
+    let bits_per_sample = match reader.read(3)? {
+        0 => info.bits_per_sample,
+        1 => 8,
+        2 => 12,
+        4 => 16,
+        5 => 20,
+        6 => 24,
+        7 => 32,
+        _ => return Err(WaveError::Flac("reserved sample size")),
+    };
+    reader.read(1)?;
+    // The frame or sample number, UTF-8 coded
+    let first = reader.read(8)? as u8;
+    reader.read(8 * first.leading_ones().saturating_sub(1))?;
+
+    let block_size = match block_size_code {
+        0 => return Err(WaveError::Flac("reserved block size")),
+        1 => 192,
+        2..=5 => 576 << (block_size_code - 2),
+        6 => reader.read(8)? as usize + 1,
+        7 => reader.read(16)? as usize + 1,
+        _ => 256 << (block_size_code - 8),
+    };
+    // The sample rate is taken from the STREAMINFO block
+    match sample_rate_code {
+        12 => { reader.read(8)?; }
+        13 | 14 => { reader.read(16)?; }
+        15 => return Err(WaveError::Flac("invalid sample rate")),
+        _ => {}
+    }
+    let header_end = reader.byte_pos();
+    if reader.read(8)? as u8 != crc8(&reader.bytes[start..header_end]) {
+        return Err(WaveError::Flac("frame header CRC mismatch"));
+    }
+
+    let num_channels = match channel_assignment {
+        0..=7 => channel_assignment as u32 + 1,
+        8..=10 => 2,
+        _ => return Err(WaveError::Flac("reserved channel assignment")),
+    };
+    if num_channels != info.num_channels || bits_per_sample != info.bits_per_sample {
+        return Err(WaveError::Flac("the format of a frame differs from STREAMINFO"));
+    }
+    let mut channels = Vec::with_capacity(num_channels as usize);
+    for channel in 0..num_channels {
+        // The side channel has an extra bit
+        let is_side = matches!((channel_assignment, channel), (8, 1) | (9, 0) | (10, 1));
+        channels.push(read_subframe(reader, bits_per_sample + is_side as u32, block_size)?);
+    }
+    if let [left, right] = &mut channels[..] {
+        match channel_assignment {
+            // Left and side
+            8 => right.iter_mut().zip(left.iter()).for_each(|(side, left)| *side = left - *side),
+            // Side and right
+            9 => left.iter_mut().zip(right.iter()).for_each(|(side, right)| *side += right),
+            // Mid and side
+            10 => {
+                for (mid, side) in left.iter_mut().zip(right.iter_mut()) {
+                    let sum = (*mid << 1) | (*side & 1);
+                    (*mid, *side) = ((sum + *side) >> 1, (sum - *side) >> 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    reader.align();
+    let frame_end = reader.byte_pos();
+    if reader.read(16)? as u16 != crc16(&reader.bytes[start..frame_end]) {
+        return Err(WaveError::Flac("frame CRC mismatch"));
+    }
+    Ok(channels)
+}
+
+/// Decodes a FLAC stream into a PCM `WaveFile`.
+pub(crate) fn decode(bytes: &[u8]) -> Result<WaveFile, WaveError> {
+    let bytes = skip_id3v2(bytes);
+    let (info, first_frame) = parse_metadata(bytes)?;
+    if info.sample_rate == 0 {
+        return Err(WaveError::Flac("invalid sample rate"));
+    }
+    // Samples that are not a multiple of 8 bits are stored in the high bits of whole bytes
+    let sample_size = info.bits_per_sample.div_ceil(8);
+    let shift = sample_size * 8 - info.bits_per_sample;
+
+    // The bits per sample of the stream are kept as the valid bits
+    let mut wave = WaveFile::new(AudioFormat::PCM, info.num_channels as u16, info.sample_rate, info.bits_per_sample as u16);
+    // The total in STREAMINFO can't be trusted to allocate up front, so no more is reserved than
+    // a stream compressed to a quarter of its size decodes to
+    let expected = (info.total_frames as usize).saturating_mul(wave.block_align() as usize);
+    let mut data = Vec::with_capacity(expected.min(bytes.len().saturating_mul(4)));
+    let mut reader = BitReader { bytes, pos: first_frame * 8 };
+    let mut num_frames = 0;
+    while info.total_frames == 0 || num_frames < info.total_frames {
+        // Without a total, the frames run up to the end of the stream or a trailing tag
+        let rest = &bytes[reader.byte_pos().min(bytes.len())..];
+        if info.total_frames == 0 && (rest.len() < 2 || rest[0] != 0xFF || rest[1] & 0xFE != 0xF8) {
+            break;
+        }
+        let channels = read_frame(&mut reader, &info)?;
+        let block_size = channels[0].len();
+        // The last frame can be longer than the remaining amount of frames in the total
+        let block_size = match info.total_frames {
+            0 => block_size,
+            total => block_size.min((total - num_frames) as usize),
+        };
+        for i in 0..block_size {
+            for channel in &channels {
+                let sample = channel[i] << shift;
+                match sample_size {
+                    // 8-bit wave samples are unsigned
+                    1 => data.push((sample + 128) as u8),
+                    _ => data.extend(&sample.to_le_bytes()[..sample_size as usize]),
+                }
+            }
+        }
+        num_frames += block_size as u64;
+    }
+    wave.add_bytes(&mut data);
+    Ok(wave)
+}
+
+// FLAC
+impl WaveFile {
+    /// Reads a FLAC file and decodes it into a PCM `WaveFile` with the channels, sample rate and
+    /// bit depth of the stream.
+    ///
+    /// Only available with the `flac` feature.
+    ///
+    /// # Errors
+    /// Returns the same errors as [fs::read()](std::fs::read) and
+    /// [from_flac_bytes](WaveFile::from_flac_bytes).
+    pub fn from_flac_file(file_path: &str) -> Result<WaveFile, WaveError> {
+        let file_content = fs::read(file_path)?;
+        WaveFile::from_flac_bytes(&file_content)
+    }
+
+    /// Decodes an in-memory FLAC file into a PCM `WaveFile` with the channels, sample rate and bit
+    /// depth of the stream.
+    ///
+    /// Bit depths that are not a multiple of 8, like 12 or 20 bits, are stored in the high bits of
+    /// the next larger whole amount of bytes: a 20-bit stream becomes a 24-bit `WaveFile`. An
+    /// ID3v2 tag in front of the stream is skipped. Only available with the `flac` feature.
+    ///
+    /// # Errors
+    /// - [WaveError::Flac] if `bytes` does not start with the `fLaC` marker, or the stream is
+    ///   corrupt (including CRC mismatches)
+    /// - [WaveError::Truncated] if the stream ends in the middle of a metadata block or frame,
+    ///   or before the amount of samples in its STREAMINFO block
+    ///
+    /// # Examples
+    /// A stereo 16-bit stream with a single frame of 4 samples, stored as a left channel with a
+    /// fixed predictor and a constant side channel:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let bytes = [
+    ///     0x66, 0x4C, 0x61, 0x43, 0x80, 0x00, 0x00, 0x22, 0x00, 0x10, 0xFF, 0xFF, 0x00, 0x00,
+    ///     0x00, 0x00, 0x00, 0x00, 0x01, 0xF4, 0x02, 0xF0, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00,
+    ///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ///     0xFF, 0xF8, 0x70, 0x88, 0x00, 0x00, 0x03, 0x22, 0x12, 0x00, 0x0A, 0x08, 0x11, 0x44,
+    ///     0x51, 0x14, 0x00, 0x00, 0x32, 0x00, 0xCF, 0x7F,
+    /// ];
+    ///
+    /// let wave = WaveFile::from_flac_bytes(&bytes).unwrap();
+    /// assert_eq!((wave.num_channels(), wave.sample_rate(), wave.bits_per_sample()), (2, 8000, 16));
+    /// assert_eq!(wave.samples_i16().unwrap(), [10, -90, 20, -80, 30, -70, 40, -60]);
+    ///
+    /// assert!(matches!(WaveFile::from_flac_bytes(b"RIFF"), Err(WaveError::Flac(_))));
+    /// ```
+    pub fn from_flac_bytes(bytes: &[u8]) -> Result<WaveFile, WaveError> {
+        decode(bytes)
+    }
+}
@@ -0,0 +1,111 @@
+//! Parsing of the fmt chunk.
+
+use super::{AudioFormat, WaveError};
+
+/// The last 14 bytes of the `KSDATAFORMAT_SUBTYPE_*` GUIDs, the first 2 bytes are the format code
+/// of the sub format.
+pub(crate) const SUBFORMAT_GUID_TAIL: [u8; 14] = [
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+];
+
+/// The fields of a parsed fmt chunk.
+pub(crate) struct FmtChunk {
+    /// The format of the samples. For `WAVE_FORMAT_EXTENSIBLE` files this is the sub format.
+    pub(crate) audio_format: AudioFormat,
+    pub(crate) num_channels: u16,
+    pub(crate) sample_rate: u32,
+    pub(crate) byte_rate: u32,
+    pub(crate) block_align: u16,
+    /// The size of the container of a sample in bits, a multiple of 8
+    pub(crate) bits_per_sample: u16,
+    /// The amount of bits of the container that are used, from the most significant bit
+    pub(crate) valid_bits_per_sample: u16,
+    pub(crate) extensible: bool,
+    pub(crate) channel_mask: u32,
+}
+
+impl FmtChunk {
+    pub(crate) fn parse(fmt: &[u8]) -> Result<FmtChunk, WaveError> {
+        if fmt.len() < 16 {
+            return Err(WaveError::Truncated);
+        }
+        let u16_at = |i: usize| u16::from_le_bytes([fmt[i], fmt[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(fmt[i..i + 4].try_into().unwrap());
+
+        let mut chunk = FmtChunk {
+            audio_format: AudioFormat::PCM,
+            num_channels: u16_at(2),
+            sample_rate: u32_at(4),
+            byte_rate: u32_at(8),
+            block_align: u16_at(12),
+            bits_per_sample: u16_at(14),
+            valid_bits_per_sample: u16_at(14),
+            extensible: false,
+            channel_mask: 0,
+        };
+
+        chunk.audio_format = match AudioFormat::try_from(u16_at(0))? {
+            AudioFormat::Extensible => {
+                // cbSize, valid bits, channel mask and the sub format GUID
+                if fmt.len() < 40 || u16_at(16) < 22 {
+                    return Err(WaveError::Truncated);
+                }
+                chunk.extensible = true;
+                chunk.valid_bits_per_sample = u16_at(18);
+                chunk.channel_mask = u32_at(20);
+                let sub_format = u16_at(24);
+                if fmt[26..40] != SUBFORMAT_GUID_TAIL {
+                    return Err(WaveError::UnsupportedFormat(AudioFormat::Extensible.get_val()));
+                }
+                match AudioFormat::try_from(sub_format)? {
+                    AudioFormat::Extensible => return Err(WaveError::UnsupportedFormat(sub_format)),
+                    format => format,
+                }
+            }
+            format => format,
+        };
+
+        // No format has samples of more than 64 bits, and larger values would overflow when
+        // rounded up
+        if chunk.bits_per_sample > 64 {
+            return Err(WaveError::UnsupportedBitsPerSample(chunk.bits_per_sample));
+        }
+        // Samples of e.g. 12 bits are stored in containers of whole bytes, which are signalled by
+        // the valid bits of an extensible fmt chunk, or by the bits per sample of a plain one
+        let container_bits = chunk.bits_per_sample.next_multiple_of(8);
+        if chunk.valid_bits_per_sample == 0 || chunk.valid_bits_per_sample > container_bits {
+            chunk.valid_bits_per_sample = container_bits;
+        }
+        chunk.bits_per_sample = container_bits;
+
+        // Frames are counted and sized by the block align, so it has to match the samples
+        if chunk.num_channels == 0 {
+            return Err(WaveError::InvalidSpec("the fmt chunk has no channels"));
+        }
+        if container_bits == 0 {
+            return Err(WaveError::UnsupportedBitsPerSample(0));
+        }
+        let computed = chunk.num_channels as u32 * (container_bits / 8) as u32;
+        if chunk.block_align as u32 != computed {
+            return Err(WaveError::BlockAlignMismatch { declared: chunk.block_align, computed });
+        }
+
+        Ok(chunk)
+    }
+}
+
+/// The speaker positions conventionally used for the given amount of channels, or 0 (no
+/// positions assigned) if there is no convention.
+pub(crate) fn default_channel_mask(num_channels: u16) -> u32 {
+    match num_channels {
+        1 => 0x4,   // front center
+        2 => 0x3,   // front left, front right
+        3 => 0x7,   // + front center
+        4 => 0x33,  // quad
+        5 => 0x37,  // 5.0
+        6 => 0x3F,  // 5.1
+        7 => 0x13F, // 6.1
+        8 => 0x63F, // 7.1
+        _ => 0,
+    }
+}
@@ -0,0 +1,308 @@
+use std::iter::{FusedIterator, Take};
+use std::marker::PhantomData;
+
+use super::data::DataChunks;
+use super::sample::{self, Sample};
+use super::{AudioFormat, WaveError, WaveFile};
+
+/// An iterator over the frames of a [WaveFile] as byte slices of `block_align` bytes, see
+/// [WaveFile::frames].
+#[derive(Debug, Clone)]
+pub struct FrameSlices<'a> {
+    chunks: DataChunks<'a>,
+}
+
+impl<'a> Iterator for FrameSlices<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.chunks.nth(n)
+    }
+}
+
+impl DoubleEndedIterator for FrameSlices<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back()
+    }
+}
+
+impl ExactSizeIterator for FrameSlices<'_> {}
+
+impl FusedIterator for FrameSlices<'_> {}
+
+/// An iterator over the samples of a [WaveFile] decoded to `S`, see [WaveFile::iter_samples].
+#[derive(Debug, Clone)]
+pub struct Samples<'a, S> {
+    chunks: DataChunks<'a>,
+    format: AudioFormat,
+    bits_per_sample: u16,
+    sample: PhantomData<S>,
+}
+
+impl<S: Sample> Iterator for Samples<'_, S> {
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        let bytes = self.chunks.next()?;
+        Some(sample::read_sample(bytes, self.format, self.bits_per_sample))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<S> {
+        let bytes = self.chunks.nth(n)?;
+        Some(sample::read_sample(bytes, self.format, self.bits_per_sample))
+    }
+}
+
+impl<S: Sample> DoubleEndedIterator for Samples<'_, S> {
+    fn next_back(&mut self) -> Option<S> {
+        let bytes = self.chunks.next_back()?;
+        Some(sample::read_sample(bytes, self.format, self.bits_per_sample))
+    }
+}
+
+impl<S: Sample> ExactSizeIterator for Samples<'_, S> {}
+
+impl<S: Sample> FusedIterator for Samples<'_, S> {}
+
+/// An iterator over the samples of one channel of a [WaveFile] decoded to `S`, see
+/// [WaveFile::iter_channel].
+#[derive(Debug, Clone)]
+pub struct ChannelSamples<'a, S> {
+    frames: FrameSlices<'a>,
+    /// The offset of the sample of the channel in a frame
+    offset: usize,
+    format: AudioFormat,
+    bits_per_sample: u16,
+    sample: PhantomData<S>,
+}
+
+impl<S: Sample> ChannelSamples<'_, S> {
+    fn read(&self, frame: &[u8]) -> S {
+        sample::read_sample(&frame[self.offset..], self.format, self.bits_per_sample)
+    }
+}
+
+impl<S: Sample> Iterator for ChannelSamples<'_, S> {
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        let frame = self.frames.next()?;
+        Some(self.read(frame))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<S> {
+        let frame = self.frames.nth(n)?;
+        Some(self.read(frame))
+    }
+}
+
+impl<S: Sample> DoubleEndedIterator for ChannelSamples<'_, S> {
+    fn next_back(&mut self) -> Option<S> {
+        let frame = self.frames.next_back()?;
+        Some(self.read(frame))
+    }
+}
+
+impl<S: Sample> ExactSizeIterator for ChannelSamples<'_, S> {}
+
+impl<S: Sample> FusedIterator for ChannelSamples<'_, S> {}
+
+/// An iterator over windows of consecutive frames of a [WaveFile], see
+/// [WaveFile::windows_frames]. Every window is an iterator over its frames.
+#[derive(Debug, Clone)]
+pub struct FrameWindows<'a> {
+    /// The frames from the start of the next window on
+    frames: FrameSlices<'a>,
+    len: usize,
+    hop: usize,
+    /// The amount of windows left
+    remaining: usize,
+}
+
+impl<'a> Iterator for FrameWindows<'a> {
+    type Item = Take<FrameSlices<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let window = self.frames.clone().take(self.len);
+        if self.remaining > 0 {
+            self.frames.nth(self.hop - 1);
+        }
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for FrameWindows<'_> {}
+
+impl FusedIterator for FrameWindows<'_> {}
+
+// Frames
+impl WaveFile {
+    /// Returns an iterator over the frames (one sample for every channel) of the audio data, each
+    /// frame being a slice of `block_align` bytes.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, 2, 3, 4, 5, 6]).unwrap();
+    ///
+    /// let frames = wave.frames();
+    /// assert_eq!(frames.len(), 3);
+    /// assert_eq!(wave.frame(1), Some(&[3, 0, 4, 0][..]));
+    /// assert_eq!(wave.frame(3), None);
+    ///
+    /// let frames: Vec<Vec<i16>> = wave.frames_i16().unwrap().collect();
+    /// assert_eq!(frames, [[1, 2], [3, 4], [5, 6]]);
+    /// ```
+    pub fn frames(&self) -> FrameSlices<'_> {
+        FrameSlices { chunks: self.audio_byte_data.chunks_exact(self.block_align.max(1) as usize) }
+    }
+
+    /// Returns an iterator over the samples of the audio data decoded to `S`, in the order they
+    /// are stored: the channels of every frame interleaved. Samples are decoded as they are
+    /// iterated, without allocating, and converted like [samples_as](WaveFile::samples_as) does.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[16384, -16384, 8192, 0]).unwrap();
+    ///
+    /// let samples = wave.iter_samples::<f32>().unwrap();
+    /// assert_eq!(samples.len(), 4);
+    /// assert_eq!(samples.collect::<Vec<_>>(), [0.5, -0.5, 0.25, 0.0]);
+    /// assert_eq!(wave.iter_samples::<i16>().unwrap().rev().nth(1), Some(8192));
+    /// ```
+    pub fn iter_samples<S: Sample>(&self) -> Result<Samples<'_, S>, WaveError> {
+        let (format, bits_per_sample) = (self.audio_format(), self.bits_per_sample);
+        sample::check_supported(format, bits_per_sample)?;
+        let chunks = self.audio_byte_data.chunks_exact((bits_per_sample / 8) as usize);
+        Ok(Samples { chunks, format, bits_per_sample, sample: PhantomData })
+    }
+
+    /// Returns an iterator over the samples of `channel` decoded to `S`, one for every frame, like
+    /// [iter_samples](WaveFile::iter_samples).
+    ///
+    /// # Errors
+    /// - [WaveError::ChannelOutOfRange] if the file doesn't have `channel`
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    ///   the file can't be decoded
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 8);
+    /// wave.add_samples(&[128u8, 0, 192, 255]).unwrap();
+    ///
+    /// let right: Vec<i16> = wave.iter_channel(1).unwrap().collect();
+    /// assert_eq!(right, [i16::MIN, 32512]);
+    /// assert!(matches!(wave.iter_channel::<i16>(2), Err(WaveError::ChannelOutOfRange { channel: 2, num_channels: 2 })));
+    /// ```
+    pub fn iter_channel<S: Sample>(&self, channel: usize) -> Result<ChannelSamples<'_, S>, WaveError> {
+        let (format, bits_per_sample) = (self.audio_format(), self.bits_per_sample);
+        sample::check_supported(format, bits_per_sample)?;
+        if channel >= self.num_channels as usize {
+            return Err(WaveError::ChannelOutOfRange { channel, num_channels: self.num_channels });
+        }
+        let offset = channel * (bits_per_sample / 8) as usize;
+        Ok(ChannelSamples { frames: self.frames(), offset, format, bits_per_sample, sample: PhantomData })
+    }
+
+    /// Returns an iterator over windows of `len` consecutive frames, the first starting at the
+    /// first frame and every next one `hop` frames later, for analysis over short stretches of
+    /// audio. With a `hop` smaller than `len` the windows overlap.
+    ///
+    /// Only whole windows are returned, so frames at the end that don't fill a window are left
+    /// out, and audio shorter than `len` gives no windows. Every window is an iterator over its
+    /// frames as slices of `block_align` bytes, like [frames](WaveFile::frames), so no audio data
+    /// is copied.
+    ///
+    /// ## Panics
+    /// If `len` or `hop` is 0.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 8);
+    /// wave.add_samples(&[1u8, 2, 3, 4, 5, 6]).unwrap();
+    ///
+    /// let windows: Vec<Vec<u8>> = wave.windows_frames(4, 1).map(|window| window.map(|frame| frame[0]).collect()).collect();
+    /// assert_eq!(windows, [[1, 2, 3, 4], [2, 3, 4, 5], [3, 4, 5, 6]]);
+    ///
+    /// // The last frame doesn't fill a window
+    /// assert_eq!(wave.windows_frames(2, 3).len(), 2);
+    /// assert_eq!(wave.windows_frames(7, 1).len(), 0);
+    /// ```
+    pub fn windows_frames(&self, len: usize, hop: usize) -> FrameWindows<'_> {
+        assert!(len > 0 && hop > 0, "windows need a length and hop of at least one frame");
+        let frames = self.frames();
+        let remaining = match frames.len().checked_sub(len) {
+            Some(rest) => rest / hop + 1,
+            None => 0,
+        };
+        FrameWindows { frames, len, hop, remaining }
+    }
+
+    /// Returns the frame at `index` as a slice of `block_align` bytes, or `None` if it is out of
+    /// range.
+    pub fn frame(&self, index: u64) -> Option<&[u8]> {
+        let block_align = self.block_align as u64;
+        let start = usize::try_from(index.checked_mul(block_align)?).ok()?;
+        self.audio_byte_data.get(start..start.checked_add(block_align as usize)?)
+    }
+
+    /// Returns an iterator over the frames of the audio data, decoded to one `i16` per channel.
+    ///
+    /// # Errors
+    /// The same errors as [samples_i16](WaveFile::samples_i16).
+    pub fn frames_i16(&self) -> Result<impl ExactSizeIterator<Item = Vec<i16>> + '_, WaveError> {
+        self.check_lossless_i16()?;
+        Ok(self.frames_as())
+    }
+
+    /// Returns an iterator over the frames of the audio data, decoded to one `f32` per channel.
+    ///
+    /// # Errors
+    /// The same errors as [samples_f32](WaveFile::samples_f32).
+    pub fn frames_f32(&self) -> Result<impl ExactSizeIterator<Item = Vec<f32>> + '_, WaveError> {
+        self.check_lossless_f32()?;
+        Ok(self.frames_as())
+    }
+
+    /// The frames decoded to `S`. The format has to be supported.
+    fn frames_as<S: Sample>(&self) -> impl ExactSizeIterator<Item = Vec<S>> + '_ {
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        let sample_len = (bits / 8) as usize;
+        self.frames().map(move |frame| {
+            frame.chunks_exact(sample_len).map(|s| S::from_f64(sample::decode(s, format, bits))).collect()
+        })
+    }
+}
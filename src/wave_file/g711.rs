@@ -0,0 +1,115 @@
+//! A-law and µ-law (G.711) companding between 16-bit linear PCM and 8-bit samples.
+//!
+//! Based on the reference implementation by Sun Microsystems (`g711.c`).
+
+const SIGN_BIT: u8 = 0x80;
+const QUANT_MASK: u8 = 0x0F;
+const SEG_SHIFT: u8 = 4;
+const SEG_MASK: u8 = 0x70;
+
+/// Bias for linear code
+const BIAS: i32 = 0x84;
+/// Maximum (14 bit) magnitude that can be µ-law encoded
+const CLIP: i32 = 8159;
+
+const SEG_A_END: [i32; 8] = [0x1F, 0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF];
+const SEG_U_END: [i32; 8] = [0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF, 0x1FFF];
+
+const ALAW_TO_LINEAR: [i16; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = alaw_to_linear(i as u8);
+        i += 1;
+    }
+    table
+};
+const MULAW_TO_LINEAR: [i16; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = mulaw_to_linear(i as u8);
+        i += 1;
+    }
+    table
+};
+
+/// Returns the segment `val` falls in, or 8 if it is larger than the last segment
+fn segment(val: i32, table: &[i32; 8]) -> u8 {
+    table.iter().position(|&end| val <= end).unwrap_or(8) as u8
+}
+
+/// Encodes a 16-bit linear sample as A-law
+pub(crate) fn linear_to_alaw(sample: i16) -> u8 {
+    let mut pcm = (sample as i32) >> 3;
+    let mask = if pcm >= 0 {
+        0xD5 // sign (7th) bit = 1
+    } else {
+        pcm = -pcm - 1;
+        0x55 // sign bit = 0
+    };
+
+    let seg = segment(pcm, &SEG_A_END);
+    if seg >= 8 {
+        return 0x7F ^ mask;
+    }
+    let mut aval = seg << SEG_SHIFT;
+    if seg < 2 {
+        aval |= ((pcm >> 1) as u8) & QUANT_MASK;
+    } else {
+        aval |= ((pcm >> seg) as u8) & QUANT_MASK;
+    }
+    aval ^ mask
+}
+
+/// Decodes an A-law sample to 16-bit linear
+pub(crate) fn alaw_to_pcm16(sample: u8) -> i16 {
+    ALAW_TO_LINEAR[sample as usize]
+}
+
+const fn alaw_to_linear(sample: u8) -> i16 {
+    let a_val = sample ^ 0x55;
+    let mut t = ((a_val & QUANT_MASK) as i32) << 4;
+    let seg = (a_val & SEG_MASK) >> SEG_SHIFT;
+    if seg == 0 {
+        t += 8;
+    } else {
+        t += 0x108;
+        t <<= seg - 1;
+    }
+    if a_val & SIGN_BIT != 0 { t as i16 } else { -t as i16 }
+}
+
+/// Encodes a 16-bit linear sample as µ-law
+pub(crate) fn linear_to_mulaw(sample: i16) -> u8 {
+    let mut pcm = (sample as i32) >> 2;
+    let mask = if pcm < 0 {
+        pcm = -pcm;
+        0x7F
+    } else {
+        0xFF
+    };
+    if pcm > CLIP {
+        pcm = CLIP;
+    }
+    pcm += BIAS >> 2;
+
+    let seg = segment(pcm, &SEG_U_END);
+    if seg >= 8 {
+        return 0x7F ^ mask;
+    }
+    let uval = (seg << 4) | (((pcm >> (seg + 1)) as u8) & QUANT_MASK);
+    uval ^ mask
+}
+
+/// Decodes a µ-law sample to 16-bit linear
+pub(crate) fn mulaw_to_pcm16(sample: u8) -> i16 {
+    MULAW_TO_LINEAR[sample as usize]
+}
+
+const fn mulaw_to_linear(sample: u8) -> i16 {
+    let u_val = !sample;
+    let mut t = (((u_val & QUANT_MASK) as i32) << 3) + BIAS;
+    t <<= (u_val & SEG_MASK) >> SEG_SHIFT;
+    if u_val & SIGN_BIT != 0 { (BIAS - t) as i16 } else { (t - BIAS) as i16 }
+}
@@ -0,0 +1,473 @@
+//! Generating test signals: tones of the basic waveforms, sine sweeps, DTMF digits and noise.
+//!
+//! Every function returns a new [WaveFile] of the given spec, with the same signal on every
+//! channel except for noise. `amplitude_dbfs` is the peak level in decibels relative to full scale, so 0.0 is the
+//! loudest signal that doesn't clip, for every format and bit depth. Append a signal to an
+//! existing file with [WaveFile::append].
+//!
+//! Tones and sweeps start at a phase of 0 and the phase is continuous over the whole signal.
+//! Sine waves, triangle waves and sweeps start at zero, but most durations end in the middle of a
+//! period: fade the end out with [WaveFile::fade_out] to avoid a click when the signal stops.
+//!
+//! The waveforms are generated as their ideal shape, without limiting their bandwidth, so square,
+//! triangle and sawtooth waves alias at high frequencies.
+//!
+//! # Examples
+//! ```rust
+//! # use jaudio::wave_file::*;
+//! # use std::time::Duration;
+//! let spec = WaveSpec::new(AudioFormat::PCM, 2, 48000, 16).unwrap();
+//! let mut tone = generate::sine(spec, 1000.0, Duration::from_millis(500), -6.0).unwrap();
+//! tone.fade_out(Duration::from_millis(10), FadeCurve::Linear).unwrap();
+//! assert_eq!(tone.num_frames(), 24000);
+//!
+//! let peak = tone.peak().unwrap();
+//! assert!((peak[0] - 0.501).abs() < 0.001 && peak[0] == peak[1]);
+//! assert_eq!(tone.samples_i16().unwrap()[2 * 23999..], [0, 0]);
+//!
+//! let sweep = generate::sweep(spec, 20.0, 20000.0, Duration::from_secs(1), SweepKind::Log, -3.0).unwrap();
+//! tone.append(&sweep).unwrap();
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::f64::consts::TAU;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use super::sample;
+use super::{FadeCurve, WaveError, WaveFile, WaveSpec};
+
+/// How the frequency of a [sweep] changes over time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepKind {
+    /// The frequency changes by the same amount of Hz every second.
+    Linear,
+    /// The frequency changes by the same amount of octaves every second, so every octave gets as
+    /// much time. Both frequencies have to be above 0.
+    Log,
+}
+
+/// Generates a sine wave of `freq_hz` Hz, lasting `duration` rounded to whole frames.
+///
+/// # Errors
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// ## Panics
+/// If `freq_hz` is negative or not finite.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// // 24-bit at full scale: a quarter period of 1 kHz at 4 kHz reaches the peak
+/// let spec = WaveSpec::new(AudioFormat::PCM, 1, 4000, 24).unwrap();
+/// let tone = generate::sine(spec, 1000.0, Duration::from_millis(1), 0.0).unwrap();
+/// let samples: Vec<i32> = tone.samples_as::<I24>().unwrap().into_iter().map(i32::from).collect();
+/// assert_eq!(samples, [0, I24::MAX.value(), 0, I24::MIN.value()]);
+/// ```
+pub fn sine(spec: WaveSpec, freq_hz: f64, duration: Duration, amplitude_dbfs: f32) -> Result<WaveFile, WaveError> {
+    check_frequency(freq_hz);
+    tone(spec, duration, amplitude_dbfs, |t| freq_hz * t, |phase| (phase * TAU).sin())
+}
+
+/// Generates a square wave of `freq_hz` Hz, lasting `duration` rounded to whole frames. Every
+/// period starts with its positive half.
+///
+/// # Errors
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// ## Panics
+/// If `freq_hz` is negative or not finite.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// let spec = WaveSpec::new(AudioFormat::IeeeFloat, 1, 8000, 32).unwrap();
+/// let tone = generate::square(spec, 2000.0, Duration::from_millis(1), -6.0206).unwrap();
+/// let samples = tone.samples_f32().unwrap();
+/// assert!(samples.iter().all(|s| (s.abs() - 0.5).abs() < 1e-4));
+/// assert!(samples[..2].iter().all(|&s| s > 0.0) && samples[2..4].iter().all(|&s| s < 0.0));
+/// ```
+pub fn square(spec: WaveSpec, freq_hz: f64, duration: Duration, amplitude_dbfs: f32) -> Result<WaveFile, WaveError> {
+    check_frequency(freq_hz);
+    tone(spec, duration, amplitude_dbfs, |t| freq_hz * t, |phase| if phase < 0.5 { 1.0 } else { -1.0 })
+}
+
+/// Generates a triangle wave of `freq_hz` Hz, lasting `duration` rounded to whole frames. Every
+/// period starts at zero and rises to the peak first.
+///
+/// # Errors
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// ## Panics
+/// If `freq_hz` is negative or not finite.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// let spec = WaveSpec::new(AudioFormat::PCM, 1, 8000, 16).unwrap();
+/// let tone = generate::triangle(spec, 1000.0, Duration::from_millis(1), 0.0).unwrap();
+/// assert_eq!(tone.samples_i16().unwrap(), [0, 16384, i16::MAX, 16384, 0, -16384, i16::MIN, -16384]);
+/// ```
+pub fn triangle(spec: WaveSpec, freq_hz: f64, duration: Duration, amplitude_dbfs: f32) -> Result<WaveFile, WaveError> {
+    check_frequency(freq_hz);
+    tone(spec, duration, amplitude_dbfs, |t| freq_hz * t, |phase| {
+        if phase < 0.25 {
+            4.0 * phase
+        } else if phase < 0.75 {
+            2.0 - 4.0 * phase
+        } else {
+            4.0 * phase - 4.0
+        }
+    })
+}
+
+/// Generates a sawtooth wave of `freq_hz` Hz, lasting `duration` rounded to whole frames. Every
+/// period rises from zero to the peak, jumps to the negative peak and rises back to zero.
+///
+/// # Errors
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// ## Panics
+/// If `freq_hz` is negative or not finite.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// let spec = WaveSpec::new(AudioFormat::PCM, 1, 8000, 8).unwrap();
+/// let tone = generate::sawtooth(spec, 2000.0, Duration::from_millis(1), 0.0).unwrap();
+/// assert_eq!(tone.samples_as::<u8>().unwrap(), [128, 192, 0, 64, 128, 192, 0, 64]);
+/// ```
+pub fn sawtooth(spec: WaveSpec, freq_hz: f64, duration: Duration, amplitude_dbfs: f32) -> Result<WaveFile, WaveError> {
+    check_frequency(freq_hz);
+    tone(spec, duration, amplitude_dbfs, |t| freq_hz * t, |phase| {
+        if phase < 0.5 {
+            2.0 * phase
+        } else {
+            2.0 * phase - 2.0
+        }
+    })
+}
+
+/// Generates a sine wave that sweeps from `start_hz` to `end_hz` Hz over `duration`, rounded to
+/// whole frames. The frequency changes as set by `kind`, and the phase is continuous.
+///
+/// # Errors
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// ## Panics
+/// If a frequency is negative or not finite, or 0 for [SweepKind::Log].
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// let spec = WaveSpec::new(AudioFormat::PCM, 1, 48000, 16).unwrap();
+/// let sweep = generate::sweep(spec, 100.0, 1000.0, Duration::from_secs(2), SweepKind::Linear, -1.0).unwrap();
+/// let samples = sweep.samples_i16().unwrap();
+///
+/// // The frequency, counted in zero crossings, goes up over time
+/// let crossings = |s: &[i16]| s.windows(2).filter(|w| (w[0] < 0) != (w[1] < 0)).count();
+/// assert!(crossings(&samples[..4800]) < crossings(&samples[48000..52800]));
+/// ```
+pub fn sweep(
+    spec: WaveSpec,
+    start_hz: f64,
+    end_hz: f64,
+    duration: Duration,
+    kind: SweepKind,
+    amplitude_dbfs: f32,
+) -> Result<WaveFile, WaveError> {
+    check_frequency(start_hz);
+    check_frequency(end_hz);
+    let secs = duration.as_secs_f64();
+    match kind {
+        SweepKind::Log if start_hz != end_hz && secs > 0.0 => {
+            assert!(start_hz > 0.0 && end_hz > 0.0, "a logarithmic sweep can't start or end at 0 Hz");
+            // The phase is the integral of the frequency, which grows exponentially
+            let rate = (end_hz / start_hz).ln() / secs;
+            tone(spec, duration, amplitude_dbfs, |t| start_hz * ((rate * t).exp() - 1.0) / rate, |phase| (phase * TAU).sin())
+        }
+        _ => {
+            let slope = if secs > 0.0 { (end_hz - start_hz) / secs } else { 0.0 };
+            tone(spec, duration, amplitude_dbfs, |t| start_hz * t + slope * t * t / 2.0, |phase| (phase * TAU).sin())
+        }
+    }
+}
+
+/// Generates the sum of sine waves of the frequencies in `freqs_hz`, lasting `duration` rounded
+/// to whole frames. The sine waves have the same level, and together they never exceed the peak
+/// level set by `amplitude_dbfs`. Without frequencies the signal is silent.
+///
+/// # Errors
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// ## Panics
+/// If a frequency is negative or not finite.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// let spec = WaveSpec::new(AudioFormat::IeeeFloat, 1, 8000, 64).unwrap();
+/// let chord = generate::multi_tone(spec, &[1000.0, 2000.0], Duration::from_millis(1), 0.0).unwrap();
+/// let expected: Vec<f64> = (0..8)
+///     .map(|i| i as f64 * std::f64::consts::TAU / 8.0)
+///     .map(|x| (x.sin() + (2.0 * x).sin()) / 2.0)
+///     .collect();
+/// let samples = chord.samples_as::<f64>().unwrap();
+/// assert!(samples.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-9));
+/// ```
+pub fn multi_tone(spec: WaveSpec, freqs_hz: &[f64], duration: Duration, amplitude_dbfs: f32) -> Result<WaveFile, WaveError> {
+    freqs_hz.iter().copied().for_each(check_frequency);
+    let scale = 1.0 / freqs_hz.len().max(1) as f64;
+    signal(spec, duration, amplitude_dbfs, |frame, values| {
+        let t = frame as f64 / spec.sample_rate as f64;
+        let sum: f64 = freqs_hz.iter().map(|freq| ((freq * t).rem_euclid(1.0) * TAU).sin()).sum();
+        values.fill(sum * scale);
+    })
+}
+
+/// Generates the dual tones of the DTMF (touch tone) `digits`, each lasting `tone_ms`
+/// milliseconds, separated by `gap_ms` milliseconds of silence.
+///
+/// The digits are `0` to `9`, `*`, `#` and `A` to `D` (or `a` to `d`). Every tone is the sum of
+/// its low and high frequency at the same level, peaking at -3 dBFS, and fades in and out over
+/// 2 ms so it starts and stops without a click. Telephony systems usually expect
+/// [WaveSpec::telephony_pcm], mono 16-bit PCM at 8 kHz, but any spec can be used.
+///
+/// # Errors
+/// - [WaveError::InvalidDtmfDigit] if `digits` contains another character
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let dialed = generate::dtmf(WaveSpec::telephony_pcm(), "555-0123", 100, 50);
+/// assert!(matches!(dialed, Err(WaveError::InvalidDtmfDigit('-'))));
+///
+/// let dialed = generate::dtmf(WaveSpec::telephony_pcm(), "5550123#", 100, 50).unwrap();
+/// // 8 tones and 7 gaps
+/// assert_eq!(dialed.num_frames(), 8 * 800 + 7 * 400);
+///
+/// let samples = dialed.samples_i16().unwrap();
+/// assert_eq!(samples[0], 0);
+/// assert!(samples[800..1200].iter().all(|&s| s == 0));
+/// assert!(samples.iter().all(|s| s.abs() <= 23198));
+/// ```
+pub fn dtmf(spec: WaveSpec, digits: &str, tone_ms: u32, gap_ms: u32) -> Result<WaveFile, WaveError> {
+    let tones = digits
+        .chars()
+        .map(|digit| dtmf_frequencies(digit).ok_or(WaveError::InvalidDtmfDigit(digit)))
+        .collect::<Result<Vec<_>, _>>()?;
+    spec.validate()?;
+    let tone_len = Duration::from_millis(tone_ms as u64);
+    let fade = Duration::from_millis(DTMF_FADE_MS).min(tone_len / 2);
+    let mut wave = WaveFile::with_spec(spec);
+    for (i, (low, high)) in tones.into_iter().enumerate() {
+        if i > 0 {
+            wave.append_silence(Duration::from_millis(gap_ms as u64));
+        }
+        let mut tone = multi_tone(spec, &[low, high], tone_len, DTMF_LEVEL_DBFS)?;
+        tone.fade_in(fade, FadeCurve::Linear)?;
+        tone.fade_out(fade, FadeCurve::Linear)?;
+        wave.append(&tone)?;
+    }
+    Ok(wave)
+}
+
+/// The peak level of a DTMF tone in dBFS
+const DTMF_LEVEL_DBFS: f32 = -3.0;
+/// The length of the fades at the start and end of a DTMF tone
+const DTMF_FADE_MS: u64 = 2;
+
+/// Returns the low and high frequency of a DTMF digit
+fn dtmf_frequencies(digit: char) -> Option<(f64, f64)> {
+    const LOW: [f64; 4] = [697.0, 770.0, 852.0, 941.0];
+    const HIGH: [f64; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+    let (row, column) = match digit.to_ascii_uppercase() {
+        '1' => (0, 0),
+        '2' => (0, 1),
+        '3' => (0, 2),
+        'A' => (0, 3),
+        '4' => (1, 0),
+        '5' => (1, 1),
+        '6' => (1, 2),
+        'B' => (1, 3),
+        '7' => (2, 0),
+        '8' => (2, 1),
+        '9' => (2, 2),
+        'C' => (2, 3),
+        '*' => (3, 0),
+        '0' => (3, 1),
+        '#' => (3, 2),
+        'D' => (3, 3),
+        _ => return None,
+    };
+    Some((LOW[row], HIGH[column]))
+}
+
+/// Generates white noise: random samples with the same energy at every frequency, lasting
+/// `duration` rounded to whole frames. The samples are spread evenly up to the peak level set by
+/// `amplitude_dbfs`, and every channel gets noise of its own.
+///
+/// With a `seed` the noise is the same every time, on every platform, so it can be used in test
+/// fixtures. Without one a random seed is picked.
+///
+/// # Errors
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// let spec = WaveSpec::new(AudioFormat::PCM, 2, 8000, 16).unwrap();
+/// let noise = generate::white_noise(spec, Duration::from_secs(1), -6.0, Some(42)).unwrap();
+/// assert_eq!(noise, generate::white_noise(spec, Duration::from_secs(1), -6.0, Some(42)).unwrap());
+/// assert_ne!(noise, generate::white_noise(spec, Duration::from_secs(1), -6.0, Some(43)).unwrap());
+///
+/// let samples = noise.samples_i16().unwrap();
+/// assert!(samples.iter().all(|s| s.abs() <= 16423));
+/// assert_ne!(samples[0], samples[1]);
+/// ```
+pub fn white_noise(spec: WaveSpec, duration: Duration, amplitude_dbfs: f32, seed: Option<u64>) -> Result<WaveFile, WaveError> {
+    let mut random = Random::new(seed);
+    signal(spec, duration, amplitude_dbfs, |_, values| values.fill_with(|| random.next()))
+}
+
+/// Generates pink noise: random samples with the same energy in every octave, so the level falls
+/// by 3 dB per octave, lasting `duration` rounded to whole frames. Every channel gets noise of its
+/// own, which never exceeds the peak level set by `amplitude_dbfs`.
+///
+/// The noise is generated with the Voss-McCartney algorithm: the sum of random values that
+/// change at halving rates, which follows the slope from the highest frequency down to below
+/// 1 Hz at 44.1 kHz. Like [white_noise], a `seed` makes it the same every time, on every
+/// platform.
+///
+/// # Errors
+/// - the errors of [WaveSpec::new] if `spec` isn't valid
+/// - [WaveError::CapacityOverflow] if the signal doesn't fit in memory
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// let spec = WaveSpec::new(AudioFormat::IeeeFloat, 1, 48000, 64).unwrap();
+/// let pink = generate::pink_noise(spec, Duration::from_secs(10), 0.0, Some(7)).unwrap().samples_as::<f64>().unwrap();
+/// let white = generate::white_noise(spec, Duration::from_secs(10), 0.0, Some(7)).unwrap().samples_as::<f64>().unwrap();
+///
+/// // The energy below a frequency, measured by averaging blocks of samples. The energy in an
+/// // octave is the difference between the energy below its top and below its bottom.
+/// let energy_below = |samples: &[f64], block: usize| {
+///     let averages = samples.chunks_exact(block).map(|c| c.iter().sum::<f64>() / block as f64);
+///     averages.map(|a| a * a).sum::<f64>() / (samples.len() / block) as f64
+/// };
+/// let octave = |samples: &[f64], block: usize| energy_below(samples, block) - energy_below(samples, 2 * block);
+///
+/// // Every octave of pink noise has about the same energy, white noise halves every octave down
+/// for block in [4, 8, 16, 32, 64] {
+///     let pink_ratio = octave(&pink, 2 * block) / octave(&pink, block);
+///     let white_ratio = octave(&white, 2 * block) / octave(&white, block);
+///     assert!((0.8..1.25).contains(&pink_ratio), "{pink_ratio}");
+///     assert!((0.4..0.6).contains(&white_ratio), "{white_ratio}");
+/// }
+/// ```
+pub fn pink_noise(spec: WaveSpec, duration: Duration, amplitude_dbfs: f32, seed: Option<u64>) -> Result<WaveFile, WaveError> {
+    let mut random = Random::new(seed);
+    let mut rows = vec![[0.0; PINK_ROWS]; spec.channels as usize];
+    for channel in &mut rows {
+        channel.fill_with(|| random.next());
+    }
+    signal(spec, duration, amplitude_dbfs, |frame, values| {
+        // Row `n` changes every `2^n` frames, at a frame of its own
+        let row = (frame + 1).trailing_zeros() as usize;
+        for (value, rows) in values.iter_mut().zip(&mut rows) {
+            if let Some(row) = rows.get_mut(row) {
+                *row = random.next();
+            }
+            // White noise on top fills in the highest octave
+            *value = (rows.iter().sum::<f64>() + random.next()) / (PINK_ROWS + 1) as f64;
+        }
+    })
+}
+
+/// The amount of random values summed for pink noise, each one covering an octave
+const PINK_ROWS: usize = 16;
+
+/// A seedable random number generator (SplitMix64), so the same seed gives the same noise on
+/// every platform
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    /// Creates a generator from `seed`, or from a random seed
+    fn new(seed: Option<u64>) -> Random {
+        let state = seed.unwrap_or_else(|| RandomState::new().build_hasher().finish());
+        Random { state }
+    }
+
+    /// Returns the next value, evenly spread in `[-1, 1)`
+    fn next(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+}
+
+/// Panics if `freq_hz` can't be the frequency of a signal
+fn check_frequency(freq_hz: f64) {
+    assert!(freq_hz.is_finite() && freq_hz >= 0.0, "the frequency must be finite and not negative");
+}
+
+/// Generates a signal of `shape`, given the phase within a period in `[0, 1)`, where `phase`
+/// returns the amount of periods since the start of the signal at a time in seconds
+fn tone(
+    spec: WaveSpec,
+    duration: Duration,
+    amplitude_dbfs: f32,
+    phase: impl Fn(f64) -> f64,
+    shape: impl Fn(f64) -> f64,
+) -> Result<WaveFile, WaveError> {
+    signal(spec, duration, amplitude_dbfs, |frame, values| {
+        values.fill(shape(phase(frame as f64 / spec.sample_rate as f64).rem_euclid(1.0)));
+    })
+}
+
+/// Generates a signal of `duration` where `next` fills in the values of every channel of a frame,
+/// given its index, in `-1.0..=1.0`
+fn signal(
+    spec: WaveSpec,
+    duration: Duration,
+    amplitude_dbfs: f32,
+    mut next: impl FnMut(u64, &mut [f64]),
+) -> Result<WaveFile, WaveError> {
+    let frames = WaveFile::with_spec(spec).frames_for_duration(duration);
+    let mut wave = WaveFile::with_capacity(spec, frames)?;
+    let amplitude = 10f64.powf(amplitude_dbfs as f64 / 20.0);
+    let len = frames as usize * wave.block_align as usize;
+    let mut values = vec![0.0; spec.channels as usize];
+    wave.audio_byte_data.extend_with(len, |data| {
+        for frame in 0..frames {
+            next(frame, &mut values);
+            for value in &values {
+                sample::encode(value * amplitude, spec.format, spec.bits_per_sample, data);
+            }
+        }
+    });
+    Ok(wave)
+}
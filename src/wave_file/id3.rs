@@ -0,0 +1,12 @@
+//! Skipping of ID3 tags around compressed streams.
+
+/// Skips an ID3v2 tag in front of the stream, which some taggers write
+pub(crate) fn skip_id3v2(bytes: &[u8]) -> &[u8] {
+    if bytes.len() < 10 || &bytes[0..3] != b"ID3" {
+        return bytes;
+    }
+    // A syncsafe integer: 7 bits per byte
+    let size = bytes[6..10].iter().fold(0usize, |size, &byte| (size << 7) | (byte & 0x7F) as usize);
+    let footer = if bytes[5] & 0x10 != 0 { 10 } else { 0 };
+    bytes.get(10 + size + footer..).unwrap_or(&[])
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+
+use super::chunk::{self, Chunks};
+use super::WaveFile;
+
+/// Metadata stored in the `LIST`/`INFO` chunk of a wave file, shown by most players.
+///
+/// Fields that are `None` are not written. INFO entries other than the named fields are kept in
+/// `other` with their raw payload, so reading and writing a file doesn't drop them.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+/// wave.add_samples(&[128u8; 3]).unwrap();
+/// wave.info_mut().title = Some(String::from("Beep"));
+/// wave.info_mut().software = Some(String::from("jaudio"));
+/// wave.info_mut().other.insert(*b"IGNR", b"Test\0".to_vec());
+///
+/// let bytes = wave.to_bytes();
+/// assert_eq!(bytes.len() % 2, 0);
+/// assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+///
+/// let parsed = WaveFile::from_bytes(&bytes).unwrap();
+/// assert_eq!(parsed.info(), wave.info());
+/// assert_eq!(parsed.info().title.as_deref(), Some("Beep"));
+/// assert_eq!(parsed.info().artist, None);
+/// assert_eq!(parsed.samples_as::<u8>().unwrap(), [128; 3]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Info {
+    /// The title of the audio (`INAM`)
+    pub title: Option<String>,
+    /// The artist (`IART`)
+    pub artist: Option<String>,
+    /// The date the audio was created, preferably formatted like `2008-12-10` (`ICRD`)
+    pub creation_date: Option<String>,
+    /// Comments about the audio (`ICMT`)
+    pub comment: Option<String>,
+    /// The software that created the file (`ISFT`)
+    pub software: Option<String>,
+    /// Other INFO entries by their FourCC, with their payload as stored (including the null
+    /// terminator of text)
+    #[cfg_attr(feature = "serde", serde(with = "other"))]
+    pub other: BTreeMap<[u8; 4], Vec<u8>>,
+}
+
+/// Serializes the other entries as a sequence of FourCC and payload pairs, as formats like JSON
+/// only have maps with text keys
+#[cfg(feature = "serde")]
+mod other {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(other: &BTreeMap<[u8; 4], Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(other)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<[u8; 4], Vec<u8>>, D::Error> {
+        Ok(Vec::<([u8; 4], Vec<u8>)>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+impl Info {
+    /// Returns whether the INFO chunk has no entries at all
+    pub fn is_empty(&self) -> bool {
+        self.fields().all(|(_, value)| value.is_none()) && self.other.is_empty()
+    }
+
+    /// The named fields with their FourCC
+    fn fields(&self) -> impl Iterator<Item = (&'static [u8; 4], &Option<String>)> {
+        [
+            (b"INAM", &self.title),
+            (b"IART", &self.artist),
+            (b"ICRD", &self.creation_date),
+            (b"ICMT", &self.comment),
+            (b"ISFT", &self.software),
+        ]
+        .into_iter()
+    }
+
+    /// Parses the sub-chunks of a `LIST` chunk of type `INFO` (without the type)
+    pub(crate) fn parse(data: &[u8]) -> Info {
+        let mut info = Info::default();
+        // Entries after a truncated one are lost, the earlier ones are kept
+        for entry in Chunks::within(data).map_while(Result::ok) {
+            let text = || {
+                let end = entry.data.iter().position(|&b| b == 0).unwrap_or(entry.data.len());
+                Some(String::from_utf8_lossy(&entry.data[..end]).into_owned())
+            };
+            match &entry.id {
+                b"INAM" => info.title = text(),
+                b"IART" => info.artist = text(),
+                b"ICRD" => info.creation_date = text(),
+                b"ICMT" => info.comment = text(),
+                b"ISFT" => info.software = text(),
+                id => {
+                    info.other.insert(*id, entry.data.to_vec());
+                }
+            }
+        }
+        info
+    }
+
+    /// Appends the `LIST`/`INFO` chunk to `out`, if there are any entries
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        if self.is_empty() {
+            return;
+        }
+        let mut list = b"INFO".to_vec();
+        for (id, value) in self.fields() {
+            if let Some(value) = value {
+                // Text is null terminated
+                let mut text = value.as_bytes().to_vec();
+                text.push(0);
+                chunk::write_chunk(&mut list, id, &text);
+            }
+        }
+        for (id, value) in &self.other {
+            chunk::write_chunk(&mut list, id, value);
+        }
+        chunk::write_chunk(out, b"LIST", &list);
+    }
+}
+
+// Metadata
+impl WaveFile {
+    /// Returns the metadata of the `LIST`/`INFO` chunk
+    pub fn info(&self) -> &Info {
+        &self.info
+    }
+
+    /// Returns the metadata of the `LIST`/`INFO` chunk for editing
+    pub fn info_mut(&mut self) -> &mut Info {
+        &mut self.info
+    }
+
+    /// Replaces the metadata of the `LIST`/`INFO` chunk
+    pub fn set_info(&mut self, info: Info) {
+        self.info = info;
+    }
+}
@@ -0,0 +1,209 @@
+//! Dumping the raw header fields of a wave file, for diagnosing malformed files.
+
+use super::{rf64, FourCC, WaveError, WaveFile};
+
+/// The position and declared size of a chunk in a wave file, see [WaveInfo].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// The FourCC identifier of the chunk, e.g. `*b"fmt "`
+    pub id: FourCC,
+    /// The offset of the chunk header from the start of the file
+    pub offset: u64,
+    /// The size of the payload as declared in the chunk header, which may be more than the file
+    /// contains. For the data chunk of an RF64 file this is the size from the `ds64` chunk.
+    pub size: u64,
+}
+
+/// The header fields of a wave file as they are stored, without any of the checks done when
+/// parsing a [WaveFile], see [WaveFile::parse_info].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaveInfo {
+    /// The magic the file starts with: `RIFF`, `RF64` or `BW64`
+    pub magic: FourCC,
+    /// The declared size of the RIFF chunk, from the `ds64` chunk for RF64 files
+    pub riff_size: u64,
+    /// The size of the parsed bytes
+    pub file_size: u64,
+    /// The format code of the fmt chunk, e.g. 1 for PCM or `0xFFFE` for `WAVE_FORMAT_EXTENSIBLE`
+    pub format_code: u16,
+    /// The amount of channels
+    pub channels: u16,
+    /// The sample rate in Hz
+    pub sample_rate: u32,
+    /// The byte rate field, which should be the sample rate times the block align
+    pub byte_rate: u32,
+    /// The block align field, which should be the amount of channels times the bytes per sample
+    pub block_align: u16,
+    /// The amount of bits per sample
+    pub bits_per_sample: u16,
+    /// The size of the extension of the fmt chunk (`cbSize`), if the fmt chunk is long enough to
+    /// have one
+    pub cb_size: Option<u16>,
+    /// The valid bits per sample of a `WAVE_FORMAT_EXTENSIBLE` fmt chunk
+    pub valid_bits_per_sample: Option<u16>,
+    /// The channel mask of a `WAVE_FORMAT_EXTENSIBLE` fmt chunk
+    pub channel_mask: Option<u32>,
+    /// The format code of the sub format GUID of a `WAVE_FORMAT_EXTENSIBLE` fmt chunk
+    pub sub_format: Option<u16>,
+    /// The amount of frames in the fact chunk, if there is one
+    pub fact_frames: Option<u32>,
+    /// The declared size of the data chunk, if there is one
+    pub data_size: Option<u64>,
+    /// Every chunk in the order of the file, including the fmt and data chunks
+    pub chunks: Vec<ChunkInfo>,
+}
+
+impl WaveInfo {
+    /// Returns a description of every field that disagrees with the others or with the file:
+    /// a byte rate or block align that doesn't match the format, a RIFF size that doesn't match
+    /// the file size, chunks that extend past the end of the file and a missing data chunk.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut bytes = WaveFile::new(AudioFormat::PCM, 2, 44100, 16).to_bytes();
+    /// assert!(WaveFile::parse_info(&bytes).unwrap().inconsistencies().is_empty());
+    ///
+    /// // Set the byte rate to that of a mono file
+    /// bytes[28..32].copy_from_slice(&(44100u32 * 2).to_le_bytes());
+    /// let info = WaveFile::parse_info(&bytes).unwrap();
+    /// assert_eq!(info.inconsistencies(), ["the byte rate 88200 doesn't match the sample rate times the block align, 176400"]);
+    /// ```
+    pub fn inconsistencies(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let block_align = self.channels as u64 * (self.bits_per_sample as u64).div_ceil(8);
+        if self.block_align as u64 != block_align {
+            problems.push(format!(
+                "the block align {} doesn't match the amount of channels times the bytes per sample, {}",
+                self.block_align, block_align
+            ));
+        }
+        let byte_rate = self.sample_rate as u64 * self.block_align as u64;
+        if self.byte_rate as u64 != byte_rate {
+            problems.push(format!(
+                "the byte rate {} doesn't match the sample rate times the block align, {}",
+                self.byte_rate, byte_rate
+            ));
+        }
+        if self.riff_size.saturating_add(8) != self.file_size {
+            problems.push(format!(
+                "the RIFF size {} doesn't match the file size of {} bytes",
+                self.riff_size, self.file_size
+            ));
+        }
+        for chunk in &self.chunks {
+            if (chunk.offset + 8).saturating_add(chunk.size) > self.file_size {
+                problems.push(format!(
+                    "the `{}` chunk at offset {} extends past the end of the file",
+                    String::from_utf8_lossy(&chunk.id), chunk.offset
+                ));
+            }
+        }
+        if self.data_size.is_none() {
+            problems.push("there is no data chunk".to_string());
+        }
+        problems
+    }
+}
+
+// Inspecting
+impl WaveFile {
+    /// Parses the header fields and chunk list of a RIFF or RF64 wave file, as they are stored.
+    ///
+    /// Unlike [from_bytes](WaveFile::from_bytes), no fields are checked against each other and
+    /// unknown formats are accepted, so this also works on files that can't be read, see
+    /// [WaveInfo::inconsistencies]. The chunks are walked until the end of `bytes` or until a
+    /// chunk extends past it. The audio data is not copied.
+    ///
+    /// # Errors
+    /// - [WaveError::NotRiff] if the bytes don't start with the `RIFF`/`WAVE` or `RF64`/`WAVE`
+    ///   magic
+    /// - [WaveError::MissingChunk] if there is no fmt chunk
+    /// - [WaveError::Truncated] if the fmt chunk is shorter than 16 bytes
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_samples_i16(&[1, 2, 3]).unwrap();
+    /// let bytes = wave.to_bytes();
+    ///
+    /// let info = WaveFile::parse_info(&bytes).unwrap();
+    /// assert_eq!(info.format_code, 1);
+    /// assert_eq!((info.channels, info.sample_rate, info.bits_per_sample), (1, 8000, 16));
+    /// assert_eq!(info.data_size, Some(6));
+    /// assert_eq!(info.chunks[0], ChunkInfo { id: *b"fmt ", offset: 12, size: 16 });
+    /// assert_eq!(info.chunks.last().unwrap().id, *b"data");
+    /// ```
+    pub fn parse_info(bytes: &[u8]) -> Result<WaveInfo, WaveError> {
+        if bytes.len() < 12 || &bytes[8..12] != b"WAVE" {
+            return Err(WaveError::NotRiff);
+        }
+        let magic: FourCC = bytes[0..4].try_into().unwrap();
+        let is_rf64 = rf64::is_rf64_magic(&magic);
+        if !is_rf64 && &magic != b"RIFF" {
+            return Err(WaveError::NotRiff);
+        }
+        let mut riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as u64;
+        let mut ds64_data_size = None;
+        let mut fmt = None;
+        let mut fact_frames = None;
+        let mut data_size = None;
+        let mut chunks = Vec::new();
+
+        let mut pos = 12u64;
+        while pos.saturating_add(8) <= bytes.len() as u64 {
+            let start = pos as usize;
+            let id: FourCC = bytes[start..start + 4].try_into().unwrap();
+            let size = u32::from_le_bytes(bytes[start + 4..start + 8].try_into().unwrap());
+            let size = match ds64_data_size {
+                Some(data_size) if &id == b"data" && size == u32::MAX => data_size,
+                _ => size as u64,
+            };
+            let end = (pos + 8).saturating_add(size).min(bytes.len() as u64) as usize;
+            let payload = &bytes[start + 8..end];
+            match &id {
+                b"ds64" if is_rf64 => {
+                    if let Some(ds64) = rf64::Ds64::parse(payload) {
+                        riff_size = ds64.riff_size;
+                        ds64_data_size = Some(ds64.data_size);
+                    }
+                }
+                b"fmt " if fmt.is_none() => fmt = Some(payload),
+                b"fact" if payload.len() >= 4 => fact_frames = Some(u32::from_le_bytes(payload[0..4].try_into().unwrap())),
+                b"data" if data_size.is_none() => data_size = Some(size),
+                _ => {}
+            }
+            chunks.push(ChunkInfo { id, offset: pos, size });
+            pos = (pos + 8).saturating_add(size).saturating_add(size % 2);
+        }
+
+        let fmt = fmt.ok_or(WaveError::MissingChunk("fmt "))?;
+        if fmt.len() < 16 {
+            return Err(WaveError::Truncated);
+        }
+        let u16_at = |i: usize| fmt.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+        let u32_at = |i: usize| fmt.get(i..i + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+        let format_code = u16_at(0).unwrap();
+        // The fields of WAVE_FORMAT_EXTENSIBLE, if present
+        let extensible = |field: Option<u16>| field.filter(|_| format_code == 0xFFFE);
+        Ok(WaveInfo {
+            magic,
+            riff_size,
+            file_size: bytes.len() as u64,
+            format_code,
+            channels: u16_at(2).unwrap(),
+            sample_rate: u32_at(4).unwrap(),
+            byte_rate: u32_at(8).unwrap(),
+            block_align: u16_at(12).unwrap(),
+            bits_per_sample: u16_at(14).unwrap(),
+            cb_size: u16_at(16),
+            valid_bits_per_sample: extensible(u16_at(18)),
+            channel_mask: u32_at(20).filter(|_| format_code == 0xFFFE),
+            sub_format: extensible(u16_at(24)),
+            fact_frames,
+            data_size,
+            chunks,
+        })
+    }
+}
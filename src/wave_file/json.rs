@@ -0,0 +1,219 @@
+use super::{AcidInfo, BroadcastExt, CuePoint, Info, LoopType, SamplerInfo, WaveFile};
+
+// JSON
+impl WaveFile {
+    /// Returns the format and metadata of the file as a JSON object, without the audio data, for
+    /// cataloging files.
+    ///
+    /// The object has the spec (`format`, `channels`, `sample_rate`, `bits_per_sample`),
+    /// `valid_bits_per_sample`, `extensible`, `channel_mask`, `num_frames`, `duration_secs`,
+    /// `data_len`, and the metadata: `info`, `broadcast_ext`, `cue_points`, `sampler_info` and
+    /// `acid_info`. Metadata the file doesn't have is `null`, or empty for `info` and
+    /// `cue_points`. The fields are named like the fields of the structs they come from, enums
+    /// are written as the name of their variant, and bytes as arrays of numbers. The `other` INFO
+    /// entries are an array of FourCC and payload pairs, like the `serde` feature writes them.
+    /// The JSON is written on one line, with the fields always in the same order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_samples_i16(&[0; 4000]).unwrap();
+    /// wave.info_mut().title = Some("\"Hello\"".to_string());
+    /// wave.info_mut().other.insert(*b"IGNR", b"Pop\0".to_vec());
+    /// wave.cue_points_mut().push(CuePoint { id: 1, position_frames: 100, label: None });
+    ///
+    /// let json = wave.metadata_json();
+    /// assert!(json.starts_with(r#"{"format":"PCM","channels":1,"sample_rate":8000,"bits_per_sample":16,"#));
+    /// assert!(json.contains(r#""num_frames":4000,"duration_secs":0.5,"data_len":8000,"#));
+    /// assert!(json.contains(r#""info":{"title":"\"Hello\"","artist":null,"#));
+    /// assert!(json.contains(r#""other":[[[73,71,78,82],[80,111,112,0]]]}"#));
+    /// assert!(json.contains(r#""cue_points":[{"id":1,"position_frames":100,"label":null}]"#));
+    /// assert!(json.ends_with(r#""sampler_info":null,"acid_info":null}"#));
+    /// ```
+    ///
+    /// With the `serde` feature, [WaveSpec](super::WaveSpec), [Info], [CuePoint], [SamplerInfo]
+    /// and [BroadcastExt] implement `Serialize` and `Deserialize`, to write them in any format
+    /// serde supports and read them back:
+    /// ```rust
+    /// # #[cfg(feature = "serde")] {
+    /// # use jaudio::wave_file::*;
+    /// let mut info = Info { title: Some("Take 3".to_string()), ..Info::default() };
+    /// info.other.insert(*b"IGNR", b"Speech\0".to_vec());
+    /// let sampler = SamplerInfo {
+    ///     loops: vec![LoopRegion { id: 1, loop_type: LoopType::Other(7), start_frame: 10, end_frame: 99, fraction: 0, play_count: 0 }],
+    ///     ..SamplerInfo::default()
+    /// };
+    /// let bext = BroadcastExt { umid: [7; 64], ..BroadcastExt::default() };
+    /// let metadata = (
+    ///     WaveSpec::cd_quality(),
+    ///     info,
+    ///     vec![CuePoint { id: 1, position_frames: 100, label: Some("Start".to_string()) }],
+    ///     sampler,
+    ///     bext,
+    /// );
+    ///
+    /// let json = serde_json::to_string(&metadata).unwrap();
+    /// assert!(json.starts_with(r#"[{"format":"PCM","channels":2,"sample_rate":44100,"bits_per_sample":16},"#));
+    /// let parsed: (WaveSpec, Info, Vec<CuePoint>, SamplerInfo, BroadcastExt) = serde_json::from_str(&json).unwrap();
+    /// assert_eq!(parsed, metadata);
+    ///
+    /// // The metadata JSON writes the same representation
+    /// let mut wave = WaveFile::new_cd_quality();
+    /// *wave.info_mut() = metadata.1;
+    /// let info = serde_json::to_string(wave.info()).unwrap();
+    /// assert!(wave.metadata_json().contains(&format!(r#""info":{},"#, info)));
+    /// # }
+    /// ```
+    pub fn metadata_json(&self) -> String {
+        object(&[
+            ("format", string(&format!("{:?}", self.audio_format()))),
+            ("channels", self.num_channels.to_string()),
+            ("sample_rate", self.sample_rate.to_string()),
+            ("bits_per_sample", self.bits_per_sample.to_string()),
+            ("valid_bits_per_sample", self.valid_bits_per_sample().to_string()),
+            ("extensible", self.is_extensible().to_string()),
+            ("channel_mask", self.channel_mask().to_string()),
+            ("num_frames", self.num_frames().to_string()),
+            ("duration_secs", number(self.duration_secs_f64())),
+            ("data_len", self.data_len().to_string()),
+            ("info", info(self.info())),
+            ("broadcast_ext", self.broadcast_ext().map_or_else(null, broadcast_ext)),
+            ("cue_points", array(self.cue_points().iter().map(cue_point))),
+            ("sampler_info", self.sampler_info().map_or_else(null, sampler_info)),
+            ("acid_info", self.acid_info().map_or_else(null, acid_info)),
+        ])
+    }
+}
+
+fn info(info: &Info) -> String {
+    // FourCCs aren't always text, so the entries are pairs like serde writes them instead of an
+    // object keyed by the FourCC
+    let other = info.other.iter().map(|(id, data)| array([bytes(id), bytes(data)].into_iter()));
+    object(&[
+        ("title", optional_string(&info.title)),
+        ("artist", optional_string(&info.artist)),
+        ("creation_date", optional_string(&info.creation_date)),
+        ("comment", optional_string(&info.comment)),
+        ("software", optional_string(&info.software)),
+        ("other", array(other)),
+    ])
+}
+
+fn broadcast_ext(bext: &BroadcastExt) -> String {
+    object(&[
+        ("description", string(&bext.description)),
+        ("originator", string(&bext.originator)),
+        ("originator_reference", string(&bext.originator_reference)),
+        ("origination_date", string(&bext.origination_date)),
+        ("origination_time", string(&bext.origination_time)),
+        ("time_reference", bext.time_reference.to_string()),
+        ("version", bext.version.to_string()),
+        ("umid", bytes(&bext.umid)),
+        ("loudness_value", bext.loudness_value.to_string()),
+        ("loudness_range", bext.loudness_range.to_string()),
+        ("max_true_peak_level", bext.max_true_peak_level.to_string()),
+        ("max_momentary_loudness", bext.max_momentary_loudness.to_string()),
+        ("max_short_term_loudness", bext.max_short_term_loudness.to_string()),
+        ("coding_history", string(&bext.coding_history)),
+    ])
+}
+
+fn cue_point(point: &CuePoint) -> String {
+    object(&[
+        ("id", point.id.to_string()),
+        ("position_frames", point.position_frames.to_string()),
+        ("label", optional_string(&point.label)),
+    ])
+}
+
+fn sampler_info(sampler: &SamplerInfo) -> String {
+    let loops = sampler.loops.iter().map(|l| {
+        let loop_type = match l.loop_type {
+            LoopType::Other(value) => object(&[("Other", value.to_string())]),
+            loop_type => string(&format!("{:?}", loop_type)),
+        };
+        object(&[
+            ("id", l.id.to_string()),
+            ("loop_type", loop_type),
+            ("start_frame", l.start_frame.to_string()),
+            ("end_frame", l.end_frame.to_string()),
+            ("fraction", l.fraction.to_string()),
+            ("play_count", l.play_count.to_string()),
+        ])
+    });
+    object(&[
+        ("manufacturer", sampler.manufacturer.to_string()),
+        ("product", sampler.product.to_string()),
+        ("midi_unity_note", sampler.midi_unity_note.to_string()),
+        ("midi_pitch_fraction", sampler.midi_pitch_fraction.to_string()),
+        ("smpte_format", sampler.smpte_format.to_string()),
+        ("smpte_offset", sampler.smpte_offset.to_string()),
+        ("loops", array(loops)),
+        ("sampler_data", bytes(&sampler.sampler_data)),
+    ])
+}
+
+fn acid_info(acid: &AcidInfo) -> String {
+    object(&[
+        ("is_loop", acid.is_loop.to_string()),
+        ("root_note", acid.root_note.map_or_else(null, |note| note.to_string())),
+        ("stretch", acid.stretch.to_string()),
+        ("disk_based", acid.disk_based.to_string()),
+        ("num_beats", acid.num_beats.to_string()),
+        ("meter_denominator", acid.meter_denominator.to_string()),
+        ("meter_numerator", acid.meter_numerator.to_string()),
+        ("tempo", number(acid.tempo as f64)),
+    ])
+}
+
+/// A JSON object of the fields, whose values are already JSON
+fn object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields.iter().map(|(key, value)| format!("{}:{}", string(key), value)).collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// A JSON array of values that are already JSON
+fn array(values: impl Iterator<Item = String>) -> String {
+    format!("[{}]", values.collect::<Vec<_>>().join(","))
+}
+
+fn bytes(bytes: &[u8]) -> String {
+    array(bytes.iter().map(u8::to_string))
+}
+
+fn null() -> String {
+    "null".to_string()
+}
+
+/// A JSON number, `null` if it isn't finite
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        null()
+    }
+}
+
+fn optional_string(value: &Option<String>) -> String {
+    value.as_deref().map_or_else(null, string)
+}
+
+/// A JSON string, with quotes, backslashes and control characters escaped
+fn string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
@@ -0,0 +1,435 @@
+use std::ops::{ControlFlow, Range};
+use std::time::Duration;
+
+use super::progress::{self, Progress};
+use super::{parallel, sample, simd};
+use super::{AudioFormat, ClipPolicy, ProgressUpdate, WaveError, WaveFile};
+
+/// A run of consecutive samples of one channel at full scale, see [WaveFile::clipped_regions].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipRegion {
+    /// The channel the samples are in
+    pub channel: usize,
+    /// The frame of the first sample
+    pub start_frame: u64,
+    /// The amount of consecutive samples at full scale
+    pub len: u64,
+}
+
+/// Returns the linear gain of `db` decibels
+fn db_to_gain(db: f32) -> f64 {
+    10f64.powf(db as f64 / 20.0)
+}
+
+// Levels
+impl WaveFile {
+    /// Amplifies (or attenuates, for a negative `db`) all samples by `db` decibels.
+    ///
+    /// Samples that end up outside of the range `-1.0..=1.0` are handled by `clip`. Integer
+    /// samples always saturate at the limits of their bit depth instead of wrapping around, float
+    /// samples only exceed 1.0 with [ClipPolicy::Unchecked].
+    ///
+    /// # Errors
+    /// [WaveError::Clipping] if a sample clips and `clip` is [ClipPolicy::Error], in which case the
+    /// file is unchanged, or [WaveError::UnsupportedFormat] or
+    /// [WaveError::UnsupportedBitsPerSample] if the samples of the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1000, -1000, 20000, 0]).unwrap();
+    ///
+    /// wave.apply_gain_db(-6.0206, ClipPolicy::Error).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [500, -500, 10000, 0]);
+    ///
+    /// assert!(matches!(wave.apply_gain_db(12.0, ClipPolicy::Error), Err(WaveError::Clipping { frame: 1 })));
+    /// wave.apply_gain_db(12.0, ClipPolicy::Clip).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [1991, -1991, i16::MAX, 0]);
+    /// ```
+    ///
+    /// 16-bit samples are amplified with vector instructions when the CPU supports them, with the
+    /// same result as amplifying every sample on its own:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let all: Vec<i16> = (i16::MIN..=i16::MAX).collect();
+    /// for db in [-60.0, -6.0206, -0.5, 0.0, 0.1, 3.0, 24.0] {
+    ///     let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    ///     wave.add_samples_i16(&all).unwrap();
+    ///     wave.apply_gain_db(db, ClipPolicy::Clip).unwrap();
+    ///
+    ///     let gain = 10f64.powf(db as f64 / 20.0);
+    ///     let scalar: Vec<i16> = all.iter().map(|s| i16::from_f64((s.to_f64() * gain).clamp(-1.0, 1.0))).collect();
+    ///     assert_eq!(wave.samples_i16().unwrap(), scalar);
+    /// }
+    ///
+    /// // Samples after the last whole vector of 8 are amplified one by one, with the same result
+    /// for len in [1, 7, 9, 13, 65535] {
+    ///     let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    ///     wave.add_samples_i16(&all[..len]).unwrap();
+    ///     wave.apply_gain_db(-0.5, ClipPolicy::Clip).unwrap();
+    ///
+    ///     let gain = 10f64.powf(-0.5 / 20.0);
+    ///     let scalar: Vec<i16> = all[..len].iter().map(|s| i16::from_f64(s.to_f64() * gain)).collect();
+    ///     assert_eq!(wave.samples_i16().unwrap(), scalar);
+    /// }
+    /// ```
+    pub fn apply_gain_db(&mut self, db: f32, clip: ClipPolicy) -> Result<(), WaveError> {
+        self.apply_gain(db_to_gain(db), clip)
+    }
+
+    /// Amplifies the audio so its loudest sample reaches `target_peak_dbfs` decibels relative to
+    /// full scale (0.0 is the loudest sample that doesn't clip). Samples that would exceed full
+    /// scale with a positive target are clipped.
+    ///
+    /// A file that is completely silent is left unchanged.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 1, 44100, 32);
+    /// wave.add_samples_f32(&[0.1, -0.2, 0.05]).unwrap();
+    ///
+    /// wave.normalize(0.0).unwrap();
+    /// assert_eq!(wave.samples_f32().unwrap(), [0.5, -1.0, 0.25]);
+    ///
+    /// let mut silent = WaveFile::new(AudioFormat::PCM, 1, 44100, 24);
+    /// silent.add_samples(&[I24::default(); 4]).unwrap();
+    /// silent.normalize(-1.0).unwrap();
+    /// assert_eq!(silent.samples_as::<I24>().unwrap(), [I24::default(); 4]);
+    /// ```
+    pub fn normalize(&mut self, target_peak_dbfs: f32) -> Result<(), WaveError> {
+        self.normalize_with_progress(target_peak_dbfs, progress::ignore)
+    }
+
+    /// Normalizes the audio like [normalize](WaveFile::normalize), calling `progress` as the
+    /// frames are processed (see [ProgressUpdate]). The frames are processed twice, once to find
+    /// the peak and once to amplify them, so the total amount of frames reported is twice the
+    /// amount of frames of the file.
+    ///
+    /// The amplified audio data is built next to the audio data of the file, and only replaces
+    /// it when all frames are done.
+    ///
+    /// # Errors
+    /// - [WaveError::Cancelled] if `progress` cancels normalizing, the file is unchanged then
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples
+    ///   of the file can't be decoded
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::ops::ControlFlow;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// wave.add_samples_i16(&[1000, -2000, 500]).unwrap();
+    ///
+    /// let result = wave.normalize_with_progress(0.0, |_| ControlFlow::Break(()));
+    /// assert!(matches!(result, Err(WaveError::Cancelled)));
+    /// assert_eq!(wave.samples_i16().unwrap(), [1000, -2000, 500]);
+    ///
+    /// let mut total = 0;
+    /// wave.normalize_with_progress(0.0, |update| {
+    ///     total = update.total_frames;
+    ///     ControlFlow::Continue(())
+    /// })
+    /// .unwrap();
+    /// assert_eq!(total, 6);
+    /// assert_eq!(wave.samples_i16().unwrap(), [16384, i16::MIN, 8192]);
+    /// ```
+    pub fn normalize_with_progress(
+        &mut self,
+        target_peak_dbfs: f32,
+        progress: impl FnMut(ProgressUpdate) -> ControlFlow<()>,
+    ) -> Result<(), WaveError> {
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        sample::check_supported(format, bits)?;
+        let frames = self.num_frames();
+        let mut progress = Progress::new(2 * frames, progress);
+        let sample_len = (bits / 8) as usize;
+        let frame_len = self.block_align as usize;
+        // The samples of the frames in a range, for the parts of a round that are split over
+        // threads with the `parallel` feature
+        let samples_of = |frames: Range<u64>| {
+            self.audio_byte_data
+                .slices(frames.start as usize * frame_len..frames.end as usize * frame_len)
+                .flat_map(|slice| slice.chunks_exact(sample_len))
+        };
+        let mut peak = 0.0f64;
+        parallel::map_rounds(
+            0..frames,
+            progress::INTERVAL,
+            |_| (),
+            |_, part| samples_of(part).map(|bytes| sample::decode(bytes, format, bits).abs()).fold(0.0, f64::max),
+            |end, parts| {
+                peak = parts.into_iter().fold(peak, f64::max);
+                progress.update(end)
+            },
+        )?;
+        if peak == 0.0 {
+            return progress.finish();
+        }
+
+        let gain = db_to_gain(target_peak_dbfs) / peak;
+        let channels = self.num_channels.max(1) as u64;
+        let mut data = Vec::with_capacity(self.audio_byte_data.len());
+        parallel::map_rounds(
+            0..frames,
+            progress::INTERVAL,
+            |_| (),
+            |_, part| {
+                let mut out = Vec::with_capacity((part.end - part.start) as usize * frame_len);
+                for (i, bytes) in (0..).zip(samples_of(part.clone())) {
+                    let value = sample::decode(bytes, format, bits) * gain;
+                    sample::encode(ClipPolicy::Clip.apply(value, part.start + i / channels)?, format, bits, &mut out);
+                }
+                Ok::<_, WaveError>(out)
+            },
+            |end, parts| {
+                for part in parts {
+                    data.extend_from_slice(&part?);
+                }
+                progress.update(frames + end)
+            },
+        )?;
+        // A partial frame at the end is kept as it is
+        let whole = frames as usize * frame_len;
+        for tail in self.audio_byte_data.slices(whole..self.audio_byte_data.len()) {
+            data.extend_from_slice(tail);
+        }
+        progress.finish()?;
+        self.audio_byte_data.set(data);
+        Ok(())
+    }
+
+    /// Returns the peak level of every channel: the largest magnitude of its normalized samples,
+    /// where 1.0 is full scale. Channels of a file without audio data have a peak of 0.0.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 24);
+    /// wave.add_samples(&[I24::new(1 << 20).unwrap(), I24::MIN, I24::new(-(1 << 21)).unwrap(), I24::default()]).unwrap();
+    ///
+    /// assert_eq!(wave.peak().unwrap(), [0.25, 1.0]);
+    /// ```
+    pub fn peak(&self) -> Result<Vec<f64>, WaveError> {
+        let mut peaks = vec![0.0f64; self.num_channels.max(1) as usize];
+        self.for_each_sample(|_, channel, sample| peaks[channel] = peaks[channel].max(sample.abs()))?;
+        Ok(peaks)
+    }
+
+    /// Returns the root mean square level of every channel, with normalized samples where 1.0 is
+    /// full scale. Channels of a file without audio data have a level of 0.0.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 44100, 32);
+    /// wave.add_samples_f32(&[0.5, 1.0, -0.5, 0.0, 0.5, 0.0, -0.5, 0.0]).unwrap();
+    ///
+    /// assert_eq!(wave.rms().unwrap(), [0.5, 0.5]);
+    /// ```
+    pub fn rms(&self) -> Result<Vec<f64>, WaveError> {
+        let frames = self.num_frames().max(1) as f64;
+        let mut sums = vec![0.0; self.num_channels.max(1) as usize];
+        self.for_each_sample(|_, channel, sample| sums[channel] += sample * sample)?;
+        Ok(sums.into_iter().map(|sum| (sum / frames).sqrt()).collect())
+    }
+
+    /// Returns the root mean square level of every channel over consecutive windows of `window`
+    /// (rounded to whole frames, at least one). The last window may be shorter than the others.
+    ///
+    /// The result contains a series of levels for every channel.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 1000, 16);
+    /// wave.add_samples_i16(&[16384, -16384, 0, 0, 8192]).unwrap();
+    ///
+    /// assert_eq!(wave.rms_windowed(Duration::from_millis(2)).unwrap(), [[0.5, 0.0, 0.25]]);
+    /// ```
+    pub fn rms_windowed(&self, window: Duration) -> Result<Vec<Vec<f64>>, WaveError> {
+        let window = self.frames_for_duration(window).max(1);
+        let frames = self.num_frames();
+        let windows = frames.div_ceil(window) as usize;
+        let mut sums = vec![vec![0.0; windows]; self.num_channels.max(1) as usize];
+        self.for_each_sample(|frame, channel, sample| sums[channel][(frame / window) as usize] += sample * sample)?;
+        for channel in &mut sums {
+            for (i, sum) in channel.iter_mut().enumerate() {
+                let len = window.min(frames - i as u64 * window);
+                *sum = (*sum / len as f64).sqrt();
+            }
+        }
+        Ok(sums)
+    }
+
+    /// Returns the DC offset of every channel: the mean of its normalized samples. Channels of a
+    /// file without audio data have an offset of 0.0.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1024, 0, 3072, -4096]).unwrap();
+    ///
+    /// assert_eq!(wave.dc_offset().unwrap(), [0.0625, -0.0625]);
+    /// ```
+    pub fn dc_offset(&self) -> Result<Vec<f64>, WaveError> {
+        let frames = self.num_frames().max(1) as f64;
+        let mut sums = vec![0.0; self.num_channels.max(1) as usize];
+        self.for_each_sample(|_, channel, sample| sums[channel] += sample)?;
+        Ok(sums.into_iter().map(|sum| sum / frames).collect())
+    }
+
+    /// Subtracts the [DC offset](WaveFile::dc_offset) of every channel from its samples, so the
+    /// channels are centered around zero. Integer samples saturate at the limits of their bit
+    /// depth.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 8);
+    /// wave.add_samples(&[138u8, 148, 128, 158]).unwrap();
+    ///
+    /// wave.remove_dc_offset().unwrap();
+    /// assert_eq!(wave.samples_as::<u8>().unwrap(), [123, 133, 113, 143]);
+    /// assert_eq!(wave.dc_offset().unwrap(), [0.0]);
+    /// ```
+    pub fn remove_dc_offset(&mut self) -> Result<(), WaveError> {
+        let offsets = self.dc_offset()?;
+        self.map_samples(0..self.num_frames(), |_, channel, sample| sample - offsets[channel])
+    }
+
+    /// Returns the runs of at least `threshold_consecutive` consecutive samples at full scale,
+    /// ordered by the frame they end at. Integer samples are at full scale at the minimum or
+    /// maximum value of their bit depth, float samples with a magnitude of 1.0 or more.
+    ///
+    /// Requiring a few consecutive samples separates clipping from legitimate peaks. A
+    /// `threshold_consecutive` of 0 counts as 1.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[
+    ///     i16::MAX, 0,
+    ///     i16::MAX, i16::MIN,
+    ///     i16::MAX, 0,
+    ///     100, i16::MIN,
+    ///     100, i16::MIN,
+    /// ]).unwrap();
+    ///
+    /// assert_eq!(wave.clipped_regions(2).unwrap(), [
+    ///     ClipRegion { channel: 0, start_frame: 0, len: 3 },
+    ///     ClipRegion { channel: 1, start_frame: 3, len: 2 },
+    /// ]);
+    /// assert_eq!(wave.clipped_regions(3).unwrap().len(), 1);
+    /// assert!(wave.is_clipped(3).unwrap());
+    /// assert!(!wave.is_clipped(4).unwrap());
+    /// ```
+    pub fn clipped_regions(&self, threshold_consecutive: usize) -> Result<Vec<ClipRegion>, WaveError> {
+        let threshold = threshold_consecutive.max(1) as u64;
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        // The values of full scale integer samples, as decoded
+        let mut encoded = Vec::new();
+        let limits = if matches!(format, AudioFormat::IeeeFloat) {
+            None
+        } else {
+            sample::check_supported(format, bits)?;
+            let limit = |value: f64, encoded: &mut Vec<u8>| {
+                encoded.clear();
+                sample::encode(value, format, bits, encoded);
+                sample::decode(encoded, format, bits)
+            };
+            Some((limit(-1.0, &mut encoded), limit(1.0, &mut encoded)))
+        };
+        let at_full_scale = |sample: f64| match limits {
+            Some((min, max)) => sample <= min || sample >= max,
+            None => sample.abs() >= 1.0,
+        };
+
+        let mut regions = Vec::new();
+        let mut runs = vec![0u64; self.num_channels.max(1) as usize];
+        let end_run = |regions: &mut Vec<ClipRegion>, channel: usize, run: u64, end: u64| {
+            if run >= threshold {
+                regions.push(ClipRegion { channel, start_frame: end - run, len: run });
+            }
+        };
+        self.for_each_sample(|frame, channel, sample| {
+            if at_full_scale(sample) {
+                runs[channel] += 1;
+            } else {
+                end_run(&mut regions, channel, runs[channel], frame);
+                runs[channel] = 0;
+            }
+        })?;
+        let frames = self.num_frames();
+        for (channel, run) in runs.into_iter().enumerate() {
+            end_run(&mut regions, channel, run, frames);
+        }
+        Ok(regions)
+    }
+
+    /// Returns whether the audio contains a run of at least `threshold_consecutive` consecutive
+    /// samples at full scale, see [WaveFile::clipped_regions].
+    ///
+    /// # Errors
+    /// See [WaveFile::clipped_regions].
+    pub fn is_clipped(&self, threshold_consecutive: usize) -> Result<bool, WaveError> {
+        Ok(!self.clipped_regions(threshold_consecutive)?.is_empty())
+    }
+
+    /// Multiplies all samples by `gain`, applying `clip` to the results
+    fn apply_gain(&mut self, gain: f64, clip: ClipPolicy) -> Result<(), WaveError> {
+        if clip == ClipPolicy::Error {
+            let channels = self.num_channels.max(1) as usize;
+            for (i, sample) in self.samples_as::<f64>()?.into_iter().enumerate() {
+                clip.apply(sample * gain, (i / channels) as u64)?;
+            }
+        }
+        if (self.audio_format(), self.bits_per_sample) == (AudioFormat::PCM, 16) && gain.is_finite() {
+            // Clipping before quantizing to 16 bits doesn't change the result, so every policy
+            // amplifies the same way
+            let block_align = self.block_align.max(1) as usize;
+            for segment in self.audio_byte_data.segments_mut() {
+                // Only the last segment can end in a partial frame, which is left as it is
+                let whole = segment.len() - segment.len() % block_align;
+                simd::gain_i16(&mut segment[..whole], gain);
+            }
+            return Ok(());
+        }
+        self.map_samples(0..self.num_frames(), |frame, _, sample| {
+            let value = sample * gain;
+            clip.apply(value, frame).unwrap_or(value)
+        })
+    }
+}
@@ -0,0 +1,196 @@
+use super::chunk::{self, Chunk};
+use super::cue;
+use super::{AcidInfo, BroadcastExt, FourCC, Info, SamplerInfo, WaveFile};
+
+// Metadata chunks
+impl WaveFile {
+    /// Parses the metadata in `chunks`, the chunks of a file other than fmt, fact and data.
+    /// Chunks that aren't recognized are kept as extra chunks, metadata that can't be parsed is
+    /// skipped. The entries of `LIST`/`adtl` chunks that aren't labels of cue points are kept as
+    /// one extra `LIST`/`adtl` chunk.
+    pub(crate) fn read_metadata(&mut self, chunks: &[Chunk]) {
+        let mut cue = None;
+        let mut adtl = Vec::new();
+        for chunk in chunks {
+            match &chunk.id {
+                b"LIST" if chunk.data.starts_with(b"INFO") => self.info = Info::parse(&chunk.data[4..]),
+                b"LIST" if chunk.data.starts_with(b"adtl") => adtl.extend(&chunk.data[4..]),
+                b"cue " if cue.is_none() => cue = Some(chunk.data),
+                b"smpl" if self.sampler_info.is_none() => self.sampler_info = SamplerInfo::parse(chunk.data),
+                b"bext" if self.broadcast_ext.is_none() => self.broadcast_ext = BroadcastExt::parse(chunk.data),
+                b"acid" if self.acid_info.is_none() => self.acid_info = AcidInfo::parse(chunk.data),
+                _ => self.extra_chunks.push((chunk.id, chunk.data.to_vec())),
+            }
+        }
+        let (cue_points, unparsed) = cue::parse(cue.unwrap_or(&[]), &adtl);
+        self.cue_points = cue_points;
+        if !unparsed.is_empty() {
+            self.extra_chunks.push((*b"LIST", [&b"adtl"[..], &unparsed].concat()));
+        }
+    }
+
+    /// The chunks that are written after the data chunk
+    pub(crate) fn trailing_chunks(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if let Some(broadcast_ext) = &self.broadcast_ext {
+            broadcast_ext.write(&mut out);
+        }
+        self.info.write(&mut out);
+        cue::write(&self.cue_points, &mut out);
+        if let Some(sampler_info) = &self.sampler_info {
+            sampler_info.write(self.sample_rate, &mut out);
+        }
+        if let Some(acid_info) = &self.acid_info {
+            acid_info.write(&mut out);
+        }
+        for (id, data) in &self.extra_chunks {
+            chunk::write_chunk(&mut out, id, data);
+        }
+        out
+    }
+}
+
+// Extra chunks
+impl WaveFile {
+    /// Returns the chunks of the file that this crate doesn't interpret, like `iXML` or vendor
+    /// specific chunks, in the order they were read. They are written after the audio data, so
+    /// reading and writing a file keeps them. `JUNK` chunks, which only reserve space, are
+    /// dropped.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// wave.add_samples(&[128u8, 129]).unwrap();
+    /// wave.extra_chunks_mut().push((*b"axml", b"<ebuCore/>".to_vec()));
+    /// wave.extra_chunks_mut().push((*b"odd ", vec![1, 2, 3]));
+    ///
+    /// let mut parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+    /// assert_eq!(parsed.extra_chunks(), wave.extra_chunks());
+    /// assert_eq!(parsed.to_bytes(), wave.to_bytes());
+    ///
+    /// parsed.clear_extra_chunks();
+    /// assert_eq!(parsed.to_bytes().len(), 44 + 2);
+    /// ```
+    ///
+    /// Entries of a `LIST`/`adtl` chunk other than the labels of cue points, like notes, are kept
+    /// in an extra `LIST`/`adtl` chunk:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// wave.add_samples(&[128u8; 4]).unwrap();
+    /// let id = wave.add_cue_point(2, Some("Verse"));
+    ///
+    /// let mut adtl = b"adtlnote".to_vec();
+    /// adtl.extend(6u32.to_le_bytes());
+    /// adtl.extend(id.to_le_bytes());
+    /// adtl.extend(b"x\0");
+    /// wave.extra_chunks_mut().push((*b"LIST", adtl));
+    ///
+    /// let parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+    /// assert_eq!(parsed.cue_points(), wave.cue_points());
+    /// assert_eq!(parsed.extra_chunks(), wave.extra_chunks());
+    /// assert_eq!(parsed.to_bytes(), wave.to_bytes());
+    ///
+    /// // Also in a file without cue points
+    /// wave.cue_points_mut().clear();
+    /// let parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+    /// assert_eq!(parsed.to_bytes(), wave.to_bytes());
+    /// ```
+    pub fn extra_chunks(&self) -> &[(FourCC, Vec<u8>)] {
+        &self.extra_chunks
+    }
+
+    /// Returns the chunks of the file that this crate doesn't interpret for editing, see
+    /// [WaveFile::extra_chunks].
+    pub fn extra_chunks_mut(&mut self) -> &mut Vec<(FourCC, Vec<u8>)> {
+        &mut self.extra_chunks
+    }
+
+    /// Removes all chunks that this crate doesn't interpret, so they aren't written
+    pub fn clear_extra_chunks(&mut self) {
+        self.extra_chunks.clear();
+    }
+
+    /// Adds a chunk with the given `id` and `payload`, written after the audio data and the
+    /// other chunks that were added before. Multiple chunks with the same id are allowed.
+    ///
+    /// ## Panics
+    /// If `id` is `fmt `, `fact`, `data` or `ds64`, which are written from the spec and audio data.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_chunk(*b"cal ", vec![1, 2, 3]);
+    /// wave.add_chunk(*b"cal ", vec![4]);
+    ///
+    /// let bytes = wave.to_bytes();
+    /// // 3 and 1 byte payloads, both followed by a pad byte
+    /// assert_eq!(bytes.len(), 44 + (8 + 4) + (8 + 2));
+    /// assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+    ///
+    /// let mut parsed = WaveFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!(parsed.chunk(*b"cal "), Some(&[1, 2, 3][..]));
+    /// assert_eq!(parsed.chunks_with_id(*b"cal ").collect::<Vec<_>>(), [&[1, 2, 3][..], &[4]]);
+    ///
+    /// assert_eq!(parsed.remove_chunk(*b"cal "), 2);
+    /// assert_eq!(parsed.chunk(*b"cal "), None);
+    /// ```
+    pub fn add_chunk(&mut self, id: FourCC, payload: Vec<u8>) {
+        assert!(
+            !matches!(&id, b"fmt " | b"fact" | b"data" | b"ds64"),
+            "the {} chunk can't be added", String::from_utf8_lossy(&id)
+        );
+        self.extra_chunks.push((id, payload));
+    }
+
+    /// Returns the payload of the first extra chunk with the given `id`, see
+    /// [WaveFile::extra_chunks].
+    pub fn chunk(&self, id: FourCC) -> Option<&[u8]> {
+        self.chunks_with_id(id).next()
+    }
+
+    /// Returns the payloads of all extra chunks with the given `id`, in order.
+    pub fn chunks_with_id(&self, id: FourCC) -> impl Iterator<Item = &[u8]> + '_ {
+        self.extra_chunks.iter().filter(move |(chunk_id, _)| *chunk_id == id).map(|(_, data)| data.as_slice())
+    }
+
+    /// Removes all extra chunks with the given `id`, and returns how many were removed.
+    pub fn remove_chunk(&mut self, id: FourCC) -> usize {
+        let len = self.extra_chunks.len();
+        self.extra_chunks.retain(|(chunk_id, _)| *chunk_id != id);
+        len - self.extra_chunks.len()
+    }
+
+    /// Returns the production metadata of the `iXML` chunk as XML, if the file has one that is
+    /// valid UTF-8. Trailing null bytes are left out.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 48000, 24);
+    /// wave.set_broadcast_ext(Some(BroadcastExt::default()));
+    /// wave.info_mut().title = Some(String::from("Scene 12"));
+    /// wave.set_ixml(String::from("<BWFXML><SCENE>12</SCENE><TAKE>3</TAKE></BWFXML>"));
+    ///
+    /// let bytes = wave.to_bytes();
+    /// assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+    ///
+    /// let parsed = WaveFile::from_bytes(&bytes).unwrap();
+    /// assert_eq!(parsed.ixml(), Some("<BWFXML><SCENE>12</SCENE><TAKE>3</TAKE></BWFXML>"));
+    /// assert_eq!(parsed.info().title.as_deref(), Some("Scene 12"));
+    /// assert!(parsed.broadcast_ext().is_some());
+    /// ```
+    pub fn ixml(&self) -> Option<&str> {
+        let ixml = self.chunk(*b"iXML")?;
+        let end = ixml.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        std::str::from_utf8(&ixml[..end]).ok()
+    }
+
+    /// Sets the production metadata written as the `iXML` chunk, replacing the current one
+    pub fn set_ixml(&mut self, ixml: String) {
+        self.remove_chunk(*b"iXML");
+        self.add_chunk(*b"iXML", ixml.into_bytes());
+    }
+}
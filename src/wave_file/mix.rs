@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use super::{WaveError, WaveFile};
+
+/// What happens to samples that end up outside of the range `-1.0..=1.0` of normalized samples,
+/// e.g. when mixing or amplifying audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipPolicy {
+    /// Samples are clamped to the range (hard clipping).
+    Clip,
+    /// The operation fails with [WaveError::Clipping], without changing the file.
+    Error,
+    /// Samples aren't checked. Float samples keep their value and may exceed 1.0, integer samples
+    /// still saturate at the limits of their bit depth.
+    Unchecked,
+}
+
+impl ClipPolicy {
+    /// Applies the policy to the normalized `value` of a sample in `frame`
+    pub(crate) fn apply(self, value: f64, frame: u64) -> Result<f64, WaveError> {
+        match self {
+            _ if (-1.0..=1.0).contains(&value) => Ok(value),
+            ClipPolicy::Clip => Ok(value.clamp(-1.0, 1.0)),
+            ClipPolicy::Error => Err(WaveError::Clipping { frame }),
+            ClipPolicy::Unchecked => Ok(value),
+        }
+    }
+}
+
+// Mixing
+impl WaveFile {
+    /// Mixes `other` into this file by adding its samples, multiplied by `gain_other`, to the
+    /// samples of this file. If `other` is longer, this file is extended with its audio.
+    ///
+    /// The formats of the files may differ, as samples are mixed as normalized values (see
+    /// [Sample](super::Sample)).
+    ///
+    /// # Errors
+    /// - [WaveError::FormatMismatch] if the files don't have the same amount of channels, sample
+    ///   rate and bits per sample
+    /// - [WaveError::Clipping] if a mixed sample clips and `clip` is [ClipPolicy::Error]
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples
+    ///   of either file can't be decoded
+    ///
+    /// Nothing is mixed when an error is returned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut music = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// music.add_samples_i16(&[1000, 30000, -30000]).unwrap();
+    /// let mut voice = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// voice.add_samples_i16(&[2000, 10000, -10000, 4000]).unwrap();
+    ///
+    /// let mut mixed = music.slice_frames(0..3).unwrap();
+    /// assert!(matches!(mixed.mix(&voice, 1.0, ClipPolicy::Error), Err(WaveError::Clipping { frame: 1 })));
+    ///
+    /// mixed.mix(&voice, 1.0, ClipPolicy::Clip).unwrap();
+    /// assert_eq!(mixed.samples_i16().unwrap(), [3000, i16::MAX, i16::MIN, 4000]);
+    ///
+    /// music.mix(&voice, 0.1, ClipPolicy::Error).unwrap();
+    /// assert_eq!(music.samples_i16().unwrap(), [1200, 31000, -31000, 400]);
+    /// ```
+    pub fn mix(&mut self, other: &WaveFile, gain_other: f32, clip: ClipPolicy) -> Result<(), WaveError> {
+        self.mix_at(Duration::ZERO, other, gain_other, clip)
+    }
+
+    /// Mixes `other` into this file like [WaveFile::mix], starting at `offset` (rounded to the
+    /// nearest frame). If `other` ends after this file, the file is extended, with silence
+    /// between the end of the file and `offset` if it starts after it.
+    ///
+    /// # Errors
+    /// See [WaveFile::mix].
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 1000, 32);
+    /// wave.add_samples_f32(&[0.25, -0.25]).unwrap();
+    /// let mut beep = WaveFile::new(AudioFormat::IeeeFloat, 2, 1000, 32);
+    /// beep.add_samples_f32(&[1.0, 1.0]).unwrap();
+    ///
+    /// wave.mix_at(Duration::from_millis(2), &beep, 0.5, ClipPolicy::Error).unwrap();
+    /// assert_eq!(wave.samples_f32().unwrap(), [0.25, -0.25, 0.0, 0.0, 0.5, 0.5]);
+    ///
+    /// // Float samples may exceed 1.0 when unchecked
+    /// wave.mix_at(Duration::from_millis(2), &beep, 1.0, ClipPolicy::Unchecked).unwrap();
+    /// assert_eq!(wave.samples_f32().unwrap()[4], 1.5);
+    /// ```
+    pub fn mix_at(&mut self, offset: Duration, other: &WaveFile, gain_other: f32, clip: ClipPolicy) -> Result<(), WaveError> {
+        self.check_same_layout(other)?;
+        let channels = self.num_channels.max(1) as usize;
+        let start = self.frames_for_duration(offset);
+        let overlay = other.samples_as::<f64>()?;
+        let end = start + (overlay.len() / channels) as u64;
+
+        // Mix into a separate buffer first, so nothing changes when a sample clips
+        let existing = self.slice_frames(start..end)?.samples_as::<f64>()?;
+        let mut mixed = Vec::with_capacity(overlay.len());
+        for (i, sample) in overlay.iter().enumerate() {
+            let value = existing.get(i).copied().unwrap_or(0.0) + sample * gain_other as f64;
+            mixed.push(clip.apply(value, start + (i / channels) as u64)?);
+        }
+
+        let frames = self.num_frames();
+        if end > frames {
+            self.append_silent_frames(end - frames);
+        }
+        self.map_samples(start..end, |frame, channel, _| mixed[(frame - start) as usize * channels + channel])
+    }
+}
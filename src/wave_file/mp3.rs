@@ -0,0 +1,1370 @@
+//! Decoding of MPEG-1/2/2.5 Layer III (MP3) streams into 16-bit PCM.
+//!
+//! The decoder is a port of [minimp3](https://github.com/lieff/minimp3) (public domain, CC0):
+//! the function and table names follow `minimp3.h` so the two can be read side by side.
+
+// Float literals are spelled exactly as in minimp3
+#![allow(clippy::excessive_precision, clippy::approx_constant)]
+// Index loops mirror the C code and keep parallel arrays in step
+#![allow(clippy::needless_range_loop)]
+
+use std::fs;
+
+mod tables;
+
+use super::id3::skip_id3v2;
+use super::{AudioFormat, WaveError, WaveFile};
+use tables::*;
+
+/// The size of a frame header
+const HDR_SIZE: usize = 4;
+const MAX_FREE_FORMAT_FRAME_SIZE: usize = 2304;
+/// The amount of consecutive frames that have to match before a frame is accepted while syncing
+const MAX_FRAME_SYNC_MATCHES: usize = 10;
+const MAX_BITRESERVOIR_BYTES: usize = 511;
+const MAX_L3_FRAME_PAYLOAD_BYTES: usize = MAX_FREE_FORMAT_FRAME_SIZE;
+const SHORT_BLOCK_TYPE: u8 = 2;
+const STOP_BLOCK_TYPE: u8 = 3;
+const MAX_SCFI: i32 = (255 - 4 - 210 + 3) & !3;
+/// The delay of the decoder in samples, which the LAME header doesn't include
+const DECODER_DELAY: u32 = 528 + 1;
+
+/// A 4-byte frame header.
+#[derive(Copy, Clone)]
+struct Header([u8; 4]);
+
+impl Header {
+    fn read(buf: &[u8]) -> Header {
+        Header([buf[0], buf[1], buf[2], buf[3]])
+    }
+
+    fn is_mono(self) -> bool {
+        self.0[3] & 0xC0 == 0xC0
+    }
+
+    fn is_ms_stereo(self) -> bool {
+        self.0[3] & 0xE0 == 0x60
+    }
+
+    fn is_free_format(self) -> bool {
+        self.0[2] & 0xF0 == 0
+    }
+
+    fn is_crc(self) -> bool {
+        self.0[1] & 1 == 0
+    }
+
+    fn test_padding(self) -> bool {
+        self.0[2] & 0x2 != 0
+    }
+
+    fn test_mpeg1(self) -> bool {
+        self.0[1] & 0x8 != 0
+    }
+
+    fn test_not_mpeg25(self) -> bool {
+        self.0[1] & 0x10 != 0
+    }
+
+    fn test_i_stereo(self) -> bool {
+        self.0[3] & 0x10 != 0
+    }
+
+    fn test_ms_stereo(self) -> bool {
+        self.0[3] & 0x20 != 0
+    }
+
+    /// 1 for Layer III, 2 for Layer II and 3 for Layer I
+    fn get_layer(self) -> u8 {
+        (self.0[1] >> 1) & 3
+    }
+
+    fn get_bitrate(self) -> u8 {
+        self.0[2] >> 4
+    }
+
+    fn get_sample_rate(self) -> u8 {
+        (self.0[2] >> 2) & 3
+    }
+
+    /// The index of the sample rate over all MPEG versions, 0 to 8
+    fn get_my_sample_rate(self) -> usize {
+        let h1 = self.0[1];
+        (self.get_sample_rate() + (((h1 >> 3) & 1) + ((h1 >> 4) & 1)) * 3) as usize
+    }
+
+    fn is_frame_576(self) -> bool {
+        self.0[1] & 14 == 2
+    }
+
+    fn is_layer_1(self) -> bool {
+        self.0[1] & 6 == 6
+    }
+
+    fn num_channels(self) -> u32 {
+        if self.is_mono() {
+            1
+        } else {
+            2
+        }
+    }
+
+    fn valid(self) -> bool {
+        let h = self.0;
+        h[0] == 0xFF
+            && (h[1] & 0xF0 == 0xF0 || h[1] & 0xFE == 0xE2)
+            && self.get_layer() != 0
+            && self.get_bitrate() != 15
+            && self.get_sample_rate() != 3
+    }
+
+    /// Whether `other` is a valid header of the same stream
+    fn compare(self, other: Header) -> bool {
+        let (h1, h2) = (self.0, other.0);
+        other.valid()
+            && (h1[1] ^ h2[1]) & 0xFE == 0
+            && (h1[2] ^ h2[2]) & 0x0C == 0
+            && self.is_free_format() == other.is_free_format()
+    }
+
+    fn bitrate_kbps(self) -> u32 {
+        2 * BITRATE_HALF_KBPS[self.test_mpeg1() as usize][self.get_layer() as usize - 1][self.get_bitrate() as usize]
+            as u32
+    }
+
+    fn sample_rate_hz(self) -> u32 {
+        SAMPLE_RATES[self.get_sample_rate() as usize] >> !self.test_mpeg1() as u32 >> !self.test_not_mpeg25() as u32
+    }
+
+    /// The amount of samples per channel in a frame
+    fn frame_samples(self) -> u32 {
+        if self.is_layer_1() {
+            384
+        } else {
+            1152 >> self.is_frame_576() as u32
+        }
+    }
+
+    /// The size of a frame without padding, `free_format_size` for free format streams
+    fn frame_bytes(self, free_format_size: usize) -> usize {
+        let mut frame_bytes = (self.frame_samples() * self.bitrate_kbps() * 125 / self.sample_rate_hz()) as usize;
+        if self.is_layer_1() {
+            // Slot align
+            frame_bytes &= !3;
+        }
+        if frame_bytes != 0 {
+            frame_bytes
+        } else {
+            free_format_size
+        }
+    }
+
+    fn padding(self) -> usize {
+        match (self.test_padding(), self.is_layer_1()) {
+            (false, _) => 0,
+            (true, true) => 4,
+            (true, false) => 1,
+        }
+    }
+}
+
+/// The side info of one granule of one channel.
+#[derive(Copy, Clone, Default)]
+struct GrInfo {
+    sfbtab: &'static [u8],
+    part_23_length: u16,
+    big_values: u16,
+    scalefac_compress: u16,
+    global_gain: u8,
+    block_type: u8,
+    mixed_block_flag: u8,
+    n_long_sfb: u8,
+    n_short_sfb: u8,
+    table_select: [u8; 3],
+    region_count: [u8; 3],
+    subblock_gain: [u8; 3],
+    preflag: u8,
+    scalefac_scale: u8,
+    count1_table: u8,
+    scfsi: u8,
+}
+
+/// Reads a stream of bits, most significant bit first. Reading past `limit` returns zeros, but
+/// still advances `pos` so overruns can be detected.
+struct Bs<'a> {
+    buf: &'a [u8],
+    pos: i32,
+    limit: i32,
+}
+
+impl<'a> Bs<'a> {
+    /// A reader of the first `bytes` bytes of `buf`, which may not be longer than `buf`
+    fn new(buf: &'a [u8], bytes: i32) -> Bs<'a> {
+        Bs { buf, pos: 0, limit: bytes * 8 }
+    }
+
+    fn get_bits(&mut self, n: u32) -> u32 {
+        let s = (self.pos & 7) as u32;
+        let mut p = (self.pos >> 3) as usize;
+        self.pos += n as i32;
+        if self.pos > self.limit {
+            return 0;
+        }
+        let mut shl = (n + s) as i32;
+        let mut next = (self.buf[p] & (255 >> s)) as u32;
+        p += 1;
+        let mut cache = 0;
+        loop {
+            shl -= 8;
+            if shl <= 0 {
+                break;
+            }
+            cache |= next << shl;
+            next = self.buf[p] as u32;
+            p += 1;
+        }
+        cache | (next >> -shl)
+    }
+}
+
+/// Reads the side info of all granules, returns `main_data_begin` or `None` if the side info
+/// is corrupt.
+fn l3_read_side_info(bs: &mut Bs, gr: &mut [GrInfo; 4], hdr: Header) -> Option<i32> {
+    let mut scfsi: u32 = 0;
+    let mut part_23_sum: i32 = 0;
+    let mut sr_idx = hdr.get_my_sample_rate();
+    sr_idx -= (sr_idx != 0) as usize;
+    let mut gr_count = if hdr.is_mono() { 1 } else { 2 };
+    let main_data_begin;
+    if hdr.test_mpeg1() {
+        gr_count *= 2;
+        main_data_begin = bs.get_bits(9) as i32;
+        scfsi = bs.get_bits(7 + gr_count);
+    } else {
+        main_data_begin = (bs.get_bits(8 + gr_count) >> gr_count) as i32;
+    }
+
+    for gr in &mut gr[..gr_count as usize] {
+        if hdr.is_mono() {
+            scfsi <<= 4;
+        }
+        gr.part_23_length = bs.get_bits(12) as u16;
+        part_23_sum += gr.part_23_length as i32;
+        gr.big_values = bs.get_bits(9) as u16;
+        if gr.big_values > 288 {
+            return None;
+        }
+        gr.global_gain = bs.get_bits(8) as u8;
+        gr.scalefac_compress = bs.get_bits(if hdr.test_mpeg1() { 4 } else { 9 }) as u16;
+        gr.sfbtab = &SCF_LONG[sr_idx];
+        gr.n_long_sfb = 22;
+        gr.n_short_sfb = 0;
+        let tables;
+        if bs.get_bits(1) != 0 {
+            gr.block_type = bs.get_bits(2) as u8;
+            if gr.block_type == 0 {
+                return None;
+            }
+            gr.mixed_block_flag = bs.get_bits(1) as u8;
+            gr.region_count[0] = 7;
+            gr.region_count[1] = 255;
+            if gr.block_type == SHORT_BLOCK_TYPE {
+                scfsi &= 0x0F0F;
+                if gr.mixed_block_flag == 0 {
+                    gr.region_count[0] = 8;
+                    gr.sfbtab = &SCF_SHORT[sr_idx];
+                    gr.n_long_sfb = 0;
+                    gr.n_short_sfb = 39;
+                } else {
+                    gr.sfbtab = &SCF_MIXED[sr_idx];
+                    gr.n_long_sfb = if hdr.test_mpeg1() { 8 } else { 6 };
+                    gr.n_short_sfb = 30;
+                }
+            }
+            tables = bs.get_bits(10) << 5;
+            gr.subblock_gain[0] = bs.get_bits(3) as u8;
+            gr.subblock_gain[1] = bs.get_bits(3) as u8;
+            gr.subblock_gain[2] = bs.get_bits(3) as u8;
+        } else {
+            gr.block_type = 0;
+            gr.mixed_block_flag = 0;
+            tables = bs.get_bits(15);
+            gr.region_count[0] = bs.get_bits(4) as u8;
+            gr.region_count[1] = bs.get_bits(3) as u8;
+            gr.region_count[2] = 255;
+        }
+        gr.table_select[0] = (tables >> 10) as u8;
+        gr.table_select[1] = ((tables >> 5) & 31) as u8;
+        gr.table_select[2] = (tables & 31) as u8;
+        gr.preflag = if hdr.test_mpeg1() { bs.get_bits(1) as u8 } else { (gr.scalefac_compress >= 500) as u8 };
+        gr.scalefac_scale = bs.get_bits(1) as u8;
+        gr.count1_table = bs.get_bits(1) as u8;
+        gr.scfsi = ((scfsi >> 12) & 15) as u8;
+        scfsi <<= 4;
+    }
+
+    if part_23_sum + bs.pos > bs.limit + main_data_begin * 8 {
+        return None;
+    }
+    Some(main_data_begin)
+}
+
+fn l3_read_scalefactors(
+    scf: &mut [u8; 40],
+    ist_pos: &mut [u8; 39],
+    scf_size: &[u8; 4],
+    scf_count: &[u8],
+    bs: &mut Bs,
+    mut scfsi: i32,
+) {
+    let mut pos = 0;
+    for i in 0..4 {
+        let cnt = scf_count[i] as usize;
+        if cnt == 0 {
+            break;
+        }
+        let scf = &mut scf[pos..pos + cnt];
+        let ist_pos = &mut ist_pos[pos..pos + cnt];
+        if scfsi & 8 != 0 {
+            // The scalefactors are shared with the previous granule
+            scf.copy_from_slice(ist_pos);
+        } else {
+            let bits = scf_size[i] as u32;
+            if bits == 0 {
+                scf.fill(0);
+                ist_pos.fill(0);
+            } else {
+                let max_scf = if scfsi < 0 { (1 << bits) - 1 } else { -1 };
+                for (scf, ist_pos) in scf.iter_mut().zip(ist_pos) {
+                    let s = bs.get_bits(bits) as i32;
+                    *ist_pos = if s == max_scf { u8::MAX } else { s as u8 };
+                    *scf = s as u8;
+                }
+            }
+        }
+        pos += cnt;
+        scfsi *= 2;
+    }
+    scf[pos..pos + 3].fill(0);
+}
+
+/// `y * 2^(-exp_q2 / 4)`
+fn l3_ldexp_q2(mut y: f32, mut exp_q2: i32) -> f32 {
+    loop {
+        let e = (30 * 4).min(exp_q2);
+        y *= EXP_FRAC[(e & 3) as usize] * ((1 << 30 >> (e >> 2)) as f32);
+        exp_q2 -= e;
+        if exp_q2 <= 0 {
+            return y;
+        }
+    }
+}
+
+fn l3_decode_scalefactors(hdr: Header, ist_pos: &mut [u8; 39], bs: &mut Bs, gr: &GrInfo, scf: &mut [f32; 40], ch: usize) {
+    let mut scf_partition: &[u8] = &SCF_PARTITIONS[(gr.n_short_sfb != 0) as usize + (gr.n_long_sfb == 0) as usize];
+    let mut scf_size = [0u8; 4];
+    let mut iscf = [0u8; 40];
+    let scf_shift = gr.scalefac_scale as u32 + 1;
+    let mut scfsi = gr.scfsi as i32;
+
+    if hdr.test_mpeg1() {
+        let part = SCF_COMPRESS[gr.scalefac_compress as usize];
+        scf_size[0] = part >> 2;
+        scf_size[1] = scf_size[0];
+        scf_size[2] = part & 3;
+        scf_size[3] = scf_size[2];
+    } else {
+        let ist = (hdr.test_i_stereo() && ch != 0) as usize;
+        let mut sfc = gr.scalefac_compress as i32 >> ist;
+        let mut k = ist * 3 * 4;
+        while sfc >= 0 {
+            let mut modprod = 1;
+            for i in (0..4).rev() {
+                let m = SCF_MOD[k + i] as i32;
+                scf_size[i] = (sfc / modprod % m) as u8;
+                modprod *= m;
+            }
+            sfc -= modprod;
+            k += 4;
+        }
+        scf_partition = &scf_partition[k..];
+        scfsi = -16;
+    }
+    l3_read_scalefactors(&mut iscf, ist_pos, &scf_size, scf_partition, bs, scfsi);
+
+    let n_long = gr.n_long_sfb as usize;
+    let n_short = gr.n_short_sfb as usize;
+    if n_short != 0 {
+        let sh = 3 - scf_shift;
+        for i in (0..n_short).step_by(3) {
+            for j in 0..3 {
+                let v = &mut iscf[n_long + i + j];
+                *v = v.wrapping_add(gr.subblock_gain[j] << sh);
+            }
+        }
+    } else if gr.preflag != 0 {
+        for (v, &p) in iscf[11..21].iter_mut().zip(&PREAMP) {
+            *v = v.wrapping_add(p);
+        }
+    }
+
+    let gain_exp = gr.global_gain as i32 - 4 - 210 - if hdr.is_ms_stereo() { 2 } else { 0 };
+    let gain = l3_ldexp_q2((1 << (MAX_SCFI / 4)) as f32, MAX_SCFI - gain_exp);
+    for (scf, &iscf) in scf.iter_mut().zip(&iscf).take(n_long + n_short) {
+        *scf = l3_ldexp_q2(gain, (iscf as i32) << scf_shift);
+    }
+}
+
+/// `x^(4/3)` for `x >= 0`
+fn l3_pow_43(mut x: i32) -> f32 {
+    let mut mult = 256;
+    if x < 129 {
+        return POW43[(16 + x) as usize];
+    }
+    if x < 1024 {
+        mult = 16;
+        x <<= 3;
+    }
+    let sign = (2 * x) & 64;
+    let frac = ((x & 63) - sign) as f32 / ((x & !63) + sign) as f32;
+    POW43[(16 + ((x + sign) >> 6)) as usize] * (1.0 + frac * ((4.0 / 3.0) + frac * (2.0 / 9.0))) * mult as f32
+}
+
+/// The 32-bit look-ahead cache of the Huffman decoder. Bytes past the end of the buffer read as
+/// zero, as a corrupt `big_values` can make the decoder run past the data of a granule.
+struct BitCache<'a> {
+    buf: &'a [u8],
+    next: usize,
+    cache: u32,
+    sh: i32,
+}
+
+impl BitCache<'_> {
+    fn byte(&self, i: usize) -> u32 {
+        self.buf.get(i).map_or(0, |&byte| byte as u32)
+    }
+
+    fn peek(&self, n: i32) -> u32 {
+        self.cache >> (32 - n)
+    }
+
+    fn flush(&mut self, n: i32) {
+        self.cache <<= n;
+        self.sh += n;
+    }
+
+    fn check(&mut self) {
+        while self.sh >= 0 {
+            self.cache |= self.byte(self.next) << self.sh;
+            self.next += 1;
+            self.sh -= 8;
+        }
+    }
+
+    /// The position in bits
+    fn pos(&self) -> i32 {
+        self.next as i32 * 8 - 24 + self.sh
+    }
+}
+
+/// Decodes the Huffman coded pairs of one scalefactor band of the big values region
+fn l3_huffman_pairs(out: &mut [f32], br: &mut BitCache, codebook: &[i16], linbits: i32, one: f32) {
+    for pair in out.chunks_exact_mut(2) {
+        let mut w = 5;
+        let mut leaf = codebook[br.peek(w) as usize] as i32;
+        while leaf < 0 {
+            br.flush(w);
+            w = leaf & 7;
+            leaf = codebook[br.peek(w).wrapping_sub((leaf >> 3) as u32) as usize] as i32;
+        }
+        br.flush(leaf >> 8);
+
+        for v in pair {
+            let mut lsb = leaf & 0x0F;
+            if linbits != 0 && lsb == 15 {
+                lsb += br.peek(linbits) as i32;
+                br.flush(linbits);
+                br.check();
+                *v = one * l3_pow_43(lsb) * if (br.cache as i32) < 0 { -1.0 } else { 1.0 };
+            } else {
+                *v = POW43[(16 + lsb) as usize - 16 * (br.cache >> 31) as usize] * one;
+            }
+            br.flush((lsb != 0) as i32);
+            leaf >>= 4;
+        }
+        br.check();
+    }
+}
+
+fn l3_huffman(dst: &mut [f32], bs: &mut Bs, gr_info: &GrInfo, scf: &[f32; 40], layer3gr_limit: i32) {
+    let mut one = 0.0f32;
+    let mut ireg = 0;
+    let mut big_val_cnt = gr_info.big_values as i32;
+    let sfbtab = gr_info.sfbtab;
+    let mut sfb = 0;
+    let mut scf_idx = 0;
+    let mut d = 0;
+
+    let start = (bs.pos / 8) as usize;
+    let mut br = BitCache { buf: bs.buf, next: start + 4, cache: 0, sh: (bs.pos & 7) - 8 };
+    br.cache = (((br.byte(start) * 256 + br.byte(start + 1)) * 256 + br.byte(start + 2)) * 256 + br.byte(start + 3))
+        << (bs.pos & 7);
+
+    while big_val_cnt > 0 {
+        let tab_num = gr_info.table_select[ireg] as usize;
+        let mut sfb_cnt = gr_info.region_count[ireg] as i32;
+        ireg += 1;
+        let codebook = &HUFFMAN_TABS[HUFFMAN_TAB_INDEX[tab_num] as usize..];
+        let linbits = HUFFMAN_LINBITS[tab_num] as i32;
+        loop {
+            let np = sfbtab[sfb] as i32 / 2;
+            sfb += 1;
+            let pairs_to_decode = big_val_cnt.min(np) as usize;
+            one = scf[scf_idx];
+            scf_idx += 1;
+            // At least one pair is decoded, `np` is never 0 here as big_values is at most 288
+            // and every band table covers 288 pairs
+            let out = &mut dst[d..d + 2 * pairs_to_decode.max(1)];
+            l3_huffman_pairs(out, &mut br, codebook, linbits, one);
+            d += out.len();
+            big_val_cnt -= np;
+            if big_val_cnt <= 0 {
+                break;
+            }
+            sfb_cnt -= 1;
+            if sfb_cnt < 0 {
+                break;
+            }
+        }
+    }
+
+    let codebook_count1: &[u8] = if gr_info.count1_table != 0 { &HUFFMAN_TAB33 } else { &HUFFMAN_TAB32 };
+    let mut np = 1 - big_val_cnt;
+    'count1: loop {
+        let mut leaf = codebook_count1[br.peek(4) as usize] as i32;
+        if leaf & 8 == 0 {
+            leaf = codebook_count1[(leaf >> 3) as usize + (br.cache << 4 >> (32 - (leaf & 3))) as usize] as i32;
+        }
+        br.flush(leaf & 7);
+        if br.pos() > layer3gr_limit {
+            break;
+        }
+        for half in 0..2 {
+            np -= 1;
+            if np == 0 {
+                np = sfbtab[sfb] as i32 / 2;
+                sfb += 1;
+                if np == 0 {
+                    break 'count1;
+                }
+                one = scf[scf_idx];
+                scf_idx += 1;
+            }
+            for s in 2 * half..2 * half + 2 {
+                if leaf & (128 >> s) != 0 {
+                    dst[d + s] = if (br.cache as i32) < 0 { -one } else { one };
+                    br.flush(1);
+                }
+            }
+        }
+        br.check();
+        d += 4;
+    }
+    bs.pos = layer3gr_limit;
+}
+
+fn l3_midside_stereo(buf: &mut [f32; 1152], start: usize, n: usize) {
+    let (left, right) = buf.split_at_mut(576);
+    for (l, r) in left[start..start + n].iter_mut().zip(&mut right[start..start + n]) {
+        let (a, b) = (*l, *r);
+        *l = a + b;
+        *r = a - b;
+    }
+}
+
+fn l3_intensity_stereo_band(buf: &mut [f32; 1152], start: usize, n: usize, kl: f32, kr: f32) {
+    let (left, right) = buf.split_at_mut(576);
+    for (l, r) in left[start..start + n].iter_mut().zip(&mut right[start..start + n]) {
+        *r = *l * kr;
+        *l *= kl;
+    }
+}
+
+/// Finds the highest band with non-zero values in the right channel, for each window
+fn l3_stereo_top_band(right: &[f32], sfb: &[u8], nbands: usize, max_band: &mut [i32; 3]) {
+    *max_band = [-1; 3];
+    let mut off = 0;
+    for i in 0..nbands {
+        let n = sfb[i] as usize;
+        if right[off..off + n].iter().any(|&x| x != 0.0) {
+            max_band[i % 3] = i as i32;
+        }
+        off += n;
+    }
+}
+
+fn l3_stereo_process(buf: &mut [f32; 1152], ist_pos: &[u8; 39], sfb: &[u8], hdr: Header, max_band: &[i32; 3], mpeg2_sh: u32) {
+    let max_pos = if hdr.test_mpeg1() { 7 } else { 64 };
+    let mut off = 0;
+    for (i, &n) in sfb.iter().take_while(|&&n| n != 0).enumerate() {
+        let n = n as usize;
+        let ipos = ist_pos[i] as u32;
+        if i as i32 > max_band[i % 3] && ipos < max_pos {
+            let s = if hdr.test_ms_stereo() { 1.41421356 } else { 1.0 };
+            let (kl, kr);
+            if hdr.test_mpeg1() {
+                kl = PAN[2 * ipos as usize];
+                kr = PAN[2 * ipos as usize + 1];
+            } else {
+                let k = l3_ldexp_q2(1.0, (((ipos + 1) >> 1) << mpeg2_sh) as i32);
+                (kl, kr) = if ipos & 1 != 0 { (k, 1.0) } else { (1.0, k) };
+            }
+            l3_intensity_stereo_band(buf, off, n, kl * s, kr * s);
+        } else if hdr.test_ms_stereo() {
+            l3_midside_stereo(buf, off, n);
+        }
+        off += n;
+    }
+}
+
+fn l3_intensity_stereo(buf: &mut [f32; 1152], ist_pos: &mut [u8; 39], gr: &[GrInfo], hdr: Header) {
+    let mut max_band = [0i32; 3];
+    let n_sfb = gr[0].n_long_sfb as usize + gr[0].n_short_sfb as usize;
+    let max_blocks = if gr[0].n_short_sfb != 0 { 3 } else { 1 };
+
+    l3_stereo_top_band(&buf[576..], gr[0].sfbtab, n_sfb, &mut max_band);
+    if gr[0].n_long_sfb != 0 {
+        max_band = [max_band[0].max(max_band[1]).max(max_band[2]); 3];
+    }
+    for i in 0..max_blocks {
+        let default_pos = if hdr.test_mpeg1() { 3 } else { 0 };
+        let itop = n_sfb - max_blocks + i;
+        let prev = itop - max_blocks;
+        ist_pos[itop] = if max_band[i] >= prev as i32 { default_pos } else { ist_pos[prev] };
+    }
+    l3_stereo_process(buf, ist_pos, gr[0].sfbtab, hdr, &max_band, (gr[1].scalefac_compress & 1) as u32);
+}
+
+/// Interleaves the three windows of the short blocks
+fn l3_reorder(grbuf: &mut [f32], scratch: &mut [f32], sfb: &[u8]) {
+    let mut src = 0;
+    let mut dst = 0;
+    for &len in sfb.iter().step_by(3).take_while(|&&len| len != 0) {
+        let len = len as usize;
+        for i in 0..len {
+            scratch[dst] = grbuf[src + i];
+            scratch[dst + 1] = grbuf[src + i + len];
+            scratch[dst + 2] = grbuf[src + i + 2 * len];
+            dst += 3;
+        }
+        src += 3 * len;
+    }
+    grbuf[..dst].copy_from_slice(&scratch[..dst]);
+}
+
+fn l3_antialias(grbuf: &mut [f32], nbands: usize) {
+    for band in 0..nbands {
+        let grbuf = &mut grbuf[18 * band..];
+        for i in 0..8 {
+            let u = grbuf[18 + i];
+            let d = grbuf[17 - i];
+            grbuf[18 + i] = u * ANTIALIAS[0][i] - d * ANTIALIAS[1][i];
+            grbuf[17 - i] = u * ANTIALIAS[1][i] + d * ANTIALIAS[0][i];
+        }
+    }
+}
+
+fn l3_dct3_9(y: &mut [f32; 9]) {
+    let mut s0 = y[0];
+    let mut s2 = y[2];
+    let mut s4 = y[4];
+    let mut s6 = y[6];
+    let mut s8 = y[8];
+    let mut t0 = s0 + s6 * 0.5;
+    s0 -= s6;
+    let mut t4 = (s4 + s2) * 0.93969262;
+    let mut t2 = (s8 + s2) * 0.76604444;
+    s6 = (s4 - s8) * 0.17364818;
+    s4 += s8 - s2;
+
+    s2 = s0 - s4 * 0.5;
+    y[4] = s4 + s0;
+    s8 = t0 - t2 + s6;
+    s0 = t0 - t4 + t2;
+    s4 = t0 + t4 - s6;
+
+    let mut s1 = y[1];
+    let mut s3 = y[3];
+    let mut s5 = y[5];
+    let mut s7 = y[7];
+
+    s3 *= 0.86602540;
+    t0 = (s5 + s1) * 0.98480775;
+    t4 = (s5 - s7) * 0.34202014;
+    t2 = (s1 + s7) * 0.64278761;
+    s1 = (s1 - s5 - s7) * 0.86602540;
+
+    s5 = t0 - s3 - t2;
+    s7 = t4 - s3 - t0;
+    s3 = t4 + s3 - t2;
+
+    y[0] = s4 - s7;
+    y[1] = s2 + s1;
+    y[2] = s0 - s3;
+    y[3] = s8 + s5;
+    y[5] = s8 - s5;
+    y[6] = s0 + s3;
+    y[7] = s2 - s1;
+    y[8] = s4 + s7;
+}
+
+fn l3_imdct36(grbuf: &mut [f32], overlap: &mut [f32], window: &[f32; 18], nbands: usize) {
+    for j in 0..nbands {
+        let grbuf = &mut grbuf[18 * j..18 * j + 18];
+        let overlap = &mut overlap[9 * j..9 * j + 9];
+        let mut co = [0f32; 9];
+        let mut si = [0f32; 9];
+        co[0] = -grbuf[0];
+        si[0] = grbuf[17];
+        for i in 0..4 {
+            si[8 - 2 * i] = grbuf[4 * i + 1] - grbuf[4 * i + 2];
+            co[1 + 2 * i] = grbuf[4 * i + 1] + grbuf[4 * i + 2];
+            si[7 - 2 * i] = grbuf[4 * i + 4] - grbuf[4 * i + 3];
+            co[2 + 2 * i] = -(grbuf[4 * i + 3] + grbuf[4 * i + 4]);
+        }
+        l3_dct3_9(&mut co);
+        l3_dct3_9(&mut si);
+
+        si[1] = -si[1];
+        si[3] = -si[3];
+        si[5] = -si[5];
+        si[7] = -si[7];
+
+        for i in 0..9 {
+            let ovl = overlap[i];
+            let sum = co[i] * TWIDDLE_9[9 + i] + si[i] * TWIDDLE_9[i];
+            overlap[i] = co[i] * TWIDDLE_9[i] - si[i] * TWIDDLE_9[9 + i];
+            grbuf[i] = ovl * window[i] - sum * window[9 + i];
+            grbuf[17 - i] = ovl * window[9 + i] + sum * window[i];
+        }
+    }
+}
+
+fn l3_idct3(x0: f32, x1: f32, x2: f32, dst: &mut [f32; 3]) {
+    let m1 = x1 * 0.86602540;
+    let a1 = x0 - x2 * 0.5;
+    dst[1] = x0 + x2;
+    dst[0] = a1 + m1;
+    dst[2] = a1 - m1;
+}
+
+fn l3_imdct12(x: &[f32], dst: &mut [f32], overlap: &mut [f32]) {
+    let mut co = [0f32; 3];
+    let mut si = [0f32; 3];
+    l3_idct3(-x[0], x[6] + x[3], x[12] + x[9], &mut co);
+    l3_idct3(x[15], x[12] - x[9], x[6] - x[3], &mut si);
+    si[1] = -si[1];
+
+    for i in 0..3 {
+        let ovl = overlap[i];
+        let sum = co[i] * TWIDDLE_3[3 + i] + si[i] * TWIDDLE_3[i];
+        overlap[i] = co[i] * TWIDDLE_3[i] - si[i] * TWIDDLE_3[3 + i];
+        dst[i] = ovl * TWIDDLE_3[2 - i] - sum * TWIDDLE_3[5 - i];
+        dst[5 - i] = ovl * TWIDDLE_3[5 - i] + sum * TWIDDLE_3[2 - i];
+    }
+}
+
+fn l3_imdct_short(grbuf: &mut [f32], overlap: &mut [f32], nbands: usize) {
+    for (grbuf, overlap) in grbuf.chunks_exact_mut(18).zip(overlap.chunks_exact_mut(9)).take(nbands) {
+        let mut tmp = [0f32; 18];
+        tmp.copy_from_slice(grbuf);
+        grbuf[..6].copy_from_slice(&overlap[..6]);
+        let (ov_out, ov_state) = overlap.split_at_mut(6);
+        l3_imdct12(&tmp, &mut grbuf[6..12], ov_state);
+        l3_imdct12(&tmp[1..], &mut grbuf[12..18], ov_state);
+        l3_imdct12(&tmp[2..], ov_out, ov_state);
+    }
+}
+
+fn l3_change_sign(grbuf: &mut [f32]) {
+    for band in grbuf.chunks_exact_mut(18).skip(1).step_by(2) {
+        for x in band.iter_mut().skip(1).step_by(2) {
+            *x = -*x;
+        }
+    }
+}
+
+fn l3_imdct_gr(grbuf: &mut [f32], overlap: &mut [f32; 288], block_type: u8, n_long_bands: usize) {
+    let (long_buf, rest_buf) = grbuf.split_at_mut(18 * n_long_bands);
+    let (long_ovl, rest_ovl) = overlap.split_at_mut(9 * n_long_bands);
+    if n_long_bands != 0 {
+        l3_imdct36(long_buf, long_ovl, &MDCT_WINDOW[0], n_long_bands);
+    }
+    if block_type == SHORT_BLOCK_TYPE {
+        l3_imdct_short(rest_buf, rest_ovl, 32 - n_long_bands);
+    } else {
+        let window = &MDCT_WINDOW[(block_type == STOP_BLOCK_TYPE) as usize];
+        l3_imdct36(rest_buf, rest_ovl, window, 32 - n_long_bands);
+    }
+}
+
+fn mp3d_dct_ii(grbuf: &mut [f32], n: usize) {
+    for k in 0..n {
+        let mut t = [[0f32; 8]; 4];
+        for i in 0..8 {
+            let x0 = grbuf[k + i * 18];
+            let x1 = grbuf[k + (15 - i) * 18];
+            let x2 = grbuf[k + (16 + i) * 18];
+            let x3 = grbuf[k + (31 - i) * 18];
+            let t0 = x0 + x3;
+            let t1 = x1 + x2;
+            let t2 = (x1 - x2) * DCT_II_SEC[3 * i];
+            let t3 = (x0 - x3) * DCT_II_SEC[3 * i + 1];
+            t[0][i] = t0 + t1;
+            t[1][i] = (t0 - t1) * DCT_II_SEC[3 * i + 2];
+            t[2][i] = t3 + t2;
+            t[3][i] = (t3 - t2) * DCT_II_SEC[3 * i + 2];
+        }
+        for x in &mut t {
+            let [mut x0, mut x1, mut x2, mut x3, mut x4, mut x5, mut x6, mut x7] = *x;
+            let mut xt = x0 - x7;
+            x0 += x7;
+            x7 = x1 - x6;
+            x1 += x6;
+            x6 = x2 - x5;
+            x2 += x5;
+            x5 = x3 - x4;
+            x3 += x4;
+            x4 = x0 - x3;
+            x0 += x3;
+            x3 = x1 - x2;
+            x1 += x2;
+            x[0] = x0 + x1;
+            x[4] = (x0 - x1) * 0.70710677;
+            x5 += x6;
+            x6 = (x6 + x7) * 0.70710677;
+            x7 += xt;
+            x3 = (x3 + x4) * 0.70710677;
+            // Rotate by PI/8
+            x5 -= x7 * 0.198912367;
+            x7 += x5 * 0.382683432;
+            x5 -= x7 * 0.198912367;
+            x0 = xt - x6;
+            xt += x6;
+            x[1] = (xt + x7) * 0.50979561;
+            x[2] = (x4 + x3) * 0.54119611;
+            x[3] = (x0 - x5) * 0.60134488;
+            x[5] = (x0 + x5) * 0.89997619;
+            x[6] = (x4 - x3) * 1.30656302;
+            x[7] = (xt - x7) * 2.56291556;
+        }
+        for i in 0..7 {
+            let y = k + i * 4 * 18;
+            grbuf[y] = t[0][i];
+            grbuf[y + 18] = t[2][i] + t[3][i] + t[3][i + 1];
+            grbuf[y + 2 * 18] = t[1][i] + t[1][i + 1];
+            grbuf[y + 3 * 18] = t[2][i + 1] + t[3][i] + t[3][i + 1];
+        }
+        let y = k + 7 * 4 * 18;
+        grbuf[y] = t[0][7];
+        grbuf[y + 18] = t[2][7] + t[3][7];
+        grbuf[y + 2 * 18] = t[1][7];
+        grbuf[y + 3 * 18] = t[3][7];
+    }
+}
+
+/// Rounds a synthesized sample, which is at 16-bit scale, to a 16-bit sample
+fn scale_pcm(sample: f32) -> i16 {
+    sample.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+/// Writes the samples at `pcm[0]` and `pcm[16 * nch]` from the filterbank history at `z`
+fn mp3d_synth_pair(pcm: &mut [i16], nch: usize, lins: &[f32], z: usize) {
+    let x = |m: usize| lins[z + m * 64];
+    let mut a;
+    a = (x(14) - x(0)) * 29.0;
+    a += (x(1) + x(13)) * 213.0;
+    a += (x(12) - x(2)) * 459.0;
+    a += (x(3) + x(11)) * 2037.0;
+    a += (x(10) - x(4)) * 5153.0;
+    a += (x(5) + x(9)) * 6574.0;
+    a += (x(8) - x(6)) * 37489.0;
+    a += x(7) * 75038.0;
+    pcm[0] = scale_pcm(a);
+
+    let x = |m: usize| lins[z + 2 + m * 64];
+    a = x(14) * 104.0;
+    a += x(12) * 1567.0;
+    a += x(10) * 9727.0;
+    a += x(8) * 64019.0;
+    a += x(6) * -9975.0;
+    a += x(4) * -45.0;
+    a += x(2) * 146.0;
+    a += x(0) * -5.0;
+    pcm[16 * nch] = scale_pcm(a);
+}
+
+/// Synthesizes 64 samples per channel from two subband samples. `xl` is the grbuf from the
+/// first of the two, `lins` the filterbank history from the first one to update.
+fn mp3d_synth(grbuf: &[f32], xl: usize, dst: &mut [i16], nch: usize, lins: &mut [f32]) {
+    let xr = xl + 576 * (nch - 1);
+    let r = nch - 1;
+    let z = 15 * 64;
+
+    lins[z + 4 * 15] = grbuf[xl + 18 * 16];
+    lins[z + 4 * 15 + 1] = grbuf[xr + 18 * 16];
+    lins[z + 4 * 15 + 2] = grbuf[xl];
+    lins[z + 4 * 15 + 3] = grbuf[xr];
+
+    lins[z + 4 * 31] = grbuf[xl + 1 + 18 * 16];
+    lins[z + 4 * 31 + 1] = grbuf[xr + 1 + 18 * 16];
+    lins[z + 4 * 31 + 2] = grbuf[xl + 1];
+    lins[z + 4 * 31 + 3] = grbuf[xr + 1];
+
+    mp3d_synth_pair(&mut dst[r..], nch, lins, 4 * 15 + 1);
+    mp3d_synth_pair(&mut dst[r + 32 * nch..], nch, lins, 4 * 15 + 64 + 1);
+    mp3d_synth_pair(dst, nch, lins, 4 * 15);
+    mp3d_synth_pair(&mut dst[32 * nch..], nch, lins, 4 * 15 + 64);
+
+    for i in (0..15).rev() {
+        lins[z + 4 * i] = grbuf[xl + 18 * (31 - i)];
+        lins[z + 4 * i + 1] = grbuf[xr + 18 * (31 - i)];
+        lins[z + 4 * i + 2] = grbuf[xl + 1 + 18 * (31 - i)];
+        lins[z + 4 * i + 3] = grbuf[xr + 1 + 18 * (31 - i)];
+        lins[z + 4 * (i + 16)] = grbuf[xl + 1 + 18 * (1 + i)];
+        lins[z + 4 * (i + 16) + 1] = grbuf[xr + 1 + 18 * (1 + i)];
+        lins[z - 64 + 4 * i + 2] = grbuf[xl + 18 * (1 + i)];
+        lins[z - 64 + 4 * i + 3] = grbuf[xr + 18 * (1 + i)];
+
+        let w = &SYNTH_WINDOW[(14 - i) * 16..];
+        let mut a = [0f32; 4];
+        let mut b = [0f32; 4];
+        for k in 0..8 {
+            let (w0, w1) = (w[2 * k], w[2 * k + 1]);
+            let vz = z + 4 * i - 64 * k;
+            let vy = 4 * i + 64 * k;
+            for j in 0..4 {
+                let (vz, vy) = (lins[vz + j], lins[vy + j]);
+                if k == 0 {
+                    b[j] = vz * w1 + vy * w0;
+                    a[j] = vz * w0 - vy * w1;
+                } else if k % 2 == 1 {
+                    b[j] += vz * w1 + vy * w0;
+                    a[j] += vy * w1 - vz * w0;
+                } else {
+                    b[j] += vz * w1 + vy * w0;
+                    a[j] += vz * w0 - vy * w1;
+                }
+            }
+        }
+
+        dst[r + (15 - i) * nch] = scale_pcm(a[1]);
+        dst[r + (17 + i) * nch] = scale_pcm(b[1]);
+        dst[(15 - i) * nch] = scale_pcm(a[0]);
+        dst[(17 + i) * nch] = scale_pcm(b[0]);
+        dst[r + (47 - i) * nch] = scale_pcm(a[3]);
+        dst[r + (49 + i) * nch] = scale_pcm(b[3]);
+        dst[(47 - i) * nch] = scale_pcm(a[2]);
+        dst[(49 + i) * nch] = scale_pcm(b[2]);
+    }
+}
+
+/// The persistent state of the decoder and its scratch memory.
+struct Decoder {
+    mdct_overlap: [[f32; 288]; 2],
+    qmf_state: [f32; 960],
+    /// The amount of bytes in the bit reservoir
+    reserv: i32,
+    reserv_buf: [u8; MAX_BITRESERVOIR_BYTES],
+    /// The header of the previous frame, to find the next one without syncing
+    header: Option<Header>,
+    free_format_bytes: usize,
+    /// The frequency lines of both channels of a granule
+    grbuf: [f32; 1152],
+    scf: [f32; 40],
+    /// The history of the synthesis filterbank
+    syn: [f32; 33 * 64],
+    ist_pos: [[u8; 39]; 2],
+    maindata: [u8; MAX_BITRESERVOIR_BYTES + MAX_L3_FRAME_PAYLOAD_BYTES],
+}
+
+impl Decoder {
+    fn new() -> Decoder {
+        Decoder {
+            mdct_overlap: [[0.0; 288]; 2],
+            qmf_state: [0.0; 960],
+            reserv: 0,
+            reserv_buf: [0; MAX_BITRESERVOIR_BYTES],
+            header: None,
+            free_format_bytes: 0,
+            grbuf: [0.0; 1152],
+            scf: [0.0; 40],
+            syn: [0.0; 33 * 64],
+            ist_pos: [[0; 39]; 2],
+            maindata: [0; MAX_BITRESERVOIR_BYTES + MAX_L3_FRAME_PAYLOAD_BYTES],
+        }
+    }
+
+    /// Clears the state, after a frame could not be found where the previous one ended
+    fn reset(&mut self) {
+        self.mdct_overlap = [[0.0; 288]; 2];
+        self.qmf_state = [0.0; 960];
+        self.reserv = 0;
+        self.header = None;
+        self.free_format_bytes = 0;
+    }
+
+    fn save_reservoir(&mut self, pos: i32, limit: i32) {
+        let mut pos = (pos + 7) / 8;
+        let mut remains = (limit / 8 - pos).max(0);
+        if remains > MAX_BITRESERVOIR_BYTES as i32 {
+            pos += remains - MAX_BITRESERVOIR_BYTES as i32;
+            remains = MAX_BITRESERVOIR_BYTES as i32;
+        }
+        let (start, len) = (pos as usize, remains as usize);
+        self.reserv_buf[..len].copy_from_slice(&self.maindata[start..start + len]);
+        self.reserv = remains;
+    }
+
+    /// Copies the part of the reservoir the frame uses and its own main data to `maindata`.
+    /// Returns the length of the main data and whether the reservoir had enough bytes.
+    fn restore_reservoir(&mut self, frame: &Bs, main_data_begin: i32) -> (usize, bool) {
+        let frame_bytes = ((frame.limit - frame.pos) / 8) as usize;
+        let bytes_have = self.reserv.min(main_data_begin) as usize;
+        let reserv_off = (self.reserv - main_data_begin).max(0) as usize;
+        self.maindata[..bytes_have].copy_from_slice(&self.reserv_buf[reserv_off..reserv_off + bytes_have]);
+        let frame_start = (frame.pos / 8) as usize;
+        self.maindata[bytes_have..bytes_have + frame_bytes]
+            .copy_from_slice(&frame.buf[frame_start..frame_start + frame_bytes]);
+        (bytes_have + frame_bytes, self.reserv >= main_data_begin)
+    }
+
+    /// Decodes one granule of the main data into `grbuf`
+    fn l3_decode(&mut self, hdr: Header, bs: &mut Bs, gr_info: &[GrInfo], nch: usize) {
+        for ch in 0..nch {
+            let layer3gr_limit = bs.pos + gr_info[ch].part_23_length as i32;
+            l3_decode_scalefactors(hdr, &mut self.ist_pos[ch], bs, &gr_info[ch], &mut self.scf, ch);
+            l3_huffman(&mut self.grbuf[576 * ch..576 * ch + 576], bs, &gr_info[ch], &self.scf, layer3gr_limit);
+        }
+
+        if hdr.test_i_stereo() {
+            l3_intensity_stereo(&mut self.grbuf, &mut self.ist_pos[1], gr_info, hdr);
+        } else if hdr.is_ms_stereo() {
+            l3_midside_stereo(&mut self.grbuf, 0, 576);
+        }
+
+        for ch in 0..nch {
+            let gr = &gr_info[ch];
+            let grbuf = &mut self.grbuf[576 * ch..576 * ch + 576];
+            let mut aa_bands = 31;
+            let n_long_bands: usize = (if gr.mixed_block_flag != 0 { 2 } else { 0 }) << (hdr.get_my_sample_rate() == 2) as u32;
+
+            if gr.n_short_sfb != 0 {
+                aa_bands = n_long_bands.saturating_sub(1);
+                l3_reorder(&mut grbuf[n_long_bands * 18..], &mut self.syn, &gr.sfbtab[gr.n_long_sfb as usize..]);
+            }
+
+            l3_antialias(grbuf, aa_bands);
+            l3_imdct_gr(grbuf, &mut self.mdct_overlap[ch], gr.block_type, n_long_bands);
+            l3_change_sign(grbuf);
+        }
+    }
+
+    /// Synthesizes the PCM samples of a granule from `grbuf`
+    fn synth_granule(&mut self, nch: usize, pcm: &mut [i16]) {
+        const NBANDS: usize = 18;
+        for ch in 0..nch {
+            mp3d_dct_ii(&mut self.grbuf[576 * ch..], NBANDS);
+        }
+
+        self.syn[..15 * 64].copy_from_slice(&self.qmf_state);
+        for i in (0..NBANDS).step_by(2) {
+            mp3d_synth(&self.grbuf, i, &mut pcm[32 * nch * i..], nch, &mut self.syn[i * 64..]);
+        }
+
+        let tail = &self.syn[NBANDS * 64..NBANDS * 64 + 15 * 64];
+        if nch == 1 {
+            // Only the history of the left channel advances for mono frames
+            for i in (0..15 * 64).step_by(2) {
+                self.qmf_state[i] = tail[i];
+            }
+        } else {
+            self.qmf_state.copy_from_slice(tail);
+        }
+    }
+
+    /// Decodes a Layer III frame, including its header, into `pcm`. Returns whether the frame
+    /// could be decoded, it can't if it is corrupt or needs bytes from the bit reservoir of
+    /// frames that weren't decoded.
+    fn decode_frame(&mut self, frame: &[u8], pcm: &mut [i16]) -> bool {
+        let hdr = Header::read(frame);
+        let nch = hdr.num_channels() as usize;
+        let mut bs_frame = Bs::new(&frame[HDR_SIZE..], frame.len() as i32 - HDR_SIZE as i32);
+        if hdr.is_crc() {
+            bs_frame.get_bits(16);
+        }
+
+        let mut gr_info = [GrInfo::default(); 4];
+        let main_data_begin = match l3_read_side_info(&mut bs_frame, &mut gr_info, hdr) {
+            Some(main_data_begin) if bs_frame.pos <= bs_frame.limit => main_data_begin,
+            _ => {
+                self.reset();
+                return false;
+            }
+        };
+
+        let (len, success) = self.restore_reservoir(&bs_frame, main_data_begin);
+        let maindata = self.maindata;
+        let mut bs = Bs::new(&maindata[..len], len as i32);
+        if success {
+            self.scf = [0.0; 40];
+            self.ist_pos = [[0; 39]; 2];
+            let granules = if hdr.test_mpeg1() { 2 } else { 1 };
+            for (igr, pcm) in pcm.chunks_exact_mut(576 * nch).take(granules).enumerate() {
+                self.grbuf = [0.0; 1152];
+                self.l3_decode(hdr, &mut bs, &gr_info[igr * nch..], nch);
+                self.synth_granule(nch, pcm);
+            }
+        }
+        self.save_reservoir(bs.pos, bs.limit);
+        success
+    }
+}
+
+/// Whether the frames following the one at the start of `bytes` are of the same stream
+fn mp3d_match_frame(bytes: &[u8], frame_bytes: usize) -> bool {
+    let first = Header::read(bytes);
+    let mut i = 0;
+    for nmatch in 0..MAX_FRAME_SYNC_MATCHES {
+        let h = Header::read(&bytes[i..]);
+        i += h.frame_bytes(frame_bytes) + h.padding();
+        if i + HDR_SIZE > bytes.len() {
+            return nmatch > 0;
+        }
+        if !first.compare(Header::read(&bytes[i..])) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Finds the next frame in `bytes`, returns its offset and size
+fn mp3d_find_frame(bytes: &[u8], free_format_bytes: &mut usize) -> Option<(usize, usize)> {
+    let mut i = 0;
+    while i + HDR_SIZE < bytes.len() {
+        let buf = &bytes[i..];
+        let h = Header::read(buf);
+        if h.valid() {
+            let mut frame_bytes = h.frame_bytes(*free_format_bytes);
+            let mut frame_and_padding = frame_bytes + h.padding();
+
+            // The size of free format frames is the distance to the next matching header
+            let mut k = HDR_SIZE;
+            while frame_bytes == 0 && k < MAX_FREE_FORMAT_FRAME_SIZE && i + 2 * k + HDR_SIZE < bytes.len() {
+                let next = Header::read(&buf[k..]);
+                if h.compare(next) {
+                    let fb = k - h.padding();
+                    let nextfb = fb + next.padding();
+                    if i + k + nextfb + HDR_SIZE <= bytes.len() && h.compare(Header::read(&buf[k + nextfb..])) {
+                        frame_and_padding = k;
+                        frame_bytes = fb;
+                        *free_format_bytes = fb;
+                    }
+                }
+                k += 1;
+            }
+
+            if (frame_bytes != 0 && i + frame_and_padding <= bytes.len() && mp3d_match_frame(buf, frame_bytes))
+                || (i == 0 && frame_and_padding == bytes.len())
+            {
+                return Some((i, frame_and_padding));
+            }
+            *free_format_bytes = 0;
+        }
+        i += 1;
+    }
+    None
+}
+
+/// The amount of frames and the gapless info of a Xing, Info or LAME header.
+struct VbrTag {
+    /// The amount of audio frames in the stream, without the frame of the tag itself
+    frames: u32,
+    /// The amount of samples per channel to skip at the start
+    delay: u32,
+    /// The amount of samples per channel to drop at the end
+    padding: u32,
+}
+
+/// Parses the Xing or Info tag in the first frame of a stream, which LAME and other encoders
+/// write in place of the audio data of a silent frame.
+fn check_vbr_tag(frame: &[u8]) -> Option<VbrTag> {
+    const FRAMES_FLAG: u8 = 1;
+    const BYTES_FLAG: u8 = 2;
+    const TOC_FLAG: u8 = 4;
+    const VBR_SCALE_FLAG: u8 = 8;
+
+    let hdr = Header::read(frame);
+    let mut bs = Bs::new(&frame[HDR_SIZE..], frame.len() as i32 - HDR_SIZE as i32);
+    if hdr.is_crc() {
+        bs.get_bits(16);
+    }
+    let mut gr_info = [GrInfo::default(); 4];
+    l3_read_side_info(&mut bs, &mut gr_info, hdr)?;
+
+    let mut pos = HDR_SIZE + bs.pos as usize / 8;
+    let tag = frame.get(pos..pos + 12)?;
+    if &tag[0..4] != b"Xing" && &tag[0..4] != b"Info" {
+        return None;
+    }
+    let flags = tag[7];
+    if flags & FRAMES_FLAG == 0 {
+        return None;
+    }
+    let frames = u32::from_be_bytes(tag[8..12].try_into().unwrap());
+    pos += 12;
+    if flags & BYTES_FLAG != 0 {
+        pos += 4;
+    }
+    if flags & TOC_FLAG != 0 {
+        pos += 100;
+    }
+    if flags & VBR_SCALE_FLAG != 0 {
+        pos += 4;
+    }
+    let (mut delay, mut padding) = (0, 0);
+    // An extension like the LAME header: the encoder delay and padding are 12 bits each
+    if frame.get(pos).is_some_and(|&byte| byte != 0) {
+        if let Some(gapless) = frame.get(pos + 21..pos + 24) {
+            delay = ((gapless[0] as u32) << 4 | (gapless[1] as u32) >> 4) + DECODER_DELAY;
+            padding = (((gapless[1] as u32 & 0xF) << 8) | gapless[2] as u32).saturating_sub(DECODER_DELAY);
+        }
+    }
+    Some(VbrTag { frames, delay, padding })
+}
+
+/// Decodes an MP3 stream into a 16-bit PCM `WaveFile`.
+pub(crate) fn decode(bytes: &[u8]) -> Result<WaveFile, WaveError> {
+    let mut bytes = skip_id3v2(bytes);
+    // An ID3v1 tag is 128 bytes at the end of the file
+    if bytes.len() >= 128 && bytes[bytes.len() - 128..].starts_with(b"TAG") {
+        bytes = &bytes[..bytes.len() - 128];
+    }
+
+    let mut decoder = Decoder::new();
+    // The header of the first frame, which the channels and sample rate are taken from
+    let mut first: Option<Header> = None;
+    let mut vbr_tag = None;
+    let mut data: Vec<u8> = Vec::new();
+    let mut pcm = [0i16; 1152 * 2];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        // The next frame usually starts right where the previous one ended
+        let next = decoder.header.filter(|&prev| rest.len() >= HDR_SIZE && prev.compare(Header::read(rest)));
+        let next = next.and_then(|_| {
+            let h = Header::read(rest);
+            let size = h.frame_bytes(decoder.free_format_bytes) + h.padding();
+            (size > HDR_SIZE && size <= rest.len()).then_some((0, size))
+        });
+        let (offset, size) = match next {
+            Some(frame) => frame,
+            None => {
+                decoder.reset();
+                match mp3d_find_frame(rest, &mut decoder.free_format_bytes) {
+                    Some(frame) => frame,
+                    None => break,
+                }
+            }
+        };
+        let frame = &rest[offset..offset + size];
+        pos += offset + size;
+        let hdr = Header::read(frame);
+        decoder.header = Some(hdr);
+        if hdr.get_layer() != 1 {
+            return Err(WaveError::Mp3("only Layer III streams are supported"));
+        }
+
+        if first.is_none() {
+            first = Some(hdr);
+            vbr_tag = check_vbr_tag(frame);
+            if vbr_tag.is_some() {
+                // The frame of the tag doesn't contain audio
+                continue;
+            }
+        }
+        let stream = first.unwrap_or(hdr);
+        // The channels or sample rate can't change within a `WaveFile`, frames that differ from
+        // the first one are skipped
+        if hdr.num_channels() != stream.num_channels() || hdr.sample_rate_hz() != stream.sample_rate_hz() {
+            continue;
+        }
+        if decoder.decode_frame(frame, &mut pcm) {
+            let samples = hdr.frame_samples() * hdr.num_channels();
+            data.extend(pcm[..samples as usize].iter().flat_map(|sample| sample.to_le_bytes()));
+        }
+    }
+
+    let Some(first) = first else {
+        return Err(WaveError::Mp3("no MPEG audio frames found"));
+    };
+    let mut wave = WaveFile::new(AudioFormat::PCM, first.num_channels() as u16, first.sample_rate_hz(), 16);
+    if let Some(tag) = vbr_tag {
+        // Drop the encoder and decoder delay at the start and the padding at the end
+        let block_align = wave.block_align() as usize;
+        let total = (tag.frames as u64 * first.frame_samples() as u64).saturating_sub(tag.delay as u64 + tag.padding as u64);
+        let start = (tag.delay as usize * block_align).min(data.len());
+        let end = (start as u64 + total * block_align as u64).min(data.len() as u64) as usize;
+        data.truncate(end);
+        data.drain(..start);
+    }
+    wave.add_bytes(&mut data);
+    Ok(wave)
+}
+
+// MP3
+impl WaveFile {
+    /// Reads an MP3 file and decodes it into a 16-bit PCM `WaveFile` with the channels and sample
+    /// rate of the stream.
+    ///
+    /// Only available with the `mp3` feature.
+    ///
+    /// # Errors
+    /// Returns the same errors as [fs::read()](std::fs::read) and
+    /// [from_mp3_bytes](WaveFile::from_mp3_bytes).
+    pub fn from_mp3_file(file_path: &str) -> Result<WaveFile, WaveError> {
+        let file_content = fs::read(file_path)?;
+        WaveFile::from_mp3_bytes(&file_content)
+    }
+
+    /// Decodes an in-memory MP3 file (MPEG-1, 2 or 2.5 Layer III) into a 16-bit PCM `WaveFile`
+    /// with the channels and sample rate of the stream. All frames are concatenated, ID3v2 and
+    /// ID3v1 tags are skipped.
+    ///
+    /// When the first frame holds a Xing or Info tag, like LAME writes for both variable and
+    /// constant bit rate files, the tag frame is skipped. With its gapless info, the encoder
+    /// delay and padding are removed so the `WaveFile` has as many frames as the original audio.
+    /// Frames that are corrupt, or whose channels or sample rate differ from the first frame,
+    /// are left out. Only available with the `mp3` feature.
+    ///
+    /// # Errors
+    /// [WaveError::Mp3] if no MPEG audio frames are found in `bytes`, or the stream is a Layer I
+    /// or Layer II stream.
+    ///
+    /// # Examples
+    /// Two silent frames of a 32 kbps mono stream at 32000 Hz:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut frame = vec![0xFF, 0xFB, 0x18, 0xC0];
+    /// frame.resize(144, 0);
+    ///
+    /// let wave = WaveFile::from_mp3_bytes(&frame.repeat(2)).unwrap();
+    /// assert_eq!((wave.num_channels(), wave.sample_rate(), wave.bits_per_sample()), (1, 32000, 16));
+    /// assert_eq!(wave.samples_i16().unwrap(), [0; 2 * 1152]);
+    ///
+    /// assert!(matches!(WaveFile::from_mp3_bytes(b"RIFF"), Err(WaveError::Mp3(_))));
+    /// ```
+    pub fn from_mp3_bytes(bytes: &[u8]) -> Result<WaveFile, WaveError> {
+        decode(bytes)
+    }
+}
@@ -0,0 +1,169 @@
+//! Splitting work over threads with the `parallel` feature. Without it, the same functions run
+//! the work on the current thread, so both give the same results.
+//!
+//! Work is done in rounds, after each of which the current thread reports progress and can stop.
+//! The threads are started once for all rounds of a call, and are sent their part of every round.
+
+use std::ops::Range;
+#[cfg(feature = "parallel")]
+use std::sync::{mpsc, Arc};
+
+/// The least amount of items given to a thread, below which starting one isn't worth it
+#[cfg(feature = "parallel")]
+const MIN_PART: u64 = 1 << 12;
+
+/// The amount of threads work can be split over, including the current thread
+#[cfg(feature = "parallel")]
+fn threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Splits `items` into rounds of `round` items, and every round into consecutive parts.
+///
+/// For every round, `prepare` runs on the current thread and returns data the parts of the round
+/// share, `f` runs on every part, and `done` gets the end of the round and the results of its
+/// parts in order. An error of `done` stops the remaining rounds and is returned. With the
+/// `parallel` feature the parts run on threads of their own.
+pub(crate) fn map_rounds<S: Send + Sync, T: Send, E>(
+    items: Range<u64>,
+    round: u64,
+    mut prepare: impl FnMut(Range<u64>) -> S,
+    f: impl Fn(&S, Range<u64>) -> T + Sync,
+    mut done: impl FnMut(u64, Vec<T>) -> Result<(), E>,
+) -> Result<(), E> {
+    let rounds = (items.start..items.end).step_by(round as usize).map(|start| start..(start + round).min(items.end));
+    #[cfg(feature = "parallel")]
+    {
+        let len = round.min(items.end.saturating_sub(items.start));
+        let parts = (threads() as u64).min(len / MIN_PART).max(1);
+        if parts > 1 {
+            let f = &f;
+            let workers = (1..parts).map(|_| move |(shared, part): (Arc<S>, Range<u64>)| f(&shared, part)).collect();
+            return with_workers(workers, |workers| {
+                for round in rounds {
+                    let shared = Arc::new(prepare(round.clone()));
+                    let part_len = (round.end - round.start).div_ceil(parts);
+                    let part = |i: u64| (round.start + i * part_len).min(round.end)..(round.start + (i + 1) * part_len).min(round.end);
+                    workers.send((1..parts).map(|i| (Arc::clone(&shared), part(i))));
+                    let mut results = vec![f(&shared, part(0))];
+                    // A thread panicked, which is passed on when the threads are joined
+                    let Some(rest) = workers.receive() else {
+                        return Ok(());
+                    };
+                    results.extend(rest);
+                    done(round.end, results)?;
+                }
+                Ok(())
+            });
+        }
+    }
+    for round in rounds {
+        let shared = prepare(round.clone());
+        done(round.end, vec![f(&shared, round)])?;
+    }
+    Ok(())
+}
+
+/// Runs `f` on every item for every round of `round` items of `items`, and calls `done` with the
+/// end of every round. An error of `done` stops the remaining rounds and is returned. A round
+/// runs even if `items` is empty, so `f` always sees the end of the items.
+///
+/// With the `parallel` feature the items are split over as many threads as there are cores, and
+/// every thread keeps its items for all rounds.
+pub(crate) fn for_each_round<T: Send, E>(
+    items: &mut [T],
+    range: Range<u64>,
+    round: u64,
+    f: impl Fn(&mut T, Range<u64>) + Sync,
+    mut done: impl FnMut(u64) -> Result<(), E>,
+) -> Result<(), E> {
+    let rounds = (range.start..range.end.max(range.start + 1))
+        .step_by(round as usize)
+        .map(|start| start..(start + round).min(range.end));
+    #[cfg(feature = "parallel")]
+    {
+        let threads = threads();
+        if threads > 1 && items.len() > 1 {
+            let f = &f;
+            let mut parts = items.chunks_mut(items.len().div_ceil(threads));
+            let first = parts.next().unwrap();
+            let workers = parts
+                .map(|part| move |round: Range<u64>| part.iter_mut().for_each(|item| f(item, round.clone())))
+                .collect();
+            return with_workers(workers, |workers| {
+                for round in rounds {
+                    workers.send(std::iter::repeat(round.clone()));
+                    first.iter_mut().for_each(|item| f(item, round.clone()));
+                    // A thread panicked, which is passed on when the threads are joined
+                    if workers.receive().is_none() {
+                        return Ok(());
+                    }
+                    done(round.end)?;
+                }
+                Ok(())
+            });
+        }
+    }
+    for round in rounds {
+        items.iter_mut().for_each(|item| f(item, round.clone()));
+        done(round.end)?;
+    }
+    Ok(())
+}
+
+/// The channels to the threads started by [with_workers]
+#[cfg(feature = "parallel")]
+struct Workers<J, R> {
+    jobs: Vec<mpsc::Sender<J>>,
+    results: Vec<mpsc::Receiver<R>>,
+}
+
+#[cfg(feature = "parallel")]
+impl<J, R> Workers<J, R> {
+    /// Sends a job to every thread, in order
+    fn send(&self, jobs: impl Iterator<Item = J>) {
+        for (sender, job) in self.jobs.iter().zip(jobs) {
+            // A thread that panicked is noticed by `receive`
+            let _ = sender.send(job);
+        }
+    }
+
+    /// Waits for the result of every thread, in order. Returns `None` if a thread panicked.
+    fn receive(&self) -> Option<Vec<R>> {
+        self.results.iter().map(|receiver| receiver.recv().ok()).collect()
+    }
+}
+
+/// Starts a thread for every function in `workers`, which runs it on every job it is sent, and
+/// returns the result of `run`. The threads stop once `run` returns, and a panic of one of them is
+/// passed on.
+#[cfg(feature = "parallel")]
+fn with_workers<J: Send, R: Send, O>(
+    workers: Vec<impl FnMut(J) -> R + Send>,
+    run: impl FnOnce(&Workers<J, R>) -> O,
+) -> O {
+    std::thread::scope(|scope| {
+        let mut channels = Workers { jobs: Vec::new(), results: Vec::new() };
+        let mut handles = Vec::new();
+        for mut worker in workers {
+            let (job_sender, jobs) = mpsc::channel();
+            let (results, result_receiver) = mpsc::channel();
+            handles.push(scope.spawn(move || {
+                for job in jobs {
+                    if results.send(worker(job)).is_err() {
+                        break;
+                    }
+                }
+            }));
+            channels.jobs.push(job_sender);
+            channels.results.push(result_receiver);
+        }
+        let out = run(&channels);
+        // Closing the job channels stops the threads
+        drop(channels);
+        for handle in handles {
+            handle.join().unwrap_or_else(|err| std::panic::resume_unwind(err));
+        }
+        out
+    })
+}
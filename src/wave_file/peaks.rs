@@ -0,0 +1,203 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use super::sample;
+use super::{WaveError, WaveFile, WaveReader};
+
+/// The amount of frames read at once by [peaks_from_file]
+const READ_FRAMES: usize = 1 << 14;
+
+// Peaks
+impl WaveFile {
+    /// Returns the lowest and highest normalized sample of every channel in `buckets`
+    /// consecutive ranges of frames, as `(min, max)` pairs from -1.0 to 1.0: the data audio
+    /// editors draw a waveform overview from.
+    ///
+    /// The result contains `buckets` pairs for every channel. The buckets cover every frame
+    /// exactly once, in order, and differ in length by at most a frame; bucket `i` holds the
+    /// frames `f` for which `f * buckets / num_frames` is `i`. A bucket without frames, when
+    /// there are more buckets than frames, is `(0.0, 0.0)`.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// let samples: Vec<i16> = (0..10).flat_map(|i| [i * 1024, -i * 2048]).collect();
+    /// wave.add_samples_i16(&samples).unwrap();
+    ///
+    /// // Frames 0..4, 4..7 and 7..10
+    /// let peaks = wave.peaks(3).unwrap();
+    /// assert_eq!(peaks[0], [(0.0, 0.09375), (0.125, 0.1875), (0.21875, 0.28125)]);
+    /// assert_eq!(peaks[1], [(-0.1875, 0.0), (-0.375, -0.25), (-0.5625, -0.4375)]);
+    ///
+    /// // More buckets than frames
+    /// let peaks = wave.peaks(20).unwrap();
+    /// assert_eq!(peaks[0][..3], [(0.0, 0.0), (0.0, 0.0), (0.03125, 0.03125)]);
+    /// ```
+    pub fn peaks(&self, buckets: usize) -> Result<Vec<Vec<(f32, f32)>>, WaveError> {
+        let mut peaks = Peaks::new(self.num_channels as usize, self.num_frames(), buckets);
+        self.for_each_sample(|frame, channel, sample| peaks.add(frame, channel, sample))?;
+        Ok(peaks.finish())
+    }
+
+    /// Draws the waveform of every channel with block characters, `width` columns wide and
+    /// `height` lines high, for a quick look at the audio in a terminal or a log.
+    ///
+    /// Every column shows the range between the lowest and highest sample of a bucket of
+    /// [peaks](WaveFile::peaks), with full scale at the top and bottom line. Every line has two
+    /// halves, drawn with `▀`, `▄` or `█`, so silence shows as a line in the middle and clipped
+    /// audio fills the whole column. The channels are drawn below each other, separated by a line
+    /// of `─`. Every line ends with a newline, and the same audio always gives the same drawing.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// // Silence, full scale, a positive and a negative signal
+    /// wave.add_samples_i16(&[0, 0, i16::MIN, i16::MAX, 8192, 16384, -16384, -8192]).unwrap();
+    ///
+    /// assert_eq!(wave.render_ascii(4, 2).unwrap(), " █▄ \n▀█ █\n");
+    ///
+    /// let stereo = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// assert_eq!(stereo.render_ascii(3, 2).unwrap(), "   \n▀▀▀\n───\n   \n▀▀▀\n");
+    /// ```
+    pub fn render_ascii(&self, width: usize, height: usize) -> Result<String, WaveError> {
+        let halves = 2 * height;
+        // The first half line, from the top, that shows `value`
+        let half = |value: f32| {
+            let half = ((1.0 - value) / 2.0 * halves as f32).floor().max(0.0) as usize;
+            half.min(halves.saturating_sub(1))
+        };
+        let mut drawing = String::new();
+        for (channel, peaks) in self.peaks(width)?.iter().enumerate() {
+            if channel > 0 {
+                drawing.extend(std::iter::repeat_n('─', width));
+                drawing.push('\n');
+            }
+            for line in 0..height {
+                for &(min, max) in peaks {
+                    let shown = half(max)..=half(min);
+                    drawing.push(match (shown.contains(&(2 * line)), shown.contains(&(2 * line + 1))) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    });
+                }
+                drawing.push('\n');
+            }
+        }
+        Ok(drawing)
+    }
+}
+
+/// Returns the waveform peaks of the file at `file_path` like [WaveFile::peaks], reading the
+/// audio data a block at a time, so the whole file is never in memory.
+///
+/// The amount of frames is taken from the size of the data chunk.
+///
+/// # Errors
+/// - the errors of [WaveReader::new]
+/// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+///   the file can't be decoded
+/// - [WaveError::Truncated] if the file ends before the end of the data chunk
+/// - [WaveError::Io] if the file can't be read
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::fs;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 24);
+/// let samples: Vec<I24> = (0..100_000).map(|i| I24::new(i % 1000 * 8000 - 4_000_000).unwrap()).collect();
+/// wave.add_samples(&samples).unwrap();
+/// let path = std::env::temp_dir().join("jaudio-peaks-from-file.wav");
+/// let path = path.to_str().unwrap();
+/// fs::write(path, wave.to_bytes()).unwrap();
+///
+/// assert_eq!(peaks_from_file(path, 800).unwrap(), wave.peaks(800).unwrap());
+/// # fs::remove_file(path).unwrap();
+/// ```
+pub fn peaks_from_file(file_path: &str, buckets: usize) -> Result<Vec<Vec<(f32, f32)>>, WaveError> {
+    let mut reader = WaveReader::new(BufReader::new(File::open(file_path)?))?;
+    let (format, bits) = (reader.audio_format(), reader.bits_per_sample());
+    sample::check_supported(format, bits)?;
+    let block_align = reader.block_align().max(1) as usize;
+    let sample_len = (bits / 8) as usize;
+    let frames = reader.data_len() / block_align as u64;
+
+    let mut peaks = Peaks::new(reader.num_channels() as usize, frames, buckets);
+    let mut buf = vec![0; READ_FRAMES * block_align];
+    let mut frame = 0;
+    loop {
+        let len = reader.read_block(&mut buf)?;
+        if len == 0 {
+            break;
+        }
+        for data in buf[..len].chunks_exact(block_align) {
+            for (channel, bytes) in data.chunks_exact(sample_len).enumerate() {
+                peaks.add(frame, channel, sample::decode(bytes, format, bits));
+            }
+            frame += 1;
+        }
+    }
+    Ok(peaks.finish())
+}
+
+/// Collects the lowest and highest sample of every channel in the buckets of [WaveFile::peaks],
+/// from samples given in order
+struct Peaks {
+    frames: u64,
+    buckets: usize,
+    /// The `(min, max)` pairs of every channel, infinite for buckets without samples
+    peaks: Vec<Vec<(f64, f64)>>,
+    /// The bucket of the last sample
+    bucket: usize,
+    /// The first frame after the current bucket
+    end: u64,
+}
+
+impl Peaks {
+    fn new(channels: usize, frames: u64, buckets: usize) -> Peaks {
+        let mut peaks = Peaks {
+            frames,
+            buckets,
+            peaks: vec![vec![(f64::INFINITY, f64::NEG_INFINITY); buckets]; channels],
+            bucket: 0,
+            end: 0,
+        };
+        peaks.end = peaks.bucket_end(0);
+        peaks
+    }
+
+    /// Returns the first frame after `bucket`
+    fn bucket_end(&self, bucket: usize) -> u64 {
+        if self.buckets == 0 {
+            return u64::MAX;
+        }
+        ((bucket as u128 + 1) * self.frames as u128).div_ceil(self.buckets as u128) as u64
+    }
+
+    fn add(&mut self, frame: u64, channel: usize, sample: f64) {
+        while frame >= self.end {
+            self.bucket += 1;
+            self.end = self.bucket_end(self.bucket);
+        }
+        if let Some((min, max)) = self.peaks.get_mut(channel).and_then(|peaks| peaks.get_mut(self.bucket)) {
+            *min = min.min(sample);
+            *max = max.max(sample);
+        }
+    }
+
+    fn finish(self) -> Vec<Vec<(f32, f32)>> {
+        let pair = |(min, max): (f64, f64)| if min > max { (0.0, 0.0) } else { (min as f32, max as f32) };
+        self.peaks.into_iter().map(|peaks| peaks.into_iter().map(pair).collect()).collect()
+    }
+}
@@ -0,0 +1,266 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read};
+use std::ops::ControlFlow;
+
+use super::convert::Tpdf;
+use super::progress::{self, Progress};
+use super::resample::Resampler;
+use super::{
+    AudioFormat, ClipPolicy, DitherMode, DownmixMode, ProgressUpdate, ResampleQuality, WaveError, WaveFile, WaveReader,
+    WaveSpec, WaveWriter,
+};
+
+/// An operation applied to the audio by [process_file], mirroring a method of [WaveFile].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaveOp {
+    /// Amplifies the audio by `db` decibels, see [WaveFile::apply_gain_db].
+    Gain {
+        /// The gain in decibels, negative to attenuate
+        db: f32,
+        /// What happens to samples that end up out of range
+        clip: ClipPolicy,
+    },
+    /// Converts the samples to another format and bit depth, see [WaveFile::convert_to].
+    Convert {
+        /// The format of the converted samples
+        format: AudioFormat,
+        /// The bits per sample of the converted samples
+        bits_per_sample: u16,
+        /// The dither applied when the bit depth is reduced
+        dither: DitherMode,
+    },
+    /// Combines the channels into one, see [WaveFile::to_mono].
+    Downmix(DownmixMode),
+    /// Resamples the audio to another sample rate, see [WaveFile::resample].
+    Resample {
+        /// The new sample rate in Hz
+        sample_rate: u32,
+        /// The interpolation used
+        quality: ResampleQuality,
+    },
+}
+
+/// A [WaveOp] with the state it carries from block to block
+enum Stage {
+    Gain { db: f32, clip: ClipPolicy, frames: u64 },
+    Convert { format: AudioFormat, bits_per_sample: u16, noise: Option<Tpdf> },
+    Downmix(DownmixMode),
+    Resample { sample_rate: u32, resampler: Resampler },
+}
+
+impl Stage {
+    /// Creates the stage of `op` for audio of `spec`, and changes `spec` to the spec of its output
+    fn new(op: WaveOp, spec: &mut WaveSpec) -> Result<Stage, WaveError> {
+        let stage = match op {
+            WaveOp::Gain { db, clip } => Stage::Gain { db, clip, frames: 0 },
+            WaveOp::Convert { format, bits_per_sample, dither } => {
+                let noise = WaveFile::with_spec(*spec).dither_noise(format, bits_per_sample, dither);
+                spec.format = format;
+                spec.bits_per_sample = bits_per_sample;
+                Stage::Convert { format, bits_per_sample, noise }
+            }
+            WaveOp::Downmix(mode) => {
+                spec.channels = 1;
+                Stage::Downmix(mode)
+            }
+            WaveOp::Resample { sample_rate, quality } => {
+                let resampler = Resampler::new(spec.channels, spec.sample_rate, sample_rate, quality);
+                spec.sample_rate = sample_rate;
+                Stage::Resample { sample_rate, resampler }
+            }
+        };
+        spec.validate()?;
+        Ok(stage)
+    }
+
+    /// Applies the stage to the next block of audio. With `finish`, the block is the last one,
+    /// and the audio the stage holds back is added to it.
+    fn apply(&mut self, mut block: WaveFile, finish: bool) -> Result<WaveFile, WaveError> {
+        match self {
+            Stage::Gain { db, clip, frames } => {
+                block.apply_gain_db(*db, *clip).map_err(|err| match err {
+                    WaveError::Clipping { frame } => WaveError::Clipping { frame: *frames + frame },
+                    err => err,
+                })?;
+                *frames += block.num_frames();
+                Ok(block)
+            }
+            Stage::Convert { format, bits_per_sample, noise } => {
+                let mut progress = Progress::new(block.num_frames(), progress::ignore);
+                block.convert_with_noise(*format, *bits_per_sample, noise.as_mut(), &mut progress)
+            }
+            Stage::Downmix(mode) => block.to_mono(*mode),
+            Stage::Resample { sample_rate, resampler } => {
+                let mut out = Vec::new();
+                resampler.push(&block.samples_as::<f64>()?, &mut out);
+                if finish {
+                    resampler.finish(&mut out);
+                }
+                let mut wave = block.empty_like(block.audio_format(), block.bits_per_sample);
+                wave.sample_rate = *sample_rate;
+                wave.byte_rate = *sample_rate * wave.block_align as u32;
+                wave.add_samples(&out)?;
+                Ok(wave)
+            }
+        }
+    }
+}
+
+/// Reads the wave file at `input`, applies `ops` to its audio in order, and writes the result to
+/// a new wave file at `output`, replacing an existing file.
+///
+/// The audio is read with a [WaveReader] and written with a [WaveWriter] `block_frames` frames at
+/// a time, so the memory used doesn't grow with the length of the files. Operations that need the
+/// audio around a frame, like resampling, carry it over from one block to the next: the result is
+/// the same as applying the methods of [WaveFile] to the whole file. Only the audio is copied,
+/// the metadata of `input` is not.
+///
+/// # Errors
+/// - the errors of [WaveReader::new] and [WaveSpec::new] if `input` can't be read
+/// - the errors of [WaveSpec::new] if an operation results in a spec that isn't valid, like a
+///   sample rate of 0 or an unsupported format: nothing is written then
+/// - [WaveError::Clipping] if a sample clips in a [WaveOp::Gain] with [ClipPolicy::Error]
+/// - [WaveError::Io] if a file can't be read or written
+///
+/// `output` is removed when an error is returned after it was created.
+///
+/// ## Panics
+/// If `block_frames` is 0.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::fs;
+/// let sine: Vec<f32> = (0..4800)
+///     .map(|i| (i as f32 * 1000.0 * std::f32::consts::TAU / 48000.0).sin() * 0.5)
+///     .collect();
+/// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 48000, 32);
+/// wave.add_samples_f32(&sine).unwrap();
+/// let input = std::env::temp_dir().join("jaudio-process-file-in.wav");
+/// let output = std::env::temp_dir().join("jaudio-process-file-out.wav");
+/// let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+/// fs::write(input, wave.to_bytes()).unwrap();
+///
+/// let ops = [
+///     WaveOp::Gain { db: -6.0, clip: ClipPolicy::Clip },
+///     WaveOp::Resample { sample_rate: 44100, quality: ResampleQuality::Sinc },
+///     WaveOp::Downmix(DownmixMode::Average),
+///     WaveOp::Convert { format: AudioFormat::PCM, bits_per_sample: 16, dither: DitherMode::Triangular },
+/// ];
+/// process_file(input, output, &ops, 256).unwrap();
+///
+/// // The same as processing the file in memory
+/// wave.apply_gain_db(-6.0, ClipPolicy::Clip).unwrap();
+/// let expected = wave
+///     .resample(44100, ResampleQuality::Sinc)
+///     .to_mono(DownmixMode::Average)
+///     .unwrap()
+///     .convert_to(AudioFormat::PCM, 16, DitherMode::Triangular)
+///     .unwrap();
+/// let processed = WaveFile::from_file(output).unwrap();
+/// assert_eq!(processed.spec(), WaveSpec::new(AudioFormat::PCM, 1, 44100, 16).unwrap());
+/// assert_eq!(processed.samples_i16().unwrap(), expected.samples_i16().unwrap());
+///
+/// let ops = [WaveOp::Resample { sample_rate: 0, quality: ResampleQuality::Linear }];
+/// assert!(matches!(process_file(input, output, &ops, 256), Err(WaveError::InvalidSpec(_))));
+/// # fs::remove_file(input).unwrap();
+/// # fs::remove_file(output).unwrap();
+/// ```
+pub fn process_file(input: &str, output: &str, ops: &[WaveOp], block_frames: usize) -> Result<(), WaveError> {
+    process_file_with_progress(input, output, ops, block_frames, progress::ignore)
+}
+
+/// Processes a file like [process_file], calling `progress` as the frames of `input` are
+/// processed (see [ProgressUpdate]).
+///
+/// # Errors
+/// The errors of [process_file], and [WaveError::Cancelled] if `progress` cancels processing.
+/// `output` is removed when an error is returned after it was created, so a cancelled run doesn't
+/// leave a partial file behind.
+///
+/// ## Panics
+/// If `block_frames` is 0.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::fs;
+/// # use std::ops::ControlFlow;
+/// # use std::path::Path;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+/// wave.add_samples_i16(&vec![1000; 8000]).unwrap();
+/// let input = std::env::temp_dir().join("jaudio-process-file-with-progress-in.wav");
+/// let output = std::env::temp_dir().join("jaudio-process-file-with-progress-out.wav");
+/// let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+/// fs::write(input, wave.to_bytes()).unwrap();
+///
+/// let ops = [WaveOp::Gain { db: 6.0, clip: ClipPolicy::Clip }];
+/// let mut last = None;
+/// process_file_with_progress(input, output, &ops, 1000, |update| {
+///     last = Some(update);
+///     ControlFlow::Continue(())
+/// })
+/// .unwrap();
+/// assert_eq!(last, Some(ProgressUpdate { frames_done: 8000, total_frames: 8000 }));
+///
+/// let result = process_file_with_progress(input, output, &ops, 1000, |_| ControlFlow::Break(()));
+/// assert!(matches!(result, Err(WaveError::Cancelled)));
+/// assert!(!Path::new(output).exists());
+/// # fs::remove_file(input).unwrap();
+/// ```
+pub fn process_file_with_progress(
+    input: &str,
+    output: &str,
+    ops: &[WaveOp],
+    block_frames: usize,
+    progress: impl FnMut(ProgressUpdate) -> ControlFlow<()>,
+) -> Result<(), WaveError> {
+    assert!(block_frames > 0, "can't process blocks of 0 frames");
+    let mut reader = WaveReader::new(BufReader::new(File::open(input)?))?;
+    let input_spec = reader.spec();
+    input_spec.validate()?;
+    let mut spec = input_spec;
+    let mut stages = ops.iter().map(|op| Stage::new(*op, &mut spec)).collect::<Result<Vec<_>, _>>()?;
+
+    let writer = WaveWriter::with_spec(BufWriter::new(File::create(output)?), spec)?;
+    let total_frames = reader.data_len() / reader.block_align().max(1) as u64;
+    let mut progress = Progress::new(total_frames, progress);
+    let result = process_blocks(&mut reader, writer, &mut stages, block_frames, input_spec, &mut progress);
+    if result.is_err() {
+        // The writer is gone, so the file is closed
+        let _ = fs::remove_file(output);
+    }
+    result
+}
+
+/// Reads the blocks of `reader`, passes them through `stages` and writes them to `writer`
+fn process_blocks<R: Read>(
+    reader: &mut WaveReader<R>,
+    mut writer: WaveWriter<BufWriter<File>>,
+    stages: &mut [Stage],
+    block_frames: usize,
+    input_spec: WaveSpec,
+    progress: &mut Progress<impl FnMut(ProgressUpdate) -> ControlFlow<()>>,
+) -> Result<(), WaveError> {
+    let mut buf = vec![0; block_frames * reader.block_align() as usize];
+    let mut frames_done = 0;
+    loop {
+        let len = reader.read_block(&mut buf)?;
+        let finish = len == 0;
+        let mut block = WaveFile::with_spec(input_spec);
+        block.audio_byte_data.set(buf[..len].to_vec());
+        frames_done += block.num_frames();
+        for stage in stages.iter_mut() {
+            block = stage.apply(block, finish)?;
+        }
+        for segment in block.audio_byte_data.segments() {
+            writer.write_bytes(segment)?;
+        }
+        if finish {
+            break;
+        }
+        progress.update(frames_done)?;
+    }
+    writer.finalize()?;
+    progress.finish()
+}
@@ -0,0 +1,102 @@
+//! Reporting the progress of long-running operations.
+
+use std::ops::ControlFlow;
+
+use super::WaveError;
+
+/// The least amount of frames processed between two calls of a progress callback
+pub(crate) const INTERVAL: u64 = 1 << 16;
+
+/// How far a long-running operation got, passed to the progress callback of methods like
+/// [WaveFile::resample_with_progress](super::WaveFile::resample_with_progress).
+///
+/// The callback is called every 65536 frames and once all frames are processed, so it can update
+/// a progress bar without slowing the operation down. Returning [ControlFlow::Break] cancels the
+/// operation, which then returns [WaveError::Cancelled].
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::ops::ControlFlow;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 48000, 16);
+/// wave.add_samples_i16(&vec![1000; 200_000]).unwrap();
+///
+/// let mut updates = Vec::new();
+/// let converted = wave.convert_bit_depth_with_progress(24, DitherMode::None, |update| {
+///     updates.push(update.frames_done);
+///     ControlFlow::Continue(())
+/// });
+/// assert_eq!(converted.unwrap().bits_per_sample(), 24);
+/// assert_eq!(updates, [65536, 131072, 196608, 200_000]);
+///
+/// // Cancelling halfway
+/// let result = wave.resample_with_progress(44100, ResampleQuality::Linear, |update| {
+///     if update.fraction() < 0.5 { ControlFlow::Continue(()) } else { ControlFlow::Break(()) }
+/// });
+/// assert!(matches!(result, Err(WaveError::Cancelled)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressUpdate {
+    /// The amount of frames processed so far
+    pub frames_done: u64,
+    /// The amount of frames to process in total
+    pub total_frames: u64,
+}
+
+impl ProgressUpdate {
+    /// Returns the part of the frames that is processed, from 0.0 to 1.0. An operation without
+    /// frames to process is done.
+    pub fn fraction(&self) -> f64 {
+        if self.total_frames == 0 {
+            return 1.0;
+        }
+        self.frames_done as f64 / self.total_frames as f64
+    }
+}
+
+/// Calls a progress callback at a bounded rate, see [ProgressUpdate]
+pub(crate) struct Progress<F> {
+    callback: F,
+    total_frames: u64,
+    /// The amount of frames from which the callback is called again
+    next: u64,
+}
+
+impl<F: FnMut(ProgressUpdate) -> ControlFlow<()>> Progress<F> {
+    pub(crate) fn new(total_frames: u64, callback: F) -> Progress<F> {
+        Progress { callback, total_frames, next: INTERVAL.min(total_frames) }
+    }
+
+    /// Reports that `frames_done` frames are processed, if enough frames were processed since the
+    /// last report.
+    ///
+    /// # Errors
+    /// [WaveError::Cancelled] if the callback breaks.
+    pub(crate) fn update(&mut self, frames_done: u64) -> Result<(), WaveError> {
+        if frames_done < self.next || self.next > self.total_frames {
+            return Ok(());
+        }
+        self.next = match frames_done.saturating_add(INTERVAL).min(self.total_frames) {
+            // Nothing is reported after all frames are done
+            next if next == frames_done => u64::MAX,
+            next => next,
+        };
+        match (self.callback)(ProgressUpdate { frames_done, total_frames: self.total_frames }) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(WaveError::Cancelled),
+        }
+    }
+
+    /// Reports that all frames are processed, unless that was reported already.
+    ///
+    /// # Errors
+    /// [WaveError::Cancelled] if the callback breaks.
+    pub(crate) fn finish(&mut self) -> Result<(), WaveError> {
+        self.update(self.total_frames)
+    }
+}
+
+/// A progress callback that never cancels, for operations without one
+pub(crate) fn ignore(_: ProgressUpdate) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+}
@@ -0,0 +1,170 @@
+use super::sample;
+use super::{AudioFormat, WaveError, WaveFile, WaveSpec};
+
+/// The order of the bytes of the samples in headerless audio data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Least significant byte first, the order of wave files.
+    LittleEndian,
+    /// Most significant byte first.
+    BigEndian,
+}
+
+/// Whether the integer samples of headerless PCM data are signed or unsigned.
+///
+/// Wave files store 8-bit samples unsigned, with silence at `0x80`, and wider samples signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signedness {
+    /// Two's complement samples, silence is 0.
+    Signed,
+    /// Offset binary samples, silence is half of the range.
+    Unsigned,
+}
+
+impl Signedness {
+    /// The signedness of PCM samples of `bits_per_sample` bits in a wave file
+    pub(crate) fn native(bits_per_sample: u16) -> Signedness {
+        if bits_per_sample == 8 {
+            Signedness::Unsigned
+        } else {
+            Signedness::Signed
+        }
+    }
+}
+
+/// Reverses the bytes of every sample of `sample_size` bytes in `bytes`
+pub(crate) fn swap_bytes(bytes: &mut [u8], sample_size: usize) {
+    if sample_size > 1 {
+        bytes.chunks_exact_mut(sample_size).for_each(<[u8]>::reverse);
+    }
+}
+
+/// Flips the sign bit of every little-endian sample of `sample_size` bytes in `bytes`, which
+/// converts between signed and unsigned samples
+pub(crate) fn flip_sign(bytes: &mut [u8], sample_size: usize) {
+    bytes.iter_mut().skip(sample_size - 1).step_by(sample_size).for_each(|byte| *byte ^= 0x80);
+}
+
+// Raw PCM
+impl WaveFile {
+    /// Creates a `WaveFile` of `spec` from headerless audio data, like the raw dumps of embedded
+    /// devices.
+    ///
+    /// Big-endian samples are converted to the little-endian layout of wave files. The samples
+    /// are expected to be signed or unsigned like in a wave file: unsigned for 8-bit PCM, signed
+    /// otherwise. Use [from_raw_pcm_with_signedness](WaveFile::from_raw_pcm_with_signedness) for
+    /// data that isn't.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] if the format of `spec` is
+    ///   [Extensible](AudioFormat::Extensible), which doesn't say how the samples are stored
+    /// - [WaveError::UnsupportedBitsPerSample] if the bits per sample of `spec` are not supported
+    ///   for its format
+    /// - [WaveError::MisalignedData] if the amount of bytes is not a whole amount of frames
+    ///
+    /// ## Panics
+    /// Like [WaveFile::new], if the byte rate of `spec` doesn't fit in 32 bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec { format: AudioFormat::PCM, channels: 2, sample_rate: 16000, bits_per_sample: 16 };
+    /// let bytes = vec![0x01, 0x00, 0xFF, 0xFE, 0x7F, 0xFF, 0x80, 0x00];
+    ///
+    /// let wave = WaveFile::from_raw_pcm(bytes.clone(), spec, ByteOrder::BigEndian).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [256, -2, 32767, -32768]);
+    ///
+    /// assert!(matches!(
+    ///     WaveFile::from_raw_pcm(bytes[..6].to_vec(), spec, ByteOrder::BigEndian),
+    ///     Err(WaveError::MisalignedData { len: 6, block_align: 4 })
+    /// ));
+    /// ```
+    pub fn from_raw_pcm(bytes: Vec<u8>, spec: WaveSpec, byte_order: ByteOrder) -> Result<WaveFile, WaveError> {
+        let signedness = Signedness::native(spec.bits_per_sample);
+        WaveFile::raw_pcm(bytes, spec, byte_order, signedness)
+    }
+
+    /// Creates a `WaveFile` of `spec` from headerless audio data whose integer samples are
+    /// `signedness`, see [from_raw_pcm](WaveFile::from_raw_pcm).
+    ///
+    /// Samples are converted to the signedness of wave files: signed 8-bit samples become
+    /// unsigned, unsigned samples of 16 bits and more become signed.
+    ///
+    /// # Errors
+    /// The errors of [from_raw_pcm](WaveFile::from_raw_pcm), and
+    /// [WaveError::UnsupportedFormat] if the format of `spec` isn't PCM and `signedness` isn't
+    /// signed: only PCM samples can be unsigned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec { format: AudioFormat::PCM, channels: 1, sample_rate: 8000, bits_per_sample: 8 };
+    /// let bytes = vec![0x00, 0x7F, 0x80];
+    /// let wave = WaveFile::from_raw_pcm_with_signedness(bytes, spec, ByteOrder::LittleEndian, Signedness::Signed);
+    /// assert_eq!(wave.unwrap().samples_i16().unwrap(), [0, 32512, -32768]);
+    ///
+    /// let spec = WaveSpec { bits_per_sample: 16, ..spec };
+    /// let bytes = vec![0x80, 0x00, 0x00, 0x00];
+    /// let wave = WaveFile::from_raw_pcm_with_signedness(bytes, spec, ByteOrder::BigEndian, Signedness::Unsigned).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [0, -32768]);
+    /// ```
+    pub fn from_raw_pcm_with_signedness(
+        bytes: Vec<u8>,
+        spec: WaveSpec,
+        byte_order: ByteOrder,
+        signedness: Signedness,
+    ) -> Result<WaveFile, WaveError> {
+        if spec.format != AudioFormat::PCM && signedness != Signedness::Signed {
+            return Err(WaveError::UnsupportedFormat(spec.format.get_val()));
+        }
+        WaveFile::raw_pcm(bytes, spec, byte_order, signedness)
+    }
+
+    /// Returns a copy of the audio data without any header, together with the spec that
+    /// describes it.
+    ///
+    /// With [ByteOrder::BigEndian] every sample is byte-swapped. Samples keep the signedness of
+    /// wave files: unsigned for 8-bit PCM, signed otherwise. The spec is that of the file at the
+    /// time of the call, so it reflects earlier conversions. The data can be read back with
+    /// [from_raw_pcm](WaveFile::from_raw_pcm).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_samples_i16(&[1, -2]).unwrap();
+    ///
+    /// let wave = wave.convert_to(AudioFormat::IeeeFloat, 32, DitherMode::None).unwrap();
+    /// let (bytes, spec) = wave.to_raw_pcm(ByteOrder::BigEndian);
+    /// assert_eq!(spec, WaveSpec { format: AudioFormat::IeeeFloat, channels: 1, sample_rate: 8000, bits_per_sample: 32 });
+    /// assert_eq!(f32::from_be_bytes(bytes[0..4].try_into().unwrap()), 1.0 / 32768.0);
+    ///
+    /// let parsed = WaveFile::from_raw_pcm(bytes, spec, ByteOrder::BigEndian).unwrap();
+    /// assert_eq!(parsed.samples_f32().unwrap(), wave.samples_f32().unwrap());
+    /// ```
+    pub fn to_raw_pcm(&self, byte_order: ByteOrder) -> (Vec<u8>, WaveSpec) {
+        let mut bytes = self.audio_byte_data.to_vec();
+        if byte_order == ByteOrder::BigEndian {
+            swap_bytes(&mut bytes, (self.bits_per_sample / 8) as usize);
+        }
+        (bytes, self.spec())
+    }
+
+    fn raw_pcm(mut bytes: Vec<u8>, spec: WaveSpec, byte_order: ByteOrder, signedness: Signedness) -> Result<WaveFile, WaveError> {
+        let bits_per_sample = spec.bits_per_sample;
+        sample::check_supported(spec.format, bits_per_sample)?;
+        let mut wave = WaveFile::new(spec.format, spec.channels, spec.sample_rate, bits_per_sample);
+        if !(bytes.len() as u64).is_multiple_of(wave.block_align as u64) {
+            return Err(WaveError::MisalignedData { len: bytes.len(), block_align: wave.block_align });
+        }
+        let sample_size = (bits_per_sample / 8) as usize;
+        if byte_order == ByteOrder::BigEndian {
+            swap_bytes(&mut bytes, sample_size);
+        }
+        if spec.format == AudioFormat::PCM && signedness != Signedness::native(bits_per_sample) {
+            flip_sign(&mut bytes, sample_size);
+        }
+        wave.audio_byte_data.set(bytes);
+        Ok(wave)
+    }
+}
@@ -0,0 +1,326 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use super::{adpcm, rf64, w64, AudioFormat, FmtChunk, WaveError, WaveFile, WaveSpec};
+
+/// Reads a wave file incrementally from any [Read] source, without loading the audio data into
+/// memory at once.
+///
+/// The header is parsed when the reader is created, after which the audio data can be read with
+/// [read_block](WaveReader::read_block) or [frames](WaveReader::frames). No seeking is needed, so
+/// non-seekable sources like stdin or a TCP stream work as well. Chunks in front of the data chunk
+/// are buffered, and can be accessed with [chunk](WaveReader::chunk).
+///
+/// RF64 and Sony Wave64 files are read as well, so files with more than 4 GB of audio data can be
+/// read without loading them into memory.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+/// wave.add_bytes(&mut (0..40).collect());
+/// let bytes = wave.to_bytes();
+///
+/// let mut reader = WaveReader::new(&bytes[..]).unwrap();
+/// assert_eq!(reader.num_channels(), 2);
+///
+/// let mut buf = [0; 16];
+/// assert_eq!(reader.read_block(&mut buf).unwrap(), 16);
+/// assert_eq!(buf[..4], [0, 1, 2, 3]);
+///
+/// let frames: Vec<Vec<u8>> = reader.frames().collect::<Result<_, _>>().unwrap();
+/// assert_eq!(frames.len(), 6);
+/// assert_eq!(frames[0], [16, 17, 18, 19]);
+/// ```
+pub struct WaveReader<R: Read> {
+    reader: R,
+    /// Holds the spec, its data is never used
+    spec: WaveFile,
+    /// Chunks found before the data chunk, except fmt
+    chunks: Vec<([u8; 4], Vec<u8>)>,
+    data_len: u64,
+    /// The amount of bytes of the data chunk that haven't been read yet
+    remaining: u64,
+    /// The bytes of a partial frame at the end of the data chunk, skipped after the last frame
+    partial: u64,
+}
+
+impl<R: Read> WaveReader<R> {
+    /// Creates a new `WaveReader`, reading the header from `reader` up to the start of the audio
+    /// data.
+    ///
+    /// # Errors
+    /// - [WaveError::NotRiff] if the source does not start with the `RIFF`/`WAVE` or `RF64`/`WAVE`
+    ///   magic, or the `riff`/`wave` GUIDs of a Wave64 file
+    /// - [WaveError::Truncated] if the source ends before the data chunk, or the fmt chunk is too
+    ///   short
+    /// - [WaveError::MissingChunk] if there is no fmt chunk before the data chunk
+    /// - [WaveError::UnsupportedFormat] for formats that can't be read incrementally (IMA ADPCM)
+    ///   and unknown formats
+    /// - [WaveError::Io] for any other error returned by `reader`
+    pub fn new(mut reader: R) -> Result<WaveReader<R>, WaveError> {
+        let mut riff = [0; w64::HEADER_SIZE];
+        read_exact(&mut reader, &mut riff[..12])?;
+        let is_w64 = &riff[0..4] == b"riff";
+        if is_w64 {
+            read_exact(&mut reader, &mut riff[12..])?;
+            if !w64::is_w64_magic(&riff) || !w64::is_wave_guid(&riff[24..40]) {
+                return Err(WaveError::NotRiff);
+            }
+        } else if (&riff[0..4] != b"RIFF" && !rf64::is_rf64_magic(&riff[0..4])) || &riff[8..12] != b"WAVE" {
+            return Err(WaveError::NotRiff);
+        }
+
+        let mut fmt = None;
+        let mut chunks = Vec::new();
+        let mut rf64_data_size = None;
+        loop {
+            let (id, size) = if is_w64 {
+                let mut header = [0; w64::CHUNK_HEADER_SIZE];
+                read_exact(&mut reader, &mut header)?;
+                let size = w64::payload_size(u64::from_le_bytes(header[16..24].try_into().unwrap()))?;
+                // Chunks without a RIFF counterpart are skipped
+                (w64::chunk_id(header[0..16].try_into().unwrap()), size)
+            } else {
+                let mut header = [0; 8];
+                read_exact(&mut reader, &mut header)?;
+                let id: [u8; 4] = header[0..4].try_into().unwrap();
+                let size = match u32::from_le_bytes(header[4..8].try_into().unwrap()) {
+                    // The size of the data chunk of an RF64 file is stored in the ds64 chunk
+                    u32::MAX if &id == b"data" => rf64_data_size.unwrap_or(u32::MAX as u64),
+                    size => size as u64,
+                };
+                (Some(id), size)
+            };
+
+            if id == Some(*b"data") {
+                let fmt: WaveFile = fmt.ok_or(WaveError::MissingChunk("fmt "))?;
+                // A partial frame at the end of the data chunk is left out, like lenient parsing
+                // does
+                let partial = size % fmt.block_align.max(1) as u64;
+                let remaining = size - partial;
+                return Ok(WaveReader { reader, spec: fmt, chunks, data_len: size, remaining, partial });
+            }
+
+            // Not allocating `size` bytes up front, it could be corrupt
+            let mut payload = Vec::new();
+            reader.by_ref().take(size).read_to_end(&mut payload)?;
+            if (payload.len() as u64) < size {
+                return Err(WaveError::Truncated);
+            }
+            // Chunks with an odd size are followed by a pad byte, Wave64 chunks are aligned to 8
+            // bytes
+            let padding = if is_w64 { w64::padding(size) } else { size % 2 };
+            read_exact(&mut reader, &mut [0; 8][..padding as usize])?;
+
+            let Some(id) = id else {
+                continue;
+            };
+            if &id == b"fmt " && fmt.is_none() {
+                if payload.len() >= 2 && u16::from_le_bytes([payload[0], payload[1]]) == adpcm::IMA_ADPCM {
+                    return Err(WaveError::UnsupportedFormat(adpcm::IMA_ADPCM));
+                }
+                fmt = Some(WaveFile::from_fmt(&FmtChunk::parse(&payload)?));
+            } else {
+                if &id == b"ds64" {
+                    rf64_data_size = rf64::Ds64::parse(&payload).map(|ds64| ds64.data_size);
+                }
+                chunks.push((id, payload));
+            }
+        }
+    }
+
+    /// Returns the spec of the audio data, see [WaveFile::spec].
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::new(AudioFormat::PCM, 2, 44100, 16).unwrap();
+    /// let bytes = WaveFile::with_spec(spec).to_bytes();
+    /// assert_eq!(WaveReader::new(&bytes[..]).unwrap().spec(), spec);
+    /// ```
+    pub fn spec(&self) -> WaveSpec {
+        self.spec.spec()
+    }
+
+    /// Returns the audio format of the samples, see [WaveFile::audio_format].
+    pub fn audio_format(&self) -> AudioFormat {
+        self.spec.audio_format()
+    }
+
+    /// Returns the number of channels.
+    pub fn num_channels(&self) -> u16 {
+        self.spec.num_channels
+    }
+
+    /// Returns the sample rate in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.spec.sample_rate
+    }
+
+    /// Returns the amount of bits per sample.
+    pub fn bits_per_sample(&self) -> u16 {
+        self.spec.bits_per_sample
+    }
+
+    /// Returns the amount of bits of every sample that carry audio, see
+    /// [WaveFile::valid_bits_per_sample].
+    pub fn valid_bits_per_sample(&self) -> u16 {
+        self.spec.valid_bits_per_sample
+    }
+
+    /// Returns the block align: the amount of bytes in one frame (one sample for every channel).
+    pub fn block_align(&self) -> u16 {
+        self.spec.block_align
+    }
+
+    /// Returns the size of the data chunk in bytes, as declared in the file.
+    pub fn data_len(&self) -> u64 {
+        self.data_len
+    }
+
+    /// Returns the payload of the first chunk with the given id that appeared before the data
+    /// chunk, e.g. `b"LIST"`.
+    pub fn chunk(&self, id: &[u8; 4]) -> Option<&[u8]> {
+        self.chunks.iter().find(|(chunk_id, _)| chunk_id == id).map(|(_, payload)| &payload[..])
+    }
+
+    /// Reads audio data into `buf`, filling as many whole frames as fit in it. Returns the amount
+    /// of bytes read, which is 0 once all audio data has been read. A partial frame at the end of
+    /// the data chunk is never returned, but it is skipped once the last frame is read, so the
+    /// underlying reader ends up past the data chunk.
+    ///
+    /// # Errors
+    /// [WaveError::Truncated] if the source ends before the end of the data chunk, or any other
+    /// error returned by the underlying reader.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// // Two stereo frames and half of a third one
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.bytes().extend(0..10);
+    /// let bytes = wave.to_bytes();
+    ///
+    /// let mut reader = WaveReader::new(&bytes[..]).unwrap();
+    /// assert_eq!(reader.data_len(), 10);
+    /// let mut buf = [0; 16];
+    /// assert_eq!(reader.read_block(&mut buf).unwrap(), 8);
+    /// assert_eq!(reader.read_block(&mut buf).unwrap(), 0);
+    ///
+    /// let mut reader = WaveReader::new(&bytes[..]).unwrap();
+    /// assert_eq!(reader.frames().count(), 2);
+    ///
+    /// // The partial frame is skipped as well, leaving the source at the end of the data chunk
+    /// let mut source = &bytes[..];
+    /// let mut reader = WaveReader::new(&mut source).unwrap();
+    /// assert_eq!(reader.read_block(&mut buf).unwrap(), 8);
+    /// assert!(source.is_empty());
+    /// ```
+    pub fn read_block(&mut self, buf: &mut [u8]) -> Result<usize, WaveError> {
+        let block_align = self.block_align().max(1) as usize;
+        let len = ((buf.len() / block_align * block_align) as u64).min(self.remaining) as usize;
+        read_exact(&mut self.reader, &mut buf[..len])?;
+        self.remaining -= len as u64;
+        if self.remaining == 0 && self.partial > 0 {
+            // A source that ends in the partial frame is fine, its bytes aren't used anyway
+            io::copy(&mut self.reader.by_ref().take(self.partial), &mut io::sink())?;
+            self.partial = 0;
+        }
+        Ok(len)
+    }
+
+    /// Returns an iterator over the remaining frames, each frame being `block_align` bytes.
+    pub fn frames(&mut self) -> Frames<'_, R> {
+        Frames { reader: self }
+    }
+}
+
+/// An iterator over the frames of a [WaveReader], see [WaveReader::frames].
+pub struct Frames<'a, R: Read> {
+    reader: &'a mut WaveReader<R>,
+}
+
+impl<R: Read> Iterator for Frames<'_, R> {
+    type Item = Result<Vec<u8>, WaveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut frame = vec![0; self.reader.block_align() as usize];
+        match self.reader.read_block(&mut frame) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(frame)),
+            Err(err) => {
+                // Don't keep yielding errors
+                self.reader.remaining = 0;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Reads the audio of the file at `file_path` from `start` on for `len`, without reading the rest
+/// of the audio data. Both times are rounded to the nearest frame, like
+/// [slice](WaveFile::slice) does.
+///
+/// Only the header is parsed, after which the reader seeks straight to the first frame of the
+/// range. The range is clamped to the audio data in the file: a range that is (partly) past its
+/// end gives a shorter or empty file. The same containers as [WaveReader] are read, so ranges of
+/// RF64 and Wave64 files of more than 4 GB can be read as well. The returned file has the spec of
+/// the file, but none of its metadata.
+///
+/// # Errors
+/// - the errors of [WaveReader::new]
+/// - [WaveError::CapacityOverflow] if the range doesn't fit in memory
+/// - [WaveError::Io] if the file can't be read
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::fs;
+/// # use std::time::Duration;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 10, 16);
+/// wave.add_samples_i16(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+/// let path = std::env::temp_dir().join("jaudio-read-range.wav");
+/// let path = path.to_str().unwrap();
+/// fs::write(path, wave.to_bytes()).unwrap();
+///
+/// let range = read_range(path, Duration::from_millis(200), Duration::from_millis(300)).unwrap();
+/// assert_eq!(range.samples_i16().unwrap(), [2, 3, 4]);
+/// assert_eq!(range.spec(), wave.spec());
+///
+/// // The range is clamped to the end of the file
+/// let tail = read_range(path, Duration::from_millis(800), Duration::from_secs(60)).unwrap();
+/// assert_eq!(tail.samples_i16().unwrap(), [8, 9]);
+/// assert_eq!(read_range(path, Duration::from_secs(60), Duration::from_secs(1)).unwrap().num_frames(), 0);
+/// # fs::remove_file(path).unwrap();
+/// ```
+pub fn read_range(file_path: &str, start: Duration, len: Duration) -> Result<WaveFile, WaveError> {
+    let file = File::open(file_path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = WaveReader::new(BufReader::new(file))?;
+    let data_start = reader.reader.stream_position()?;
+    let block_align = reader.block_align();
+
+    // A data chunk that claims more than the file holds is clamped as well
+    let frames = reader.data_len.min(file_len.saturating_sub(data_start)) / block_align.max(1) as u64;
+    let first = reader.spec.frames_for_duration(start).min(frames);
+    let end = first.saturating_add(reader.spec.frames_for_duration(len)).min(frames);
+    let size = usize::try_from((end - first) * block_align as u64)
+        .map_err(|_| WaveError::CapacityOverflow { frames: end - first, block_align })?;
+
+    reader.reader.seek(SeekFrom::Start(data_start + first * block_align as u64))?;
+    let mut bytes = vec![0; size];
+    read_exact(&mut reader.reader, &mut bytes)?;
+    let mut wave = reader.spec;
+    wave.audio_byte_data.set(bytes);
+    Ok(wave)
+}
+
+/// [Read::read_exact], mapping an unexpected end of file to [WaveError::Truncated]
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), WaveError> {
+    reader.read_exact(buf).map_err(|err| match err.kind() {
+        io::ErrorKind::UnexpectedEof => WaveError::Truncated,
+        _ => WaveError::Io(err),
+    })
+}
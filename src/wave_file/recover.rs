@@ -0,0 +1,371 @@
+//! Recovering wave files with corrupt sizes, like those of recorders that crashed before
+//! finishing the header.
+
+use std::fs::{self, OpenOptions};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use super::{rf64, WaveError, WaveFile};
+
+/// How strictly [WaveFile::from_bytes_with_options] treats the common corruptions of files
+/// written by recorders that didn't finish the header.
+///
+/// A lenient parser recovers from them:
+/// - a data chunk size of 0 or `0xFFFFFFFF` while audio follows, or a size larger than the file:
+///   the audio data is read up to the end of the file, and a partial final frame is dropped
+/// - a RIFF size that doesn't match the file: chunks are read up to the end of the file, and a
+///   truncated chunk after the data chunk is dropped
+/// - a missing pad byte after the last chunk
+/// - a byte rate that isn't the sample rate times the block align: it is recomputed
+///
+/// A strict parser rejects them with [WaveError::InvalidDataSize], [WaveError::Truncated],
+/// [WaveError::MissingPadByte] and [WaveError::ByteRateMismatch].
+///
+/// Only RIFF and RF64 files are recovered, other containers are parsed like
+/// [from_bytes](WaveFile::from_bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Whether to reject corrupt files instead of recovering them, `false` by default
+    pub strict: bool,
+}
+
+impl ParseOptions {
+    /// Options that reject corrupt files.
+    pub const fn strict() -> ParseOptions {
+        ParseOptions { strict: true }
+    }
+
+    /// Options that recover corrupt files.
+    pub const fn lenient() -> ParseOptions {
+        ParseOptions { strict: false }
+    }
+}
+
+/// The size fields changed by [repair_header], as the old and the new value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RepairReport {
+    /// The RIFF size, from the `ds64` chunk for RF64 files, if it was wrong
+    pub riff_size: Option<(u64, u64)>,
+    /// The size of the data chunk, from the `ds64` chunk for RF64 files, if it was wrong
+    pub data_size: Option<(u64, u64)>,
+}
+
+impl RepairReport {
+    /// Returns whether any size was changed.
+    pub fn changed(&self) -> bool {
+        self.riff_size.is_some() || self.data_size.is_some()
+    }
+}
+
+/// Corrects the RIFF size and data size of the RIFF or RF64 file at `file_path` in place, like
+/// those of a recorder that crashed before finishing the header.
+///
+/// The chunks are walked like a [lenient](ParseOptions::lenient) parser does, and the sizes
+/// are recomputed from the length of the file. Only the size fields are written, the rest of the
+/// file is left as it is. A truncated chunk after the data chunk is left out of the RIFF size
+/// rather than removed.
+///
+/// # Errors
+/// - [WaveError::NotRiff] if the file doesn't start with the `RIFF`/`WAVE` or `RF64`/`WAVE`
+///   magic
+/// - [WaveError::MissingChunk] if the file has no complete fmt chunk, and
+///   [WaveError::Truncated] if it is shorter than 16 bytes: nothing is written then
+/// - [WaveError::TooLarge] if the audio data of a RIFF file doesn't fit in its 32-bit sizes
+/// - [WaveError::Io] if the file can't be read or written
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::fs;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+/// wave.add_samples_i16(&[1, 2, 3]).unwrap();
+/// let mut bytes = wave.to_bytes();
+/// // The placeholder sizes of a recorder that crashed
+/// bytes[4..8].copy_from_slice(&36u32.to_le_bytes());
+/// bytes[40..44].copy_from_slice(&0u32.to_le_bytes());
+///
+/// let path = std::env::temp_dir().join("jaudio-repair-header.wav");
+/// let path = path.to_str().unwrap();
+/// fs::write(path, &bytes).unwrap();
+///
+/// let report = repair_header(path).unwrap();
+/// assert_eq!(report.riff_size, Some((36, 42)));
+/// assert_eq!(report.data_size, Some((0, 6)));
+/// assert_eq!(WaveFile::from_file(path).unwrap().samples_i16().unwrap(), [1, 2, 3]);
+///
+/// assert!(!repair_header(path).unwrap().changed());
+/// # fs::remove_file(path).unwrap();
+/// ```
+pub fn repair_header(file_path: &str) -> Result<RepairReport, WaveError> {
+    let mut file = OpenOptions::new().read(true).write(true).open(file_path)?;
+    let len = file.metadata()?.len();
+    let layout = Layout::scan(&mut file, len)?;
+    match &layout.fmt {
+        None => return Err(WaveError::MissingChunk("fmt ")),
+        Some(fmt) if fmt.len() < 16 => return Err(WaveError::Truncated),
+        Some(_) => {}
+    }
+
+    let report = RepairReport {
+        riff_size: Some((layout.riff_size, layout.end - 8)).filter(|(old, new)| old != new),
+        data_size: layout.data.as_ref().map(|data| (data.declared, data.actual)).filter(|(old, new)| old != new),
+    };
+    for (offset, patch) in layout.patches()? {
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(&patch)?;
+    }
+    file.flush()?;
+    Ok(report)
+}
+
+/// The data chunk as laid out in a file
+pub(crate) struct DataLayout {
+    /// The offset of the chunk header
+    pub(crate) offset: u64,
+    /// The size in the chunk header, or in the ds64 chunk of an RF64 file
+    pub(crate) declared: u64,
+    /// The amount of bytes of audio data in the file
+    pub(crate) actual: u64,
+}
+
+/// The chunks of a RIFF or RF64 file, walked without trusting the RIFF size and the data size
+pub(crate) struct Layout {
+    /// The offset of the payload of the ds64 chunk of an RF64 file
+    pub(crate) ds64: Option<u64>,
+    /// The RIFF size in the header, or in the ds64 chunk of an RF64 file
+    pub(crate) riff_size: u64,
+    /// The payload of the fmt chunk, if it is complete
+    pub(crate) fmt: Option<Vec<u8>>,
+    /// The offset of the payload of the fact chunk
+    pub(crate) fact: Option<u64>,
+    pub(crate) data: Option<DataLayout>,
+    /// The end of the last complete chunk
+    pub(crate) end: u64,
+    /// Whether the last chunk has an odd size and the file ends without its pad byte
+    pub(crate) missing_pad: bool,
+}
+
+impl Layout {
+    /// Walks the chunks of the `len` bytes of the RIFF or RF64 file in `reader`.
+    ///
+    /// A data chunk whose size is larger than the file, or 0 or `0xFFFFFFFF` while it isn't
+    /// followed by well-formed chunks, is taken to last until the end of the file. Walking stops
+    /// at a chunk that extends past the end of the file.
+    pub(crate) fn scan<R: Read + Seek>(reader: &mut R, len: u64) -> Result<Layout, WaveError> {
+        let mut header = [0; 12];
+        if len < 12 || reader.read_exact(&mut header).is_err() || &header[8..12] != b"WAVE" {
+            return Err(WaveError::NotRiff);
+        }
+        let is_rf64 = rf64::is_rf64_magic(&header[0..4]);
+        if !is_rf64 && &header[0..4] != b"RIFF" {
+            return Err(WaveError::NotRiff);
+        }
+        let mut layout = Layout {
+            ds64: None,
+            riff_size: u32::from_le_bytes(header[4..8].try_into().unwrap()) as u64,
+            fmt: None,
+            fact: None,
+            data: None,
+            end: 12,
+            missing_pad: false,
+        };
+        let mut ds64_data_size = None;
+
+        let mut pos = 12;
+        while let Some((id, size)) = read_chunk_header(reader, pos, len)? {
+            let available = len - pos - 8;
+            let size = match ds64_data_size {
+                Some(data_size) if &id == b"data" && size == u32::MAX => data_size,
+                _ => size as u64,
+            };
+            match &id {
+                b"data" if layout.data.is_none() => {
+                    let to_end = size > available
+                        || ((size == 0 || size == u32::MAX as u64) && available > 0 && !well_formed(reader, pos + 8, len)?);
+                    let actual = if to_end { available } else { size };
+                    layout.data = Some(DataLayout { offset: pos, declared: size, actual });
+                    if to_end {
+                        layout.end = len;
+                        break;
+                    }
+                }
+                _ if size > available => break,
+                b"ds64" if is_rf64 && layout.ds64.is_none() => {
+                    let ds64 = read_payload(reader, pos, size.min(rf64::DS64_SIZE as u64))?;
+                    let ds64 = rf64::Ds64::parse(&ds64).ok_or(WaveError::Truncated)?;
+                    layout.ds64 = Some(pos + 8);
+                    layout.riff_size = ds64.riff_size;
+                    ds64_data_size = Some(ds64.data_size);
+                }
+                b"fmt " if layout.fmt.is_none() => layout.fmt = Some(read_payload(reader, pos, size)?),
+                b"fact" if layout.fact.is_none() && size >= 4 => layout.fact = Some(pos + 8),
+                _ => {}
+            }
+            pos += 8 + size;
+            if size % 2 == 1 {
+                if pos == len {
+                    layout.missing_pad = true;
+                } else {
+                    pos += 1;
+                }
+            }
+            layout.end = pos;
+        }
+        if is_rf64 && layout.ds64.is_none() {
+            return Err(WaveError::MissingChunk("ds64"));
+        }
+        Ok(layout)
+    }
+
+    /// Returns the writes that correct the RIFF size and data size of the file, as the offset
+    /// and the new bytes of every size field that is wrong.
+    ///
+    /// # Errors
+    /// [WaveError::TooLarge] if the corrected sizes of a RIFF file don't fit in 32 bits.
+    pub(crate) fn patches(&self) -> Result<Vec<(u64, Vec<u8>)>, WaveError> {
+        let riff_size = self.end - 8;
+        let mut patches = Vec::new();
+        match self.ds64 {
+            Some(ds64) => {
+                if self.riff_size != riff_size {
+                    patches.push((ds64, riff_size.to_le_bytes().to_vec()));
+                }
+                if let Some(data) = self.data.as_ref().filter(|data| data.declared != data.actual) {
+                    patches.push((ds64 + 8, data.actual.to_le_bytes().to_vec()));
+                }
+            }
+            None => {
+                let size = |size: u64| u32::try_from(size).map_err(|_| WaveError::TooLarge { size: riff_size });
+                if self.riff_size != riff_size {
+                    patches.push((4, size(riff_size)?.to_le_bytes().to_vec()));
+                }
+                if let Some(data) = self.data.as_ref().filter(|data| data.declared != data.actual) {
+                    patches.push((data.offset + 4, size(data.actual)?.to_le_bytes().to_vec()));
+                }
+            }
+        }
+        Ok(patches)
+    }
+}
+
+/// Reads the id and size of the chunk at `pos`, `None` if fewer than 8 bytes are left
+fn read_chunk_header<R: Read + Seek>(reader: &mut R, pos: u64, len: u64) -> Result<Option<([u8; 4], u32)>, WaveError> {
+    if len.saturating_sub(pos) < 8 {
+        return Ok(None);
+    }
+    let mut header = [0; 8];
+    reader.seek(SeekFrom::Start(pos))?;
+    reader.read_exact(&mut header)?;
+    Ok(Some((header[0..4].try_into().unwrap(), u32::from_le_bytes(header[4..8].try_into().unwrap()))))
+}
+
+/// Reads the first `size` bytes of the payload of the chunk at `pos`
+fn read_payload<R: Read + Seek>(reader: &mut R, pos: u64, size: u64) -> Result<Vec<u8>, WaveError> {
+    let mut payload = Vec::new();
+    reader.seek(SeekFrom::Start(pos + 8))?;
+    reader.take(size).read_to_end(&mut payload)?;
+    Ok(payload)
+}
+
+/// Whether the bytes from `pos` to `len` are chunks with printable ids that end with the file
+fn well_formed<R: Read + Seek>(reader: &mut R, mut pos: u64, len: u64) -> Result<bool, WaveError> {
+    while let Some((id, size)) = read_chunk_header(reader, pos, len)? {
+        if !id.iter().all(|&b| b.is_ascii_alphanumeric() || b == b' ') {
+            return Ok(false);
+        }
+        pos += 8 + size as u64 + size as u64 % 2;
+    }
+    // A missing pad byte is allowed
+    Ok(pos == len || pos == len + 1)
+}
+
+// Recovering
+impl WaveFile {
+    /// Parses an in-memory wave file like [from_bytes](WaveFile::from_bytes), either recovering
+    /// from or rejecting corrupt sizes, see [ParseOptions].
+    ///
+    /// Recovering a file whose RIFF size or data size is wrong makes a corrected copy of `bytes`.
+    ///
+    /// # Errors
+    /// The errors of [from_bytes](WaveFile::from_bytes). With [ParseOptions::strict] also
+    /// [WaveError::InvalidDataSize], [WaveError::MissingPadByte] and
+    /// [WaveError::ByteRateMismatch]. With [ParseOptions::lenient],
+    /// [WaveError::TooLarge] if the recovered audio data doesn't fit in a RIFF file.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 16);
+    /// wave.add_samples_i16(&[1, 2, 3, 4]).unwrap();
+    /// let mut bytes = wave.to_bytes();
+    ///
+    /// // A recorder that crashed before writing the RIFF and data sizes
+    /// bytes[4..8].copy_from_slice(&0u32.to_le_bytes());
+    /// bytes[40..44].copy_from_slice(&0u32.to_le_bytes());
+    /// assert!(matches!(
+    ///     WaveFile::from_bytes_with_options(&bytes, ParseOptions::strict()),
+    ///     Err(WaveError::InvalidDataSize { declared: 0, actual: 8 })
+    /// ));
+    /// let recovered = WaveFile::from_bytes_with_options(&bytes, ParseOptions::lenient()).unwrap();
+    /// assert_eq!(recovered.samples_i16().unwrap(), [1, 2, 3, 4]);
+    ///
+    /// // A byte rate that is off, and a partial frame at the end
+    /// bytes[28..32].copy_from_slice(&8000u32.to_le_bytes());
+    /// bytes.push(5);
+    /// let recovered = WaveFile::from_bytes_with_options(&bytes, ParseOptions::lenient()).unwrap();
+    /// assert_eq!(recovered.byte_rate(), 16000);
+    /// assert_eq!(recovered.samples_i16().unwrap(), [1, 2, 3, 4]);
+    /// ```
+    pub fn from_bytes_with_options(bytes: &[u8], options: ParseOptions) -> Result<WaveFile, WaveError> {
+        let layout = match Layout::scan(&mut Cursor::new(bytes), bytes.len() as u64) {
+            Ok(layout) => layout,
+            // RIFX, Wave64 and files that aren't wave files at all
+            Err(WaveError::NotRiff) => return WaveFile::from_bytes(bytes),
+            Err(err) => return Err(err),
+        };
+        let mut wave = if options.strict {
+            if let Some(data) = layout.data.as_ref().filter(|data| data.declared != data.actual) {
+                return Err(WaveError::InvalidDataSize { declared: data.declared, actual: data.actual });
+            }
+            if layout.missing_pad {
+                return Err(WaveError::MissingPadByte);
+            }
+            WaveFile::from_bytes(bytes)?
+        } else {
+            let patches = layout.patches()?;
+            if patches.is_empty() && layout.end == bytes.len() as u64 {
+                WaveFile::from_bytes(bytes)?
+            } else {
+                let mut bytes = bytes[..layout.end as usize].to_vec();
+                for (offset, patch) in patches {
+                    let offset = offset as usize;
+                    bytes[offset..offset + patch.len()].copy_from_slice(&patch);
+                }
+                WaveFile::from_bytes(&bytes)?
+            }
+        };
+
+        let byte_rate = wave.sample_rate as u64 * wave.block_align as u64;
+        if wave.byte_rate as u64 != byte_rate {
+            if options.strict {
+                return Err(WaveError::ByteRateMismatch { declared: wave.byte_rate, computed: byte_rate });
+            }
+            wave.byte_rate = u32::try_from(byte_rate).unwrap_or(wave.byte_rate);
+        }
+        if !options.strict && wave.block_align > 0 {
+            let len = wave.audio_byte_data.len();
+            wave.audio_byte_data.make_contiguous().truncate(len - len % wave.block_align as usize);
+        }
+        Ok(wave)
+    }
+
+    /// Reads a wave file like [from_file](WaveFile::from_file), either recovering from or
+    /// rejecting corrupt sizes, see [ParseOptions].
+    ///
+    /// # Errors
+    /// Returns the same errors as [fs::read()](std::fs::read) and
+    /// [from_bytes_with_options](WaveFile::from_bytes_with_options).
+    pub fn from_file_with_options(file_path: &str, options: ParseOptions) -> Result<WaveFile, WaveError> {
+        let file_content = fs::read(file_path)?;
+        WaveFile::from_bytes_with_options(&file_content, options)
+    }
+}
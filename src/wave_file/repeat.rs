@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use super::{LoopRegion, LoopType, WaveError, WaveFile};
+
+// Repeating
+impl WaveFile {
+    /// Returns a new file with the audio data repeated `times` times, one copy after the other.
+    /// Repeating 0 times gives a file without audio data.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, 2, 3, 4]).unwrap();
+    ///
+    /// assert_eq!(wave.repeat(3).samples_i16().unwrap(), [1, 2, 3, 4, 1, 2, 3, 4, 1, 2, 3, 4]);
+    /// assert_eq!(wave.repeat(0).num_frames(), 0);
+    /// ```
+    pub fn repeat(&self, times: u32) -> WaveFile {
+        let mut wave = self.empty_like(self.audio_format(), self.bits_per_sample);
+        wave.extend_tiled(self, self.num_frames() * times as u64);
+        wave
+    }
+
+    /// Returns a new file with the audio data repeated until it lasts `target`, rounded to whole
+    /// frames. The last repetition is cut off at the frame where `target` is reached, and a
+    /// `target` shorter than the audio only keeps its start.
+    ///
+    /// A file without audio data gives a file without audio data.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 1000, 16);
+    /// wave.add_samples_i16(&[1, 2, 3, 4]).unwrap();
+    ///
+    /// let looped = wave.loop_to_duration(Duration::from_millis(10));
+    /// assert_eq!(looped.samples_i16().unwrap(), [1, 2, 3, 4, 1, 2, 3, 4, 1, 2]);
+    /// assert_eq!(looped.duration(), Duration::from_millis(10));
+    /// ```
+    pub fn loop_to_duration(&self, target: Duration) -> WaveFile {
+        let mut wave = self.empty_like(self.audio_format(), self.bits_per_sample);
+        wave.extend_tiled(self, self.frames_for_duration(target));
+        wave
+    }
+
+    /// Returns a new file that plays the audio the way a sampler plays `region`, like a
+    /// [LoopRegion] of the [sampler information](WaveFile::sampler_info): the frames before the
+    /// loop once, then the loop as many times as needed to last at least `target`, then the
+    /// frames after the loop once.
+    ///
+    /// The loop is always played a whole amount of times, and at least once, so the jumps back
+    /// to its start stay seamless and the result can be a bit longer than `target`. A
+    /// [LoopType::Backward] loop is played backward and a [LoopType::PingPong] loop forward and
+    /// backward in turns; other loop types are played forward. The `play_count` of the loop is
+    /// ignored, `target` decides how often it plays.
+    ///
+    /// # Errors
+    /// [WaveError::LoopOutOfRange] if the loop starts after it ends or ends after the last frame
+    /// of the audio.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 1000, 16);
+    /// // An intro, a loop of 3 frames and a tail
+    /// wave.add_samples_i16(&[10, 11, 1, 2, 3, 20]).unwrap();
+    /// let mut region = LoopRegion {
+    ///     id: 0, loop_type: LoopType::Forward, start_frame: 2, end_frame: 4, fraction: 0, play_count: 0,
+    /// };
+    ///
+    /// let preview = wave.loop_region_to_duration(&region, Duration::from_millis(10)).unwrap();
+    /// assert_eq!(preview.samples_i16().unwrap(), [10, 11, 1, 2, 3, 1, 2, 3, 1, 2, 3, 20]);
+    ///
+    /// region.loop_type = LoopType::PingPong;
+    /// let preview = wave.loop_region_to_duration(&region, Duration::from_millis(10)).unwrap();
+    /// assert_eq!(preview.samples_i16().unwrap(), [10, 11, 1, 2, 3, 3, 2, 1, 1, 2, 3, 20]);
+    ///
+    /// region.end_frame = 6;
+    /// assert!(matches!(
+    ///     wave.loop_region_to_duration(&region, Duration::from_millis(10)),
+    ///     Err(WaveError::LoopOutOfRange { start_frame: 2, end_frame: 6, num_frames: 6 })
+    /// ));
+    /// ```
+    pub fn loop_region_to_duration(&self, region: &LoopRegion, target: Duration) -> Result<WaveFile, WaveError> {
+        let num_frames = self.num_frames();
+        let (start, end) = (region.start_frame as u64, region.end_frame as u64 + 1);
+        if start >= end || end > num_frames {
+            return Err(WaveError::LoopOutOfRange {
+                start_frame: region.start_frame,
+                end_frame: region.end_frame,
+                num_frames,
+            });
+        }
+
+        // One cycle of the loop, which is repeated
+        let forward = self.slice_frames(start..end)?;
+        let mut backward = forward.clone();
+        backward.reverse();
+        let cycle = match region.loop_type {
+            LoopType::Backward => backward,
+            LoopType::PingPong => {
+                let mut cycle = forward;
+                cycle.append(&backward)?;
+                cycle
+            }
+            _ => forward,
+        };
+
+        let loop_len = end - start;
+        let rest = start + (num_frames - end);
+        let times = self.frames_for_duration(target).saturating_sub(rest).div_ceil(loop_len).max(1);
+        let mut wave = self.empty_like(self.audio_format(), self.bits_per_sample);
+        wave.audio_byte_data.reserve(((rest + times * loop_len) * self.block_align as u64) as usize);
+        wave.append(&self.slice_frames(0..start)?)?;
+        wave.extend_tiled(&cycle, times * loop_len);
+        wave.append(&self.slice_frames(end..num_frames)?)?;
+        Ok(wave)
+    }
+
+    /// Appends `frames` frames of the audio data of `source`, which has the same format, repeated
+    /// from its start as often as needed. Nothing is appended if `source` has no audio data.
+    fn extend_tiled(&mut self, source: &WaveFile, frames: u64) {
+        let len = source.audio_byte_data.len();
+        if len == 0 {
+            return;
+        }
+        let mut left = (frames * self.block_align as u64) as usize;
+        self.audio_byte_data.reserve(left);
+        while left > 0 {
+            for slice in source.audio_byte_data.slices(0..left.min(len)) {
+                self.audio_byte_data.extend_from_slice(slice);
+            }
+            left -= left.min(len);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::f64::consts::PI;
+use std::ops::ControlFlow;
+
+use super::parallel;
+use super::progress::{self, Progress};
+use super::{ProgressUpdate, WaveError, WaveFile};
+
+/// Zero crossings of the sinc kernel on either side of a sample for [ResampleQuality::Sinc]
+const SINC_ZERO_CROSSINGS: f64 = 16.0;
+
+/// The interpolation used by [WaveFile::resample].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// Linear interpolation between the two nearest frames. Fast, but it doesn't filter, so
+    /// downsampling can alias and high frequencies are slightly damped.
+    Linear,
+    /// Band-limited interpolation with a Blackman windowed sinc kernel. When downsampling, the
+    /// kernel is stretched so frequencies above the new Nyquist frequency are filtered out.
+    Sinc,
+}
+
+// Resampling
+impl WaveFile {
+    /// Returns a copy of the file resampled to `target_rate` Hz, keeping the format and bit depth.
+    ///
+    /// Every channel is interpolated on its own. The resampled file has
+    /// `num_frames * target_rate / sample_rate` frames, rounded to the nearest frame, so the
+    /// duration changes by at most half a frame.
+    ///
+    /// ## Panics
+    /// If `target_rate` is 0, the byte rate at `target_rate` doesn't fit in 32 bits or the
+    /// samples of the file can't be decoded, see
+    /// [resample_with_progress](WaveFile::resample_with_progress).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let sine: Vec<f32> = (0..4800)
+    ///     .map(|i| (i as f32 * 1000.0 * std::f32::consts::TAU / 48000.0).sin() * 0.5)
+    ///     .collect();
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 1, 48000, 32);
+    /// wave.add_samples_f32(&sine).unwrap();
+    ///
+    /// let resampled = wave.resample(44100, ResampleQuality::Sinc);
+    /// assert_eq!(resampled.sample_rate(), 44100);
+    /// assert_eq!(resampled.byte_rate(), 44100 * 4);
+    /// assert_eq!(resampled.num_frames(), 4410);
+    /// assert_eq!(resampled.duration(), wave.duration());
+    ///
+    /// // Away from the edges the sine is reconstructed closely
+    /// let samples = resampled.samples_f32().unwrap();
+    /// for i in 100..4300 {
+    ///     let expected = (i as f32 * 1000.0 * std::f32::consts::TAU / 44100.0).sin() * 0.5;
+    ///     assert!((samples[i] - expected).abs() < 1e-3);
+    /// }
+    ///
+    /// let mut stereo = WaveFile::new(AudioFormat::PCM, 2, 8000, 16);
+    /// stereo.add_samples_i16(&[0, 100, 1000, 200, 2000, 300]).unwrap();
+    /// let doubled = stereo.resample(16000, ResampleQuality::Linear);
+    /// assert_eq!(doubled.samples_i16().unwrap(), [0, 100, 500, 150, 1000, 200, 1500, 250, 2000, 300, 2000, 300]);
+    /// ```
+    pub fn resample(&self, target_rate: u32, quality: ResampleQuality) -> WaveFile {
+        self.resample_with_progress(target_rate, quality, progress::ignore).unwrap()
+    }
+
+    /// Resamples the file like [resample](WaveFile::resample), calling `progress` as the frames
+    /// are resampled (see [ProgressUpdate]).
+    ///
+    /// # Errors
+    /// - [WaveError::Cancelled] if `progress` cancels resampling
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    ///   the file can't be decoded
+    /// - [WaveError::InvalidSpec] if the byte rate at `target_rate` doesn't fit in 32 bits
+    ///
+    /// ## Panics
+    /// If `target_rate` is 0.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::ops::ControlFlow;
+    /// let wave = WaveFile::new(AudioFormat::IeeeFloat, 8, 48000, 64);
+    /// let resampled = wave.resample_with_progress(u32::MAX, ResampleQuality::Linear, |_| ControlFlow::Continue(()));
+    /// assert!(matches!(resampled, Err(WaveError::InvalidSpec(_))));
+    /// ```
+    pub fn resample_with_progress(
+        &self,
+        target_rate: u32,
+        quality: ResampleQuality,
+        progress: impl FnMut(ProgressUpdate) -> ControlFlow<()>,
+    ) -> Result<WaveFile, WaveError> {
+        assert!(target_rate > 0, "can't resample to a sample rate of 0 Hz");
+        let byte_rate = target_rate
+            .checked_mul(self.block_align as u32)
+            .ok_or(WaveError::InvalidSpec("the byte rate doesn't fit in 32 bits"))?;
+        let samples = self.samples_as::<f64>()?;
+        let channels = self.num_channels.max(1) as usize;
+        let frames = samples.len() / channels;
+        let mut progress = Progress::new(frames as u64, progress);
+
+        // Every channel has a resampler of its own, so the channels can be resampled in parallel.
+        // The result doesn't depend on the size of the rounds the input is pushed in, and the
+        // resamplers are finished in the last round.
+        let mut states: Vec<_> = (0..channels)
+            .map(|channel| (channel, Resampler::new(1, self.sample_rate, target_rate, quality), Vec::new()))
+            .collect();
+        parallel::for_each_round(
+            &mut states,
+            0..frames as u64,
+            progress::INTERVAL,
+            |(channel, resampler, out), round| {
+                let (start, end) = (round.start as usize, round.end as usize);
+                let input: Vec<f64> = samples[start * channels..end * channels].iter().skip(*channel).step_by(channels).copied().collect();
+                resampler.push(&input, out);
+                if end == frames {
+                    resampler.finish(out);
+                }
+            },
+            |end| progress.update(end),
+        )?;
+        progress.finish()?;
+
+        let out_frames = states.first().map_or(0, |(_, _, out)| out.len());
+        let mut out = Vec::with_capacity(out_frames * channels);
+        for frame in 0..out_frames {
+            out.extend(states.iter().map(|(_, _, channel_out)| channel_out[frame]));
+        }
+
+        let mut wave = self.empty_like(self.audio_format(), self.bits_per_sample);
+        wave.sample_rate = target_rate;
+        wave.byte_rate = byte_rate;
+        wave.add_samples(&out).unwrap();
+        Ok(wave)
+    }
+}
+
+/// Resamples interleaved audio that is given a block at a time, see [WaveFile::resample].
+///
+/// Only the frames that the frames still to come need are kept, so the result is the same as
+/// resampling all audio at once, while the memory used doesn't grow with the length of the audio.
+pub(crate) struct Resampler {
+    channels: usize,
+    source_rate: u32,
+    target_rate: u32,
+    quality: ResampleQuality,
+    /// The distance between output frames, in input frames
+    step: f64,
+    /// The frames that are still needed, one `Vec` per channel
+    input: Vec<Vec<f64>>,
+    /// The index of the first frame in `input`
+    start: usize,
+    /// The amount of input frames pushed so far
+    in_frames: usize,
+    /// The index of the next output frame
+    out_frame: usize,
+}
+
+impl Resampler {
+    pub(crate) fn new(num_channels: u16, source_rate: u32, target_rate: u32, quality: ResampleQuality) -> Resampler {
+        let channels = num_channels.max(1) as usize;
+        Resampler {
+            channels,
+            source_rate,
+            target_rate,
+            quality,
+            step: source_rate as f64 / target_rate as f64,
+            input: vec![Vec::new(); channels],
+            start: 0,
+            in_frames: 0,
+            out_frame: 0,
+        }
+    }
+
+    /// Adds interleaved input samples, and appends the output frames that can be computed from
+    /// the input so far to `out`
+    pub(crate) fn push(&mut self, samples: &[f64], out: &mut Vec<f64>) {
+        for (i, sample) in samples.iter().enumerate() {
+            self.input[i % self.channels].push(*sample);
+        }
+        self.in_frames += samples.len() / self.channels;
+        // Every output frame before the rounded output length of the input so far exists, no
+        // matter how much input follows
+        let known = self.out_frames(self.in_frames);
+        while self.out_frame < known {
+            let position = self.out_frame as f64 * self.step;
+            let last_needed = match self.quality {
+                ResampleQuality::Linear => position.floor() as usize + 1,
+                ResampleQuality::Sinc => (position + self.half_width()).floor() as usize,
+            };
+            if last_needed >= self.in_frames {
+                break;
+            }
+            self.interpolate(position, out);
+        }
+        self.discard();
+    }
+
+    /// Appends the remaining output frames to `out`, treating the input pushed so far as all
+    /// input
+    pub(crate) fn finish(&mut self, out: &mut Vec<f64>) {
+        while self.out_frame < self.out_frames(self.in_frames) {
+            self.interpolate(self.out_frame as f64 * self.step, out);
+        }
+    }
+
+    /// The amount of output frames for `in_frames` input frames, rounded to the nearest frame
+    fn out_frames(&self, in_frames: usize) -> usize {
+        ((in_frames as u128 * self.target_rate as u128 + self.source_rate as u128 / 2)
+            / self.source_rate.max(1) as u128) as usize
+    }
+
+    /// The cutoff of the sinc kernel relative to the Nyquist frequency of the input
+    fn cutoff(&self) -> f64 {
+        (1.0 / self.step).min(1.0)
+    }
+
+    fn half_width(&self) -> f64 {
+        SINC_ZERO_CROSSINGS / self.cutoff()
+    }
+
+    /// Appends the output frame at `position` to `out`
+    fn interpolate(&mut self, position: f64, out: &mut Vec<f64>) {
+        for input in &self.input {
+            out.push(match self.quality {
+                ResampleQuality::Linear => linear(input, self.start, position),
+                ResampleQuality::Sinc => sinc(input, self.start, position, self.cutoff()),
+            });
+        }
+        self.out_frame += 1;
+    }
+
+    /// Drops the input frames that no output frame still to come needs, keeping at least the last
+    /// frame
+    fn discard(&mut self) {
+        let position = self.out_frame as f64 * self.step;
+        let first_needed = match self.quality {
+            ResampleQuality::Linear => position.floor() as usize,
+            ResampleQuality::Sinc => (position - self.half_width()).ceil().max(0.0) as usize,
+        };
+        let len = self.input[0].len();
+        let discard = first_needed.saturating_sub(self.start).min(len.saturating_sub(1));
+        if discard > 0 {
+            for input in &mut self.input {
+                input.drain(..discard);
+            }
+            self.start += discard;
+        }
+    }
+}
+
+/// Interpolates linearly between the frames around `position`, holding the last frame. The first
+/// frame of `input` is frame `start`.
+fn linear(input: &[f64], start: usize, position: f64) -> f64 {
+    let index = position.floor() as usize;
+    let frac = position - index as f64;
+    let local = index.saturating_sub(start);
+    match (input.get(local), input.get(local + 1)) {
+        (Some(a), Some(b)) => a + (b - a) * frac,
+        (Some(a), None) => *a,
+        _ => input.last().copied().unwrap_or(0.0),
+    }
+}
+
+/// Interpolates with a windowed sinc kernel with its cutoff at `cutoff` times the Nyquist
+/// frequency of the input. Frames outside of the input count as silence. The first frame of
+/// `input` is frame `start`.
+fn sinc(input: &[f64], start: usize, position: f64, cutoff: f64) -> f64 {
+    let half_width = SINC_ZERO_CROSSINGS / cutoff;
+    let first = (position - half_width).ceil().max(0.0) as usize;
+    let end = ((position + half_width).floor() as usize + 1).min(start + input.len());
+    let mut sum = 0.0;
+    for (offset, sample) in input.iter().enumerate().take(end.saturating_sub(start)).skip(first.saturating_sub(start)) {
+        let x = position - (start + offset) as f64;
+        let window = 0.42 + 0.5 * (PI * x / half_width).cos() + 0.08 * (2.0 * PI * x / half_width).cos();
+        let sinc = if x == 0.0 { 1.0 } else { (PI * cutoff * x).sin() / (PI * cutoff * x) };
+        sum += sample * cutoff * sinc * window;
+    }
+    sum
+}
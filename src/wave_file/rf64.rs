@@ -0,0 +1,109 @@
+//! RF64 (EBU Tech 3306): wave files with 64-bit sizes, for audio larger than 4 GB.
+
+use super::{WaveError, WaveFile};
+
+/// When a wave file is written as RF64 rather than RIFF.
+///
+/// RF64 files start with `RF64` instead of `RIFF`, and store the sizes of the file and of the data
+/// chunk in a `ds64` chunk following the magic. The 32-bit size fields are set to `u32::MAX`.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+/// wave.add_samples_i16(&[1, 2, 3, 4]).unwrap();
+/// assert_eq!(wave.rf64_mode(), Rf64Mode::Auto);
+/// // Small files are written as RIFF
+/// assert_eq!(&wave.to_bytes()[0..4], b"RIFF");
+///
+/// wave.set_rf64_mode(Rf64Mode::Always);
+/// let bytes = wave.to_bytes();
+/// assert_eq!(&bytes[0..4], b"RF64");
+/// assert_eq!(&bytes[4..8], u32::MAX.to_le_bytes());
+/// assert_eq!(&bytes[12..16], b"ds64");
+/// // The riff and data sizes
+/// assert_eq!(&bytes[20..28], ((bytes.len() - 8) as u64).to_le_bytes());
+/// assert_eq!(&bytes[28..36], 8u64.to_le_bytes());
+///
+/// let parsed = WaveFile::from_bytes(&bytes).unwrap();
+/// assert_eq!(parsed.samples_i16().unwrap(), [1, 2, 3, 4]);
+/// assert_eq!(parsed.rf64_mode(), Rf64Mode::Always);
+/// assert_eq!(parsed.to_bytes(), bytes);
+/// assert_eq!(WaveReader::new(&bytes[..]).unwrap().data_len(), 8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rf64Mode {
+    /// Write RF64 only when the file doesn't fit in the 32-bit sizes of RIFF
+    Auto,
+    /// Always write RF64, also for small files
+    Always,
+    /// Never write RF64: writing a file that doesn't fit in RIFF fails with
+    /// [WaveError::TooLarge], for readers that don't support RF64
+    Never,
+}
+
+/// The container of a header written by [WaveFile::header].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Container {
+    /// A plain RIFF header
+    Riff,
+    /// A RIFF header with a `JUNK` chunk the size of a `ds64` chunk, so it can be turned into an
+    /// RF64 header in place once the size is known
+    RiffWithJunk,
+    /// An RF64 header with a `ds64` chunk
+    Rf64,
+}
+
+/// The size of the payload of a `ds64` chunk without a table
+pub(crate) const DS64_SIZE: u32 = 28;
+
+/// The 64-bit sizes of a `ds64` chunk
+pub(crate) struct Ds64 {
+    pub(crate) riff_size: u64,
+    pub(crate) data_size: u64,
+}
+
+impl Ds64 {
+    /// Parses the payload of a `ds64` chunk, `None` if it is too short
+    pub(crate) fn parse(ds64: &[u8]) -> Option<Ds64> {
+        let u64_at = |i: usize| ds64.get(i..i + 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()));
+        Some(Ds64 { riff_size: u64_at(0)?, data_size: u64_at(8)? })
+    }
+}
+
+/// Whether `magic` starts an RF64 file. `BW64` (ITU-R BS.2088) has the same layout.
+pub(crate) fn is_rf64_magic(magic: &[u8]) -> bool {
+    magic == b"RF64" || magic == b"BW64"
+}
+
+// RF64
+impl WaveFile {
+    /// Returns when the file is written as RF64, see [Rf64Mode]. Files read from an RF64 file
+    /// are written as RF64 again.
+    pub fn rf64_mode(&self) -> Rf64Mode {
+        self.rf64_mode
+    }
+
+    /// Sets when the file is written as RF64, see [Rf64Mode].
+    pub fn set_rf64_mode(&mut self, rf64_mode: Rf64Mode) {
+        self.rf64_mode = rf64_mode;
+    }
+
+    /// The container to write a file with a data chunk of `data_size` bytes followed by
+    /// `trailing_size` bytes of other chunks in. With `reserve`, a RIFF header has room for a
+    /// `ds64` chunk.
+    ///
+    /// # Errors
+    /// [WaveError::TooLarge] if the file doesn't fit in RIFF and the mode is [Rf64Mode::Never].
+    pub(crate) fn container(&self, data_size: u64, trailing_size: u64, reserve: bool) -> Result<Container, WaveError> {
+        let riff = if reserve { Container::RiffWithJunk } else { Container::Riff };
+        let size = self.riff_size(data_size, trailing_size, riff);
+        let fits = size <= u32::MAX as u64;
+        match self.rf64_mode {
+            Rf64Mode::Always => Ok(Container::Rf64),
+            Rf64Mode::Auto if !fits => Ok(Container::Rf64),
+            Rf64Mode::Never if !fits => Err(WaveError::TooLarge { size }),
+            Rf64Mode::Auto | Rf64Mode::Never => Ok(riff),
+        }
+    }
+}
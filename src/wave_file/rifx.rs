@@ -0,0 +1,140 @@
+//! RIFX: the big-endian variant of RIFF, written by some old SGI and Pro Tools software.
+
+use super::adpcm;
+use super::chunk::{self, Chunks};
+use super::raw;
+use super::WaveError;
+
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Swaps the byte order of the fields of `size` bytes at `offsets` in `data`, skipping fields that
+/// don't fit
+fn swap_fields(data: &mut [u8], size: usize, offsets: &[usize]) {
+    for &offset in offsets {
+        if let Some(field) = data.get_mut(offset..offset + size) {
+            field.reverse();
+        }
+    }
+}
+
+/// Swaps the byte order of the sizes of the sub-chunks in `list`, the payload of a `LIST` chunk
+/// without its type, and of the numbers in the entries of an `adtl` list
+fn swap_list(list_type: &[u8], list: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(list.len());
+    for entry in Chunks::within_big_endian(list).map_while(Result::ok) {
+        let mut data = entry.data.to_vec();
+        match (list_type, &entry.id) {
+            // The cue point id
+            (b"adtl", b"labl" | b"note") => swap_fields(&mut data, 4, &[0]),
+            // The cue point id and sample length, then the country, language, dialect and code
+            // page after the purpose FourCC
+            (b"adtl", b"ltxt") => {
+                swap_fields(&mut data, 4, &[0, 4]);
+                swap_fields(&mut data, 2, &[12, 14, 16, 18]);
+            }
+            _ => {}
+        }
+        chunk::write_chunk(&mut out, &entry.id, &data);
+    }
+    out
+}
+
+/// Swaps the byte order of the numbers in the payload of a `cue `, `smpl`, `bext` or `acid`
+/// chunk, so it can be parsed like the payload of a RIFF file. Text, FourCCs and opaque data are
+/// kept as they are.
+fn swap_metadata(id: &[u8; 4], data: &mut [u8]) {
+    match id {
+        b"cue " => {
+            swap_fields(data, 4, &[0]);
+            // The id, position, chunk start, block start and sample offset of every cue point,
+            // skipping the FourCC of the chunk it refers to
+            for point in data.get_mut(4..).unwrap_or_default().chunks_exact_mut(24) {
+                swap_fields(point, 4, &[0, 4, 12, 16, 20]);
+            }
+        }
+        b"smpl" => {
+            // Manufacturer up to the size of the sampler data, then the fields of every loop
+            swap_fields(data, 4, &[0, 4, 8, 12, 16, 20, 24, 28, 32]);
+            let num_loops = data.get(28..32).map_or(0, |n| u32::from_le_bytes(n.try_into().unwrap()) as usize);
+            for sample_loop in data.get_mut(36..).unwrap_or_default().chunks_exact_mut(24).take(num_loops) {
+                swap_fields(sample_loop, 4, &[0, 4, 8, 12, 16, 20]);
+            }
+        }
+        b"bext" => {
+            // The time reference, the version and the loudness fields, around the text and UMID
+            swap_fields(data, 4, &[338, 342]);
+            swap_fields(data, 2, &[346, 412, 414, 416, 418, 420]);
+        }
+        b"acid" => {
+            // The flags, root note, two unknown fields, beats, meter and tempo
+            swap_fields(data, 4, &[0, 8, 12, 20]);
+            swap_fields(data, 2, &[4, 6, 16, 18]);
+        }
+        _ => {}
+    }
+}
+
+/// Converts a RIFX file to a little-endian RIFF file.
+///
+/// The fmt, fact, data, `cue `, `smpl`, `bext` and `acid` chunks are converted, as are the sizes
+/// of the sub-chunks of `LIST` chunks and the numbers in the entries of `adtl` lists. The payloads
+/// of other chunks are copied as stored.
+///
+/// # Errors
+/// - [WaveError::NotRiff] if `bytes` does not start with the `RIFX`/`WAVE` magic
+/// - [WaveError::Truncated] if a chunk declares more bytes than `bytes` contains
+/// - [WaveError::MissingChunk] if the data chunk comes before the fmt chunk
+/// - [WaveError::UnsupportedFormat] for IMA ADPCM, of which the blocks can't be converted
+pub(crate) fn to_riff(bytes: &[u8]) -> Result<Vec<u8>, WaveError> {
+    let mut chunks = Vec::new();
+    // The amount of bytes of a sample, from the fmt chunk
+    let mut sample_size = None;
+    for chunk in Chunks::rifx(bytes)? {
+        let chunk = chunk?;
+        let mut data = chunk.data.to_vec();
+        match &chunk.id {
+            b"fmt " => {
+                if data.len() < 16 {
+                    return Err(WaveError::Truncated);
+                }
+                // wFormatTag, nChannels, nBlockAlign, wBitsPerSample and cbSize, then
+                // nSamplesPerSec and nAvgBytesPerSec
+                swap_fields(&mut data, 2, &[0, 2, 12, 14, 16]);
+                swap_fields(&mut data, 4, &[4, 8]);
+                match u16::from_le_bytes([data[0], data[1]]) {
+                    adpcm::IMA_ADPCM => return Err(WaveError::UnsupportedFormat(adpcm::IMA_ADPCM)),
+                    // wValidBitsPerSample, dwChannelMask and the first fields of the sub format
+                    // GUID
+                    WAVE_FORMAT_EXTENSIBLE => {
+                        swap_fields(&mut data, 2, &[18, 28, 30]);
+                        swap_fields(&mut data, 4, &[20, 24]);
+                    }
+                    _ => {}
+                }
+                let num_channels = u16::from_le_bytes([data[2], data[3]]) as usize;
+                let block_align = u16::from_le_bytes([data[12], data[13]]) as usize;
+                sample_size = Some(block_align.checked_div(num_channels).unwrap_or(0));
+            }
+            b"fact" => swap_fields(&mut data, 4, &[0]),
+            b"data" => {
+                let sample_size = sample_size.ok_or(WaveError::MissingChunk("fmt "))?;
+                raw::swap_bytes(&mut data, sample_size);
+            }
+            b"LIST" if data.len() >= 4 => {
+                let list = swap_list(&data[..4], &data[4..]);
+                data.truncate(4);
+                data.extend(list);
+            }
+            id => swap_metadata(id, &mut data),
+        }
+        chunks.push((chunk.id, data));
+    }
+
+    let mut out = b"RIFF\0\0\0\0WAVE".to_vec();
+    for (id, data) in &chunks {
+        chunk::write_chunk(&mut out, id, data);
+    }
+    let riff_size = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(out)
+}
@@ -0,0 +1,700 @@
+//! Converting between the bytes in the data chunk and sample values.
+
+use std::fmt;
+use std::ops::Range;
+
+use super::{g711, simd, AudioFormat, WaveError, WaveFile};
+
+/// A type that can be stored as a sample in a [WaveFile].
+///
+/// Samples are normalized to `-1.0..1.0` by dividing integer samples by `2^(bits - 1)`, so the
+/// minimum value of a signed integer maps to exactly -1.0 and the maximum to slightly under 1.0
+/// (e.g. `i16::MAX` maps to `32767 / 32768`). Converting back multiplies by the same factor,
+/// rounds and clamps, so converting to a type with more bits and back is lossless.
+///
+/// `u8` follows the convention of 8-bit wave files: it is unsigned, with 128 as the zero point.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// assert_eq!(i16::MIN.to_f64(), -1.0);
+/// assert_eq!(128u8.to_f64(), 0.0);
+/// assert_eq!(i16::from_f64(0.5), 16384);
+///
+/// // Widening is lossless
+/// let widened: I24 = (-1234i16).convert();
+/// assert_eq!(widened.value(), -1234 * 256);
+/// assert_eq!(widened.convert::<i16>(), -1234);
+/// ```
+pub trait Sample: Copy {
+    /// The format samples of this type are stored as
+    const FORMAT: AudioFormat;
+    /// The amount of bits a sample of this type is stored in
+    const BITS: u16;
+
+    /// Returns the sample normalized to `-1.0..1.0`
+    fn to_f64(self) -> f64;
+
+    /// Creates a sample from a value normalized to `-1.0..1.0`. Integers are rounded and clamped
+    /// to their range.
+    fn from_f64(value: f64) -> Self;
+
+    /// Appends the little endian byte representation of the sample (`BITS / 8` bytes) to `out`
+    fn write_le(self, out: &mut Vec<u8>);
+
+    /// Reads a sample from its little endian byte representation. `bytes` has to be at least
+    /// `BITS / 8` bytes long.
+    fn read_le(bytes: &[u8]) -> Self;
+
+    /// Converts the sample to another sample type
+    fn convert<T: Sample>(self) -> T {
+        T::from_f64(self.to_f64())
+    }
+}
+
+/// A signed 24-bit integer sample, stored in 3 bytes.
+///
+/// # Examples
+/// Writing a 24-bit ramp covering the whole range and reading it back bit-exact:
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let ramp: Vec<I24> = (I24::MIN.value()..=I24::MAX.value())
+///     .step_by(997)
+///     .chain([I24::MAX.value(), -1, 0, 1])
+///     .map(|s| I24::new(s).unwrap())
+///     .collect();
+///
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 48000, 24);
+/// wave.add_samples(&ramp).unwrap();
+/// assert_eq!(wave.block_align(), 6);
+/// assert_eq!(wave.data_len(), ramp.len() * 3);
+///
+/// let parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+/// assert_eq!(parsed.data_len(), ramp.len() * 3);
+/// assert_eq!(parsed.samples_as::<I24>().unwrap(), ramp);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct I24(i32);
+
+impl I24 {
+    /// The smallest value of a 24-bit integer: -2^23
+    pub const MIN: I24 = I24(-(1 << 23));
+    /// The largest value of a 24-bit integer: 2^23 - 1
+    pub const MAX: I24 = I24((1 << 23) - 1);
+
+    /// Returns `value` as an `I24`, or `None` if it is out of range
+    pub fn new(value: i32) -> Option<I24> {
+        if (I24::MIN.0..=I24::MAX.0).contains(&value) {
+            Some(I24(value))
+        } else {
+            None
+        }
+    }
+
+    /// Returns `value` as an `I24`, clamped to its range
+    pub fn saturating(value: i32) -> I24 {
+        I24(value.clamp(I24::MIN.0, I24::MAX.0))
+    }
+
+    /// Returns the value as an `i32`
+    pub fn value(self) -> i32 {
+        self.0
+    }
+}
+
+impl From<i16> for I24 {
+    fn from(value: i16) -> Self {
+        I24(value as i32)
+    }
+}
+
+impl From<I24> for i32 {
+    fn from(value: I24) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for I24 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Sample for u8 {
+    const FORMAT: AudioFormat = AudioFormat::PCM;
+    const BITS: u16 = 8;
+
+    fn to_f64(self) -> f64 {
+        (self as f64 - 128.0) / 128.0
+    }
+
+    fn from_f64(value: f64) -> Self {
+        (quantize(value, 8) + 128) as u8
+    }
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.push(self);
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+}
+
+impl Sample for i16 {
+    const FORMAT: AudioFormat = AudioFormat::PCM;
+    const BITS: u16 = 16;
+
+    fn to_f64(self) -> f64 {
+        self as f64 / 32768.0
+    }
+
+    fn from_f64(value: f64) -> Self {
+        quantize(value, 16) as i16
+    }
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend(self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        i16::from_le_bytes([bytes[0], bytes[1]])
+    }
+}
+
+impl Sample for I24 {
+    const FORMAT: AudioFormat = AudioFormat::PCM;
+    const BITS: u16 = 24;
+
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / 8_388_608.0
+    }
+
+    fn from_f64(value: f64) -> Self {
+        I24(quantize(value, 24) as i32)
+    }
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend(&self.0.to_le_bytes()[..3]);
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        // Sign extend the 3 bytes by placing them in the upper bytes of an i32
+        I24(i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8)
+    }
+}
+
+impl Sample for i32 {
+    const FORMAT: AudioFormat = AudioFormat::PCM;
+    const BITS: u16 = 32;
+
+    fn to_f64(self) -> f64 {
+        self as f64 / 2_147_483_648.0
+    }
+
+    fn from_f64(value: f64) -> Self {
+        quantize(value, 32) as i32
+    }
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend(self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        i32::from_le_bytes(bytes[..4].try_into().unwrap())
+    }
+}
+
+impl Sample for f32 {
+    const FORMAT: AudioFormat = AudioFormat::IeeeFloat;
+    const BITS: u16 = 32;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend(self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        f32::from_le_bytes(bytes[..4].try_into().unwrap())
+    }
+}
+
+impl Sample for f64 {
+    const FORMAT: AudioFormat = AudioFormat::IeeeFloat;
+    const BITS: u16 = 64;
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend(self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        f64::from_le_bytes(bytes[..8].try_into().unwrap())
+    }
+}
+
+/// Checks that samples of the given format and bit depth can be decoded and encoded.
+pub(crate) fn check_supported(format: AudioFormat, bits_per_sample: u16) -> Result<(), WaveError> {
+    match (format, bits_per_sample) {
+        (AudioFormat::PCM, 8 | 16 | 24 | 32) => Ok(()),
+        (AudioFormat::IeeeFloat, 32 | 64) => Ok(()),
+        (AudioFormat::ALaw | AudioFormat::MuLaw, 8) => Ok(()),
+        (AudioFormat::Extensible, _) => Err(WaveError::UnsupportedFormat(format.get_val())),
+        (_, bits) => Err(WaveError::UnsupportedBitsPerSample(bits)),
+    }
+}
+
+/// Decodes a single sample of `bits_per_sample / 8` bytes to a normalized value.
+///
+/// The format must be [supported](check_supported).
+pub(crate) fn decode(bytes: &[u8], format: AudioFormat, bits_per_sample: u16) -> f64 {
+    match (format, bits_per_sample) {
+        (AudioFormat::PCM, 8) => u8::read_le(bytes).to_f64(),
+        (AudioFormat::PCM, 16) => i16::read_le(bytes).to_f64(),
+        (AudioFormat::PCM, 24) => I24::read_le(bytes).to_f64(),
+        (AudioFormat::PCM, 32) => i32::read_le(bytes).to_f64(),
+        (AudioFormat::IeeeFloat, 32) => f32::read_le(bytes).to_f64(),
+        (AudioFormat::IeeeFloat, 64) => f64::read_le(bytes),
+        (AudioFormat::ALaw, _) => g711::alaw_to_pcm16(bytes[0]).to_f64(),
+        (AudioFormat::MuLaw, _) => g711::mulaw_to_pcm16(bytes[0]).to_f64(),
+        _ => unreachable!("unsupported sample format"),
+    }
+}
+
+/// Decodes samples of a supported format to the [Sample] type `S`, reading them as they are if
+/// they already are of that type
+pub(crate) fn read_samples<'a, S: Sample>(samples: impl Iterator<Item = &'a [u8]>, format: AudioFormat, bits_per_sample: u16) -> Vec<S> {
+    if format == S::FORMAT && bits_per_sample == S::BITS {
+        samples.map(S::read_le).collect()
+    } else {
+        samples.map(|s| S::from_f64(decode(s, format, bits_per_sample))).collect()
+    }
+}
+
+/// Decodes a single sample of a supported format to the [Sample] type `S`, reading it as it is if
+/// it already is of that type
+pub(crate) fn read_sample<S: Sample>(bytes: &[u8], format: AudioFormat, bits_per_sample: u16) -> S {
+    if format == S::FORMAT && bits_per_sample == S::BITS {
+        S::read_le(bytes)
+    } else {
+        S::from_f64(decode(bytes, format, bits_per_sample))
+    }
+}
+
+/// Encodes a normalized value as a single sample, appending its bytes to `out`. Integer samples
+/// are rounded, and clamped to their range.
+///
+/// The format must be [supported](check_supported).
+pub(crate) fn encode(value: f64, format: AudioFormat, bits_per_sample: u16, out: &mut Vec<u8>) {
+    match (format, bits_per_sample) {
+        (AudioFormat::PCM, 8) => u8::from_f64(value).write_le(out),
+        (AudioFormat::PCM, 16) => i16::from_f64(value).write_le(out),
+        (AudioFormat::PCM, 24) => I24::from_f64(value).write_le(out),
+        (AudioFormat::PCM, 32) => i32::from_f64(value).write_le(out),
+        (AudioFormat::IeeeFloat, 32) => f32::from_f64(value).write_le(out),
+        (AudioFormat::IeeeFloat, 64) => value.write_le(out),
+        (AudioFormat::ALaw, _) => out.push(g711::linear_to_alaw(i16::from_f64(value))),
+        (AudioFormat::MuLaw, _) => out.push(g711::linear_to_mulaw(i16::from_f64(value))),
+        _ => unreachable!("unsupported sample format"),
+    }
+}
+
+/// Scales a normalized value to a signed integer of `bits` bits, rounding and clamping it.
+fn quantize(value: f64, bits: u16) -> i64 {
+    let max = (1i64 << (bits - 1)) as f64;
+    (value * max).round().clamp(-max, max - 1.0) as i64
+}
+
+// Adding samples
+impl WaveFile {
+    /// Adds interleaved 16-bit samples to the audio data, serialized in little endian.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the file isn't
+    ///   16-bit PCM. Use [add_samples_i16_converted](WaveFile::add_samples_i16_converted) to
+    ///   convert the samples to the format of the file.
+    /// - [WaveError::MisalignedData] if the amount of samples isn't divisible by the number of
+    ///   channels
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, -1, 256, -256]).unwrap();
+    /// assert_eq!(wave.bytes(), &[1, 0, 255, 255, 0, 1, 0, 255]);
+    ///
+    /// assert!(wave.add_samples_i16(&[1, 2, 3]).is_err());
+    /// assert!(wave.add_samples_f32(&[0.5, 0.5]).is_err());
+    /// ```
+    pub fn add_samples_i16(&mut self, samples: &[i16]) -> Result<(), WaveError> {
+        self.check_sample_format(AudioFormat::PCM, 16)?;
+        self.add_samples_with(samples, |&s, out| out.extend(s.to_le_bytes()))
+    }
+
+    /// Adds interleaved 32-bit float samples to the audio data, serialized in little endian.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the file isn't
+    ///   32-bit IEEE float. Use [add_samples_f32_converted](WaveFile::add_samples_f32_converted)
+    ///   to convert the samples to the format of the file.
+    /// - [WaveError::MisalignedData] if the amount of samples isn't divisible by the number of
+    ///   channels
+    pub fn add_samples_f32(&mut self, samples: &[f32]) -> Result<(), WaveError> {
+        self.check_sample_format(AudioFormat::IeeeFloat, 32)?;
+        self.add_samples_with(samples, |&s, out| out.extend(s.to_le_bytes()))
+    }
+
+    /// Adds interleaved 16-bit samples to the audio data, converting them to the format and bit
+    /// depth of the file. Samples are rounded when the file has a lower bit depth.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if samples can't
+    ///   be converted to the format of the file
+    /// - [WaveError::MisalignedData] if the amount of samples isn't divisible by the number of
+    ///   channels
+    pub fn add_samples_i16_converted(&mut self, samples: &[i16]) -> Result<(), WaveError> {
+        self.add_samples(samples)
+    }
+
+    /// Adds interleaved 32-bit float samples, normalized to `-1.0..1.0`, to the audio data,
+    /// converting them to the format and bit depth of the file. Integer samples are rounded and
+    /// clamped.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if samples can't
+    ///   be converted to the format of the file
+    /// - [WaveError::MisalignedData] if the amount of samples isn't divisible by the number of
+    ///   channels
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// wave.add_samples_f32_converted(&[0.5, -1.0, 2.0]).unwrap();
+    /// assert_eq!(wave.bytes(), &[0, 64, 0, 128, 255, 127]);
+    ///
+    /// // Converting to 16 bits uses vector instructions when the CPU supports them, with the same
+    /// // result as converting every sample on its own
+    /// let samples: Vec<f32> = (-70000..70000).map(|i| i as f32 / 65536.0).chain([f32::NAN, f32::INFINITY]).collect();
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// wave.add_samples_f32_converted(&samples).unwrap();
+    /// let scalar: Vec<i16> = samples.iter().map(|&s| i16::from_f64(s as f64)).collect();
+    /// assert_eq!(wave.samples_i16().unwrap(), scalar);
+    /// ```
+    pub fn add_samples_f32_converted(&mut self, samples: &[f32]) -> Result<(), WaveError> {
+        if (self.audio_format(), self.bits_per_sample) == (AudioFormat::PCM, 16) {
+            return self.add_encoded(samples.len(), |data| simd::f32_to_i16(samples, data));
+        }
+        self.add_samples(samples)
+    }
+    
+    /// Adds interleaved samples of any [Sample] type to the audio data, converting them to the
+    /// format and bit depth of the file (see [Sample] for how samples are converted).
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if samples can't
+    ///   be converted to the format of the file
+    /// - [WaveError::MisalignedData] if the amount of samples isn't divisible by the number of
+    ///   channels
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 24);
+    /// wave.add_samples(&[0.5f32, -1.0]).unwrap();
+    /// wave.add_samples(&[i16::MAX]).unwrap();
+    ///
+    /// let samples: Vec<i32> = wave.samples_as::<I24>().unwrap().into_iter().map(i32::from).collect();
+    /// assert_eq!(samples, [1 << 22, -(1 << 23), 32767 << 8]);
+    /// ```
+    pub fn add_samples<S: Sample>(&mut self, samples: &[S]) -> Result<(), WaveError> {
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        check_supported(format, bits)?;
+        if format == S::FORMAT && bits == S::BITS {
+            self.add_samples_with(samples, |&s, out| s.write_le(out))
+        } else {
+            self.add_samples_with(samples, |&s, out| encode(s.to_f64(), format, bits, out))
+        }
+    }
+
+    /// Adds the interleaved samples of an iterator to the audio data, converting them like
+    /// [add_samples](WaveFile::add_samples). Nothing is added if it errors.
+    ///
+    /// The samples are encoded as they are iterated, and only checked to be whole frames at the
+    /// end. This is what the [Extend] implementations use, without panicking.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if samples can't
+    ///   be converted to the format of the file
+    /// - [WaveError::MisalignedData] if the amount of samples isn't divisible by the number of
+    ///   channels
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.try_extend((0..4).map(|i| i as f32 / 4.0)).unwrap();
+    /// assert_eq!(wave.samples_i16().unwrap(), [0, 8192, 16384, 24576]);
+    ///
+    /// assert!(matches!(wave.try_extend([1i16, 2, 3]), Err(WaveError::MisalignedData { len: 6, block_align: 4 })));
+    /// assert_eq!(wave.num_frames(), 2);
+    /// ```
+    pub fn try_extend<S: Sample, I: IntoIterator<Item = S>>(&mut self, samples: I) -> Result<(), WaveError> {
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        check_supported(format, bits)?;
+        let samples = samples.into_iter();
+        let mut bytes = Vec::with_capacity(samples.size_hint().0 * (bits / 8) as usize);
+        if format == S::FORMAT && bits == S::BITS {
+            samples.for_each(|s| s.write_le(&mut bytes));
+        } else {
+            samples.for_each(|s| encode(s.to_f64(), format, bits, &mut bytes));
+        }
+        if !bytes.len().is_multiple_of(self.block_align as usize) {
+            return Err(WaveError::MisalignedData { len: bytes.len(), block_align: self.block_align });
+        }
+        self.audio_byte_data.append(&mut bytes);
+        Ok(())
+    }
+
+    /// Errors if the samples of the file aren't of the given format and bit depth
+    fn check_sample_format(&self, format: AudioFormat, bits_per_sample: u16) -> Result<(), WaveError> {
+        if self.audio_format() != format {
+            return Err(WaveError::UnsupportedFormat(self.audio_format().get_val()));
+        }
+        if self.bits_per_sample != bits_per_sample {
+            return Err(WaveError::UnsupportedBitsPerSample(self.bits_per_sample));
+        }
+        Ok(())
+    }
+
+    /// Serializes whole frames of interleaved samples with `encode`, and adds them to the audio
+    /// data.
+    fn add_samples_with<T>(&mut self, samples: &[T], encode: impl Fn(&T, &mut Vec<u8>)) -> Result<(), WaveError> {
+        self.add_encoded(samples.len(), |data| {
+            for sample in samples {
+                encode(sample, data);
+            }
+        })
+    }
+
+    /// Adds `len` samples that `encode` appends to the audio data, erroring if they aren't whole
+    /// frames
+    fn add_encoded(&mut self, len: usize, encode: impl FnOnce(&mut Vec<u8>)) -> Result<(), WaveError> {
+        if !len.is_multiple_of(self.num_channels as usize) {
+            return Err(WaveError::MisalignedData {
+                len: len * (self.bits_per_sample / 8) as usize,
+                block_align: self.block_align,
+            });
+        }
+        self.audio_byte_data.extend_with(len * (self.bits_per_sample / 8) as usize, encode);
+        Ok(())
+    }
+}
+
+/// Adds 16-bit samples like [try_extend](WaveFile::try_extend), converting them to the format of
+/// the file.
+///
+/// ## Panics
+/// If the file can't hold converted samples, or if the samples don't make whole frames, see
+/// [try_extend](WaveFile::try_extend).
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 8);
+/// wave.extend([i16::MIN, 0, 16384]);
+/// assert_eq!(wave.bytes(), &[0, 128, 192]);
+/// ```
+impl Extend<i16> for WaveFile {
+    fn extend<I: IntoIterator<Item = i16>>(&mut self, samples: I) {
+        self.try_extend(samples).unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+/// Adds 32-bit float samples, normalized to `-1.0..1.0`, like [try_extend](WaveFile::try_extend),
+/// converting them to the format of the file.
+///
+/// ## Panics
+/// If the file can't hold converted samples, or if the samples don't make whole frames, see
+/// [try_extend](WaveFile::try_extend).
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 44100, 32);
+/// wave.extend((0..100).flat_map(|i| [i as f32 / 100.0, 0.0]));
+/// assert_eq!(wave.num_frames(), 100);
+///
+/// let result = std::panic::catch_unwind(move || wave.extend([0.5f32]));
+/// assert!(result.is_err());
+/// ```
+impl Extend<f32> for WaveFile {
+    fn extend<I: IntoIterator<Item = f32>>(&mut self, samples: I) {
+        self.try_extend(samples).unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+// Mapping samples
+impl WaveFile {
+    /// Calls `f` for every sample in the audio data with the index of the frame, the channel and
+    /// the normalized value of the sample, without decoding the whole file at once. The samples
+    /// are read with [iter_samples](WaveFile::iter_samples), like all analysis of the samples.
+    /// Samples of a file with 0 channels are given as those of one channel, so buffers per
+    /// channel need at least one.
+    pub(crate) fn for_each_sample(&self, mut f: impl FnMut(u64, usize, f64)) -> Result<(), WaveError> {
+        let channels = self.num_channels.max(1) as usize;
+        let (mut frame, mut channel) = (0, 0);
+        for sample in self.iter_samples::<f64>()? {
+            f(frame, channel, sample);
+            channel += 1;
+            if channel == channels {
+                (frame, channel) = (frame + 1, 0);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces every sample of the frames in `frames` with the result of `f`, which is given the
+    /// index of the frame, the channel and the normalized value of the sample. Frames past the end
+    /// of the audio data are ignored.
+    pub(crate) fn map_samples(
+        &mut self,
+        frames: Range<u64>,
+        mut f: impl FnMut(u64, usize, f64) -> f64,
+    ) -> Result<(), WaveError> {
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        check_supported(format, bits)?;
+        let sample_len = (bits / 8) as usize;
+        let block_align = self.block_align as usize;
+        let end = frames.end.min(self.num_frames());
+        let mut encoded = Vec::with_capacity(sample_len);
+        for frame in frames.start..end {
+            let start = frame as usize * block_align;
+            let Some(data) = self.audio_byte_data.get_mut(start..start + block_align) else { break };
+            for (channel, bytes) in data.chunks_exact_mut(sample_len).enumerate() {
+                encoded.clear();
+                encode(f(frame, channel, decode(bytes, format, bits)), format, bits, &mut encoded);
+                bytes.copy_from_slice(&encoded);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Reading samples
+impl WaveFile {
+    /// Returns the interleaved samples in the audio data as 16-bit integers. 8-bit samples are
+    /// widened and A-law and µ-law samples are decoded.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples in
+    ///   the file can't be decoded
+    /// - [WaveError::LossyConversion] if the samples can't be represented as `i16` without losing
+    ///   precision (24 and 32-bit PCM, float). Use [samples_as](WaveFile::samples_as) to convert
+    ///   them anyway.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 8);
+    /// wave.add_bytes(&mut vec![0, 128, 255]);
+    /// assert_eq!(wave.samples_i16().unwrap(), [-32768, 0, 32512]);
+    ///
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 8000, 24);
+    /// wave.add_samples(&[I24::MAX]).unwrap();
+    /// assert!(matches!(wave.samples_i16(), Err(WaveError::LossyConversion { .. })));
+    /// assert_eq!(wave.samples_as::<i16>().unwrap(), [i16::MAX]);
+    /// ```
+    pub fn samples_i16(&self) -> Result<Vec<i16>, WaveError> {
+        self.check_lossless_i16()?;
+        self.samples_as()
+    }
+
+    /// Returns the interleaved samples in the audio data as 32-bit floats, normalized to
+    /// `-1.0..1.0` for integer formats. Float samples are passed through as is.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples in
+    ///   the file can't be decoded
+    /// - [WaveError::LossyConversion] if the samples can't be represented as `f32` without losing
+    ///   precision (32-bit PCM, 64-bit float). Use [samples_as](WaveFile::samples_as) to convert
+    ///   them anyway.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 24);
+    /// wave.add_samples(&[I24::MIN, I24::new(1 << 22).unwrap()]).unwrap();
+    /// assert_eq!(wave.samples_f32().unwrap(), [-1.0, 0.5]);
+    /// ```
+    ///
+    /// 16-bit samples are converted with vector instructions when the CPU supports them, with the
+    /// same result as converting every sample on its own:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let all: Vec<i16> = (i16::MIN..=i16::MAX).collect();
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+    /// wave.add_samples_i16(&all).unwrap();
+    ///
+    /// let scalar: Vec<f32> = all.iter().map(|s| s.to_f64() as f32).collect();
+    /// assert_eq!(wave.samples_f32().unwrap(), scalar);
+    /// ```
+    pub fn samples_f32(&self) -> Result<Vec<f32>, WaveError> {
+        self.check_lossless_f32()?;
+        if (self.audio_format(), self.bits_per_sample) == (AudioFormat::PCM, 16) {
+            let mut samples = Vec::with_capacity(self.audio_byte_data.len() / 2);
+            for segment in self.audio_byte_data.segments() {
+                simd::i16_to_f32(segment, &mut samples);
+            }
+            return Ok(samples);
+        }
+        self.samples_as()
+    }
+
+    /// Errors if the samples can't be decoded to `i16` without losing precision
+    pub(crate) fn check_lossless_i16(&self) -> Result<(), WaveError> {
+        check_supported(self.audio_format(), self.bits_per_sample)?;
+        match (self.audio_format(), self.bits_per_sample) {
+            (AudioFormat::PCM, 8 | 16) | (AudioFormat::ALaw | AudioFormat::MuLaw, _) => Ok(()),
+            (format, bits_per_sample) => Err(WaveError::LossyConversion { format, bits_per_sample }),
+        }
+    }
+
+    /// Errors if the samples can't be decoded to `f32` without losing precision
+    pub(crate) fn check_lossless_f32(&self) -> Result<(), WaveError> {
+        check_supported(self.audio_format(), self.bits_per_sample)?;
+        match (self.audio_format(), self.bits_per_sample) {
+            (AudioFormat::PCM, 8 | 16 | 24)
+            | (AudioFormat::IeeeFloat, 32)
+            | (AudioFormat::ALaw | AudioFormat::MuLaw, _) => Ok(()),
+            (format, bits_per_sample) => Err(WaveError::LossyConversion { format, bits_per_sample }),
+        }
+    }
+
+    /// Returns the interleaved samples in the audio data, converted to the [Sample] type `S`
+    /// (see [Sample] for how samples are converted). The conversion may lose precision.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples in
+    /// the file can't be decoded.
+    pub fn samples_as<S: Sample>(&self) -> Result<Vec<S>, WaveError> {
+        let (format, bits) = (self.audio_format(), self.bits_per_sample);
+        check_supported(format, bits)?;
+        let sample_len = (bits / 8) as usize;
+        Ok(read_samples(self.audio_byte_data.chunks_exact(sample_len), format, bits))
+    }
+}
@@ -0,0 +1,232 @@
+use super::chunk;
+use super::{WaveError, WaveFile};
+
+/// How a [LoopRegion] is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoopType {
+    /// Play from the start to the end, then jump back to the start
+    Forward,
+    /// Play forward, then backward, then forward again
+    PingPong,
+    /// Play from the end to the start
+    Backward,
+    /// A type specific to a manufacturer
+    Other(u32),
+}
+
+impl LoopType {
+    fn get_val(self) -> u32 {
+        match self {
+            LoopType::Forward => 0,
+            LoopType::PingPong => 1,
+            LoopType::Backward => 2,
+            LoopType::Other(value) => value,
+        }
+    }
+}
+
+impl From<u32> for LoopType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => LoopType::Forward,
+            1 => LoopType::PingPong,
+            2 => LoopType::Backward,
+            value => LoopType::Other(value),
+        }
+    }
+}
+
+/// A loop of a sampler instrument, see [SamplerInfo].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoopRegion {
+    /// The identifier of the loop, which can be matched to a [CuePoint](super::CuePoint) id
+    pub id: u32,
+    /// How the loop is played
+    pub loop_type: LoopType,
+    /// The first frame of the loop
+    pub start_frame: u32,
+    /// The last frame of the loop, which is played as well
+    pub end_frame: u32,
+    /// The fraction of a frame at which the loop ends, from 0 (none) to `u32::MAX` (almost a
+    /// whole frame)
+    pub fraction: u32,
+    /// How many times the loop is played, 0 for infinitely
+    pub play_count: u32,
+}
+
+/// The sampler information of the `smpl` chunk of a wave file: the MIDI note the audio plays and
+/// its loop points.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 44100, 16);
+/// wave.add_samples_i16(&[0; 1000]).unwrap();
+///
+/// let mut sampler = SamplerInfo { midi_unity_note: 69, ..SamplerInfo::default() };
+/// sampler.loops.push(LoopRegion {
+///     id: 0, loop_type: LoopType::Forward, start_frame: 100, end_frame: 999, fraction: 0, play_count: 0,
+/// });
+/// wave.set_sampler_info(sampler.clone()).unwrap();
+///
+/// let parsed = WaveFile::from_bytes(&wave.to_bytes()).unwrap();
+/// assert_eq!(parsed.sampler_info(), Some(&sampler));
+///
+/// // Loops have to be within the audio
+/// sampler.loops[0].end_frame = 1000;
+/// assert!(matches!(wave.set_sampler_info(sampler.clone()), Err(WaveError::LoopOutOfRange { .. })));
+/// assert_eq!(wave.set_sampler_info_clamped(sampler), 1);
+/// assert_eq!(wave.sampler_info().unwrap().loops[0].end_frame, 999);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SamplerInfo {
+    /// The MIDI manufacturer association code of the manufacturer of the intended sampler, 0 for
+    /// none
+    pub manufacturer: u32,
+    /// The product code of the intended sampler, 0 for none
+    pub product: u32,
+    /// The MIDI note at which the audio plays at its original pitch, 60 is middle C
+    pub midi_unity_note: u32,
+    /// The fraction of a semitone above `midi_unity_note` the audio plays at, from 0 to
+    /// `u32::MAX` (almost a whole semitone)
+    pub midi_pitch_fraction: u32,
+    /// The SMPTE format of `smpte_offset`: 0 for none, or 24, 25, 29 (30 drop frame) or 30 frames
+    /// per second
+    pub smpte_format: u32,
+    /// The SMPTE time at which the audio starts, as hours, minutes, seconds and frames bytes
+    pub smpte_offset: u32,
+    /// The loops of the audio
+    pub loops: Vec<LoopRegion>,
+    /// Data specific to the sampler
+    pub sampler_data: Vec<u8>,
+}
+
+impl Default for SamplerInfo {
+    fn default() -> Self {
+        SamplerInfo {
+            manufacturer: 0,
+            product: 0,
+            midi_unity_note: 60,
+            midi_pitch_fraction: 0,
+            smpte_format: 0,
+            smpte_offset: 0,
+            loops: Vec::new(),
+            sampler_data: Vec::new(),
+        }
+    }
+}
+
+impl SamplerInfo {
+    /// Parses a `smpl` chunk, `None` if it is too short
+    pub(crate) fn parse(smpl: &[u8]) -> Option<SamplerInfo> {
+        let u32_at = |i: usize| smpl.get(i..i + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+        let num_loops = u32_at(28)? as usize;
+        let data_len = u32_at(32)? as usize;
+        let loops = smpl[36..]
+            .chunks_exact(24)
+            .take(num_loops)
+            .map(|l| {
+                let u32_at = |i: usize| u32::from_le_bytes(l[i..i + 4].try_into().unwrap());
+                LoopRegion {
+                    id: u32_at(0),
+                    loop_type: LoopType::from(u32_at(4)),
+                    start_frame: u32_at(8),
+                    end_frame: u32_at(12),
+                    fraction: u32_at(16),
+                    play_count: u32_at(20),
+                }
+            })
+            .collect::<Vec<_>>();
+        let data_start = 36 + loops.len() * 24;
+        let sampler_data = smpl.get(data_start..).unwrap_or(&[]);
+        Some(SamplerInfo {
+            manufacturer: u32_at(0)?,
+            product: u32_at(4)?,
+            midi_unity_note: u32_at(12)?,
+            midi_pitch_fraction: u32_at(16)?,
+            smpte_format: u32_at(20)?,
+            smpte_offset: u32_at(24)?,
+            loops,
+            sampler_data: sampler_data[..data_len.min(sampler_data.len())].to_vec(),
+        })
+    }
+
+    /// Appends the `smpl` chunk to `out`, for audio at `sample_rate` Hz
+    pub(crate) fn write(&self, sample_rate: u32, out: &mut Vec<u8>) {
+        // The duration of a frame in nanoseconds
+        let sample_period = 1_000_000_000u32.checked_div(sample_rate).unwrap_or(0);
+        let mut smpl = Vec::with_capacity(36 + self.loops.len() * 24 + self.sampler_data.len());
+        for value in [
+            self.manufacturer,
+            self.product,
+            sample_period,
+            self.midi_unity_note,
+            self.midi_pitch_fraction,
+            self.smpte_format,
+            self.smpte_offset,
+            self.loops.len() as u32,
+            self.sampler_data.len() as u32,
+        ] {
+            smpl.extend(value.to_le_bytes());
+        }
+        for l in &self.loops {
+            for value in [l.id, l.loop_type.get_val(), l.start_frame, l.end_frame, l.fraction, l.play_count] {
+                smpl.extend(value.to_le_bytes());
+            }
+        }
+        smpl.extend(&self.sampler_data);
+        chunk::write_chunk(out, b"smpl", &smpl);
+    }
+}
+
+// Sampler information
+impl WaveFile {
+    /// Returns the sampler information of the `smpl` chunk, if the file has one
+    pub fn sampler_info(&self) -> Option<&SamplerInfo> {
+        self.sampler_info.as_ref()
+    }
+
+    /// Sets the sampler information that is written as the `smpl` chunk.
+    ///
+    /// # Errors
+    /// [WaveError::LoopOutOfRange] if a loop starts after it ends or ends after the last frame of
+    /// the audio. The sampler information is not changed in that case.
+    pub fn set_sampler_info(&mut self, sampler_info: SamplerInfo) -> Result<(), WaveError> {
+        let num_frames = self.num_frames();
+        for l in &sampler_info.loops {
+            if l.start_frame > l.end_frame || l.end_frame as u64 >= num_frames {
+                return Err(WaveError::LoopOutOfRange { start_frame: l.start_frame, end_frame: l.end_frame, num_frames });
+            }
+        }
+        self.sampler_info = Some(sampler_info);
+        Ok(())
+    }
+
+    /// Sets the sampler information that is written as the `smpl` chunk, first clamping the
+    /// loops to the audio: loops that end after the last frame end at the last frame, and loops
+    /// that start after their end start at their end.
+    ///
+    /// Returns the amount of loops that had to be clamped.
+    pub fn set_sampler_info_clamped(&mut self, mut sampler_info: SamplerInfo) -> usize {
+        let last_frame = self.num_frames().saturating_sub(1).min(u32::MAX as u64) as u32;
+        let mut clamped = 0;
+        for l in &mut sampler_info.loops {
+            let (start_frame, end_frame) = (l.start_frame, l.end_frame);
+            l.end_frame = l.end_frame.min(last_frame);
+            l.start_frame = l.start_frame.min(l.end_frame);
+            if (start_frame, end_frame) != (l.start_frame, l.end_frame) {
+                clamped += 1;
+            }
+        }
+        self.sampler_info = Some(sampler_info);
+        clamped
+    }
+
+    /// Removes the sampler information, returning it
+    pub fn remove_sampler_info(&mut self) -> Option<SamplerInfo> {
+        self.sampler_info.take()
+    }
+}
@@ -0,0 +1,185 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use super::{WaveError, WaveFile};
+
+// Silence
+impl WaveFile {
+    /// Returns the ranges of frames where every channel stays below `threshold_dbfs` decibels
+    /// relative to full scale for at least `min_duration`, rounded to whole frames, in order.
+    ///
+    /// `min_duration` works as a hangover time: quiet moments shorter than it, like the pauses
+    /// between words or the zero crossings of a waveform, don't count as silence. A
+    /// `min_duration` shorter than a frame counts as one frame.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 1000, 32);
+    /// wave.add_samples_f32(&[0.0; 2 * 30]).unwrap();
+    /// wave.add_samples_f32(&[0.5; 2 * 40]).unwrap();
+    /// // A short dip, and a frame with one loud channel
+    /// wave.add_samples_f32(&[0.0; 2 * 5]).unwrap();
+    /// wave.add_samples_f32(&[0.5; 2 * 40]).unwrap();
+    /// wave.add_samples_f32(&[0.0; 2 * 20]).unwrap();
+    /// wave.add_samples_f32(&[0.0, 0.5]).unwrap();
+    /// wave.add_samples_f32(&[0.001; 2 * 30]).unwrap();
+    ///
+    /// let silences = wave.detect_silence(-40.0, Duration::from_millis(10)).unwrap();
+    /// assert_eq!(silences, [0..30, 115..135, 136..166]);
+    /// ```
+    pub fn detect_silence(&self, threshold_dbfs: f32, min_duration: Duration) -> Result<Vec<Range<u64>>, WaveError> {
+        let min_len = self.frames_for_duration(min_duration).max(1);
+        let mut silences = Vec::new();
+        // The first frame of the current quiet run
+        let mut quiet_from = 0;
+        self.for_each_loud_frame(threshold_dbfs, |frame| {
+            if frame - quiet_from >= min_len {
+                silences.push(quiet_from..frame);
+            }
+            quiet_from = frame + 1;
+        })?;
+        let frames = self.num_frames();
+        if frames.saturating_sub(quiet_from) >= min_len {
+            silences.push(quiet_from..frames);
+        }
+        Ok(silences)
+    }
+
+    /// Splits the audio into the parts between the silences found by
+    /// [detect_silence](WaveFile::detect_silence), like the utterances of a recording of speech.
+    /// Silence at the start and end of the file is left out.
+    ///
+    /// Every part keeps up to `padding` of the silence around it, rounded to whole frames, so
+    /// words don't start or end abruptly. Two parts take at most half of the silence between
+    /// them each, so they never overlap.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 1000, 16);
+    /// for (level, len) in [(0, 100), (10000, 300), (0, 200), (-10000, 150), (20, 8), (10000, 50), (0, 60)] {
+    ///     wave.add_samples_i16(&vec![level; len]).unwrap();
+    /// }
+    ///
+    /// let parts = wave.split_on_silence(-50.0, Duration::from_millis(50), Duration::ZERO).unwrap();
+    /// let lens: Vec<u64> = parts.iter().map(|part| part.num_frames()).collect();
+    /// assert_eq!(lens, [300, 208]);
+    ///
+    /// // 80 ms of padding, but only 60 ms of silence at the end
+    /// let parts = wave.split_on_silence(-50.0, Duration::from_millis(50), Duration::from_millis(80)).unwrap();
+    /// let lens: Vec<u64> = parts.iter().map(|part| part.num_frames()).collect();
+    /// assert_eq!(lens, [80 + 300 + 80, 80 + 208 + 60]);
+    /// assert_eq!(parts[0].samples_i16().unwrap()[80], 10000);
+    /// ```
+    pub fn split_on_silence(
+        &self,
+        threshold_dbfs: f32,
+        min_duration: Duration,
+        padding: Duration,
+    ) -> Result<Vec<WaveFile>, WaveError> {
+        let frames = self.num_frames();
+        let mut sounds = Vec::new();
+        let mut sound_from = 0;
+        for silence in self.detect_silence(threshold_dbfs, min_duration)? {
+            if silence.start > sound_from {
+                sounds.push(sound_from..silence.start);
+            }
+            sound_from = silence.end;
+        }
+        if sound_from < frames {
+            sounds.push(sound_from..frames);
+        }
+
+        let padding = self.frames_for_duration(padding);
+        let mut parts = Vec::with_capacity(sounds.len());
+        for (i, sound) in sounds.iter().enumerate() {
+            let before = match i.checked_sub(1) {
+                Some(previous) => (sound.start - sounds[previous].end) / 2,
+                None => sound.start,
+            };
+            let after = match sounds.get(i + 1) {
+                Some(next) => (next.start - sound.end) / 2,
+                None => frames - sound.end,
+            };
+            let start = sound.start - before.min(padding);
+            let end = sound.end + after.min(padding);
+            parts.push(self.slice_frames(start..end)?);
+        }
+        Ok(parts)
+    }
+
+    /// Removes the silence at the start and end of the audio data, where every channel stays
+    /// below `threshold_dbfs` decibels relative to full scale, keeping up to `keep` of it
+    /// (rounded to whole frames) before the first and after the last frame that isn't silent.
+    /// Silence is detected the same way as by [detect_silence](WaveFile::detect_silence).
+    ///
+    /// A file that is silent from start to end is emptied when `empty_if_silent` is true, and
+    /// left unchanged otherwise. Returns the amount of frames that were removed.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the samples of
+    /// the file can't be decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 1000, 16);
+    /// wave.add_samples_i16(&[0; 2 * 100]).unwrap();
+    /// wave.add_samples_i16(&[0, 5000, 8000, -8000, 3, 2, 6000, 0]).unwrap();
+    /// wave.add_samples_i16(&[1; 2 * 3]).unwrap();
+    ///
+    /// assert_eq!(wave.trim_silence(-40.0, Duration::from_millis(2), false).unwrap(), 98 + 1);
+    /// assert_eq!(wave.samples_i16().unwrap()[..6], [0, 0, 0, 0, 0, 5000]);
+    /// assert_eq!(wave.num_frames(), 2 + 4 + 2);
+    ///
+    /// let mut silent = WaveFile::new(AudioFormat::PCM, 1, 1000, 16);
+    /// silent.add_samples_i16(&[3; 50]).unwrap();
+    /// assert_eq!(silent.trim_silence(-40.0, Duration::ZERO, false).unwrap(), 0);
+    /// assert_eq!(silent.num_frames(), 50);
+    /// assert_eq!(silent.trim_silence(-40.0, Duration::ZERO, true).unwrap(), 50);
+    /// assert_eq!(silent.num_frames(), 0);
+    /// ```
+    pub fn trim_silence(&mut self, threshold_dbfs: f32, keep: Duration, empty_if_silent: bool) -> Result<u64, WaveError> {
+        let mut sound: Option<Range<u64>> = None;
+        self.for_each_loud_frame(threshold_dbfs, |frame| {
+            sound = Some(sound.as_ref().map_or(frame, |sound| sound.start)..frame + 1);
+        })?;
+        let frames = self.num_frames();
+        let (start, end) = match sound {
+            Some(sound) => {
+                let keep = self.frames_for_duration(keep);
+                (sound.start.saturating_sub(keep), (sound.end + keep).min(frames))
+            }
+            None if empty_if_silent => (0, 0),
+            None => return Ok(0),
+        };
+        self.remove_frames(end..frames)?;
+        self.remove_frames(0..start)?;
+        Ok(frames - (end - start))
+    }
+
+    /// Calls `f` with every frame in which a channel reaches `threshold_dbfs` decibels relative
+    /// to full scale: the frames that aren't silent
+    fn for_each_loud_frame(&self, threshold_dbfs: f32, mut f: impl FnMut(u64)) -> Result<(), WaveError> {
+        let threshold = 10f64.powf(threshold_dbfs as f64 / 20.0);
+        let mut last = None;
+        self.for_each_sample(|frame, _, sample| {
+            if sample.abs() >= threshold && last != Some(frame) {
+                last = Some(frame);
+                f(frame);
+            }
+        })
+    }
+}
@@ -0,0 +1,149 @@
+//! Vectorized versions of the conversions that dominate processing 16-bit audio.
+//!
+//! The vectorized version is picked at runtime when the CPU supports it, and the samples it
+//! leaves over are converted by the scalar version. Both round and saturate the same way as
+//! [Sample::from_f64], so they give the same results.
+
+use super::Sample;
+
+/// Appends the 16-bit little endian samples in `bytes` to `out` as normalized `f32` samples. A
+/// byte left over at the end is ignored.
+pub(crate) fn i16_to_f32(bytes: &[u8], out: &mut Vec<f32>) {
+    out.reserve(bytes.len() / 2);
+    #[allow(unused_mut)]
+    let mut rest = bytes;
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("sse2") {
+        // SAFETY: the CPU supports SSE2
+        rest = unsafe { sse2::i16_to_f32(bytes, out) };
+    }
+    out.extend(rest.chunks_exact(2).map(|bytes| i16::read_le(bytes).to_f64() as f32));
+}
+
+/// Appends normalized `f32` samples to `out` as 16-bit little endian samples, rounded and
+/// clamped like [Sample::from_f64] does.
+pub(crate) fn f32_to_i16(samples: &[f32], out: &mut Vec<u8>) {
+    out.reserve(samples.len() * 2);
+    #[allow(unused_mut)]
+    let mut rest = samples;
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("sse2") {
+        // SAFETY: the CPU supports SSE2
+        rest = unsafe { sse2::f32_to_i16(samples, out) };
+    }
+    for &sample in rest {
+        i16::from_f64(sample as f64).write_le(out);
+    }
+}
+
+/// Multiplies the 16-bit little endian samples in `bytes` by `gain` in place, rounding and
+/// clamping them like [Sample::from_f64] does. A byte left over at the end is unchanged.
+///
+/// `gain` has to be finite.
+pub(crate) fn gain_i16(bytes: &mut [u8], gain: f64) {
+    #[allow(unused_mut)]
+    let mut rest = bytes;
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("sse2") {
+        // SAFETY: the CPU supports SSE2
+        rest = unsafe { sse2::gain_i16(rest, gain) };
+    }
+    for bytes in rest.chunks_exact_mut(2) {
+        let sample = i16::from_f64(i16::read_le(bytes).to_f64() * gain);
+        bytes.copy_from_slice(&sample.to_le_bytes());
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    //! SSE2 versions of the conversions, 8 samples at a time. They return the part of the input
+    //! they didn't convert.
+
+    use std::arch::x86_64::*;
+
+    /// The scale of a normalized value as a 16-bit sample
+    const SCALE: f64 = 32768.0;
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn i16_to_f32<'a>(bytes: &'a [u8], out: &mut Vec<f32>) -> &'a [u8] {
+        let chunks = bytes.chunks_exact(16);
+        let rest = chunks.remainder();
+        let scale = _mm_set1_ps((1.0 / SCALE) as f32);
+        let mut converted = [0.0f32; 8];
+        for chunk in chunks {
+            let (low, high) = widen(_mm_loadu_si128(chunk.as_ptr().cast()));
+            // Dividing by a power of two is exact, like it is for the scalar version
+            _mm_storeu_ps(converted.as_mut_ptr(), _mm_mul_ps(_mm_cvtepi32_ps(low), scale));
+            _mm_storeu_ps(converted.as_mut_ptr().add(4), _mm_mul_ps(_mm_cvtepi32_ps(high), scale));
+            out.extend_from_slice(&converted);
+        }
+        rest
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn f32_to_i16<'a>(samples: &'a [f32], out: &mut Vec<u8>) -> &'a [f32] {
+        let chunks = samples.chunks_exact(8);
+        let rest = chunks.remainder();
+        let mut converted = [0u8; 16];
+        for chunk in chunks {
+            let low = _mm_loadu_ps(chunk.as_ptr());
+            let high = _mm_loadu_ps(chunk.as_ptr().add(4));
+            let low = join(quantize(_mm_cvtps_pd(low)), quantize(_mm_cvtps_pd(_mm_movehl_ps(low, low))));
+            let high = join(quantize(_mm_cvtps_pd(high)), quantize(_mm_cvtps_pd(_mm_movehl_ps(high, high))));
+            _mm_storeu_si128(converted.as_mut_ptr().cast(), _mm_packs_epi32(low, high));
+            out.extend_from_slice(&converted);
+        }
+        rest
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn gain_i16(bytes: &mut [u8], gain: f64) -> &mut [u8] {
+        let len = bytes.len() - bytes.len() % 16;
+        let (chunks, rest) = bytes.split_at_mut(len);
+        let gain = _mm_set1_pd(gain);
+        let scale = _mm_set1_pd(1.0 / SCALE);
+        // The same operations as the scalar version: normalize, then amplify
+        let amplify = |samples: __m128i| {
+            let low = _mm_mul_pd(_mm_mul_pd(_mm_cvtepi32_pd(samples), scale), gain);
+            let high = _mm_mul_pd(_mm_mul_pd(_mm_cvtepi32_pd(_mm_shuffle_epi32::<0b1110>(samples)), scale), gain);
+            join(quantize(low), quantize(high))
+        };
+        for chunk in chunks.chunks_exact_mut(16) {
+            let (low, high) = widen(_mm_loadu_si128(chunk.as_ptr().cast()));
+            _mm_storeu_si128(chunk.as_mut_ptr().cast(), _mm_packs_epi32(amplify(low), amplify(high)));
+        }
+        rest
+    }
+
+    /// Sign extends 8 16-bit samples to two vectors of 4 32-bit samples
+    #[target_feature(enable = "sse2")]
+    unsafe fn widen(samples: __m128i) -> (__m128i, __m128i) {
+        // Placing the samples in the upper halves and shifting them back extends the sign
+        let low = _mm_srai_epi32::<16>(_mm_unpacklo_epi16(samples, samples));
+        let high = _mm_srai_epi32::<16>(_mm_unpackhi_epi16(samples, samples));
+        (low, high)
+    }
+
+    /// Joins the 2 32-bit values in the lower halves of two vectors into one vector of 4
+    #[target_feature(enable = "sse2")]
+    unsafe fn join(low: __m128i, high: __m128i) -> __m128i {
+        _mm_unpacklo_epi64(low, high)
+    }
+
+    /// Scales 2 normalized values to 16-bit samples, in the lower half of the result. They are
+    /// rounded half away from zero and clamped like the scalar version, NaN becomes 0.
+    #[target_feature(enable = "sse2")]
+    unsafe fn quantize(values: __m128d) -> __m128i {
+        let scaled = _mm_mul_pd(values, _mm_set1_pd(SCALE));
+        let scaled = _mm_and_pd(scaled, _mm_cmpord_pd(scaled, scaled));
+        // Clamping to integer bounds before rounding gives the same result as after
+        let clamped = _mm_min_pd(_mm_max_pd(scaled, _mm_set1_pd(-SCALE)), _mm_set1_pd(SCALE - 1.0));
+        let truncated = _mm_cvtepi32_pd(_mm_cvttpd_epi32(clamped));
+        // The fraction is exact, so comparing it with a half rounds like f64::round
+        let fraction = _mm_sub_pd(clamped, truncated);
+        let one = _mm_set1_pd(1.0);
+        let up = _mm_and_pd(_mm_cmpge_pd(fraction, _mm_set1_pd(0.5)), one);
+        let down = _mm_and_pd(_mm_cmple_pd(fraction, _mm_set1_pd(-0.5)), one);
+        _mm_cvttpd_epi32(_mm_add_pd(truncated, _mm_sub_pd(up, down)))
+    }
+}
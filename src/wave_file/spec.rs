@@ -0,0 +1,259 @@
+use super::sample::{self, Sample};
+use super::{AudioFormat, WaveError, WaveFile};
+
+/// The format of the audio data of a wave file: everything needed to interpret its samples.
+///
+/// The same spec describes a [WaveFile], a [WaveReader](super::WaveReader) and a
+/// [WaveWriter](super::WaveWriter), so specs can be compared before files are combined. Use
+/// [WaveSpec::new] to build one that is checked to describe a valid wave file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WaveSpec {
+    /// The format of the samples
+    pub format: AudioFormat,
+    /// The amount of channels
+    pub channels: u16,
+    /// The amount of frames per second, in Hz
+    pub sample_rate: u32,
+    /// The amount of bits of every sample
+    pub bits_per_sample: u16,
+}
+
+impl WaveSpec {
+    /// Creates a new spec, checking that it describes a wave file that can be written and read.
+    ///
+    /// # Errors
+    /// - [WaveError::UnsupportedFormat] for [AudioFormat::Extensible], which doesn't say how the
+    ///   samples are stored, use the sub format instead
+    /// - [WaveError::UnsupportedBitsPerSample] if `bits_per_sample` is not supported for `format`:
+    ///   8, 16, 24 or 32 for PCM, 32 or 64 for IEEE float and 8 for A-law and µ-law
+    /// - [WaveError::InvalidSpec] if `channels` or `sample_rate` is 0, or if the byte rate doesn't
+    ///   fit in 32 bits
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::new(AudioFormat::PCM, 2, 44100, 16).unwrap();
+    /// assert_eq!(spec.sample_rate, 44100);
+    ///
+    /// // Sample rate and bits per sample swapped
+    /// assert!(matches!(
+    ///     WaveSpec::new(AudioFormat::PCM, 2, 16, 44100),
+    ///     Err(WaveError::UnsupportedBitsPerSample(_))
+    /// ));
+    /// assert!(matches!(WaveSpec::new(AudioFormat::MuLaw, 1, 8000, 16), Err(WaveError::UnsupportedBitsPerSample(16))));
+    /// assert!(matches!(WaveSpec::new(AudioFormat::PCM, 0, 8000, 16), Err(WaveError::InvalidSpec(_))));
+    /// ```
+    pub fn new(format: AudioFormat, channels: u16, sample_rate: u32, bits_per_sample: u16) -> Result<WaveSpec, WaveError> {
+        let spec = WaveSpec { format, channels, sample_rate, bits_per_sample };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Stereo 16-bit PCM at 44.1 kHz, the format of audio CDs.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::cd_quality();
+    /// assert_eq!(spec, WaveSpec::new(AudioFormat::PCM, 2, 44100, 16).unwrap());
+    /// ```
+    pub const fn cd_quality() -> WaveSpec {
+        WaveSpec { format: AudioFormat::PCM, channels: 2, sample_rate: 44100, bits_per_sample: 16 }
+    }
+
+    /// Mono 8-bit µ-law at 8 kHz, the format of North American and Japanese telephony (G.711).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::telephony_mulaw();
+    /// assert_eq!(spec, WaveSpec::new(AudioFormat::MuLaw, 1, 8000, 8).unwrap());
+    /// ```
+    pub const fn telephony_mulaw() -> WaveSpec {
+        WaveSpec { format: AudioFormat::MuLaw, channels: 1, sample_rate: 8000, bits_per_sample: 8 }
+    }
+
+    /// Mono 16-bit PCM at 8 kHz, telephony audio before it is encoded with G.711 or after it is
+    /// decoded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::telephony_pcm();
+    /// assert_eq!(spec, WaveSpec::new(AudioFormat::PCM, 1, 8000, 16).unwrap());
+    /// ```
+    pub const fn telephony_pcm() -> WaveSpec {
+        WaveSpec { format: AudioFormat::PCM, channels: 1, sample_rate: 8000, bits_per_sample: 16 }
+    }
+
+    /// Stereo 16-bit PCM at 48 kHz, the format of DVD and most video.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::dvd();
+    /// assert_eq!(spec, WaveSpec::new(AudioFormat::PCM, 2, 48000, 16).unwrap());
+    /// ```
+    pub const fn dvd() -> WaveSpec {
+        WaveSpec { format: AudioFormat::PCM, channels: 2, sample_rate: 48000, bits_per_sample: 16 }
+    }
+
+    /// Stereo 24-bit PCM at 96 kHz, a common high resolution studio format.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::hi_res();
+    /// assert_eq!(spec, WaveSpec::new(AudioFormat::PCM, 2, 96000, 24).unwrap());
+    /// ```
+    pub const fn hi_res() -> WaveSpec {
+        WaveSpec { format: AudioFormat::PCM, channels: 2, sample_rate: 96000, bits_per_sample: 24 }
+    }
+
+    /// Checks the spec like [WaveSpec::new]
+    pub(crate) fn validate(&self) -> Result<(), WaveError> {
+        sample::check_supported(self.format, self.bits_per_sample)?;
+        if self.channels == 0 {
+            return Err(WaveError::InvalidSpec("a wave file needs at least one channel"));
+        }
+        if self.sample_rate == 0 {
+            return Err(WaveError::InvalidSpec("the sample rate is 0"));
+        }
+        let block_align = self.channels as u32 * (self.bits_per_sample as u32 / 8);
+        if block_align > u16::MAX as u32 {
+            return Err(WaveError::InvalidSpec("the block align doesn't fit in 16 bits"));
+        }
+        if block_align.checked_mul(self.sample_rate).is_none() {
+            return Err(WaveError::InvalidSpec("the byte rate doesn't fit in 32 bits"));
+        }
+        Ok(())
+    }
+
+    /// Creates a new file of this spec holding the interleaved samples of an iterator, converted
+    /// to the format of the spec like [add_samples](WaveFile::add_samples).
+    ///
+    /// # Errors
+    /// - The errors of [WaveSpec::new] if the spec is invalid
+    /// - [WaveError::MisalignedData] if the amount of samples isn't divisible by the number of
+    ///   channels
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::new(AudioFormat::PCM, 1, 8000, 16).unwrap();
+    /// let sine = (0..8000).map(|i| (i as f32 * 440.0 / 8000.0 * std::f32::consts::TAU).sin() * 0.5);
+    ///
+    /// let wave = spec.collect(sine).unwrap();
+    /// assert_eq!(wave.spec(), spec);
+    /// assert_eq!(wave.duration_secs_f64(), 1.0);
+    ///
+    /// let stereo = WaveSpec::cd_quality();
+    /// assert!(matches!(stereo.collect([0i16; 3]), Err(WaveError::MisalignedData { .. })));
+    /// ```
+    pub fn collect<S: Sample, I: IntoIterator<Item = S>>(self, samples: I) -> Result<WaveFile, WaveError> {
+        self.validate()?;
+        let mut wave = WaveFile::with_spec(self);
+        wave.try_extend(samples)?;
+        Ok(wave)
+    }
+}
+
+impl WaveFile {
+    /// Creates a new, empty `WaveFile` of `spec`, see [WaveFile::new].
+    ///
+    /// ## Panics
+    /// Like [WaveFile::new], if the spec is invalid. Specs created with [WaveSpec::new] never are.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let spec = WaveSpec::new(AudioFormat::PCM, 2, 44100, 16).unwrap();
+    /// let wave = WaveFile::with_spec(spec);
+    /// assert_eq!(wave.spec(), spec);
+    /// assert_eq!(wave.num_frames(), 0);
+    /// ```
+    pub fn with_spec(spec: WaveSpec) -> WaveFile {
+        WaveFile::new(spec.format, spec.channels, spec.sample_rate, spec.bits_per_sample)
+    }
+
+    /// Creates a new, empty `WaveFile` of `spec` with room for `frames` frames, so adding that
+    /// much audio data doesn't allocate again (see [reserve_frames](WaveFile::reserve_frames)).
+    ///
+    /// # Errors
+    /// - The errors of [WaveSpec::new] if the spec is invalid
+    /// - [WaveError::CapacityOverflow] if the frames don't fit in memory
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// // Ten minutes of 24-bit stereo at 48 kHz
+    /// let spec = WaveSpec::new(AudioFormat::PCM, 2, 48000, 24).unwrap();
+    /// let wave = WaveFile::with_capacity(spec, 10 * 60 * 48000).unwrap();
+    /// assert!(wave.capacity_frames() >= 10 * 60 * 48000);
+    /// assert_eq!(wave.num_frames(), 0);
+    ///
+    /// assert!(matches!(WaveFile::with_capacity(spec, u64::MAX), Err(WaveError::CapacityOverflow { .. })));
+    /// ```
+    pub fn with_capacity(spec: WaveSpec, frames: u64) -> Result<WaveFile, WaveError> {
+        spec.validate()?;
+        let mut wave = WaveFile::with_spec(spec);
+        wave.reserve_frames(frames)?;
+        Ok(wave)
+    }
+
+    /// Creates a new, empty `WaveFile` of [WaveSpec::cd_quality]: stereo 16-bit PCM at 44.1 kHz.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new_cd_quality();
+    /// wave.add_samples_i16(&[0, 0]).unwrap();
+    /// assert_eq!(wave.spec(), WaveSpec::cd_quality());
+    /// ```
+    pub fn new_cd_quality() -> WaveFile {
+        WaveFile::with_spec(WaveSpec::cd_quality())
+    }
+
+    /// Creates a new, empty `WaveFile` of [WaveSpec::telephony_mulaw]: mono 8-bit µ-law at 8 kHz.
+    pub fn new_telephony_mulaw() -> WaveFile {
+        WaveFile::with_spec(WaveSpec::telephony_mulaw())
+    }
+
+    /// Creates a new, empty `WaveFile` of [WaveSpec::telephony_pcm]: mono 16-bit PCM at 8 kHz.
+    pub fn new_telephony_pcm() -> WaveFile {
+        WaveFile::with_spec(WaveSpec::telephony_pcm())
+    }
+
+    /// Creates a new, empty `WaveFile` of [WaveSpec::dvd]: stereo 16-bit PCM at 48 kHz.
+    pub fn new_dvd() -> WaveFile {
+        WaveFile::with_spec(WaveSpec::dvd())
+    }
+
+    /// Creates a new, empty `WaveFile` of [WaveSpec::hi_res]: stereo 24-bit PCM at 96 kHz.
+    pub fn new_hi_res() -> WaveFile {
+        WaveFile::with_spec(WaveSpec::hi_res())
+    }
+
+    /// Returns the spec of the file: the format, channels, sample rate and bits per sample its
+    /// audio data is stored in.
+    ///
+    /// For files written with `WAVE_FORMAT_EXTENSIBLE` the format is the sub format, see
+    /// [audio_format](WaveFile::audio_format).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let wave = WaveFile::new(AudioFormat::IeeeFloat, 2, 48000, 32);
+    /// let spec = WaveSpec { format: AudioFormat::IeeeFloat, channels: 2, sample_rate: 48000, bits_per_sample: 32 };
+    /// assert_eq!(wave.spec(), spec);
+    /// ```
+    pub fn spec(&self) -> WaveSpec {
+        WaveSpec {
+            format: self.audio_format(),
+            channels: self.num_channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample: self.bits_per_sample,
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{WaveError, WaveFile, WaveReader, WaveSpec, WaveWriter};
+
+/// The amount of frames copied at once by [split_file_every]
+const COPY_FRAMES: usize = 1 << 14;
+
+// Splitting
+impl WaveFile {
+    /// Splits the audio into segments of `segment`, rounded to whole frames, like for a service
+    /// that only accepts audio up to a certain length. The last segment holds the frames that
+    /// are left and can be shorter. A file without audio data gives no segments.
+    ///
+    /// With an `overlap`, rounded to whole frames, every segment after the first starts with the
+    /// last `overlap` of the segment before it, so the context at a boundary is in both
+    /// segments. Segments are never longer than `segment`, a segment starts `segment - overlap`
+    /// after the one before it.
+    ///
+    /// # Errors
+    /// [WaveError::InvalidRange] if the audio data can't be sliced, see
+    /// [slice_frames](WaveFile::slice_frames).
+    ///
+    /// ## Panics
+    /// If `segment` is shorter than a frame, or `overlap` isn't shorter than `segment`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::time::Duration;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 1, 1000, 16);
+    /// wave.add_samples_i16(&(0..2500).map(|i| i as i16).collect::<Vec<_>>()).unwrap();
+    ///
+    /// let segments = wave.split_every(Duration::from_secs(1), Duration::ZERO).unwrap();
+    /// let lens: Vec<u64> = segments.iter().map(|segment| segment.num_frames()).collect();
+    /// assert_eq!(lens, [1000, 1000, 500]);
+    ///
+    /// // 100 ms of context repeated at every boundary
+    /// let segments = wave.split_every(Duration::from_secs(1), Duration::from_millis(100)).unwrap();
+    /// let lens: Vec<u64> = segments.iter().map(|segment| segment.num_frames()).collect();
+    /// assert_eq!(lens, [1000, 1000, 700]);
+    /// assert_eq!(segments[1].samples_i16().unwrap()[0], 900);
+    /// ```
+    pub fn split_every(&self, segment: Duration, overlap: Duration) -> Result<Vec<WaveFile>, WaveError> {
+        let (segment, overlap) = split_lengths(self.spec(), segment, overlap);
+        let frames = self.num_frames();
+        let mut segments = Vec::new();
+        let mut start = 0;
+        while start < frames {
+            let end = (start + segment).min(frames);
+            segments.push(self.slice_frames(start..end)?);
+            if end == frames {
+                break;
+            }
+            start = end - overlap;
+        }
+        Ok(segments)
+    }
+}
+
+/// Splits the wave file at `input` into segments of `segment` with `overlap`, like
+/// [WaveFile::split_every], and writes them to new wave files in `output_dir`. Returns the paths
+/// of the files in order.
+///
+/// The files are named after `input` with the number of the segment, starting at 1: `talk.wav`
+/// is split into `talk-001.wav`, `talk-002.wav` and so on. Existing files are replaced. The audio
+/// is copied a block at a time, so the whole file is never in memory. Only the audio is copied,
+/// the metadata of `input` is not.
+///
+/// # Errors
+/// - the errors of [WaveReader::new] and [WaveSpec::new] if `input` can't be read
+/// - [WaveError::Io] if a file can't be read or written
+///
+/// The segments written before an error are left in `output_dir`.
+///
+/// ## Panics
+/// If `segment` is shorter than a frame, or `overlap` isn't shorter than `segment`.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::fs;
+/// # use std::time::Duration;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 8000, 16);
+/// wave.add_samples_i16(&(0..2 * 20000).map(|i| i as i16).collect::<Vec<_>>()).unwrap();
+/// let dir = std::env::temp_dir().join("jaudio-split-file-every");
+/// fs::create_dir_all(&dir).unwrap();
+/// let input = dir.join("talk.wav");
+/// fs::write(&input, wave.to_bytes()).unwrap();
+///
+/// let paths = split_file_every(input.to_str().unwrap(), dir.to_str().unwrap(), Duration::from_secs(1), Duration::from_millis(250)).unwrap();
+/// assert_eq!(paths.len(), 3);
+/// assert!(paths[0].ends_with("talk-001.wav"));
+///
+/// // The same segments as splitting in memory
+/// let expected = wave.split_every(Duration::from_secs(1), Duration::from_millis(250)).unwrap();
+/// for (path, expected) in paths.iter().zip(&expected) {
+///     let segment = WaveFile::from_file(path.to_str().unwrap()).unwrap();
+///     assert_eq!(segment.samples_i16().unwrap(), expected.samples_i16().unwrap());
+/// }
+/// # fs::remove_dir_all(dir).unwrap();
+/// ```
+pub fn split_file_every(input: &str, output_dir: &str, segment: Duration, overlap: Duration) -> Result<Vec<PathBuf>, WaveError> {
+    let mut reader = WaveReader::new(BufReader::new(File::open(input)?))?;
+    let spec = reader.spec();
+    spec.validate()?;
+    let (segment, overlap) = split_lengths(spec, segment, overlap);
+    let block_align = reader.block_align() as usize;
+    let segment_len = segment as usize * block_align;
+    let overlap_len = overlap as usize * block_align;
+    let name = Path::new(input).file_stem().and_then(|stem| stem.to_str()).unwrap_or("segment");
+
+    let mut paths = Vec::new();
+    let copy_len = COPY_FRAMES * block_align;
+    let mut buf = vec![0; copy_len];
+    // The end of the segment before, which the next segment starts with
+    let mut carried = Vec::new();
+    loop {
+        let mut len = reader.read_block(&mut buf[..(segment_len - carried.len()).min(copy_len)])?;
+        if len == 0 {
+            break;
+        }
+        let path = Path::new(output_dir).join(format!("{}-{:03}.wav", name, paths.len() + 1));
+        let mut writer = WaveWriter::with_spec(BufWriter::new(File::create(&path)?), spec)?;
+        paths.push(path);
+        writer.write_bytes(&carried)?;
+        let mut written = carried.len();
+        let mut tail = carried;
+        while len > 0 {
+            writer.write_bytes(&buf[..len])?;
+            written += len;
+            tail.extend_from_slice(&buf[..len]);
+            if tail.len() > 2 * overlap_len.max(copy_len) {
+                tail.drain(..tail.len() - overlap_len);
+            }
+            // Nothing is read once the segment is full
+            len = reader.read_block(&mut buf[..(segment_len - written).min(copy_len)])?;
+        }
+        writer.finalize()?;
+        tail.drain(..tail.len().saturating_sub(overlap_len));
+        carried = tail;
+        if written < segment_len {
+            break;
+        }
+    }
+    Ok(paths)
+}
+
+/// Returns the amount of frames of a segment and its overlap of [WaveFile::split_every]
+fn split_lengths(spec: WaveSpec, segment: Duration, overlap: Duration) -> (u64, u64) {
+    let wave = WaveFile::with_spec(spec);
+    let (segment, overlap) = (wave.frames_for_duration(segment), wave.frames_for_duration(overlap));
+    assert!(segment > 0, "a segment must be at least a frame long");
+    assert!(overlap < segment, "the overlap must be shorter than a segment");
+    (segment, overlap)
+}
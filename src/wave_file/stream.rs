@@ -0,0 +1,126 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use super::data::AudioData;
+use super::{WaveError, WaveFile};
+
+/// Reads the byte representation of a [WaveFile] (see [WaveFile::to_bytes]) without building it
+/// in memory, see [WaveFile::reader].
+///
+/// Only the header and the metadata chunks after the audio data are built when the reader is
+/// created; the audio data is read from the file as it is.
+pub struct WaveFileReader<'a> {
+    header: Vec<u8>,
+    data: &'a AudioData,
+    /// The pad byte of the data chunk, if needed, and the chunks after it
+    trailing: Vec<u8>,
+    pos: u64,
+    /// The size of the RIFF chunk if the file is too large to write, see [WaveError::TooLarge]
+    too_large: Option<u64>,
+}
+
+impl WaveFileReader<'_> {
+    /// The total amount of bytes, the same as [WaveFile::encoded_size]
+    pub fn len(&self) -> u64 {
+        (self.header.len() + self.data.len() + self.trailing.len()) as u64
+    }
+
+    /// Whether there are no bytes to read, which is never the case
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bytes at the current position, up to the end of the part they are in
+    fn remaining_part(&self) -> &[u8] {
+        let header_len = self.header.len() as u64;
+        let data_end = header_len + self.data.len() as u64;
+        if self.pos < header_len {
+            &self.header[self.pos as usize..]
+        } else if self.pos < data_end {
+            let start = (self.pos - header_len) as usize;
+            self.data.slices(start..self.data.len()).next().unwrap_or(&[])
+        } else {
+            self.trailing.get((self.pos - data_end) as usize..).unwrap_or(&[])
+        }
+    }
+}
+
+impl Read for WaveFileReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(size) = self.too_large {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, WaveError::TooLarge { size }));
+        }
+        let part = self.remaining_part();
+        let n = part.len().min(buf.len());
+        buf[..n].copy_from_slice(&part[..n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for WaveFileReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek to a negative position"))?;
+        Ok(self.pos)
+    }
+}
+
+// Streaming
+impl WaveFile {
+    /// Returns a reader of the byte representation of the `WaveFile` (see
+    /// [to_bytes](WaveFile::to_bytes)), which can be passed to anything that takes a [Read]
+    /// without copying the audio data. It also implements [Seek], so reads can resume at any
+    /// position.
+    ///
+    /// If the file doesn't fit in RIFF and the [Rf64Mode](super::Rf64Mode) is
+    /// [Never](super::Rf64Mode::Never), every read returns an error of kind
+    /// [InvalidInput](io::ErrorKind::InvalidInput) wrapping [WaveError::TooLarge].
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::io::{Read, Seek, SeekFrom};
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, 2, 3, 4]).unwrap();
+    /// wave.info_mut().title = Some("Stream".to_string());
+    ///
+    /// let mut reader = wave.reader();
+    /// assert_eq!(reader.len(), wave.encoded_size());
+    ///
+    /// // Reads of a few bytes at a time split the header
+    /// let mut bytes = Vec::new();
+    /// let mut buf = [0; 7];
+    /// loop {
+    ///     let n = reader.read(&mut buf).unwrap();
+    ///     if n == 0 {
+    ///         break;
+    ///     }
+    ///     bytes.extend_from_slice(&buf[..n]);
+    /// }
+    /// assert_eq!(bytes, wave.to_bytes());
+    ///
+    /// reader.seek(SeekFrom::Start(8)).unwrap();
+    /// reader.read_exact(&mut buf[..4]).unwrap();
+    /// assert_eq!(&buf[..4], b"WAVE");
+    /// ```
+    pub fn reader(&self) -> WaveFileReader<'_> {
+        let data_size = self.audio_byte_data.len() as u64;
+        let mut trailing = Vec::new();
+        // Chunks with an odd size are padded with a byte that is not counted in their size
+        if !data_size.is_multiple_of(2) {
+            trailing.push(0);
+        }
+        let chunks = self.trailing_chunks();
+        trailing.extend_from_slice(&chunks);
+        let (header, too_large) = match self.container(data_size, chunks.len() as u64, false) {
+            Ok(container) => (self.header(data_size, chunks.len() as u64, container), None),
+            Err(WaveError::TooLarge { size }) => (Vec::new(), Some(size)),
+            Err(_) => unreachable!("only TooLarge is returned"),
+        };
+        WaveFileReader { header, data: &self.audio_byte_data, trailing, pos: 0, too_large }
+    }
+}
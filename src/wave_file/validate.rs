@@ -0,0 +1,154 @@
+use std::fmt;
+
+use super::sample;
+use super::{AudioFormat, WaveError, WaveFile};
+
+/// A problem found by [WaveFile::validate]: a property of the file that would make it write a
+/// file that readers reject or misinterpret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// The amount of bytes of audio data is not a whole amount of frames.
+    MisalignedData {
+        /// The amount of bytes of audio data
+        len: usize,
+        /// The block align the amount of bytes should be divisible by
+        block_align: u16,
+    },
+    /// The byte rate is not the sample rate times the amount of channels times the bytes per
+    /// sample.
+    ByteRateMismatch {
+        /// The byte rate of the file
+        byte_rate: u32,
+        /// The byte rate computed from the other properties
+        expected: u64,
+    },
+    /// The block align is not the amount of channels times the bytes per sample.
+    BlockAlignMismatch {
+        /// The block align of the file
+        declared: u16,
+        /// The block align computed from the amount of channels and bits per sample
+        computed: u32,
+    },
+    /// The amount of bits per sample is not supported for the format.
+    UnsupportedBitsPerSample {
+        /// The format of the samples
+        format: AudioFormat,
+        /// The amount of bits per sample
+        bits_per_sample: u16,
+    },
+    /// The amount of channels is 0.
+    InvalidChannels(u16),
+    /// The sample rate is 0.
+    ZeroSampleRate,
+    /// The file doesn't fit in the 32-bit sizes of a RIFF file, and writing it as RF64 is not
+    /// allowed.
+    TooLarge {
+        /// The size the RIFF chunk would have, in bytes
+        size: u64,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::MisalignedData { len, block_align } => write!(
+                f,
+                "{} bytes of audio data do not fit evenly in blocks of {} bytes",
+                len, block_align
+            ),
+            ValidationIssue::ByteRateMismatch { byte_rate, expected } => write!(
+                f,
+                "the byte rate is {}, but the format needs {}",
+                byte_rate, expected
+            ),
+            ValidationIssue::BlockAlignMismatch { declared, computed } => write!(
+                f,
+                "the block align is {}, but the format needs {}",
+                declared, computed
+            ),
+            ValidationIssue::UnsupportedBitsPerSample { format, bits_per_sample } => write!(
+                f,
+                "{} bits per sample are not supported for {:?}",
+                bits_per_sample, format
+            ),
+            ValidationIssue::InvalidChannels(channels) => write!(f, "{} channels is not a valid amount of channels", channels),
+            ValidationIssue::ZeroSampleRate => write!(f, "the sample rate is 0"),
+            ValidationIssue::TooLarge { size } => write!(
+                f,
+                "a RIFF chunk of {} bytes doesn't fit in a 32-bit size, RF64 is needed",
+                size
+            ),
+        }
+    }
+}
+
+// Validation
+impl WaveFile {
+    /// Checks that the properties of the file are consistent with each other and with the audio
+    /// data, and returns every problem that was found. This catches changes made through
+    /// [bytes](WaveFile::bytes) that break the alignment of the frames, and files that were read
+    /// with inconsistent headers.
+    ///
+    /// # Errors
+    /// Every [ValidationIssue] that applies to the file.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 44100, 16);
+    /// wave.add_samples_i16(&[1, 2]).unwrap();
+    /// assert!(wave.validate().is_ok());
+    ///
+    /// wave.bytes().push(3);
+    /// assert_eq!(wave.validate(), Err(vec![ValidationIssue::MisalignedData { len: 5, block_align: 4 }]));
+    ///
+    /// // A header with the byte rate of a mono file
+    /// let mut bytes = WaveFile::new(AudioFormat::PCM, 2, 44100, 16).to_bytes();
+    /// bytes[28..32].copy_from_slice(&88200u32.to_le_bytes());
+    /// let issues = WaveFile::from_bytes(&bytes).unwrap().validate().unwrap_err();
+    /// assert_eq!(issues[0].to_string(), "the byte rate is 88200, but the format needs 176400");
+    ///
+    /// // Without channels, frames have no bytes
+    /// let mut wave = WaveFile::new(AudioFormat::PCM, 0, 44100, 16);
+    /// wave.bytes().extend([1, 0]);
+    /// assert_eq!(wave.validate(), Err(vec![
+    ///     ValidationIssue::InvalidChannels(0),
+    ///     ValidationIssue::MisalignedData { len: 2, block_align: 0 },
+    /// ]));
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        if self.num_channels == 0 {
+            issues.push(ValidationIssue::InvalidChannels(self.num_channels));
+        }
+        if self.sample_rate == 0 {
+            issues.push(ValidationIssue::ZeroSampleRate);
+        }
+        let format = self.audio_format();
+        if sample::check_supported(format, self.bits_per_sample).is_err() {
+            issues.push(ValidationIssue::UnsupportedBitsPerSample { format, bits_per_sample: self.bits_per_sample });
+        }
+        let computed = self.num_channels as u32 * (self.bits_per_sample / 8) as u32;
+        if self.block_align as u32 != computed {
+            issues.push(ValidationIssue::BlockAlignMismatch { declared: self.block_align, computed });
+        }
+        let expected = self.sample_rate as u64 * computed as u64;
+        if self.byte_rate as u64 != expected {
+            issues.push(ValidationIssue::ByteRateMismatch { byte_rate: self.byte_rate, expected });
+        }
+        // No audio data fits in blocks of 0 bytes
+        let len = self.audio_byte_data.len();
+        if len > 0 && (self.block_align == 0 || !len.is_multiple_of(self.block_align as usize)) {
+            issues.push(ValidationIssue::MisalignedData { len, block_align: self.block_align });
+        }
+        let trailing_size = self.trailing_chunks().len() as u64;
+        if let Err(WaveError::TooLarge { size }) = self.container(len as u64, trailing_size, false) {
+            issues.push(ValidationIssue::TooLarge { size });
+        }
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::ops::Range;
+use std::slice::ChunksExact;
+use std::time::Duration;
+
+use super::chunk::{Chunk, Chunks};
+use super::sample;
+use super::{adpcm, w64, FmtChunk, WaveError, WaveFile, WaveSpec};
+
+/// A read-only view of a wave file in memory, that borrows its audio data instead of copying it.
+///
+/// Only the header is parsed when the view is created, so a view of a memory-mapped file or an
+/// embedded asset of any size is cheap to make. Samples are only decoded for the frames that are
+/// asked for, and [slice_frames](WaveView::slice_frames) copies a range of frames into an owned
+/// [WaveFile].
+///
+/// Every chunk size is checked against the length of the bytes, so a corrupt or malicious header
+/// gives an error instead of a view past the end of the bytes.
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::time::Duration;
+/// let mut wave = WaveFile::new(AudioFormat::PCM, 2, 1000, 16);
+/// wave.add_samples_i16(&[1, -1, 2, -2, 3, -3, 4, -4]).unwrap();
+/// let bytes = wave.to_bytes();
+///
+/// let view = WaveView::new(&bytes).unwrap();
+/// assert_eq!(view.spec(), wave.spec());
+/// assert_eq!(view.num_frames(), 4);
+/// assert_eq!(view.duration(), Duration::from_millis(4));
+/// assert_eq!(view.frames().nth(1), Some(&[2, 0, 254, 255][..]));
+/// assert_eq!(view.samples_i16_at(2..9).unwrap(), [3, -3, 4, -4]);
+///
+/// let owned = view.slice_frames(1..3).unwrap();
+/// assert_eq!(owned.samples_i16().unwrap(), [2, -2, 3, -3]);
+///
+/// // A data chunk that claims more bytes than there are
+/// let mut corrupt = bytes.clone();
+/// corrupt[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+/// assert!(matches!(WaveView::new(&corrupt), Err(WaveError::Truncated)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WaveView<'a> {
+    /// Holds the spec, its data is never used
+    spec: WaveFile,
+    data: &'a [u8],
+}
+
+impl<'a> WaveView<'a> {
+    /// Parses the header of the wave file in `bytes` and creates a view of its audio data.
+    ///
+    /// RIFF, RF64 and Sony Wave64 files can be viewed. The audio data of RIFX files and IMA ADPCM
+    /// files has to be converted to be read, use [WaveFile::from_bytes] for those.
+    ///
+    /// # Errors
+    /// - [WaveError::NotRiff] if `bytes` does not start with the `RIFF`/`WAVE` or `RF64`/`WAVE`
+    ///   magic, or the `riff`/`wave` GUIDs of a Wave64 file
+    /// - [WaveError::Truncated] if a chunk header is truncated, a chunk declares more bytes than
+    ///   `bytes` contains or the fmt or ds64 chunk is too short
+    /// - [WaveError::MissingChunk] if the fmt or data chunk is missing, or the ds64 chunk of an
+    ///   RF64 file
+    /// - [WaveError::UnsupportedFormat] if the (sub) format is not PCM, IEEE float, A-law or µ-law
+    pub fn new(bytes: &'a [u8]) -> Result<WaveView<'a>, WaveError> {
+        if w64::is_w64_magic(bytes) {
+            WaveView::from_chunks(w64::W64Chunks::new(bytes)?)
+        } else {
+            WaveView::from_chunks(Chunks::new(bytes)?)
+        }
+    }
+
+    /// Creates a view from the fmt and data chunks of a file
+    fn from_chunks(chunks: impl Iterator<Item = Result<Chunk<'a>, WaveError>>) -> Result<WaveView<'a>, WaveError> {
+        let mut fmt = None;
+        let mut data = None;
+        for chunk in chunks {
+            let chunk = chunk?;
+            match &chunk.id {
+                b"fmt " if fmt.is_none() => fmt = Some(chunk.data),
+                b"data" if data.is_none() => data = Some(chunk.data),
+                _ => {}
+            }
+        }
+        let fmt = fmt.ok_or(WaveError::MissingChunk("fmt "))?;
+        let data = data.ok_or(WaveError::MissingChunk("data"))?;
+        if fmt.len() >= 2 && u16::from_le_bytes([fmt[0], fmt[1]]) == adpcm::IMA_ADPCM {
+            return Err(WaveError::UnsupportedFormat(adpcm::IMA_ADPCM));
+        }
+        Ok(WaveView { spec: WaveFile::from_fmt(&FmtChunk::parse(fmt)?), data })
+    }
+
+    /// Returns the spec of the audio data, see [WaveFile::spec].
+    pub fn spec(&self) -> WaveSpec {
+        self.spec.spec()
+    }
+
+    /// Returns the block align: the amount of bytes in one frame (one sample for every channel).
+    pub fn block_align(&self) -> u16 {
+        self.spec.block_align
+    }
+
+    /// Returns the audio data as it is stored in the file, without copying it.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the amount of whole frames in the audio data.
+    pub fn num_frames(&self) -> u64 {
+        match self.block_align() {
+            0 => 0,
+            block_align => (self.data.len() / block_align as usize) as u64,
+        }
+    }
+
+    /// Returns the playback duration of the audio data, see [WaveFile::duration].
+    pub fn duration(&self) -> Duration {
+        self.spec.duration_of(self.num_frames())
+    }
+
+    /// Returns an iterator over the frames of the audio data, each frame being `block_align`
+    /// bytes. A partial frame at the end is left out.
+    pub fn frames(&self) -> ChunksExact<'a, u8> {
+        self.data.chunks_exact(self.block_align().max(1) as usize)
+    }
+
+    /// Returns the interleaved samples of the frames in `range` as 16-bit samples, like
+    /// [WaveFile::samples_i16] does for the whole file. The range is clamped to the audio data.
+    ///
+    /// # Errors
+    /// - [WaveError::InvalidRange] if the range starts after it ends
+    /// - the errors of [WaveFile::samples_i16]
+    pub fn samples_i16_at(&self, range: Range<u64>) -> Result<Vec<i16>, WaveError> {
+        self.spec.check_lossless_i16()?;
+        let bytes = &self.data[self.frame_byte_range(range)?];
+        let bits = self.spec.bits_per_sample;
+        Ok(sample::read_samples(bytes.chunks_exact((bits / 8) as usize), self.spec.audio_format(), bits))
+    }
+
+    /// Copies the frames in `range` into a new file with the same spec, see
+    /// [WaveFile::slice_frames]. The metadata of the file is not copied.
+    ///
+    /// # Errors
+    /// [WaveError::InvalidRange] if the range starts after it ends.
+    pub fn slice_frames(&self, range: Range<u64>) -> Result<WaveFile, WaveError> {
+        let mut wave = self.spec.clone();
+        wave.audio_byte_data.set(self.data[self.frame_byte_range(range)?].to_vec());
+        Ok(wave)
+    }
+
+    /// Returns the range of bytes of the frames in `range`, clamped to the audio data
+    fn frame_byte_range(&self, range: Range<u64>) -> Result<Range<usize>, WaveError> {
+        if range.start > range.end {
+            return Err(WaveError::InvalidRange { start: range.start, end: range.end });
+        }
+        let frames = self.num_frames();
+        let block_align = self.block_align() as usize;
+        Ok(range.start.min(frames) as usize * block_align..range.end.min(frames) as usize * block_align)
+    }
+}
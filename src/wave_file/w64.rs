@@ -0,0 +1,100 @@
+//! Sony Wave64: wave files with 16-byte GUID chunk ids and 64-bit chunk sizes.
+
+use super::chunk::Chunk;
+use super::WaveError;
+
+/// The last 12 bytes of the GUIDs of the `riff` and `list` chunks, the first 4 bytes spell the
+/// (lowercase) chunk id
+const RIFF_GUID_TAIL: [u8; 12] = [0x2E, 0x91, 0xCF, 0x11, 0xA5, 0xD6, 0x28, 0xDB, 0x04, 0xC1, 0x00, 0x00];
+/// The tail of the `list` GUID, which differs from the `riff` GUID in one byte
+const LIST_GUID_TAIL: [u8; 12] = [0x2F, 0x91, 0xCF, 0x11, 0xA5, 0xD6, 0x28, 0xDB, 0x04, 0xC1, 0x00, 0x00];
+/// The last 12 bytes of the GUIDs of the `wave` form and of chunks that have a FourCC in RIFF,
+/// like `fmt `, `fact` and `data`
+const FOURCC_GUID_TAIL: [u8; 12] = [0xF3, 0xAC, 0xD3, 0x11, 0x8C, 0xD1, 0x00, 0xC0, 0x4F, 0x8E, 0xDB, 0x8A];
+
+/// The size of the header of a chunk: its GUID and 64-bit size
+pub(crate) const CHUNK_HEADER_SIZE: usize = 24;
+/// The size of the file header: the `riff` chunk header and the `wave` GUID
+pub(crate) const HEADER_SIZE: usize = CHUNK_HEADER_SIZE + 16;
+
+/// Whether `bytes` starts with the `riff` GUID of a Wave64 file
+pub(crate) fn is_w64_magic(bytes: &[u8]) -> bool {
+    bytes.len() >= 16 && &bytes[0..4] == b"riff" && bytes[4..16] == RIFF_GUID_TAIL
+}
+
+/// Whether `guid` is the `wave` GUID that follows the file size
+pub(crate) fn is_wave_guid(guid: &[u8]) -> bool {
+    guid.len() == 16 && &guid[0..4] == b"wave" && guid[4..16] == FOURCC_GUID_TAIL
+}
+
+/// Maps the GUID of a chunk to the FourCC of the same chunk in a RIFF file, `None` for chunks
+/// that don't have one
+pub(crate) fn chunk_id(guid: &[u8; 16]) -> Option<[u8; 4]> {
+    let id: [u8; 4] = guid[0..4].try_into().unwrap();
+    if guid[4..16] == FOURCC_GUID_TAIL {
+        match &id {
+            // Only reserve space, like JUNK chunks
+            b"junk" => Some(*b"JUNK"),
+            _ => Some(id),
+        }
+    } else if id == *b"list" && guid[4..16] == LIST_GUID_TAIL {
+        Some(*b"LIST")
+    } else {
+        None
+    }
+}
+
+/// The size of the payload of a chunk with a header of `size`, which counts the header itself
+pub(crate) fn payload_size(size: u64) -> Result<u64, WaveError> {
+    size.checked_sub(CHUNK_HEADER_SIZE as u64).ok_or(WaveError::Truncated)
+}
+
+/// The amount of padding after a payload of `size` bytes, chunks are aligned to 8 bytes
+pub(crate) fn padding(size: u64) -> u64 {
+    (8 - size % 8) % 8
+}
+
+/// Iterates over the chunks of the `wave` form of a Wave64 file that have a RIFF FourCC. Chunks
+/// with other GUIDs are skipped.
+pub(crate) struct W64Chunks<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> W64Chunks<'a> {
+    /// Verifies the `riff`/`wave` GUIDs and returns an iterator over the chunks following them.
+    pub(crate) fn new(bytes: &'a [u8]) -> Result<W64Chunks<'a>, WaveError> {
+        if !is_w64_magic(bytes) || bytes.len() < HEADER_SIZE || !is_wave_guid(&bytes[24..40]) {
+            return Err(WaveError::NotRiff);
+        }
+        // The size of the riff chunk counts the whole file
+        let riff_size = u64::from_le_bytes(bytes[16..24].try_into().unwrap());
+        let end = bytes.len().min(usize::try_from(riff_size).unwrap_or(usize::MAX));
+        Ok(W64Chunks { bytes: &bytes[..end], pos: HEADER_SIZE })
+    }
+}
+
+impl<'a> Iterator for W64Chunks<'a> {
+    type Item = Result<Chunk<'a>, WaveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.bytes.len() {
+            let chunk = self.bytes.get(self.pos..self.pos + CHUNK_HEADER_SIZE).and_then(|header| {
+                let guid: [u8; 16] = header[0..16].try_into().unwrap();
+                let size = payload_size(u64::from_le_bytes(header[16..24].try_into().unwrap())).ok()?;
+                let start = self.pos + CHUNK_HEADER_SIZE;
+                let data = self.bytes.get(start..start.checked_add(usize::try_from(size).ok()?)?)?;
+                Some((chunk_id(&guid), data))
+            });
+            let Some((id, data)) = chunk else {
+                self.pos = self.bytes.len();
+                return Some(Err(WaveError::Truncated));
+            };
+            self.pos += CHUNK_HEADER_SIZE + data.len() + padding(data.len() as u64) as usize;
+            if let Some(id) = id {
+                return Some(Ok(Chunk { id, data }));
+            }
+        }
+        None
+    }
+}
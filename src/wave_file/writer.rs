@@ -0,0 +1,282 @@
+use std::io::{Seek, SeekFrom, Write};
+
+use super::rf64::Container;
+use super::{AudioFormat, Info, Rf64Mode, Sample, WaveError, WaveFile, WaveSpec};
+
+/// Writes a wave file block by block, for when the length of the audio isn't known up front
+/// (e.g. when recording).
+///
+/// The header is written with placeholder sizes when the writer is created, and patched with the
+/// real sizes in [finalize](WaveWriter::finalize). Dropping the writer finalizes it as well, but
+/// errors are ignored then. If the program stops before either happens, the file has a
+/// data chunk size of 0, which can be recovered by recomputing the sizes from the file length.
+///
+/// The header leaves room for a `ds64` chunk in a `JUNK` chunk, so files that grow beyond 4 GB
+/// are turned into RF64 files when finalized (see [Rf64Mode](super::Rf64Mode)).
+///
+/// # Examples
+/// ```rust
+/// # use jaudio::wave_file::*;
+/// # use std::io::Cursor;
+/// let mut writer = WaveWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM, 2, 44100, 16).unwrap();
+/// for _ in 0..10 {
+///     writer.write_samples_i16(&[0, 0, 1000, -1000]).unwrap();
+/// }
+/// let bytes = writer.finalize().unwrap().into_inner();
+/// assert_eq!(&bytes[12..16], b"JUNK");
+///
+/// let mut wave = WaveFile::from_bytes(&bytes).unwrap();
+/// assert_eq!(wave.bytes().len(), 10 * 8);
+/// assert!(wave.extra_chunks().is_empty());
+/// ```
+pub struct WaveWriter<W: Write + Seek> {
+    writer: Option<W>,
+    /// Holds the spec, its data is never used
+    spec: WaveFile,
+    /// The position of the start of the header in `writer`
+    start: u64,
+    data_len: u64,
+}
+
+impl<W: Write + Seek> WaveWriter<W> {
+    /// Creates a new `WaveWriter` and writes the header to `writer`, at its current position.
+    ///
+    /// See [WaveFile::new] for the parameters.
+    ///
+    /// # Errors
+    /// The errors of [WaveFile::try_new] if the parameters don't describe a wave file, nothing is
+    /// written then. Otherwise any error returned by `writer`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::io::Cursor;
+    /// let writer = WaveWriter::new(Cursor::new(Vec::new()), AudioFormat::IeeeFloat, 1, 44100, 16);
+    /// assert!(matches!(writer, Err(WaveError::UnsupportedBitsPerSample(16))));
+    /// ```
+    pub fn new(
+        mut writer: W,
+        audio_format: AudioFormat,
+        num_channels: u16,
+        sample_rate: u32,
+        bits_per_sample: u16
+    ) -> Result<WaveWriter<W>, WaveError>
+    {
+        let spec = WaveFile::try_new(audio_format, num_channels, sample_rate, bits_per_sample)?;
+        let start = writer.stream_position()?;
+        // Room is left for a ds64 chunk, in case the file grows too large for RIFF
+        writer.write_all(&spec.header(0, 0, Container::RiffWithJunk))?;
+        Ok(WaveWriter { writer: Some(writer), spec, start, data_len: 0 })
+    }
+
+    /// Creates a new `WaveWriter` for audio of `spec` and writes the header to `writer`, at its
+    /// current position.
+    ///
+    /// # Errors
+    /// The errors of [WaveSpec::new] if the spec is invalid, nothing is written then. Otherwise
+    /// any error returned by `writer`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::io::Cursor;
+    /// let bytes = WaveFile::new(AudioFormat::IeeeFloat, 1, 48000, 32).to_bytes();
+    /// let reader = WaveReader::new(&bytes[..]).unwrap();
+    ///
+    /// // Write a file in the same format as the one being read
+    /// let writer = WaveWriter::with_spec(Cursor::new(Vec::new()), reader.spec()).unwrap();
+    /// assert_eq!(writer.spec(), reader.spec());
+    ///
+    /// let spec = WaveSpec { channels: 0, ..reader.spec() };
+    /// assert!(WaveWriter::with_spec(Cursor::new(Vec::new()), spec).is_err());
+    /// ```
+    pub fn with_spec(writer: W, spec: WaveSpec) -> Result<WaveWriter<W>, WaveError> {
+        spec.validate()?;
+        WaveWriter::new(writer, spec.format, spec.channels, spec.sample_rate, spec.bits_per_sample)
+    }
+
+    /// Returns the spec of the audio data written by this writer.
+    pub fn spec(&self) -> WaveSpec {
+        self.spec.spec()
+    }
+
+    /// Writes audio data to the data chunk.
+    ///
+    /// # Errors
+    /// [WaveError::MisalignedData] if the amount of bytes isn't divisible by the block align and
+    /// [WaveError::TooLarge] if the file would no longer fit in RIFF while RF64 is not allowed,
+    /// nothing is written in those cases. Otherwise any error returned by the underlying writer.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), WaveError> {
+        self.spec.check_streamed(self.data_len, bytes.len())?;
+        self.writer().write_all(bytes)?;
+        self.data_len += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Writes interleaved 16-bit samples to the data chunk.
+    ///
+    /// # Errors
+    /// [WaveError::UnsupportedFormat] or [WaveError::UnsupportedBitsPerSample] if the file isn't
+    /// 16-bit PCM and [WaveError::MisalignedData] if the amount of samples isn't divisible by the
+    /// amount of channels. Otherwise any error returned by the underlying writer.
+    pub fn write_samples_i16(&mut self, samples: &[i16]) -> Result<(), WaveError> {
+        self.spec.add_samples_i16(samples)?;
+        self.write_spec_data()
+    }
+
+    /// Writes interleaved samples of any [Sample] type to the data chunk, converting them to the
+    /// format and bit depth of the file like [WaveFile::add_samples].
+    ///
+    /// # Errors
+    /// The errors of [WaveFile::add_samples], or any error returned by the underlying writer.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::io::Cursor;
+    /// let ramp: Vec<I24> = (-4..4).map(|i| I24::new(i << 20).unwrap()).collect();
+    ///
+    /// let mut writer = WaveWriter::new(Cursor::new(Vec::new()), AudioFormat::PCM, 1, 96000, 24).unwrap();
+    /// writer.write_samples(&ramp).unwrap();
+    /// let bytes = writer.finalize().unwrap().into_inner();
+    ///
+    /// assert_eq!(WaveFile::from_bytes(&bytes).unwrap().samples_as::<I24>().unwrap(), ramp);
+    /// ```
+    pub fn write_samples<S: Sample>(&mut self, samples: &[S]) -> Result<(), WaveError> {
+        self.spec.add_samples(samples)?;
+        self.write_spec_data()
+    }
+
+    /// Writes the samples that were encoded into the data of `spec`
+    fn write_spec_data(&mut self) -> Result<(), WaveError> {
+        let bytes = std::mem::take(self.spec.audio_byte_data.make_contiguous());
+        self.write_bytes(&bytes)
+    }
+
+    /// Returns the metadata of the `LIST`/`INFO` chunk for editing. It is written after the audio
+    /// data when the writer is finalized.
+    pub fn info_mut(&mut self) -> &mut Info {
+        self.spec.info_mut()
+    }
+
+    /// Sets when the file is written as RF64, see [Rf64Mode]. By default a file is turned into
+    /// RF64 when finalized if it grew too large for RIFF.
+    ///
+    /// # Examples
+    /// Writing 4 GB to a writer that only keeps the header:
+    /// ```rust
+    /// # use jaudio::wave_file::*;
+    /// # use std::io::{self, Seek, SeekFrom, Write};
+    /// #[derive(Default)]
+    /// struct Header { pos: u64, bytes: Vec<u8> }
+    ///
+    /// impl Write for Header {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         let keep = 80u64.saturating_sub(self.pos).min(buf.len() as u64) as usize;
+    ///         let pos = self.pos as usize;
+    ///         if keep > 0 {
+    ///             self.bytes.resize(self.bytes.len().max(pos + keep), 0);
+    ///             self.bytes[pos..pos + keep].copy_from_slice(&buf[..keep]);
+    ///         }
+    ///         self.pos += buf.len() as u64;
+    ///         Ok(buf.len())
+    ///     }
+    ///     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// }
+    ///
+    /// impl Seek for Header {
+    ///     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+    ///         self.pos = match pos {
+    ///             SeekFrom::Start(pos) => pos,
+    ///             SeekFrom::Current(offset) => self.pos.checked_add_signed(offset).unwrap(),
+    ///             SeekFrom::End(_) => unimplemented!(),
+    ///         };
+    ///         Ok(self.pos)
+    ///     }
+    /// }
+    ///
+    /// let block = vec![0; 1 << 20];
+    ///
+    /// let mut writer = WaveWriter::new(Header::default(), AudioFormat::PCM, 2, 48000, 16).unwrap();
+    /// writer.set_rf64_mode(Rf64Mode::Never);
+    /// let error = loop {
+    ///     if let Err(error) = writer.write_bytes(&block) {
+    ///         break error;
+    ///     }
+    /// };
+    /// assert!(matches!(error, WaveError::TooLarge { .. }));
+    /// assert_eq!(writer.data_len(), 4095 << 20);
+    ///
+    /// let mut writer = WaveWriter::new(Header::default(), AudioFormat::PCM, 2, 48000, 16).unwrap();
+    /// for _ in 0..4097 {
+    ///     writer.write_bytes(&block).unwrap();
+    /// }
+    /// let header = writer.finalize().unwrap().bytes;
+    /// assert_eq!(&header[0..4], b"RF64");
+    /// assert_eq!(&header[12..16], b"ds64");
+    /// // The data size
+    /// assert_eq!(&header[28..36], (4097u64 << 20).to_le_bytes());
+    /// ```
+    pub fn set_rf64_mode(&mut self, rf64_mode: Rf64Mode) {
+        self.spec.set_rf64_mode(rf64_mode);
+    }
+
+    /// The amount of bytes of audio data written so far.
+    pub fn data_len(&self) -> u64 {
+        self.data_len
+    }
+
+    /// Pads the data chunk and patches the sizes in the header, then returns the underlying
+    /// writer, positioned at the end of the file.
+    ///
+    /// # Errors
+    /// [WaveError::TooLarge] if the trailing chunks make the file too large for RIFF while RF64
+    /// is not allowed, the header is not patched then. Otherwise any error returned by the
+    /// underlying writer.
+    pub fn finalize(mut self) -> Result<W, WaveError> {
+        let result = self.finish();
+        let writer = self.writer.take().unwrap();
+        result?;
+        Ok(writer)
+    }
+
+    fn finish(&mut self) -> Result<(), WaveError> {
+        let (header, tail) = self.spec.header_and_tail(self.data_len, true)?;
+        let start = self.start;
+        let writer = self.writer();
+        writer.write_all(&tail)?;
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(start))?;
+        writer.write_all(&header)?;
+        writer.seek(SeekFrom::Start(end))?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn writer(&mut self) -> &mut W {
+        // Only taken in finalize, after which the WaveWriter is gone
+        self.writer.as_mut().unwrap()
+    }
+}
+
+impl<W: Write + Seek> Drop for WaveWriter<W> {
+    fn drop(&mut self) {
+        if self.writer.is_some() {
+            let _ = self.finish();
+        }
+    }
+}
+
+// Streaming
+impl WaveFile {
+    /// Checks that `len` bytes of audio data can be written by a streaming writer after the
+    /// `data_len` bytes it wrote before
+    pub(crate) fn check_streamed(&self, data_len: u64, len: usize) -> Result<(), WaveError> {
+        if !len.is_multiple_of(self.block_align as usize) {
+            return Err(WaveError::MisalignedData { len, block_align: self.block_align });
+        }
+        // Without RF64 the data has to fit, the trailing chunks are checked when finalizing
+        self.container(data_len + len as u64, 0, true)?;
+        Ok(())
+    }
+}